@@ -5,6 +5,7 @@ use crate::phosphor_icons::regular;
 /// of test HTML files and responsive UI controls
 use eframe::egui;
 use std::path::PathBuf;
+use zver::network::Url;
 
 /// Address bar component for URL navigation and DevTools toggle
 pub struct AddressBar {
@@ -16,6 +17,8 @@ pub struct AddressBar {
     pub devtools_open: bool,
     /// Index of selected HTML file in dropdown (-1 = none)
     pub selected_html_index: Option<usize>,
+    /// Directory the test files were discovered in
+    pub fixtures_dir: Option<PathBuf>,
 }
 
 /// Result of rendering the address bar UI
@@ -33,6 +36,7 @@ impl Default for AddressBar {
             html_files: Vec::new(),
             devtools_open: false,
             selected_html_index: None,
+            fixtures_dir: None,
         };
         bar.scan_html_files();
         bar
@@ -50,6 +54,7 @@ impl AddressBar {
     /// This implements automatic resource discovery, reducing manual configuration
     pub fn scan_html_files(&mut self) {
         self.html_files.clear();
+        self.fixtures_dir = None;
 
         // Try to find tests directory relative to current working directory
         let test_paths = [
@@ -69,6 +74,7 @@ impl AddressBar {
                     }
                 }
                 if !self.html_files.is_empty() {
+                    self.fixtures_dir = Some(test_path.clone());
                     break;
                 }
             }
@@ -189,6 +195,17 @@ impl AddressBar {
                             });
                     }
 
+            if let Some(dir) = &self.fixtures_dir
+                && ui
+                    .button(regular::FOLDER_OPEN)
+                    .on_hover_text("Browse test folder")
+                    .clicked()
+                && let Ok(url) = Url::from_file_path(dir)
+            {
+                self.url_input = url.as_directory().into();
+                result.load_url = Some(self.url_input.clone());
+            }
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 let button = if self.devtools_open {
                     egui::Button::new(regular::BUG).fill(egui::Color32::from_rgb(100, 150, 255))
@@ -10,7 +10,7 @@ use zver::Zver;
 use zver::dom::serialization::serialize_dom;
 use zver::dom::{Document, Node};
 use zver::js::JSValue;
-use zver::network::NetworkLogEntry;
+use zver::network::{CacheStatus, NetworkLogEntry};
 
 #[derive(Debug, Clone)]
 struct DomTreeNode {
@@ -450,18 +450,42 @@ impl DevTools {
         egui::ScrollArea::vertical()
             .max_height(404.0)
             .show(ui, |ui| {
-                for entry in self.cached_network_logs.iter().rev() {
-                    ui.horizontal_wrapped(|ui| {
-                        let status_color = if entry.status.starts_with("OK") {
-                            egui::Color32::from_rgb(120, 255, 180)
-                        } else {
-                            egui::Color32::from_rgb(255, 150, 150)
-                        };
-                        ui.colored_label(status_color, format!("[{}]", entry.status));
-                        ui.label(format!("({}) {}", entry.source, entry.url));
+                egui::Grid::new("network_requests")
+                    .num_columns(6)
+                    .striped(true)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        for header in ["Status", "Cache", "Type", "Size", "Source", "URL"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+
+                        for entry in self.cached_network_logs.iter().rev() {
+                            let status_color = if entry.status.starts_with("OK") {
+                                egui::Color32::from_rgb(120, 255, 180)
+                            } else {
+                                egui::Color32::from_rgb(255, 150, 150)
+                            };
+                            let status = match entry.status_code {
+                                Some(code) => code.to_string(),
+                                None => entry.status.clone(),
+                            };
+                            ui.colored_label(status_color, status);
+
+                            let cache_color = match entry.cache {
+                                CacheStatus::Hit => egui::Color32::from_rgb(150, 200, 255),
+                                CacheStatus::Revalidated => egui::Color32::from_rgb(255, 220, 120),
+                                CacheStatus::Stale => egui::Color32::from_rgb(255, 160, 90),
+                                CacheStatus::Miss | CacheStatus::Bypass => egui::Color32::GRAY,
+                            };
+                            ui.colored_label(cache_color, entry.cache.to_string());
+                            ui.label(entry.content_type.as_deref().unwrap_or("-"));
+                            ui.label(format_size(entry.size));
+                            ui.label(&entry.source);
+                            ui.label(&entry.url);
+                            ui.end_row();
+                        }
                     });
-                    ui.separator();
-                }
             });
     }
 
@@ -708,3 +732,14 @@ impl DevTools {
         self.cached_console_logs.push(entry);
     }
 }
+
+/// Formats a body size for the Network tab
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,317 @@
+/// Translation of egui input into engine input events
+///
+/// Pointer events are taken only over the page canvas and converted to page
+/// coordinates, except releases of buttons pressed over the canvas, which reach
+/// the page wherever the pointer is; keyboard events are forwarded while no egui
+/// widget (such as the address bar) holds keyboard focus.
+use eframe::egui;
+use zver::input::{InputEvent, MouseButton};
+use zver::js::events::Modifiers;
+
+/// Lines are scrolled by this many CSS pixels, like a typical desktop browser
+const LINE_HEIGHT: f32 = 40.0;
+
+/// Collects this frame's input over the page canvas
+///
+/// # Arguments
+/// * `ui` - egui UI context
+/// * `response` - Response of the page canvas
+///
+/// # Returns
+/// Engine events in the order egui received them
+pub fn collect_input(ui: &egui::Ui, response: &egui::Response) -> Vec<InputEvent> {
+    // Only the visible part of the canvas receives pointer events
+    let rect = response.rect.intersect(ui.clip_rect());
+    let origin = response.rect.min;
+    let keyboard = ui.ctx().memory(|memory| memory.focused().is_none());
+    let hover_pos = response.hover_pos();
+    let mut was_inside = response.contains_pointer();
+    // Buttons pressed over the canvas, kept across frames until released
+    let pressed_id = egui::Id::new("zver_pressed_buttons");
+    let mut pressed: Vec<egui::PointerButton> = ui
+        .ctx()
+        .data(|data| data.get_temp(pressed_id))
+        .unwrap_or_default();
+
+    let events = ui.input(|input| {
+        let mut events = Vec::new();
+        for event in &input.events {
+            match event {
+                egui::Event::PointerMoved(pos) => {
+                    let inside = rect.contains(*pos);
+                    if inside {
+                        let local = *pos - origin;
+                        events.push(InputEvent::PointerMove {
+                            x: local.x,
+                            y: local.y,
+                            modifiers: modifiers(input.modifiers),
+                        });
+                    } else if was_inside {
+                        events.push(InputEvent::PointerLeave);
+                    }
+                    was_inside = inside;
+                }
+                egui::Event::PointerGone => events.push(InputEvent::PointerLeave),
+                egui::Event::PointerButton {
+                    pos,
+                    button: egui_button,
+                    pressed: is_pressed,
+                    modifiers: mods,
+                } => {
+                    let Some(button) = mouse_button(*egui_button) else {
+                        continue;
+                    };
+                    let held = pressed.contains(egui_button);
+                    if *is_pressed && rect.contains(*pos) {
+                        if !held {
+                            pressed.push(*egui_button);
+                        }
+                    } else if !*is_pressed && (held || rect.contains(*pos)) {
+                        // The page releases a button pressed on it even when the pointer
+                        // left the canvas; a point outside the page hits no element
+                        pressed.retain(|held| held != egui_button);
+                    } else {
+                        continue;
+                    }
+                    let local = *pos - origin;
+                    let (x, y, modifiers) = (local.x, local.y, modifiers(*mods));
+                    events.push(if *is_pressed {
+                        InputEvent::PointerDown {
+                            x,
+                            y,
+                            button,
+                            modifiers,
+                        }
+                    } else {
+                        InputEvent::PointerUp {
+                            x,
+                            y,
+                            button,
+                            modifiers,
+                        }
+                    });
+                }
+                egui::Event::MouseWheel {
+                    unit,
+                    delta,
+                    modifiers: mods,
+                } => {
+                    let Some(pos) = hover_pos else {
+                        continue;
+                    };
+                    let scale = match unit {
+                        egui::MouseWheelUnit::Point => 1.0,
+                        egui::MouseWheelUnit::Line => LINE_HEIGHT,
+                        egui::MouseWheelUnit::Page => rect.height(),
+                    };
+                    // egui moves the content, DOM deltas move the viewport
+                    let local = pos - origin;
+                    events.push(InputEvent::Wheel {
+                        x: local.x,
+                        y: local.y,
+                        delta_x: -delta.x * scale,
+                        delta_y: -delta.y * scale,
+                        modifiers: modifiers(*mods),
+                    });
+                }
+                egui::Event::Key {
+                    key,
+                    physical_key,
+                    pressed,
+                    modifiers: mods,
+                    ..
+                } if keyboard => {
+                    let key_value = key_value(*key, mods.shift);
+                    let code = key_code(physical_key.unwrap_or(*key));
+                    let modifiers = modifiers(*mods);
+                    events.push(if *pressed {
+                        InputEvent::KeyDown {
+                            key: key_value,
+                            code,
+                            modifiers,
+                        }
+                    } else {
+                        InputEvent::KeyUp {
+                            key: key_value,
+                            code,
+                            modifiers,
+                        }
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        // The button went up without an event reaching egui (e.g. released
+        // outside the window after it lost focus)
+        if let Some(pos) = input.pointer.latest_pos() {
+            for egui_button in pressed.extract_if(.., |held| !input.pointer.button_down(*held)) {
+                if let Some(button) = mouse_button(egui_button) {
+                    let local = pos - origin;
+                    events.push(InputEvent::PointerUp {
+                        x: local.x,
+                        y: local.y,
+                        button,
+                        modifiers: modifiers(input.modifiers),
+                    });
+                }
+            }
+        }
+        events
+    });
+
+    ui.ctx()
+        .data_mut(|data| data.insert_temp(pressed_id, pressed));
+    events
+}
+
+fn modifiers(modifiers: egui::Modifiers) -> Modifiers {
+    Modifiers {
+        alt: modifiers.alt,
+        ctrl: modifiers.ctrl,
+        shift: modifiers.shift,
+        meta: modifiers.mac_cmd,
+    }
+}
+
+fn mouse_button(button: egui::PointerButton) -> Option<MouseButton> {
+    match button {
+        egui::PointerButton::Primary => Some(MouseButton::Primary),
+        egui::PointerButton::Secondary => Some(MouseButton::Secondary),
+        egui::PointerButton::Middle => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+/// `KeyboardEvent.key`: the character for printable keys, otherwise the key name
+fn key_value(key: egui::Key, shift: bool) -> String {
+    let name = key.name();
+    match key {
+        egui::Key::ArrowDown
+        | egui::Key::ArrowLeft
+        | egui::Key::ArrowRight
+        | egui::Key::ArrowUp => {
+            format!("Arrow{name}")
+        }
+        egui::Key::Space => " ".to_string(),
+        egui::Key::Minus => "-".to_string(),
+        _ if name.len() == 1 && name.chars().all(|c| c.is_ascii_alphabetic()) => {
+            if shift {
+                name.to_string()
+            } else {
+                name.to_lowercase()
+            }
+        }
+        _ => {
+            let symbol = key.symbol_or_name();
+            if symbol.chars().count() == 1 {
+                symbol.to_string()
+            } else {
+                name.to_string()
+            }
+        }
+    }
+}
+
+/// `KeyboardEvent.code`: the physical key (`KeyA`, `Digit1`, `ArrowUp`)
+fn key_code(key: egui::Key) -> String {
+    let name = key.name();
+    match key {
+        egui::Key::ArrowDown
+        | egui::Key::ArrowLeft
+        | egui::Key::ArrowRight
+        | egui::Key::ArrowUp => {
+            format!("Arrow{name}")
+        }
+        _ if name.len() == 1 && name.chars().all(|c| c.is_ascii_alphabetic()) => {
+            format!("Key{name}")
+        }
+        _ if name.len() == 1 && name.chars().all(|c| c.is_ascii_digit()) => {
+            format!("Digit{name}")
+        }
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs one frame with `events`; the 200×100 page canvas sits at the window origin
+    fn frame(ctx: &egui::Context, events: Vec<egui::Event>) -> Vec<InputEvent> {
+        let input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(400.0, 300.0),
+            )),
+            events,
+            ..Default::default()
+        };
+        let mut collected = Vec::new();
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::NONE)
+                .show(ctx, |ui| {
+                    let (response, _) =
+                        ui.allocate_painter(egui::vec2(200.0, 100.0), egui::Sense::click());
+                    collected = collect_input(ui, &response);
+                });
+        });
+        collected
+    }
+
+    fn button(x: f32, y: f32, pressed: bool) -> egui::Event {
+        egui::Event::PointerButton {
+            pos: egui::pos2(x, y),
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::default(),
+        }
+    }
+
+    fn releases(events: &[InputEvent]) -> Vec<(f32, f32)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                InputEvent::PointerUp { x, y, .. } => Some((*x, *y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn release_outside_canvas_reaches_page_after_press_on_it() {
+        let ctx = egui::Context::default();
+        frame(&ctx, Vec::new());
+
+        let pressed = frame(
+            &ctx,
+            vec![
+                egui::Event::PointerMoved(egui::pos2(50.0, 50.0)),
+                button(50.0, 50.0, true),
+            ],
+        );
+        assert!(
+            pressed
+                .iter()
+                .any(|event| matches!(event, InputEvent::PointerDown { .. }))
+        );
+
+        // Dragged over the DevTools panel and released there
+        let released = frame(
+            &ctx,
+            vec![
+                egui::Event::PointerMoved(egui::pos2(300.0, 250.0)),
+                button(300.0, 250.0, false),
+            ],
+        );
+        assert_eq!(releases(&released), [(300.0, 250.0)]);
+
+        // The button is no longer held: later releases elsewhere stay with egui
+        let elsewhere = frame(
+            &ctx,
+            vec![button(300.0, 250.0, true), button(300.0, 250.0, false)],
+        );
+        assert!(elsewhere.is_empty(), "{elsewhere:?}");
+    }
+}
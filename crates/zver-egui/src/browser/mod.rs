@@ -1,5 +1,6 @@
 pub mod address_bar;
 pub mod devtools;
+pub mod input;
 pub mod render_view;
 /// Browser module exports
 ///
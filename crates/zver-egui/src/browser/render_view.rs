@@ -1,12 +1,18 @@
-use crate::egui_integration::render_clean_layout_from_results;
+use super::input::collect_input;
+use super::tab::TabStatus;
+use crate::egui_integration::{PageResources, page_textures, render_clean_display_list};
 /// Render view component for clean page rendering
 ///
 /// Implements TRIZ principle of "Obedinenie" (Merging) where layout results
-/// are combined with rendering logic in a unified, scrollable viewport
+/// are combined with rendering logic in a unified viewport that the engine
+/// scrolls itself
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 use zver::Zver;
-use zver::layout::RenderInfo;
+use zver::dom::Document;
+use zver::layout::{LayoutEngine, RenderInfo};
+use zver::navigation::NavigationRequest;
+use zver::render::hit_test::hit_test;
 
 /// Render view for displaying page content
 pub struct RenderView;
@@ -19,17 +25,34 @@ impl RenderView {
     /// * `engine` - The Zver engine to render from
     /// * `runtime` - Tokio runtime for async operations
     /// * `show_debug_overlays` - Whether to show debug information
+    /// * `status` - Status of the tab, set to an error when the engine fails
+    ///   to resize the viewport or handle input
+    ///
+    /// # Returns
+    /// The navigation requested by a link or form during this frame, unless
+    /// page scripts cancelled it
     pub fn render(
         ui: &mut egui::Ui,
         engine: &Arc<Zver>,
         runtime: &Arc<Runtime>,
         _show_debug_overlays: bool,
         highlighted_node: Option<usize>,
-    ) {
-        runtime.block_on(async {
+        status: &mut TabStatus,
+    ) -> Option<NavigationRequest> {
+        Self::sync_viewport(ui, engine, runtime, status);
+
+        let input_events = runtime.block_on(async {
             let layout = engine.layout.read().await;
             let dom = engine.dom.read().await;
-            let resolved_styles = layout.resolved_styles().clone();
+            let render = engine.render.read().await;
+            let display_list = render.display_list().clone();
+            let textures = page_textures(ui.ctx(), &display_list, &render);
+            drop(render);
+            let css = engine.css.read().await;
+            let resources = PageResources {
+                fonts: &css.fonts,
+                textures: &textures,
+            };
 
             let render_info = layout.collect_render_info(&dom);
 
@@ -39,35 +62,93 @@ impl RenderView {
                 ui.centered_and_justified(|ui| {
                     ui.label("No content loaded. Enter a URL and press Load.");
                 });
-                return;
+                return Vec::new();
             }
 
-            // Calculate content dimensions
-            let (canvas_width, canvas_height) = Self::calculate_content_size(&render_info);
+            // The engine scrolls the document and nested containers itself:
+            // the canvas is exactly the viewport, and wheel and keys go to the page
+            let (response, painter) =
+                ui.allocate_painter(ui.available_size(), egui::Sense::click());
+            let painter = painter.with_clip_rect(response.rect.intersect(ui.clip_rect()));
 
-            drop(layout);
-            drop(dom);
+            // White background for clean render
+            painter.rect_filled(response.rect, 0.0, egui::Color32::WHITE);
 
-            // Render in scrollable area with white background
-            egui::ScrollArea::both().auto_shrink(false).show(ui, |ui| {
-                let (response, painter) = ui.allocate_painter(
-                    egui::vec2(canvas_width, canvas_height),
-                    egui::Sense::hover(),
-                );
+            // Render layout results
+            render_clean_display_list(
+                &painter,
+                response.rect.min,
+                &display_list,
+                &resources,
+                &render_info,
+                highlighted_node,
+            );
 
-                // White background for clean render
-                painter.rect_filled(response.rect, 0.0, egui::Color32::WHITE);
+            let pointer = response
+                .hover_pos()
+                .map(|pos| pos - response.rect.min.to_vec2());
+            let hovered_link = pointer.and_then(|pos| Self::link_at(&layout, &dom, pos));
+            if hovered_link.is_some() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
 
-                // Render layout results
-                render_clean_layout_from_results(
-                    &painter,
-                    response.rect.min,
-                    &render_info,
-                    &resolved_styles,
-                    highlighted_node,
-                );
-            });
+            collect_input(ui, &response)
         });
+
+        // Dispatched after the read guards above are released: the engine
+        // takes DOM and JS write locks while handling input. The restyled
+        // page is shown on the next frame
+        if !input_events.is_empty() {
+            ui.ctx().request_repaint();
+        }
+        let mut navigation = None;
+        for event in input_events {
+            match runtime.block_on(engine.dispatch_input(event)) {
+                Ok(outcome) => navigation = outcome.navigation.or(navigation),
+                Err(err) => *status = TabStatus::Error(format!("Input handling failed: {}", err)),
+            }
+        }
+
+        navigation
+    }
+
+    /// Resizes the engine viewport to the panel so media queries and `resize` follow the window
+    fn sync_viewport(
+        ui: &egui::Ui,
+        engine: &Arc<Zver>,
+        runtime: &Arc<Runtime>,
+        status: &mut TabStatus,
+    ) {
+        let available = ui.available_size();
+        if available.x < 1.0 || available.y < 1.0 {
+            return;
+        }
+
+        runtime.block_on(async {
+            let (width, height) = engine.layout.read().await.viewport_size();
+            if (width - available.x).abs() < 1.0 && (height - available.y).abs() < 1.0 {
+                return;
+            }
+            if let Err(err) = engine
+                .set_viewport(available.x, available.y, ui.ctx().pixels_per_point())
+                .await
+            {
+                *status = TabStatus::Error(format!("Viewport resize failed: {}", err));
+            }
+        });
+    }
+
+    /// Returns the `href` of the link under the point, using the engine's hit testing
+    fn link_at(layout: &LayoutEngine, dom: &Document, pos: egui::Pos2) -> Option<String> {
+        let hit = hit_test(layout, dom, pos.x, pos.y)?;
+        hit.path().find_map(|id| {
+            let node = dom.nodes.get(&id)?;
+            if node.tag_name() == Some("a") {
+                node.attributes.get("href").cloned()
+            } else {
+                None
+            }
+        })
     }
 
     /// Calculates the content size from render info
@@ -113,7 +194,15 @@ impl RenderView {
         runtime.block_on(async {
             let layout = engine.layout.read().await;
             let dom = engine.dom.read().await;
-            let resolved_styles = layout.resolved_styles().clone();
+            let render = engine.render.read().await;
+            let display_list = render.display_list().clone();
+            let textures = page_textures(ui.ctx(), &display_list, &render);
+            drop(render);
+            let css = engine.css.read().await;
+            let resources = PageResources {
+                fonts: &css.fonts,
+                textures: &textures,
+            };
 
             let render_info = layout.collect_render_info(&dom);
 
@@ -159,8 +248,9 @@ impl RenderView {
                 render_layout_results_in_painter(
                     &painter,
                     response.rect.min,
+                    &display_list,
+                    &resources,
                     &render_info,
-                    &resolved_styles,
                     true, // show_debug = true
                     highlighted_node,
                 );
@@ -4,7 +4,9 @@
 /// maintains independent state and can be added/removed dynamically
 use std::sync::Arc;
 use tokio::runtime::Runtime;
-use zver::Zver;
+use zver::navigation::{NavigationRequest, NavigationTarget};
+use zver::network::Url;
+use zver::{Zver, ZverError, ZverResult};
 
 /// Status of a browser tab
 #[derive(Debug, Clone, PartialEq)]
@@ -41,7 +43,7 @@ pub struct Tab {
 impl Tab {
     /// Выполняет JS в контексте движка вкладки.
     #[allow(dead_code)]
-    pub fn eval_js(&self, code: &str) -> Result<zver::js::JSValue, String> {
+    pub fn eval_js(&self, code: &str) -> ZverResult<zver::js::JSValue> {
         self.engine.eval_js(code)
     }
     /// Creates a new tab with a fresh engine instance
@@ -67,27 +69,67 @@ impl Tab {
     /// * `url` - The URL to load
     /// * `runtime` - Tokio runtime for blocking async operations
     pub fn load_url(&mut self, url: String, runtime: &Arc<Runtime>, track_history: bool) {
+        // Normalize to an absolute URL so history and relative links agree
+        let url = Url::parse(&url).map(String::from).unwrap_or(url);
+        self.begin_load(url.clone(), track_history);
+
+        let engine = Arc::clone(&self.engine);
+        let result = runtime.block_on(async move { engine.load_url(&url).await });
+        self.finish_load(result);
+    }
+
+    /// Performs a navigation requested by a link or form on the page
+    ///
+    /// POST submissions are recorded in history by their URL, so going back
+    /// and forward reloads that URL with GET.
+    ///
+    /// # Arguments
+    /// * `request` - Navigation produced by the engine for a click or submit
+    /// * `runtime` - Tokio runtime for blocking async operations
+    pub fn navigate(&mut self, request: NavigationRequest, runtime: &Arc<Runtime>) {
+        if request.body.is_none() {
+            self.load_url(request.url.into(), runtime, true);
+            return;
+        }
+        self.begin_load(request.url.to_string(), true);
+
+        let engine = Arc::clone(&self.engine);
+        let result = runtime.block_on(async move { engine.navigate(&request).await });
+        self.finish_load(result);
+    }
+
+    /// Records the new URL in history and marks the tab as loading
+    fn begin_load(&mut self, url: String, track_history: bool) {
         if track_history && !self.url.is_empty() {
             self.back_stack.push(self.url.clone());
             self.forward_stack.clear();
         }
-        self.url = url.clone();
-        self.status = TabStatus::Loading;
         self.title = Self::extract_title_from_url(&url);
+        self.url = url;
+        self.status = TabStatus::Loading;
+    }
 
-        let engine = Arc::clone(&self.engine);
-        let result = runtime.block_on(async move { engine.load_url(&url).await });
-
+    fn finish_load(&mut self, result: ZverResult<()>) {
         match result {
             Ok(_) => {
                 self.status = TabStatus::Loaded;
             }
             Err(e) => {
-                self.status = TabStatus::Error(format!("{}", e));
+                self.status = TabStatus::Error(Self::describe_load_error(&e));
             }
         }
     }
 
+    /// Formats a load failure for the error page, hinting when a retry may help
+    fn describe_load_error(error: &ZverError) -> String {
+        let hint = match error {
+            ZverError::Network(_) if error.is_recoverable() => " (try reloading the page)",
+            ZverError::Network(_) => " (check the address)",
+            _ => "",
+        };
+        format!("{}{}", error, hint)
+    }
+
     /// Extracts a display title from a URL
     ///
     /// # Arguments
@@ -100,25 +142,25 @@ impl Tab {
             return "New Tab".to_string();
         }
 
-        // Extract filename from file:// URLs
-        if let Some(path) = url.strip_prefix("file://")
-            && let Some(filename) = path.split(['/', '\\']).next_back()
-        {
-            return filename.to_string();
-        }
-
-        // Extract domain from http(s):// URLs
-        if let Some(rest) = url
-            .strip_prefix("http://")
-            .or_else(|| url.strip_prefix("https://"))
-            && let Some(domain) = rest.split('/').next()
-        {
-            return domain.to_string();
+        if let Ok(parsed) = Url::parse(url) {
+            // Filename for file:// URLs, host for http(s)://
+            let title = if parsed.is_file() {
+                parsed.file_name()
+            } else if parsed.is_http() {
+                parsed.host_str().map(str::to_string)
+            } else if parsed.is_about_blank() {
+                Some(parsed.to_string())
+            } else {
+                None
+            };
+            if let Some(title) = title {
+                return title;
+            }
         }
 
         // Fallback to truncated URL
-        if url.len() > 30 {
-            format!("{}...", &url[..27])
+        if url.chars().count() > 30 {
+            format!("{}...", url.chars().take(27).collect::<String>())
         } else {
             url.to_string()
         }
@@ -232,7 +274,7 @@ impl TabManager {
     #[allow(dead_code)]
     pub fn eval_in_active_tab(&self, code: &str) -> Result<zver::js::JSValue, String> {
         if let Some(tab) = self.get_active_tab() {
-            tab.eval_js(code)
+            tab.eval_js(code).map_err(|e| e.to_string())
         } else {
             Err("No active tab".to_string())
         }
@@ -300,6 +342,23 @@ impl TabManager {
         }
     }
 
+    /// Performs a navigation requested by the page in the active tab
+    ///
+    /// Requests with [`NavigationTarget::Blank`] open in a new tab; when the
+    /// tab limit is reached they replace the active page instead.
+    ///
+    /// # Arguments
+    /// * `request` - Navigation produced by a link click or form submission
+    pub fn navigate_from_active_tab(&mut self, request: NavigationRequest) {
+        let runtime = Arc::clone(&self.runtime);
+        if request.target == NavigationTarget::Blank {
+            self.add_tab();
+        }
+        if let Some(tab) = self.get_active_tab_mut() {
+            tab.navigate(request, &runtime);
+        }
+    }
+
     /// Reloads the active tab
     pub fn reload_active_tab(&mut self) {
         let runtime = Arc::clone(&self.runtime);
@@ -1,62 +1,275 @@
 use std::collections::HashMap;
-
-use zver::css::color::{Color, get_default_color_for_tag, parse_css_color};
+use std::sync::Arc;
+use zver::css::color::{Color, get_default_color_for_tag};
+use zver::css::fonts::{self, LoadedFont};
 use zver::layout::RenderInfo;
 use zver::layout::render::get_debug_info;
-use zver::layout::types::{ComputedStyle, FontStyle, FontWeight};
+use zver::layout::types::{FontStyle, FontWeight};
+use zver::render::{DisplayItem, DisplayList, Framebuffer, Rect as ZverRect, RenderEngine};
+
+/// Ресурсы страницы, с которыми исполняется display list
+pub struct PageResources<'a> {
+    /// Загруженные @font-face: текст рисуется теми же начертаниями, которыми его измерил движок
+    pub fonts: &'a [LoadedFont],
+    /// Текстуры `<img>` по абсолютному URL, см. [`page_textures`]
+    pub textures: &'a HashMap<String, egui::TextureHandle>,
+}
 
 /// Конвертирует Color из движка в egui::Color32
 pub fn color_to_egui(color: Color) -> egui::Color32 {
     egui::Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
 }
 
-/// Визуальный рендеринг layout результатов в egui с использованием painter
+fn rect_to_egui(offset: egui::Pos2, rect: &ZverRect) -> egui::Rect {
+    egui::Rect::from_min_size(
+        egui::pos2(offset.x + rect.x, offset.y + rect.y),
+        egui::vec2(rect.width.max(0.0), rect.height.max(0.0)),
+    )
+}
+
+/// Семейство egui с начертанием страницы; `face == None` — встроенный DejaVu Sans.
+///
+/// Шрифт регистрируется в egui под ключом из семейства, веса и стиля и становится
+/// доступен со следующего кадра — до тех пор возвращается `None`.
+fn page_font_family(ctx: &egui::Context, face: Option<&LoadedFont>) -> Option<egui::FontFamily> {
+    use egui::epaint::text::{FontInsert, FontPriority, InsertFontFamily};
+
+    let name = match face {
+        Some(font) => format!(
+            "zver:{}:{}:{}",
+            font.face.family.to_lowercase(),
+            font.face.weight.to_numeric(),
+            font.face.style
+        ),
+        None => "zver:default".to_string(),
+    };
+    let family = egui::FontFamily::Name(name.as_str().into());
+    if ctx.fonts(|fonts| fonts.definitions().families.contains_key(&family)) {
+        return Some(family);
+    }
+
+    let data = match face {
+        Some(font) => egui::FontData::from_owned(font.data.as_deref()?.clone()),
+        None => egui::FontData::from_static(fonts::DEFAULT_FONT_DATA),
+    };
+    ctx.add_font(FontInsert::new(
+        &name,
+        data,
+        vec![InsertFontFamily {
+            family,
+            priority: FontPriority::Highest,
+        }],
+    ));
+    ctx.request_repaint();
+    None
+}
+
+/// Загружает в egui изображения display list и возвращает их текстуры по `src`.
+///
+/// Текстура переживает кадр, пока в display list остаётся то же декодированное
+/// изображение; текстуры ушедших со страницы изображений освобождаются.
+pub fn page_textures(
+    ctx: &egui::Context,
+    display_list: &DisplayList,
+    render: &RenderEngine,
+) -> HashMap<String, egui::TextureHandle> {
+    type Cache = HashMap<String, (Arc<Framebuffer>, egui::TextureHandle)>;
+
+    let id = egui::Id::new("zver_page_textures");
+    let previous: Cache = ctx
+        .data_mut(|data| data.remove_temp(id))
+        .unwrap_or_default();
+    let mut cache = Cache::new();
+
+    for item in display_list {
+        let DisplayItem::Image { src, .. } = item else {
+            continue;
+        };
+        if cache.contains_key(src) {
+            continue;
+        }
+        let Some(image) = render.image(src) else {
+            continue;
+        };
+        let texture = match previous.get(src) {
+            Some((uploaded, texture)) if Arc::ptr_eq(uploaded, &image) => texture.clone(),
+            _ => ctx.load_texture(
+                src.as_str(),
+                egui::ColorImage::from_rgba_unmultiplied(
+                    [image.width() as usize, image.height() as usize],
+                    image.pixels(),
+                ),
+                egui::TextureOptions::LINEAR,
+            ),
+        };
+        cache.insert(src.clone(), (image, texture));
+    }
+
+    let textures = cache
+        .iter()
+        .map(|(src, (_, texture))| (src.clone(), texture.clone()))
+        .collect();
+    ctx.data_mut(|data| data.insert_temp(id, cache));
+    textures
+}
+
+/// Исполняет display list движка через egui painter.
+///
+/// Порядок, обрезка и прозрачность уже решены движком — здесь команды
+/// лишь переводятся в примитивы egui.
+pub fn paint_display_list(
+    painter: &egui::Painter,
+    offset: egui::Pos2,
+    display_list: &DisplayList,
+    resources: &PageResources<'_>,
+) {
+    use egui::text::{LayoutJob, TextFormat};
+    use egui::{FontFamily, FontId, Vec2};
+
+    // Стек painter'ов: PushClip/PushOpacity создают производный painter
+    let mut painters = vec![painter.clone()];
+
+    for item in display_list {
+        let current = painters.last().unwrap_or(painter).clone();
+
+        match item {
+            DisplayItem::FillRect { rect, color, .. } => {
+                current.rect_filled(rect_to_egui(offset, rect), 0.0, color_to_egui(*color));
+            }
+            DisplayItem::StrokeBorder {
+                rect,
+                widths,
+                color,
+                ..
+            } => {
+                let color = color_to_egui(*color);
+                let inner_height = rect.height - widths.top - widths.bottom;
+                let sides = [
+                    ZverRect::new(rect.x, rect.y, rect.width, widths.top),
+                    ZverRect::new(
+                        rect.x,
+                        rect.y + rect.height - widths.bottom,
+                        rect.width,
+                        widths.bottom,
+                    ),
+                    ZverRect::new(rect.x, rect.y + widths.top, widths.left, inner_height),
+                    ZverRect::new(
+                        rect.x + rect.width - widths.right,
+                        rect.y + widths.top,
+                        widths.right,
+                        inner_height,
+                    ),
+                ];
+                for side in sides {
+                    if side.width > 0.0 && side.height > 0.0 {
+                        current.rect_filled(rect_to_egui(offset, &side), 0.0, color);
+                    }
+                }
+            }
+            DisplayItem::GlyphRun {
+                rect,
+                text,
+                font,
+                color,
+                ..
+            } => {
+                let text_color = color_to_egui(*color);
+                let face = font.face(resources.fonts);
+                let family =
+                    page_font_family(painter.ctx(), face).unwrap_or(FontFamily::Proportional);
+                // Встроенный DejaVu Sans есть только в Regular — начертание синтезируем
+                let synthetic = face.is_none();
+                let format = TextFormat {
+                    font_id: FontId::new(font.size.clamp(4.0, 200.0), family),
+                    color: text_color,
+                    italics: synthetic && matches!(font.style, FontStyle::Italic),
+                    ..Default::default()
+                };
+
+                // Перенос уже выполнен движком: одна команда — одна строка
+                let job = LayoutJob::single_section(text.clone(), format);
+                let galley = current.fonts_mut(|fonts| fonts.layout_job(job));
+                let text_pos = egui::pos2(offset.x + rect.x, offset.y + rect.y);
+
+                current.galley(text_pos, galley.clone(), text_color);
+                if synthetic && matches!(font.weight, FontWeight::Bold) {
+                    current.galley(text_pos + Vec2::new(0.6, 0.0), galley, text_color);
+                }
+            }
+            DisplayItem::Image { rect, src, .. } => {
+                // Изображение растягивается на прямоугольник элемента, как в движке
+                if let Some(texture) = resources.textures.get(src) {
+                    current.image(
+                        texture.id(),
+                        rect_to_egui(offset, rect),
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+                }
+            }
+            DisplayItem::PushClip { rect } => {
+                painters.push(current.with_clip_rect(rect_to_egui(offset, rect)));
+            }
+            DisplayItem::PushOpacity { opacity } => {
+                let mut layer = current;
+                layer.multiply_opacity(*opacity);
+                painters.push(layer);
+            }
+            DisplayItem::PopClip | DisplayItem::PopOpacity => {
+                if painters.len() > 1 {
+                    painters.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Визуальный рендеринг страницы с отладочными рамками и подписями узлов
 #[allow(dead_code)]
 pub fn render_layout_results_in_painter(
     painter: &egui::Painter,
     offset: egui::Pos2,
+    display_list: &DisplayList,
+    resources: &PageResources<'_>,
     render_info: &[RenderInfo],
-    resolved_styles: &HashMap<usize, ComputedStyle>,
     show_debug: bool,
     highlighted_node: Option<usize>,
 ) {
-    use egui::{Color32, FontFamily, FontId, Rect, Stroke, Vec2};
+    use egui::{Color32, Rect, Stroke, Vec2};
 
-    struct DebugOverlay {
-        rect: Rect,
-        label: String,
+    // Подложка по тегам, чтобы были видны блоки без собственного фона
+    if show_debug {
+        for info in render_info {
+            if info.node.tag_name.is_some() && info.should_render() {
+                let rect = Rect::from_min_size(
+                    egui::pos2(offset.x + info.layout.x, offset.y + info.layout.y),
+                    Vec2::new(info.layout.width, info.layout.height),
+                );
+                painter.rect_filled(
+                    rect,
+                    0.0,
+                    color_to_egui(get_default_color_for_tag(&info.node.tag_name)),
+                );
+            }
+        }
     }
 
-    let mut debug_overlays = Vec::new();
-
-    for info in render_info {
-        let width = info.layout.width;
-        let height = info.layout.height;
+    paint_display_list(painter, offset, display_list, resources);
 
-        if width <= 0.0 || height <= 0.0 {
-            continue;
-        }
-
-        let style = resolved_styles.get(&info.layout.node_id);
-        let background_color = style
-            .and_then(|s| s.background_color.as_deref())
-            .and_then(parse_css_color)
-            .map(color_to_egui)
-            .or_else(|| {
-                show_debug.then(|| color_to_egui(get_default_color_for_tag(&info.node.tag_name)))
-            })
-            .unwrap_or(Color32::TRANSPARENT);
+    if show_debug {
+        for info in render_info {
+            let width = info.layout.width;
+            let height = info.layout.height;
 
-        let rect = Rect::from_min_size(
-            egui::pos2(offset.x + info.layout.x, offset.y + info.layout.y),
-            Vec2::new(width.max(1.0), height.max(1.0)),
-        );
+            if width <= 0.0 || height <= 0.0 {
+                continue;
+            }
 
-        if background_color != Color32::TRANSPARENT {
-            painter.rect_filled(rect, 0.0, background_color);
-        }
+            let rect = Rect::from_min_size(
+                egui::pos2(offset.x + info.layout.x, offset.y + info.layout.y),
+                Vec2::new(width.max(1.0), height.max(1.0)),
+            );
 
-        if show_debug {
             painter.rect_stroke(
                 rect,
                 0.0,
@@ -71,177 +284,34 @@ pub fn render_layout_results_in_painter(
                     width as i32,
                     height as i32
                 );
-                debug_overlays.push(DebugOverlay { rect, label });
+                let label_width = (label.len() as f32 * 6.0 + 10.0).min(rect.width());
+                let debug_bg = Rect::from_min_size(rect.min, Vec2::new(label_width, 14.0));
+                painter.rect_filled(debug_bg, 0.0, Color32::from_black_alpha(96));
+
+                painter.text(
+                    rect.min + Vec2::new(2.0, 2.0),
+                    egui::Align2::LEFT_TOP,
+                    label,
+                    egui::FontId::proportional(9.0),
+                    Color32::WHITE,
+                );
             }
         }
     }
 
-    for info in render_info {
-        if info.node.tag_name.is_some() {
-            continue;
-        }
-
-        let width = info.layout.width;
-        let height = info.layout.height;
-        if width <= 0.0 || height <= 0.0 {
-            continue;
-        }
-
-        let text_content = info.node.text_content.clone().unwrap_or_default();
-        let trimmed = text_content.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        let style = resolved_styles.get(&info.layout.node_id);
-        let text_color = style
-            .and_then(|s| s.color.as_deref())
-            .and_then(parse_css_color)
-            .map(color_to_egui)
-            .unwrap_or(Color32::BLACK);
-        let font_size = style.map(|s| s.font_size).unwrap_or(16.0).clamp(8.0, 72.0);
-        let font_family = FontFamily::Proportional;
-        let font_id = FontId::new(font_size, font_family);
-        let is_bold = style
-            .map(|s| matches!(s.font_weight, FontWeight::Bold))
-            .unwrap_or(false);
-
-        use egui::text::{LayoutJob, TextFormat};
-
-        let format = TextFormat {
-            font_id,
-            color: text_color,
-            italics: style
-                .map(|s| matches!(s.font_style, FontStyle::Italic))
-                .unwrap_or(false),
-            ..Default::default()
-        };
-
-        let mut job = LayoutJob::single_section(trimmed.to_owned(), format);
-        // Используем content_width для правильного переноса текста
-        job.wrap.max_width = info.layout.content_width.max(1.0);
-
-        let galley = painter.fonts_mut(|fonts| fonts.layout_job(job));
-
-        // Используем content_x/content_y для позиционирования текста внутри контентной области
-        let text_pos = egui::pos2(
-            offset.x + info.layout.content_x + 2.0,
-            offset.y + info.layout.content_y + 2.0,
-        );
-        painter.galley(text_pos, galley.clone(), text_color);
-        if is_bold {
-            painter.galley(text_pos + Vec2::new(0.6, 0.0), galley, text_color);
-        }
-    }
-
-    if show_debug {
-        for overlay in debug_overlays {
-            let width = (overlay.label.len() as f32 * 6.0 + 10.0).min(overlay.rect.width());
-            let debug_bg = Rect::from_min_size(overlay.rect.min, Vec2::new(width, 14.0));
-            painter.rect_filled(debug_bg, 0.0, Color32::from_black_alpha(96));
-
-            let text_pos = overlay.rect.min + Vec2::new(2.0, 2.0);
-            painter.text(
-                text_pos,
-                egui::Align2::LEFT_TOP,
-                overlay.label,
-                egui::FontId::proportional(9.0),
-                Color32::WHITE,
-            );
-        }
-    }
-
     draw_selected_highlight_overlay(painter, offset, render_info, highlighted_node);
 }
 
 /// Чистовой рендеринг без отладочной информации
-pub fn render_clean_layout_from_results(
+pub fn render_clean_display_list(
     painter: &egui::Painter,
     offset: egui::Pos2,
+    display_list: &DisplayList,
+    resources: &PageResources<'_>,
     render_info: &[RenderInfo],
-    resolved_styles: &HashMap<usize, ComputedStyle>,
     highlighted_node: Option<usize>,
 ) {
-    use egui::text::{LayoutJob, TextFormat};
-    use egui::{Color32, FontFamily, FontId, Rect, Vec2};
-
-    for info in render_info {
-        let width = info.layout.width;
-        let height = info.layout.height;
-
-        if width <= 0.0 || height <= 0.0 {
-            continue;
-        }
-
-        if let Some(style) = resolved_styles.get(&info.layout.node_id)
-            && let Some(color) = style
-                .background_color
-                .as_deref()
-                .and_then(parse_css_color)
-                .map(color_to_egui)
-        {
-            let rect = Rect::from_min_size(
-                egui::pos2(offset.x + info.layout.x, offset.y + info.layout.y),
-                Vec2::new(width.max(1.0), height.max(1.0)),
-            );
-            painter.rect_filled(rect, 0.0, color);
-        }
-    }
-
-    for info in render_info {
-        if info.node.tag_name.is_some() {
-            continue;
-        }
-
-        let width = info.layout.width;
-        let height = info.layout.height;
-        if width <= 0.0 || height <= 0.0 {
-            continue;
-        }
-
-        let text_content = info.node.text_content.clone().unwrap_or_default();
-        let trimmed = text_content.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
-
-        let style = resolved_styles.get(&info.layout.node_id);
-        let text_color = style
-            .and_then(|s| s.color.as_deref())
-            .and_then(parse_css_color)
-            .map(color_to_egui)
-            .unwrap_or(Color32::BLACK);
-        let font_size = style.map(|s| s.font_size).unwrap_or(16.0).clamp(8.0, 72.0);
-        let is_bold = style
-            .map(|s| matches!(s.font_weight, FontWeight::Bold))
-            .unwrap_or(false);
-
-        let format = TextFormat {
-            font_id: FontId::new(font_size, FontFamily::Proportional),
-            color: text_color,
-            italics: style
-                .map(|s| matches!(s.font_style, FontStyle::Italic))
-                .unwrap_or(false),
-            ..Default::default()
-        };
-
-        let mut job = LayoutJob::single_section(trimmed.to_owned(), format);
-        // Используем content_width для правильного переноса текста
-        job.wrap.max_width = info.layout.content_width.max(1.0);
-
-        let galley = painter.fonts_mut(|fonts| fonts.layout_job(job));
-        // Используем content_x/content_y для позиционирования текста внутри контентной области
-        let text_pos = egui::pos2(
-            offset.x + info.layout.content_x + 2.0,
-            offset.y + info.layout.content_y + 2.0,
-        );
-
-        painter.galley(text_pos, galley.clone(), text_color);
-        if is_bold {
-            painter.galley(text_pos + Vec2::new(0.6, 0.0), galley, text_color);
-        }
-    }
-
+    paint_display_list(painter, offset, display_list, resources);
     draw_selected_highlight_overlay(painter, offset, render_info, highlighted_node);
 }
 
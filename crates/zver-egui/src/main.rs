@@ -108,23 +108,34 @@ impl eframe::App for ZverBrowser {
 
         // Central panel: Render view
         egui::CentralPanel::default().show(ctx, |ui| {
-            if let Some(tab) = self.tab_manager.get_active_tab() {
+            let mut navigation = None;
+            if let Some(tab) = self.tab_manager.get_active_tab_mut() {
                 ui.heading(format!("{} {}", regular::GLOBE, tab.title));
                 ui.label(format!("Status: {:?}", tab.status));
                 ui.separator();
 
-                RenderView::render(
+                navigation = RenderView::render(
                     ui,
                     &tab.engine,
                     &self.runtime,
                     self.devtools.show_debug_overlays(),
                     self.devtools.selected_node_id(),
+                    &mut tab.status,
                 );
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.label("No active tab");
                 });
             }
+
+            if let Some(request) = navigation {
+                let message = match request.body {
+                    Some(_) => format!("Submitted form: POST {}", request.url),
+                    None => format!("Navigated to: {}", request.url),
+                };
+                self.tab_manager.navigate_from_active_tab(request);
+                self.sync_active_tab_after_navigation(&message);
+            }
         });
     }
 }
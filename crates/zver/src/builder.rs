@@ -0,0 +1,164 @@
+//! Настройка движка до загрузки страницы.
+//!
+//! [`ZverBuilder`] собирает параметры устройства и сети в одном месте и
+//! раздаёт их всем подсистемам: размеры viewport видят и layout, и @media,
+//! и скрипты (`innerWidth`), а User-Agent одинаков у [`network::NetworkEngine`],
+//! [`resource_loader::ResourceLoader`] и `navigator.userAgent`.
+
+use crate::clock::Clock;
+use crate::css::media_queries::MediaType;
+use crate::network::NetworkConfig;
+use crate::{Zver, css, dom, js, layout, network, render, resource_loader};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Построитель [`Zver`] с заданными viewport, сетью, JS и часами.
+///
+/// # Примеры
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use zver::Zver;
+/// use zver::clock::Clock;
+/// use zver::css::media_queries::MediaType;
+///
+/// let engine = Zver::builder()
+///     .viewport(375.0, 667.0)
+///     .device_pixel_ratio(2.0)
+///     .media_type(MediaType::Screen)
+///     .user_agent("Mozilla/5.0 (iPhone) Zver")
+///     .accept_language("ru-RU,ru;q=0.9")
+///     .timeout(Duration::from_secs(5))
+///     .javascript_enabled(false)
+///     .clock(Clock::new_virtual())
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZverBuilder {
+    viewport_width: f32,
+    viewport_height: f32,
+    device_pixel_ratio: f32,
+    media_type: MediaType,
+    network: NetworkConfig,
+    javascript_enabled: bool,
+    clock: Clock,
+}
+
+impl Default for ZverBuilder {
+    fn default() -> Self {
+        Self {
+            viewport_width: 800.0,
+            viewport_height: 600.0,
+            device_pixel_ratio: 1.0,
+            media_type: MediaType::Screen,
+            network: NetworkConfig::default(),
+            javascript_enabled: true,
+            clock: Clock::real(),
+        }
+    }
+}
+
+impl ZverBuilder {
+    /// Настройки по умолчанию: viewport 800x600, `screen`, JS включён, реальные часы
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Размер viewport в CSS-пикселях
+    pub fn viewport(mut self, width: f32, height: f32) -> Self {
+        self.viewport_width = width;
+        self.viewport_height = height;
+        self
+    }
+
+    /// Физических пикселей на CSS-пиксель; неположительные значения игнорируются
+    pub fn device_pixel_ratio(mut self, device_pixel_ratio: f32) -> Self {
+        if device_pixel_ratio > 0.0 {
+            self.device_pixel_ratio = device_pixel_ratio;
+        }
+        self
+    }
+
+    /// Тип медиа для @media и атрибутов `media`
+    pub fn media_type(mut self, media_type: MediaType) -> Self {
+        self.media_type = media_type;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.network.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn accept_language(mut self, accept_language: impl Into<String>) -> Self {
+        self.network.accept_language = accept_language.into();
+        self
+    }
+
+    /// Предел на весь запрос, включая чтение тела
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.network.timeout = timeout;
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.network.connect_timeout = timeout;
+        self
+    }
+
+    /// Исполнять ли `<script>` при загрузке страницы
+    pub fn javascript_enabled(mut self, enabled: bool) -> Self {
+        self.javascript_enabled = enabled;
+        self
+    }
+
+    /// Часы страницы; см. [`Clock::new_virtual`]
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn build(self) -> Zver {
+        let (width, height, dpr) = (
+            self.viewport_width,
+            self.viewport_height,
+            self.device_pixel_ratio,
+        );
+
+        let mut css = css::StyleEngine::new();
+        css.set_viewport(width, height);
+        css.set_media_type(self.media_type);
+
+        let mut render = render::RenderEngine::new();
+        render.set_device_pixel_ratio(dpr);
+
+        let layout = layout::LayoutEngine::new(width, height);
+        let scroll = layout.scroll_offsets().clone();
+
+        let dom = Arc::new(RwLock::new(dom::Document::new()));
+        let mut js = js::JSEngine::with_clock(self.clock.clone())
+            .with_scroll(scroll.clone())
+            .with_dom(dom.clone());
+        js.set_viewport(width, height, dpr);
+        js.set_navigator(&self.network.user_agent, &self.network.accept_language);
+
+        Zver {
+            dom,
+            css: Arc::new(RwLock::new(css)),
+            layout: Arc::new(RwLock::new(layout)),
+            render: Arc::new(RwLock::new(render)),
+            network: Arc::new(RwLock::new(network::NetworkEngine::with_config(
+                &self.network,
+            ))),
+            js: Arc::new(RwLock::new(js)),
+            resource_loader: Arc::new(RwLock::new(resource_loader::ResourceLoader::with_config(
+                self.network,
+            ))),
+            clock: self.clock,
+            scroll,
+            javascript_enabled: self.javascript_enabled,
+            input: Default::default(),
+        }
+    }
+}
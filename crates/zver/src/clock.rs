@@ -0,0 +1,123 @@
+//! Часы страницы — общий источник времени движка.
+//!
+//! Одни и те же часы видят `setTimeout`/`setInterval`, `requestAnimationFrame`,
+//! `Date.now()`, `performance.now()` и выборка CSS-анимаций. Реальные часы идут
+//! сами и лишь сдвигаются вперёд в [`crate::Zver::advance`]; виртуальные стоят
+//! на месте, пока их явно не переведут, поэтому страницу можно «прокрутить»
+//! на 10 секунд без реального ожидания.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Начало отсчёта виртуальных часов по умолчанию: 2000-01-01T00:00:00Z
+const VIRTUAL_EPOCH: Duration = Duration::from_secs(946_684_800);
+
+#[derive(Debug)]
+enum ClockState {
+    Real {
+        origin: Instant,
+        wall_origin: SystemTime,
+        /// Накопленный перевод вперёд
+        skew: Duration,
+    },
+    Virtual {
+        wall_origin: SystemTime,
+        elapsed: Duration,
+    },
+}
+
+/// Часы страницы. Клоны разделяют одно состояние.
+#[derive(Debug, Clone)]
+pub struct Clock {
+    state: Arc<Mutex<ClockState>>,
+}
+
+impl Clock {
+    /// Часы, идущие в реальном времени
+    pub fn real() -> Self {
+        Self::from_state(ClockState::Real {
+            origin: Instant::now(),
+            wall_origin: SystemTime::now(),
+            skew: Duration::ZERO,
+        })
+    }
+
+    /// Виртуальные часы, стоящие на 2000-01-01T00:00:00Z до перевода вперёд
+    pub fn new_virtual() -> Self {
+        Self::virtual_at(UNIX_EPOCH + VIRTUAL_EPOCH)
+    }
+
+    /// Виртуальные часы с заданным настенным временем старта
+    pub fn virtual_at(wall_origin: SystemTime) -> Self {
+        Self::from_state(ClockState::Virtual {
+            wall_origin,
+            elapsed: Duration::ZERO,
+        })
+    }
+
+    fn from_state(state: ClockState) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ClockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        matches!(*self.state(), ClockState::Virtual { .. })
+    }
+
+    /// Монотонное время с момента создания часов
+    pub fn now(&self) -> Duration {
+        match &*self.state() {
+            ClockState::Real { origin, skew, .. } => origin.elapsed() + *skew,
+            ClockState::Virtual { elapsed, .. } => *elapsed,
+        }
+    }
+
+    /// Настенное время для `Date`
+    pub fn system_time(&self) -> SystemTime {
+        let wall_origin = match &*self.state() {
+            ClockState::Real { wall_origin, .. } | ClockState::Virtual { wall_origin, .. } => {
+                *wall_origin
+            }
+        };
+        wall_origin + self.now()
+    }
+
+    /// Переводит часы вперёд на `by`
+    pub fn advance(&self, by: Duration) {
+        match &mut *self.state() {
+            ClockState::Real { skew, .. } => *skew += by,
+            ClockState::Virtual { elapsed, .. } => *elapsed += by,
+        }
+    }
+
+    /// Переводит часы вперёд до `time`; назад часы не идут
+    pub fn advance_to(&self, time: Duration) {
+        let now = self.now();
+        if time > now {
+            self.advance(time - now);
+        }
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::real()
+    }
+}
+
+impl boa_engine::context::Clock for Clock {
+    fn now(&self) -> boa_engine::context::time::JsInstant {
+        let since_epoch = self
+            .system_time()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        boa_engine::context::time::JsInstant::new(since_epoch.as_secs(), since_epoch.subsec_nanos())
+    }
+}
@@ -9,11 +9,13 @@
 //! Спецификация: https://www.w3.org/TR/css-animations-1/
 //! Референс: https://developer.mozilla.org/en-US/docs/Web/CSS/@keyframes
 
-#[allow(unused_imports)] // ParserInput используется в тестах
 use cssparser::{ParseError, Parser, ParserInput, Token};
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
+use std::time::Duration;
 
-use super::properties::Property;
+use super::color;
+use super::properties::{self, Property};
 
 /// Один keyframe в анимации (точка во времени).
 ///
@@ -87,6 +89,7 @@ pub enum EasingFunction {
     Steps { count: u32, jump_start: bool },
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for EasingFunction {
     fn default() -> Self {
         Self::Ease
@@ -253,6 +256,7 @@ pub enum AnimationDirection {
     AlternateReverse,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for AnimationDirection {
     fn default() -> Self {
         Self::Normal
@@ -272,6 +276,7 @@ pub enum AnimationFillMode {
     Both,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for AnimationFillMode {
     fn default() -> Self {
         Self::None
@@ -311,6 +316,69 @@ impl KeyframesDefinition {
         });
     }
 
+    /// Вычисляет анимируемые свойства в точке `progress` (0.0 .. 1.0) итерации.
+    ///
+    /// `easing` применяется к каждому отрезку между соседними keyframes. Если
+    /// свойство не задано в 0% или 100%, недостающий край берётся из `base` —
+    /// вычисленного стиля элемента без анимации.
+    pub fn sample(
+        &self,
+        progress: f32,
+        easing: &EasingFunction,
+        base: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        // Значения keyframes приводятся к тому же виду, что и в каскаде
+        let steps: Vec<(f32, HashMap<String, String>)> = self
+            .steps
+            .iter()
+            .map(|step| {
+                let values = step
+                    .properties
+                    .iter()
+                    .filter_map(|p| properties::parse_property(&p.name, &p.value).ok())
+                    .flatten()
+                    .map(|p| (p.name, p.value))
+                    .collect();
+                (step.offset, values)
+            })
+            .collect();
+        let names: BTreeSet<&String> = steps.iter().flat_map(|(_, values)| values.keys()).collect();
+
+        let mut result = HashMap::new();
+        for name in names {
+            let mut frames: Vec<(f32, &str)> = steps
+                .iter()
+                .filter_map(|(offset, values)| Some((*offset, values.get(name)?.as_str())))
+                .collect();
+            if let Some(base_value) = base.get(name) {
+                if frames.first().is_some_and(|(offset, _)| *offset > 0.0) {
+                    frames.insert(0, (0.0, base_value));
+                }
+                if frames.last().is_some_and(|(offset, _)| *offset < 1.0) {
+                    frames.push((1.0, base_value));
+                }
+            }
+
+            let next_index = frames
+                .iter()
+                .position(|(offset, _)| *offset >= progress)
+                .unwrap_or(frames.len() - 1);
+            let (to_offset, to) = frames[next_index];
+            let (from_offset, from) = frames[next_index.saturating_sub(1)];
+            let range = to_offset - from_offset;
+            let local = if range > 0.0 {
+                ((progress - from_offset) / range).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+            result.insert(
+                name.clone(),
+                interpolate_value(from, to, easing.apply(local)),
+            );
+        }
+        result
+    }
+
     /// Находит два keyframe между которыми находится заданный прогресс.
     ///
     /// Возвращает (предыдущий keyframe, следующий keyframe, локальный прогресс между ними).
@@ -444,6 +512,265 @@ impl AnimationConfig {
             ..Default::default()
         }
     }
+
+    /// Собирает конфигурацию из вычисленных `animation` и `animation-*` свойств.
+    ///
+    /// Поддерживается одна анимация на элемент: из списков через запятую берётся
+    /// первый элемент. Возвращает `None` для `animation-name: none`.
+    pub fn from_properties(properties: &HashMap<String, String>) -> Option<Self> {
+        let mut config = properties
+            .get("animation")
+            .map(|value| Self::parse_shorthand(first_item(value)))
+            .unwrap_or_default();
+
+        if let Some(name) = properties.get("animation-name") {
+            config.name = first_item(name).to_string();
+        }
+        if let Some(duration) = properties
+            .get("animation-duration")
+            .and_then(|v| parse_time(first_item(v)))
+        {
+            config.duration = duration;
+        }
+        if let Some(delay) = properties
+            .get("animation-delay")
+            .and_then(|v| parse_time(first_item(v)))
+        {
+            config.delay = delay;
+        }
+        if let Some(easing) = properties
+            .get("animation-timing-function")
+            .and_then(|v| parse_easing(first_item(v)))
+        {
+            config.timing_function = easing;
+        }
+        if let Some(count) = properties
+            .get("animation-iteration-count")
+            .and_then(|v| parse_iteration_count(first_item(v)))
+        {
+            config.iteration_count = count;
+        }
+        if let Some(direction) = properties
+            .get("animation-direction")
+            .and_then(|v| parse_direction(first_item(v)))
+        {
+            config.direction = direction;
+        }
+        if let Some(fill_mode) = properties
+            .get("animation-fill-mode")
+            .and_then(|v| parse_fill_mode(first_item(v)))
+        {
+            config.fill_mode = fill_mode;
+        }
+
+        (!config.name.is_empty() && config.name != "none").then_some(config)
+    }
+
+    /// Разбирает одно значение шортката `animation`.
+    ///
+    /// Первое время — длительность, второе — задержка; нераспознанный
+    /// идентификатор считается именем анимации.
+    fn parse_shorthand(value: &str) -> Self {
+        let mut config = Self::default();
+        let mut times = 0;
+        let mut input = ParserInput::new(value);
+        let mut parser = Parser::new(&mut input);
+
+        while !parser.is_exhausted() {
+            if let Ok(easing) = parser.try_parse(EasingFunction::parse) {
+                config.timing_function = easing;
+                continue;
+            }
+            let Ok(token) = parser.next().cloned() else {
+                break;
+            };
+            match token {
+                Token::Dimension { value, unit, .. } => {
+                    let Some(seconds) = time_in_seconds(value, &unit) else {
+                        continue;
+                    };
+                    if times == 0 {
+                        config.duration = seconds;
+                    } else {
+                        config.delay = seconds;
+                    }
+                    times += 1;
+                }
+                Token::Number { value, .. } => config.iteration_count = value.max(0.0),
+                Token::Ident(ident) => {
+                    if let Some(count) = parse_iteration_count(&ident) {
+                        config.iteration_count = count;
+                    } else if let Some(direction) = parse_direction(&ident) {
+                        config.direction = direction;
+                    } else if let Some(fill_mode) = parse_fill_mode(&ident) {
+                        config.fill_mode = fill_mode;
+                    } else if !ident.eq_ignore_ascii_case("running")
+                        && !ident.eq_ignore_ascii_case("paused")
+                    {
+                        // Имена анимаций чувствительны к регистру
+                        config.name = ident.to_string();
+                    }
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Прогресс текущей итерации (0.0 .. 1.0) через `elapsed` секунд после старта
+    /// с учётом задержки, числа повторов, направления и режима заполнения.
+    ///
+    /// Возвращает `None`, когда анимация не влияет на стиль: во время задержки
+    /// без `backwards` и после завершения без `forwards`.
+    pub fn progress_at(&self, elapsed: f32) -> Option<f32> {
+        let fills_backwards = matches!(
+            self.fill_mode,
+            AnimationFillMode::Backwards | AnimationFillMode::Both
+        );
+        let fills_forwards = matches!(
+            self.fill_mode,
+            AnimationFillMode::Forwards | AnimationFillMode::Both
+        );
+
+        let active = elapsed - self.delay;
+        let total = if self.duration > 0.0 {
+            self.duration * self.iteration_count
+        } else {
+            0.0
+        };
+        let (iteration, progress) = if active < 0.0 {
+            if !fills_backwards {
+                return None;
+            }
+            (0.0, 0.0)
+        } else if active >= total {
+            if !fills_forwards {
+                return None;
+            }
+            // Конец последней (возможно, неполной) итерации
+            let count = self.iteration_count;
+            let fraction = count.fract();
+            if count > 0.0 && fraction == 0.0 {
+                (count - 1.0, 1.0)
+            } else {
+                (count.floor(), fraction)
+            }
+        } else {
+            let position = active / self.duration;
+            (position.floor(), position.fract())
+        };
+
+        let odd = iteration % 2.0 == 1.0;
+        let reversed = match self.direction {
+            AnimationDirection::Normal => false,
+            AnimationDirection::Reverse => true,
+            AnimationDirection::Alternate => odd,
+            AnimationDirection::AlternateReverse => !odd,
+        };
+        Some(if reversed { 1.0 - progress } else { progress })
+    }
+}
+
+/// CSS-анимация, назначенная элементу.
+#[derive(Debug, Clone)]
+pub struct RunningAnimation {
+    pub config: AnimationConfig,
+    /// Момент старта по часам страницы; задаётся первой выборкой
+    pub start: Option<Duration>,
+    /// Вычисленный стиль элемента без анимации
+    pub base: HashMap<String, String>,
+}
+
+/// Интерполирует вычисленные значения: цвета и числа с одинаковой единицей
+/// плавно, остальные — дискретно, со сменой на середине.
+pub fn interpolate_value(from: &str, to: &str, t: f32) -> String {
+    if let (Some(a), Some(b)) = (color::parse_css_color(from), color::parse_css_color(to)) {
+        let lerp = |x: u8, y: u8| (f32::from(x) + (f32::from(y) - f32::from(x)) * t).round() as u8;
+        return properties::format_color(color::Color::new(
+            lerp(a.r, b.r),
+            lerp(a.g, b.g),
+            lerp(a.b, b.b),
+            lerp(a.a, b.a),
+        ));
+    }
+
+    if let (Some((a, unit_a)), Some((b, unit_b))) = (split_number(from), split_number(to)) {
+        // Безразмерный ноль совместим с любой единицей
+        let unit = match (unit_a, unit_b) {
+            (a_unit, b_unit) if a_unit == b_unit => Some(a_unit),
+            ("", b_unit) if a == 0.0 => Some(b_unit),
+            (a_unit, "") if b == 0.0 => Some(a_unit),
+            _ => None,
+        };
+        if let Some(unit) = unit {
+            return format!("{}{}", properties::format_float(a + (b - a) * t), unit);
+        }
+    }
+
+    if t < 0.5 { from } else { to }.to_string()
+}
+
+/// Разделяет `12.5px` на число и единицу
+fn split_number(value: &str) -> Option<(f32, &str)> {
+    let value = value.trim();
+    let split = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(value.len(), |(i, _)| i);
+    let number = value[..split].parse().ok()?;
+    let unit = &value[split..];
+    unit.chars()
+        .all(|c| c.is_ascii_alphabetic() || c == '%')
+        .then_some((number, unit))
+}
+
+fn first_item(value: &str) -> &str {
+    value.split(',').next().unwrap_or_default().trim()
+}
+
+fn time_in_seconds(value: f32, unit: &str) -> Option<f32> {
+    match unit.to_ascii_lowercase().as_str() {
+        "s" => Some(value),
+        "ms" => Some(value / 1000.0),
+        _ => None,
+    }
+}
+
+fn parse_time(value: &str) -> Option<f32> {
+    let (number, unit) = split_number(value)?;
+    time_in_seconds(number, unit)
+}
+
+fn parse_easing(value: &str) -> Option<EasingFunction> {
+    let mut input = ParserInput::new(value);
+    EasingFunction::parse(&mut Parser::new(&mut input)).ok()
+}
+
+fn parse_iteration_count(value: &str) -> Option<f32> {
+    if value.eq_ignore_ascii_case("infinite") {
+        return Some(f32::INFINITY);
+    }
+    value.parse::<f32>().ok().filter(|count| *count >= 0.0)
+}
+
+fn parse_direction(value: &str) -> Option<AnimationDirection> {
+    match value.to_ascii_lowercase().as_str() {
+        "normal" => Some(AnimationDirection::Normal),
+        "reverse" => Some(AnimationDirection::Reverse),
+        "alternate" => Some(AnimationDirection::Alternate),
+        "alternate-reverse" => Some(AnimationDirection::AlternateReverse),
+        _ => None,
+    }
+}
+
+fn parse_fill_mode(value: &str) -> Option<AnimationFillMode> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Some(AnimationFillMode::None),
+        "forwards" => Some(AnimationFillMode::Forwards),
+        "backwards" => Some(AnimationFillMode::Backwards),
+        "both" => Some(AnimationFillMode::Both),
+        _ => None,
+    }
 }
 
 /// Парсит декларации внутри keyframe блока.
@@ -0,0 +1,474 @@
+//! Математические функции CSS: `calc()`, `min()`, `max()` и `clamp()`.
+//!
+//! Выражение разбирается в дерево [`CalcExpr`] с проверкой типов по CSS Values 4:
+//! складывать можно только значения одного типа, умножать — на число, делить —
+//! только на число. Проценты в длинах считаются длинами.
+//!
+//! Выражение только из чисел и абсолютных длин сворачивается при разборе.
+//! Относительные единицы вычисляются при наследовании ([`CalcExpr::resolve_units`]),
+//! а проценты — при layout, когда известен containing block; до этого выражение
+//! сохраняется целиком и вычисляется через [`CalcContext`].
+
+use std::fmt;
+
+use cssparser::{ParseError, Parser, ParserInput, Token};
+
+use super::properties::format_float;
+
+/// Узел математического выражения CSS
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcExpr {
+    Number(f32),
+    /// Длина с единицей в нижнем регистре
+    Length(f32, String),
+    /// Процент: `50%` хранится как `50.0`
+    Percentage(f32),
+    Sum(Box<CalcExpr>, Box<CalcExpr>),
+    Difference(Box<CalcExpr>, Box<CalcExpr>),
+    Product(Box<CalcExpr>, Box<CalcExpr>),
+    Quotient(Box<CalcExpr>, Box<CalcExpr>),
+    Min(Vec<CalcExpr>),
+    Max(Vec<CalcExpr>),
+    /// `clamp(min, value, max)`
+    Clamp(Box<CalcExpr>, Box<CalcExpr>, Box<CalcExpr>),
+}
+
+/// Тип выражения: проценты в длинах относятся к длинам
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalcType {
+    Number,
+    Length,
+}
+
+/// Данные для вычисления процентов и относительных единиц
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalcContext {
+    /// База процентов; `None` — проценты не вычисляются
+    pub percent_basis: Option<f32>,
+    pub font_size: f32,
+    pub root_font_size: f32,
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+    /// Ширина `0` в шрифте элемента (`ch`)
+    pub ch_width: f32,
+    /// Высота `x` в шрифте элемента (`ex`)
+    pub ex_height: f32,
+}
+
+impl Default for CalcContext {
+    fn default() -> Self {
+        Self {
+            percent_basis: None,
+            font_size: 16.0,
+            root_font_size: 16.0,
+            viewport_width: 0.0,
+            viewport_height: 0.0,
+            ch_width: 8.0,
+            ex_height: 8.0,
+        }
+    }
+}
+
+/// Является ли функция математической (`calc`, `min`, `max`, `clamp`)
+pub fn is_math_function(name: &str) -> bool {
+    ["calc", "min", "max", "clamp"]
+        .iter()
+        .any(|function| name.eq_ignore_ascii_case(function))
+}
+
+/// Размер абсолютной единицы в пикселях
+fn absolute_unit(unit: &str) -> Option<f32> {
+    Some(match unit {
+        "px" => 1.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        "q" => 96.0 / 101.6,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        _ => return None,
+    })
+}
+
+/// Поддерживается ли единица длины (в нижнем регистре)
+pub fn is_length_unit(unit: &str) -> bool {
+    absolute_unit(unit).is_some()
+        || matches!(
+            unit,
+            "em" | "rem" | "ex" | "ch" | "vw" | "vh" | "vmin" | "vmax"
+        )
+}
+
+impl CalcExpr {
+    /// Разбирает значение, целиком состоящее из одной математической функции.
+    ///
+    /// Для длин и чисел без функции возвращает `None`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut input = ParserInput::new(value.trim());
+        let mut parser = Parser::new(&mut input);
+        let name = match parser.next() {
+            Ok(Token::Function(name)) if is_math_function(name) => name.clone(),
+            _ => return None,
+        };
+        let expr = parser
+            .parse_nested_block(|input| Self::parse_function(&name, input))
+            .ok()?;
+        parser.is_exhausted().then_some(expr)
+    }
+
+    /// Разбирает длину, процент, число или математическую функцию
+    pub fn parse_value(value: &str) -> Option<Self> {
+        let mut input = ParserInput::new(value.trim());
+        let mut parser = Parser::new(&mut input);
+        let expr = match parser.next().ok()?.clone() {
+            Token::Number { value, .. } => Self::Number(value),
+            Token::Percentage { unit_value, .. } => Self::Percentage(unit_value * 100.0),
+            Token::Dimension { value, unit, .. } => {
+                let unit = unit.to_ascii_lowercase();
+                if !is_length_unit(&unit) {
+                    return None;
+                }
+                Self::Length(value, unit)
+            }
+            Token::Function(name) if is_math_function(&name) => parser
+                .parse_nested_block(|input| Self::parse_function(&name, input))
+                .ok()?,
+            _ => return None,
+        };
+        parser.is_exhausted().then_some(expr)
+    }
+
+    /// Разбирает аргументы математической функции `name`, чей блок уже открыт
+    pub fn parse_function<'i>(
+        name: &str,
+        input: &mut Parser<'i, '_>,
+    ) -> Result<Self, ParseError<'i, ()>> {
+        let name = name.to_ascii_lowercase();
+        let mut arguments = Vec::new();
+        loop {
+            arguments.push(input.parse_until_before(cssparser::Delimiter::Comma, parse_sum)?);
+            if input.is_exhausted() {
+                break;
+            }
+            input.expect_comma()?;
+        }
+
+        let expr = match (name.as_str(), arguments.len()) {
+            ("calc", 1) => arguments.remove(0),
+            ("min", _) => Self::Min(arguments),
+            ("max", _) => Self::Max(arguments),
+            ("clamp", 3) => {
+                let max = arguments.pop().map(Box::new);
+                let value = arguments.pop().map(Box::new);
+                let min = arguments.pop().map(Box::new);
+                match (min, value, max) {
+                    (Some(min), Some(value), Some(max)) => Self::Clamp(min, value, max),
+                    _ => return Err(input.new_custom_error(())),
+                }
+            }
+            _ => return Err(input.new_custom_error(())),
+        };
+        if expr.calc_type().is_none() {
+            return Err(input.new_custom_error(()));
+        }
+        Ok(expr)
+    }
+
+    /// Тип выражения; `None` — выражение некорректно (например, `1px * 2px`)
+    pub fn calc_type(&self) -> Option<CalcType> {
+        match self {
+            Self::Number(_) => Some(CalcType::Number),
+            Self::Length(..) | Self::Percentage(_) => Some(CalcType::Length),
+            Self::Sum(left, right) | Self::Difference(left, right) => {
+                let left = left.calc_type()?;
+                (left == right.calc_type()?).then_some(left)
+            }
+            Self::Product(left, right) => match (left.calc_type()?, right.calc_type()?) {
+                (CalcType::Number, other) | (other, CalcType::Number) => Some(other),
+                _ => None,
+            },
+            Self::Quotient(left, right) => match right.calc_type()? {
+                CalcType::Number => left.calc_type(),
+                CalcType::Length => None,
+            },
+            Self::Min(arguments) | Self::Max(arguments) => {
+                let (first, rest) = arguments.split_first()?;
+                let kind = first.calc_type()?;
+                rest.iter()
+                    .all(|argument| argument.calc_type() == Some(kind))
+                    .then_some(kind)
+            }
+            Self::Clamp(min, value, max) => {
+                let kind = value.calc_type()?;
+                (min.calc_type() == Some(kind) && max.calc_type() == Some(kind)).then_some(kind)
+            }
+        }
+    }
+
+    /// Есть ли в выражении проценты
+    pub fn has_percentage(&self) -> bool {
+        self.any_leaf(&|leaf| matches!(leaf, Self::Percentage(_)))
+    }
+
+    /// Вычисляется ли выражение без контекста: только числа и абсолютные длины
+    pub fn is_absolute(&self) -> bool {
+        !self.any_leaf(&|leaf| match leaf {
+            Self::Percentage(_) => true,
+            Self::Length(_, unit) => absolute_unit(unit).is_none(),
+            _ => false,
+        })
+    }
+
+    fn any_leaf(&self, predicate: &dyn Fn(&Self) -> bool) -> bool {
+        match self {
+            Self::Number(_) | Self::Length(..) | Self::Percentage(_) => predicate(self),
+            Self::Sum(left, right)
+            | Self::Difference(left, right)
+            | Self::Product(left, right)
+            | Self::Quotient(left, right) => left.any_leaf(predicate) || right.any_leaf(predicate),
+            Self::Min(arguments) | Self::Max(arguments) => arguments
+                .iter()
+                .any(|argument| argument.any_leaf(predicate)),
+            Self::Clamp(min, value, max) => {
+                min.any_leaf(predicate) || value.any_leaf(predicate) || max.any_leaf(predicate)
+            }
+        }
+    }
+
+    /// Вычисляет выражение: длины — в пикселях, числа — как есть.
+    ///
+    /// `None`, если нужен процент без базы или результат не конечен.
+    pub fn eval(&self, context: &CalcContext) -> Option<f32> {
+        let value = match self {
+            Self::Number(value) => *value,
+            Self::Length(value, unit) => value * unit_size(unit, context)?,
+            Self::Percentage(percent) => percent / 100.0 * context.percent_basis?,
+            Self::Sum(left, right) => left.eval(context)? + right.eval(context)?,
+            Self::Difference(left, right) => left.eval(context)? - right.eval(context)?,
+            Self::Product(left, right) => left.eval(context)? * right.eval(context)?,
+            Self::Quotient(left, right) => left.eval(context)? / right.eval(context)?,
+            Self::Min(arguments) => arguments
+                .iter()
+                .map(|argument| argument.eval(context))
+                .try_fold(f32::INFINITY, |min, value| Some(min.min(value?)))?,
+            Self::Max(arguments) => arguments
+                .iter()
+                .map(|argument| argument.eval(context))
+                .try_fold(f32::NEG_INFINITY, |max, value| Some(max.max(value?)))?,
+            // При min > max побеждает min
+            Self::Clamp(min, value, max) => value
+                .eval(context)?
+                .min(max.eval(context)?)
+                .max(min.eval(context)?),
+        };
+        value.is_finite().then_some(value)
+    }
+
+    /// Переводит все длины в пиксели; остаются только числа и проценты
+    pub fn resolve_units(self, context: &CalcContext) -> Self {
+        let resolve = |expr: Box<Self>| Box::new(expr.resolve_units(context));
+        let expr = match self {
+            Self::Length(value, unit) if unit != "px" => match unit_size(&unit, context) {
+                Some(size) => Self::Length(value * size, "px".to_string()),
+                None => Self::Length(value, unit),
+            },
+            Self::Sum(left, right) => Self::Sum(resolve(left), resolve(right)),
+            Self::Difference(left, right) => Self::Difference(resolve(left), resolve(right)),
+            Self::Product(left, right) => Self::Product(resolve(left), resolve(right)),
+            Self::Quotient(left, right) => Self::Quotient(resolve(left), resolve(right)),
+            Self::Min(arguments) => Self::Min(
+                arguments
+                    .into_iter()
+                    .map(|argument| argument.resolve_units(context))
+                    .collect(),
+            ),
+            Self::Max(arguments) => Self::Max(
+                arguments
+                    .into_iter()
+                    .map(|argument| argument.resolve_units(context))
+                    .collect(),
+            ),
+            Self::Clamp(min, value, max) => Self::Clamp(resolve(min), resolve(value), resolve(max)),
+            leaf => leaf,
+        };
+        expr.simplify()
+    }
+
+    /// Сворачивает выражение, вычислимое без контекста, в одно значение
+    pub fn simplify(self) -> Self {
+        if !self.is_absolute() {
+            return self;
+        }
+        match (self.calc_type(), self.eval(&CalcContext::default())) {
+            (Some(CalcType::Number), Some(value)) => Self::Number(value),
+            (Some(CalcType::Length), Some(value)) => Self::Length(value, "px".to_string()),
+            _ => self,
+        }
+    }
+
+    /// Записывает узел внутри выражения, без обёртки `calc()`
+    fn write_inner(&self, f: &mut fmt::Formatter<'_>, parenthesize_sums: bool) -> fmt::Result {
+        match self {
+            Self::Number(value) => f.write_str(&format_float(*value)),
+            Self::Length(value, unit) => write!(f, "{}{unit}", format_float(*value)),
+            Self::Percentage(percent) => write!(f, "{}%", format_float(*percent)),
+            Self::Sum(left, right) | Self::Difference(left, right) => {
+                let operator = if matches!(self, Self::Sum(..)) {
+                    "+"
+                } else {
+                    "-"
+                };
+                if parenthesize_sums {
+                    f.write_str("(")?;
+                }
+                left.write_inner(f, false)?;
+                write!(f, " {operator} ")?;
+                right.write_inner(f, true)?;
+                if parenthesize_sums {
+                    f.write_str(")")?;
+                }
+                Ok(())
+            }
+            Self::Product(left, right) | Self::Quotient(left, right) => {
+                let operator = if matches!(self, Self::Product(..)) {
+                    "*"
+                } else {
+                    "/"
+                };
+                left.write_inner(f, true)?;
+                write!(f, " {operator} ")?;
+                // Правый операнд деления в скобках, если он сам произведение
+                let nested = matches!(**right, Self::Product(..) | Self::Quotient(..));
+                if nested {
+                    f.write_str("(")?;
+                }
+                right.write_inner(f, true)?;
+                if nested {
+                    f.write_str(")")?;
+                }
+                Ok(())
+            }
+            Self::Min(arguments) | Self::Max(arguments) => {
+                f.write_str(if matches!(self, Self::Min(_)) {
+                    "min("
+                } else {
+                    "max("
+                })?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    argument.write_inner(f, false)?;
+                }
+                f.write_str(")")
+            }
+            Self::Clamp(min, value, max) => {
+                f.write_str("clamp(")?;
+                min.write_inner(f, false)?;
+                f.write_str(", ")?;
+                value.write_inner(f, false)?;
+                f.write_str(", ")?;
+                max.write_inner(f, false)?;
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+/// Сериализация вычисленного значения: одиночное значение пишется как есть,
+/// операции оборачиваются в `calc()`
+impl fmt::Display for CalcExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sum(..) | Self::Difference(..) | Self::Product(..) | Self::Quotient(..) => {
+                f.write_str("calc(")?;
+                self.write_inner(f, false)?;
+                f.write_str(")")
+            }
+            _ => self.write_inner(f, false),
+        }
+    }
+}
+
+/// Размер единицы длины в пикселях
+fn unit_size(unit: &str, context: &CalcContext) -> Option<f32> {
+    if let Some(size) = absolute_unit(unit) {
+        return Some(size);
+    }
+    Some(match unit {
+        "em" => context.font_size,
+        "rem" => context.root_font_size,
+        "ex" => context.ex_height,
+        "ch" => context.ch_width,
+        "vw" => context.viewport_width / 100.0,
+        "vh" => context.viewport_height / 100.0,
+        "vmin" => context.viewport_width.min(context.viewport_height) / 100.0,
+        "vmax" => context.viewport_width.max(context.viewport_height) / 100.0,
+        _ => return None,
+    })
+}
+
+/// `<sum> = <product> [ [ '+' | '-' ] <product> ]*`
+fn parse_sum<'i>(input: &mut Parser<'i, '_>) -> Result<CalcExpr, ParseError<'i, ()>> {
+    let mut expr = parse_product(input)?;
+    loop {
+        let operator = input.try_parse(|input| match input.next()? {
+            Token::Delim(operator @ ('+' | '-')) => Ok(*operator),
+            _ => Err(input.new_custom_error::<(), ()>(())),
+        });
+        let Ok(operator) = operator else {
+            break;
+        };
+        let right = Box::new(parse_product(input)?);
+        expr = match operator {
+            '+' => CalcExpr::Sum(Box::new(expr), right),
+            _ => CalcExpr::Difference(Box::new(expr), right),
+        };
+    }
+    if input.is_exhausted() {
+        Ok(expr)
+    } else {
+        Err(input.new_custom_error(()))
+    }
+}
+
+/// `<product> = <value> [ [ '*' | '/' ] <value> ]*`
+fn parse_product<'i>(input: &mut Parser<'i, '_>) -> Result<CalcExpr, ParseError<'i, ()>> {
+    let mut expr = parse_value(input)?;
+    loop {
+        let operator = input.try_parse(|input| match input.next()? {
+            Token::Delim(operator @ ('*' | '/')) => Ok(*operator),
+            _ => Err(input.new_custom_error::<(), ()>(())),
+        });
+        let Ok(operator) = operator else {
+            break;
+        };
+        let right = Box::new(parse_value(input)?);
+        expr = match operator {
+            '*' => CalcExpr::Product(Box::new(expr), right),
+            _ => CalcExpr::Quotient(Box::new(expr), right),
+        };
+    }
+    Ok(expr)
+}
+
+fn parse_value<'i>(input: &mut Parser<'i, '_>) -> Result<CalcExpr, ParseError<'i, ()>> {
+    let token = input.next()?.clone();
+    match token {
+        Token::Number { value, .. } => Ok(CalcExpr::Number(value)),
+        Token::Percentage { unit_value, .. } => Ok(CalcExpr::Percentage(unit_value * 100.0)),
+        Token::Dimension { value, unit, .. } => {
+            let unit = unit.to_ascii_lowercase();
+            if is_length_unit(&unit) {
+                Ok(CalcExpr::Length(value, unit))
+            } else {
+                Err(input.new_custom_error(()))
+            }
+        }
+        Token::ParenthesisBlock => input.parse_nested_block(parse_sum),
+        Token::Function(name) if is_math_function(&name) => {
+            input.parse_nested_block(|input| CalcExpr::parse_function(&name, input))
+        }
+        _ => Err(input.new_custom_error(())),
+    }
+}
@@ -14,7 +14,7 @@ use std::str::FromStr;
 use cssparser::{ParseError, Parser, Token};
 use std::collections::HashMap;
 use std::fmt;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 /// Формат файла шрифта.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -104,6 +104,7 @@ pub enum FontWeight {
     Weight(u16),
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for FontWeight {
     fn default() -> Self {
         Self::Normal
@@ -160,6 +161,7 @@ pub enum FontStyle {
     Oblique,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for FontStyle {
     fn default() -> Self {
         Self::Normal
@@ -412,6 +414,8 @@ pub struct LoadedFont {
     pub face: FontFace,
     /// Данные fontdue Font (обернутые в Arc для клонирования)
     pub fontdue_font: Option<Arc<fontdue::Font>>,
+    /// Исходные байты файла — для бэкендов со своей растеризацией (egui)
+    pub data: Option<Arc<Vec<u8>>>,
 }
 
 impl std::fmt::Debug for LoadedFont {
@@ -419,6 +423,7 @@ impl std::fmt::Debug for LoadedFont {
         f.debug_struct("LoadedFont")
             .field("face", &self.face)
             .field("fontdue_font", &self.fontdue_font.is_some())
+            .field("data", &self.data.as_ref().map(|data| data.len()))
             .finish()
     }
 }
@@ -429,6 +434,7 @@ impl LoadedFont {
         Self {
             face,
             fontdue_font: None,
+            data: None,
         }
     }
 
@@ -437,6 +443,7 @@ impl LoadedFont {
         match fontdue::Font::from_bytes(data, fontdue::FontSettings::default()) {
             Ok(font) => {
                 self.fontdue_font = Some(Arc::new(font));
+                self.data = Some(Arc::new(data.to_vec()));
                 Ok(())
             }
             Err(e) => Err(format!("Failed to load font: {}", e)),
@@ -449,6 +456,101 @@ impl LoadedFont {
     }
 }
 
+/// Байты встроенного шрифта DejaVu Sans
+pub const DEFAULT_FONT_DATA: &[u8] = include_bytes!("../../../../assets/fonts/DejaVuSans.ttf");
+
+/// Встроенный шрифт по умолчанию (DejaVu Sans).
+///
+/// Используется, когда ни один загруженный @font-face не подходит под запрошенное семейство.
+/// Возвращает `None`, если встроенный файл шрифта повреждён.
+pub fn default_font() -> Option<Arc<fontdue::Font>> {
+    static DEFAULT_FONT: OnceLock<Option<Arc<fontdue::Font>>> = OnceLock::new();
+    DEFAULT_FONT
+        .get_or_init(|| {
+            match fontdue::Font::from_bytes(DEFAULT_FONT_DATA, fontdue::FontSettings::default()) {
+                Ok(font) => Some(Arc::new(font)),
+                Err(e) => {
+                    tracing::warn!("Failed to load built-in DejaVu Sans font: {}", e);
+                    None
+                }
+            }
+        })
+        .clone()
+}
+
+/// Шрифт из цепочки fallback вместе с семейством, по которому он найден.
+#[derive(Debug, Clone)]
+pub struct FallbackFont {
+    /// Семейство из `font-family`; `None` — встроенный [`default_font`]
+    pub family: Option<String>,
+    pub font: Arc<fontdue::Font>,
+}
+
+/// Строит цепочку шрифтов для посимвольного fallback.
+///
+/// Семейства из списка (`"Foo", Bar, sans-serif`) перебираются по порядку. Для каждого
+/// сначала ищется точное совпадение веса и стиля, затем любое загруженное начертание
+/// того же семейства. Последним в цепочку добавляется [`default_font`].
+pub fn fallback_fonts(
+    fonts: &[LoadedFont],
+    family: Option<&str>,
+    weight: FontWeight,
+    style: FontStyle,
+) -> Vec<FallbackFont> {
+    let families = family
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().trim_matches(|c| c == '"' || c == '\''))
+        .filter(|name| !name.is_empty());
+
+    let mut chain = Vec::new();
+    for name in families {
+        let matched = select_face(fonts, name, weight, style);
+        if let Some(font) = matched.and_then(|font| font.fontdue_font.clone()) {
+            chain.push(FallbackFont {
+                family: Some(name.to_string()),
+                font,
+            });
+        }
+    }
+
+    chain.extend(default_font().map(|font| FallbackFont { family: None, font }));
+    chain
+}
+
+/// Подбирает загруженное начертание одного семейства: сначала с точным весом и стилем,
+/// затем любое начертание того же семейства.
+pub fn select_face<'a>(
+    fonts: &'a [LoadedFont],
+    family: &str,
+    weight: FontWeight,
+    style: FontStyle,
+) -> Option<&'a LoadedFont> {
+    let mut loaded = fonts.iter().filter(|font| {
+        font.fontdue_font.is_some() && font.face.family.eq_ignore_ascii_case(family)
+    });
+    let first = loaded.clone().next();
+    loaded
+        .find(|font| {
+            font.face.weight.to_numeric() == weight.to_numeric() && font.face.style == style
+        })
+        .or(first)
+}
+
+/// Подбирает основной шрифт по значению `font-family`, весу и стилю — первый
+/// в цепочке [`fallback_fonts`]. Если ничего не нашлось — возвращается [`default_font`].
+pub fn select_font(
+    fonts: &[LoadedFont],
+    family: Option<&str>,
+    weight: FontWeight,
+    style: FontStyle,
+) -> Option<Arc<fontdue::Font>> {
+    fallback_fonts(fonts, family, weight, style)
+        .into_iter()
+        .next()
+        .map(|fallback| fallback.font)
+}
+
 /// Парсит список источников шрифтов (src дескриптор).
 fn parse_font_sources<'i, 't>(
     input: &mut Parser<'i, 't>,
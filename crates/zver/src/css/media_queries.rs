@@ -26,6 +26,7 @@ pub enum MediaType {
     Speech,
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for MediaType {
     fn default() -> Self {
         Self::All
@@ -357,6 +358,29 @@ impl MediaQuery {
     }
 }
 
+/// Проверяет список медиа-запросов через запятую (атрибут `media`, `@import ... <media>`).
+///
+/// Пустой список соответствует всем устройствам; запрос, который не удалось
+/// разобрать, считается несоответствующим (`not all`).
+pub fn media_list_matches(
+    list: &str,
+    viewport_width: f32,
+    viewport_height: f32,
+    media_type: MediaType,
+) -> bool {
+    if list.trim().is_empty() {
+        return true;
+    }
+
+    list.split(',').any(|query| {
+        let mut input = cssparser::ParserInput::new(query);
+        let mut parser = Parser::new(&mut input);
+        parser
+            .parse_entirely(MediaQuery::parse)
+            .is_ok_and(|query| query.matches(viewport_width, viewport_height, media_type.clone()))
+    })
+}
+
 /// Парсит одно медиа-условие (media feature).
 fn parse_media_feature<'i, 't>(
     input: &mut Parser<'i, 't>,
@@ -539,4 +563,28 @@ mod tests {
         assert!(!query.matches(500.0, 600.0, MediaType::Screen));
         assert!(!query.matches(1200.0, 600.0, MediaType::Screen));
     }
+
+    #[test]
+    fn test_media_list_matches() {
+        assert!(media_list_matches("", 800.0, 600.0, MediaType::Screen));
+        assert!(media_list_matches(
+            "print, screen",
+            800.0,
+            600.0,
+            MediaType::Screen
+        ));
+        assert!(!media_list_matches(
+            "print",
+            800.0,
+            600.0,
+            MediaType::Screen
+        ));
+        assert!(!media_list_matches(
+            "screen and (min-width: 1200px)",
+            800.0,
+            600.0,
+            MediaType::Screen
+        ));
+        assert!(!media_list_matches("(((", 800.0, 600.0, MediaType::Screen));
+    }
 }
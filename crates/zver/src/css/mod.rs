@@ -5,14 +5,18 @@
 //!   адаптированный фоллбек для упрощённого синтаксиса.
 //! - [`selectors`] — интеграция с crate `selectors` и адаптер DOM-узлов.
 //! - [`properties`] — нормализация и валидация CSS-свойств.
+//! - [`calc`] — математические функции `calc()`, `min()`, `max()`, `clamp()`.
 //! - [`media_queries`] — поддержка @media queries (Фаза 2).
 //! - [`animations`] — поддержка @keyframes анимаций (Фаза 2).
 //! - [`fonts`] — поддержка @font-face и загрузка шрифтов (Фаза 2).
+//! - [`stylesheets`] — сбор `<style>`/`<link>` и раскрытие `@import`.
+//! - [`variables`] — пользовательские свойства и подстановка `var()`.
 //!
 //! Внешний API (`StyleEngine`) сохраняет обратную совместимость, но внутренняя
 //! архитектура стала модульной, что упрощает расширение функциональности.
 
 pub mod animations;
+pub mod calc;
 pub mod color;
 pub mod fonts;
 pub mod media_queries;
@@ -20,9 +24,13 @@ pub mod parser;
 pub mod properties;
 pub mod selectors;
 pub mod serializer;
+pub mod stylesheets;
+pub mod variables;
 
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::dom::Document;
 use parser::{CssParseOptions, ParsedRule, StylesheetParser};
@@ -38,12 +46,26 @@ pub struct StyleRule {
     pub declarations: Vec<Property>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PseudoStyle {
     pub properties: HashMap<String, String>,
     pub content: String,
 }
 
+/// Вычисленные стили элемента и его псевдоэлементов
+type ElementStyles = (
+    usize,
+    HashMap<String, String>,
+    HashMap<PseudoElement, PseudoStyle>,
+);
+
+/// Результат каскада элемента до подстановки `var()`
+type CascadedStyles = (
+    usize,
+    HashMap<String, String>,
+    HashMap<PseudoElement, HashMap<String, String>>,
+);
+
 /// Хранилище стилей и механизм применения CSS к DOM.
 #[derive(Debug, Clone, Default)]
 pub struct StyleEngine {
@@ -56,12 +78,16 @@ pub struct StyleEngine {
     /// Вычисленные стили по node_id.
     pub computed_styles: HashMap<usize, HashMap<String, String>>,
     pub pseudo_element_styles: HashMap<usize, HashMap<PseudoElement, PseudoStyle>>,
+    /// Вычисленные пользовательские свойства (`--name`) по node_id, включая унаследованные
+    pub custom_properties: HashMap<usize, Arc<variables::CustomProperties>>,
 
     // === Фаза 2: @-правила ===
     /// @media правила (Фаза 2)
     pub media_rules: Vec<media_queries::MediaRule>,
     /// @keyframes определения по имени (Фаза 2)
     pub keyframes: HashMap<String, animations::KeyframesDefinition>,
+    /// CSS-анимации элементов по node_id; их значения наложены на `computed_styles`
+    pub animations: HashMap<usize, animations::RunningAnimation>,
     /// @font-face определения (Фаза 2)
     pub fonts: Vec<fonts::LoadedFont>,
 
@@ -86,9 +112,37 @@ impl StyleEngine {
     }
 
     /// Устанавливает размеры viewport для @media queries.
-    pub fn set_viewport(&mut self, width: f32, height: f32) {
+    ///
+    /// Возвращает `true`, если изменился набор подходящих @media правил и
+    /// каскад нужно пересчитать.
+    pub fn set_viewport(&mut self, width: f32, height: f32) -> bool {
+        let before = self.matching_media_rules();
         self.viewport_width = width;
         self.viewport_height = height;
+        before != self.matching_media_rules()
+    }
+
+    /// Для каждого @media правила — применимо ли оно к текущему viewport
+    fn matching_media_rules(&self) -> Vec<bool> {
+        self.media_rules
+            .iter()
+            .map(|rule| {
+                rule.applies_to(
+                    self.viewport_width,
+                    self.viewport_height,
+                    self.media_type.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Текущие параметры устройства для фильтрации `media` у таблиц стилей.
+    pub fn media_environment(&self) -> stylesheets::MediaEnvironment {
+        stylesheets::MediaEnvironment {
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+            media_type: self.media_type.clone(),
+        }
     }
 
     /// Устанавливает тип медиа для @media queries.
@@ -97,17 +151,55 @@ impl StyleEngine {
     }
 
     /// Парсит CSS-строку и обновляет внутренний набор правил (Фаза 2: с поддержкой @-rules).
+    ///
+    /// Строгий режим: любая неверная декларация делает всю таблицу ошибкой.
     pub fn parse_css(&mut self, css: &str) -> Result<(), parser::CssParseError> {
+        self.clear_rules();
+        let mut stylesheet = StylesheetParser::new(CssParseOptions::default());
+        let parsed_stylesheet = stylesheet.parse_stylesheet(css)?;
+        self.add_stylesheet(&stylesheet, css, parsed_stylesheet);
+        self.finish_rules();
+        Ok(())
+    }
+
+    /// Парсит таблицы стилей документа по отдельности, в порядке каскада.
+    ///
+    /// Как требует CSS Syntax, неверные декларации и правила отбрасываются с
+    /// предупреждением в лог, а остальная часть таблицы применяется.
+    pub fn parse_stylesheets<S: AsRef<str>>(&mut self, sheets: &[S]) {
+        self.clear_rules();
+        let mut stylesheet = StylesheetParser::new(CssParseOptions::default());
+        for (index, css) in sheets.iter().enumerate() {
+            let css = css.as_ref();
+            match stylesheet.parse_stylesheet_recovering(css) {
+                Ok((parsed_stylesheet, error)) => {
+                    if let Some(error) = error {
+                        tracing::warn!("Stylesheet #{index}: dropped invalid CSS: {error}");
+                    }
+                    self.add_stylesheet(&stylesheet, css, parsed_stylesheet);
+                }
+                Err(error) => tracing::warn!("Stylesheet #{index} skipped: {error}"),
+            }
+        }
+        self.finish_rules();
+    }
+
+    fn clear_rules(&mut self) {
         self.rules.clear();
         self.parsed_selectors.clear();
         self.media_rules.clear();
         self.keyframes.clear();
         self.fonts.clear();
         self.pseudo_element_styles.clear();
+    }
 
-        let mut stylesheet = StylesheetParser::new(CssParseOptions::default());
-        let mut parsed_stylesheet = stylesheet.parse_stylesheet(css)?;
-
+    /// Добавляет правила одной таблицы после уже добавленных
+    fn add_stylesheet(
+        &mut self,
+        stylesheet: &StylesheetParser,
+        css: &str,
+        mut parsed_stylesheet: parser::ParsedStylesheet,
+    ) {
         // Fallback если нет обычных правил
         if parsed_stylesheet.rules.is_empty()
             && parsed_stylesheet.media_rules.is_empty()
@@ -140,7 +232,7 @@ impl StyleEngine {
         }
 
         // Сохраняем @-правила (Фаза 2)
-        self.media_rules = parsed_stylesheet.media_rules;
+        self.media_rules.extend(parsed_stylesheet.media_rules);
 
         // Преобразуем keyframes в HashMap
         for kf in parsed_stylesheet.keyframes {
@@ -153,21 +245,23 @@ impl StyleEngine {
             let loaded_font = fonts::LoadedFont::new(font_face);
             self.fonts.push(loaded_font);
         }
+    }
 
+    fn finish_rules(&mut self) {
         // Удаляем записи кэша, которые не использовались на текущем проходе.
         self.selector_cache
             .retain(|_, compiled| compiled.is_marked());
         for compiled in self.selector_cache.values_mut() {
             compiled.reset_usage_flag();
         }
-
-        Ok(())
     }
 
     /// Применяет каскад CSS к DOM-дереву и формирует карту вычисленных стилей.
     pub fn apply_styles(&mut self, document: &Document) -> Result<(), parser::CssParseError> {
         self.computed_styles.clear();
         self.pseudo_element_styles.clear();
+        self.custom_properties.clear();
+        self.animations.clear();
 
         let element_ids: Vec<usize> = document
             .nodes
@@ -176,35 +270,231 @@ impl StyleEngine {
             .map(|(id, _)| *id)
             .collect();
 
-        type CascadedStyles = (
-            usize,
-            HashMap<String, String>,
-            HashMap<PseudoElement, HashMap<String, String>>,
-        );
-        let cascaded: Vec<CascadedStyles> = element_ids
+        let cascaded = self.cascade_elements(document, &element_ids);
+        for (node_id, styles, pseudo) in self.resolve_variables(document, cascaded) {
+            if !styles.is_empty() {
+                self.computed_styles.insert(node_id, styles);
+            }
+            if !pseudo.is_empty() {
+                self.pseudo_element_styles.insert(node_id, pseudo);
+            }
+        }
+        self.track_animations(&element_ids);
+        Ok(())
+    }
+
+    /// Пересчитывает каскад только для поддеревьев `roots` после изменения DOM.
+    ///
+    /// Возвращает элементы, чьи вычисленные стили (или стили псевдоэлементов)
+    /// действительно изменились, — только их нужно заново раскладывать.
+    pub fn restyle(&mut self, document: &Document, roots: &[usize]) -> Vec<usize> {
+        let mut element_ids = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<usize> = roots.to_vec();
+        while let Some(node_id) = stack.pop() {
+            let Some(node) = document.nodes.get(&node_id) else {
+                continue;
+            };
+            if !node.is_element() || !visited.insert(node_id) {
+                continue;
+            }
+            element_ids.push(node_id);
+            stack.extend(node.children.iter().copied());
+        }
+
+        let mut changed = Vec::new();
+        let cascaded = self.cascade_elements(document, &element_ids);
+        for (node_id, styles, pseudo) in self.resolve_variables(document, cascaded) {
+            let old_styles = self.computed_styles.remove(&node_id).unwrap_or_default();
+            let old_pseudo = self
+                .pseudo_element_styles
+                .remove(&node_id)
+                .unwrap_or_default();
+            if old_styles != styles || old_pseudo != pseudo {
+                changed.push(node_id);
+            }
+            if !styles.is_empty() {
+                self.computed_styles.insert(node_id, styles);
+            }
+            if !pseudo.is_empty() {
+                self.pseudo_element_styles.insert(node_id, pseudo);
+            }
+        }
+        self.track_animations(&element_ids);
+        changed.sort_unstable();
+        changed
+    }
+
+    /// Есть ли элементы с CSS-анимацией
+    pub fn has_animations(&self) -> bool {
+        !self.animations.is_empty()
+    }
+
+    /// Накладывает значения CSS-анимаций в момент `now` по часам страницы.
+    ///
+    /// Анимация стартует при первой выборке после того, как элемент её получил.
+    /// Возвращает элементы, чьи вычисленные стили изменились.
+    pub fn sample_animations(&mut self, now: Duration) -> Vec<usize> {
+        let mut changed = Vec::new();
+        for (&node_id, animation) in &mut self.animations {
+            let start = *animation.start.get_or_insert(now);
+            let elapsed = now.saturating_sub(start).as_secs_f32();
+
+            let mut styles = animation.base.clone();
+            if let Some(progress) = animation.config.progress_at(elapsed)
+                && let Some(keyframes) = self.keyframes.get(&animation.config.name)
+            {
+                styles.extend(keyframes.sample(
+                    progress,
+                    &animation.config.timing_function,
+                    &animation.base,
+                ));
+            }
+
+            if self.computed_styles.get(&node_id) != Some(&styles) {
+                self.computed_styles.insert(node_id, styles);
+                changed.push(node_id);
+            }
+        }
+        changed.sort_unstable();
+        changed
+    }
+
+    /// Запоминает анимации пересчитанных элементов и их стиль без анимации.
+    ///
+    /// Анимация с тем же именем продолжается с прежнего момента старта.
+    fn track_animations(&mut self, element_ids: &[usize]) {
+        for &node_id in element_ids {
+            let Some(styles) = self.computed_styles.get(&node_id) else {
+                self.animations.remove(&node_id);
+                continue;
+            };
+            let config = animations::AnimationConfig::from_properties(styles)
+                .filter(|config| self.keyframes.contains_key(&config.name));
+            let Some(config) = config else {
+                self.animations.remove(&node_id);
+                continue;
+            };
+
+            let start = self
+                .animations
+                .get(&node_id)
+                .filter(|running| running.config.name == config.name)
+                .and_then(|running| running.start);
+            let base = styles.clone();
+            self.animations.insert(
+                node_id,
+                animations::RunningAnimation {
+                    config,
+                    start,
+                    base,
+                },
+            );
+        }
+    }
+
+    /// Есть ли в таблицах селекторы с `+` или `~`.
+    ///
+    /// Тогда изменение элемента влияет и на последующих соседей, и
+    /// пересчитывать нужно поддерево родителя.
+    pub fn has_sibling_combinators(&self) -> bool {
+        self.parsed_selectors.iter().any(|list| {
+            list.slice().iter().any(|selector| {
+                selector.iter_raw_match_order().any(|component| {
+                    matches!(component, ::selectors::parser::Component::Combinator(c) if c.is_sibling())
+                })
+            })
+        })
+    }
+
+    /// Параллельно вычисляет каскад для указанных элементов.
+    fn cascade_elements(&self, document: &Document, element_ids: &[usize]) -> Vec<CascadedStyles> {
+        element_ids
             .par_iter()
             .map(|&node_id| {
                 let (cascade, pseudo) = self.compute_styles_for_node(document, node_id);
-                let normalized = Self::normalize_cascade(cascade);
                 let normalized_pseudo = pseudo
                     .into_iter()
                     .map(|(pseudo, properties)| (pseudo, Self::normalize_cascade(properties)))
                     .collect();
-                (node_id, normalized, normalized_pseudo)
+                (node_id, Self::normalize_cascade(cascade), normalized_pseudo)
             })
-            .collect();
+            .collect()
+    }
 
-        for (node_id, styles, pseudo_styles) in cascaded {
-            if !styles.is_empty() {
-                self.computed_styles.insert(node_id, styles);
+    /// Вычисляет пользовательские свойства и подставляет `var()`.
+    ///
+    /// Пользовательские свойства наследуются, поэтому элементы обрабатываются
+    /// от родителей к детям; родители вне `cascaded` берутся из
+    /// `custom_properties`.
+    fn resolve_variables(
+        &mut self,
+        document: &Document,
+        mut cascaded: Vec<CascadedStyles>,
+    ) -> Vec<ElementStyles> {
+        let depth = |mut node_id: usize| {
+            let mut depth = 0usize;
+            while let Some(parent) = document.nodes.get(&node_id).and_then(|node| node.parent) {
+                depth += 1;
+                node_id = parent;
             }
+            depth
+        };
+        cascaded.sort_by_cached_key(|(node_id, ..)| depth(*node_id));
 
-            let pseudo = Self::build_pseudo_styles(pseudo_styles);
-            if !pseudo.is_empty() {
-                self.pseudo_element_styles.insert(node_id, pseudo);
-            }
+        let empty = Arc::new(variables::CustomProperties::new());
+        cascaded
+            .into_iter()
+            .map(|(node_id, mut styles, pseudo)| {
+                let inherited = document
+                    .nodes
+                    .get(&node_id)
+                    .and_then(|node| node.parent)
+                    .and_then(|parent| self.custom_properties.get(&parent))
+                    .unwrap_or(&empty)
+                    .clone();
+                let custom = Self::resolve_element_variables(&inherited, &mut styles);
+                let pseudo = pseudo
+                    .into_iter()
+                    .map(|(pseudo, mut properties)| {
+                        Self::resolve_element_variables(&custom, &mut properties);
+                        (pseudo, properties)
+                    })
+                    .collect();
+                if custom.is_empty() {
+                    self.custom_properties.remove(&node_id);
+                } else {
+                    self.custom_properties.insert(node_id, custom);
+                }
+                (node_id, styles, Self::build_pseudo_styles(pseudo))
+            })
+            .collect()
+    }
+
+    /// Подставляет `var()` в стили одного элемента и возвращает его
+    /// пользовательские свойства.
+    fn resolve_element_variables(
+        inherited: &Arc<variables::CustomProperties>,
+        styles: &mut HashMap<String, String>,
+    ) -> Arc<variables::CustomProperties> {
+        let declared: HashMap<String, String> = styles
+            .iter()
+            .filter(|(name, _)| variables::is_custom_property(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let custom = if declared.is_empty() {
+            inherited.clone()
+        } else {
+            Arc::new(variables::resolve_custom_properties(inherited, &declared))
+        };
+        for name in declared.keys() {
+            match custom.get(name) {
+                Some(value) => styles.insert(name.clone(), value.clone()),
+                None => styles.remove(name),
+            };
         }
-        Ok(())
+        variables::substitute_properties(styles, &custom);
+        custom
     }
 
     /// Преобразует каскад AppliedProperty в финальные строковые значения.
@@ -17,6 +17,7 @@ use super::fonts::FontFace;
 use super::media_queries::{MediaQuery, MediaRule};
 use super::properties::{self, Property, PropertyParseError};
 use super::selectors::{self, SelectorListHandle};
+use crate::error::{ParseTarget, SourceLocation, ZverError};
 
 /// Настройки парсинга стилей.
 #[derive(Debug, Clone, Copy)]
@@ -36,13 +37,31 @@ impl Default for CssParseOptions {
 /// Высокоуровневая ошибка парсинга CSS.
 #[derive(Debug, Error, Clone)]
 pub enum CssParseError {
-    #[error("CSS parse error: {0}")]
-    Syntax(String),
+    #[error("CSS parse error: {message}")]
+    Syntax {
+        message: String,
+        /// Позиция первой ошибки в таблице стилей
+        location: Option<SourceLocation>,
+    },
 }
 
 impl CssParseError {
-    fn from_messages(errors: Vec<String>) -> Self {
-        CssParseError::Syntax(errors.join("\n"))
+    fn from_messages(errors: Vec<String>, location: Option<SourceLocation>) -> Self {
+        CssParseError::Syntax {
+            message: errors.join("\n"),
+            location,
+        }
+    }
+}
+
+impl From<CssParseError> for ZverError {
+    fn from(error: CssParseError) -> Self {
+        let CssParseError::Syntax { message, location } = error;
+        ZverError::Parse {
+            target: ParseTarget::Css,
+            message,
+            location,
+        }
     }
 }
 
@@ -100,7 +119,22 @@ impl StylesheetParser {
     }
 
     /// Парсит таблицу стилей, возвращая ParsedStylesheet с разделением на обычные правила и @-правила (Фаза 2).
+    ///
+    /// Любая отброшенная декларация или правило делает результат ошибкой.
     pub fn parse_stylesheet(&mut self, css: &str) -> Result<ParsedStylesheet, CssParseError> {
+        match self.parse_stylesheet_recovering(css)? {
+            (stylesheet, None) => Ok(stylesheet),
+            (_, Some(error)) => Err(error),
+        }
+    }
+
+    /// Парсит таблицу стилей с восстановлением по правилам CSS Syntax: неверные
+    /// декларации и правила отбрасываются, остальное попадает в результат вместе
+    /// с описанием отброшенного.
+    pub fn parse_stylesheet_recovering(
+        &mut self,
+        css: &str,
+    ) -> Result<(ParsedStylesheet, Option<CssParseError>), CssParseError> {
         let mut input = ParserInput::new(css);
         let mut parser = Parser::new(&mut input);
         let mut rule_parser = RuleCollector::new(self.options);
@@ -109,6 +143,7 @@ impl StylesheetParser {
 
         let mut result = ParsedStylesheet::default();
         let mut errors = Vec::new();
+        let mut first_location = None;
 
         for item_result in &mut stylesheet {
             match item_result {
@@ -122,20 +157,30 @@ impl StylesheetParser {
                 },
                 Err((err, slice)) => {
                     let message = format!("{} (near `{}`)", err, slice.trim());
+                    // В cssparser строки считаются с 0, столбцы — с 1
+                    let location = SourceLocation::new(err.location.line + 1, err.location.column);
                     if self.options.recover_from_errors {
                         errors.push(message);
+                        first_location.get_or_insert(location);
                     } else {
-                        return Err(CssParseError::Syntax(message));
+                        return Err(CssParseError::from_messages(vec![message], Some(location)));
                     }
                 }
             }
         }
 
-        if !errors.is_empty() {
-            return Err(CssParseError::from_messages(errors));
+        // Отброшенные декларации внутри правил
+        for (message, location) in rule_parser.errors {
+            if !self.options.recover_from_errors {
+                return Err(CssParseError::from_messages(vec![message], Some(location)));
+            }
+            errors.push(message);
+            first_location.get_or_insert(location);
         }
 
-        Ok(result)
+        let error =
+            (!errors.is_empty()).then(|| CssParseError::from_messages(errors, first_location));
+        Ok((result, error))
     }
 
     /// Фоллбек-парсер с упрощённым синтаксисом (историческое поведение движка).
@@ -169,18 +214,24 @@ impl StylesheetParser {
 
 /// Парсит inline-стили (значение атрибута `style`).
 pub fn parse_inline_declarations(inline: &str) -> Result<Vec<Property>, CssParseError> {
-    parse_declarations_from_str(inline)
-        .map_err(|err| CssParseError::Syntax(format!("inline style error: {}", err)))
+    parse_declarations_from_str(inline).map_err(|err| {
+        CssParseError::from_messages(vec![format!("inline style error: {}", err)], None)
+    })
 }
 
 /// Вспомогательный коллекционер правил для `StyleSheetParser`.
 struct RuleCollector {
     _options: CssParseOptions,
+    /// Отброшенные декларации и вложенные правила с позициями
+    errors: Vec<(String, SourceLocation)>,
 }
 
 impl RuleCollector {
     fn new(options: CssParseOptions) -> Self {
-        Self { _options: options }
+        Self {
+            _options: options,
+            errors: Vec::new(),
+        }
     }
 }
 
@@ -188,7 +239,6 @@ impl RuleCollector {
 enum RuleParseError {
     EmptySelector,
     InvalidSelector(String),
-    InvalidDeclaration(String),
     UnsupportedAtRule(String),
 }
 
@@ -197,7 +247,6 @@ impl fmt::Display for RuleParseError {
         match self {
             RuleParseError::EmptySelector => write!(f, "selector cannot be empty"),
             RuleParseError::InvalidSelector(reason) => write!(f, "{reason}"),
-            RuleParseError::InvalidDeclaration(reason) => write!(f, "{reason}"),
             RuleParseError::UnsupportedAtRule(reason) => write!(f, "{reason}"),
         }
     }
@@ -283,17 +332,13 @@ impl<'i> cssparser::QualifiedRuleParser<'i> for RuleCollector {
         input: &mut Parser<'i, 't>,
     ) -> Result<Self::QualifiedRule, cssparser::ParseError<'i, Self::Error>> {
         let (selector_text, selector_list) = prelude;
+        let declarations = parse_declarations_from_parser(input, &mut self.errors);
 
-        match parse_declarations_from_parser(input) {
-            Ok(declarations) => Ok(CssRule::Style(ParsedRule {
-                selector_text,
-                selector_list,
-                declarations,
-            })),
-            Err(err) => {
-                Err(input.new_custom_error(RuleParseError::InvalidDeclaration(err.to_string())))
-            }
-        }
+        Ok(CssRule::Style(ParsedRule {
+            selector_text,
+            selector_list,
+            declarations,
+        }))
     }
 }
 
@@ -352,12 +397,20 @@ impl<'i> cssparser::AtRuleParser<'i> for RuleCollector {
                 let mut nested_parser = RuleCollector::new(self._options);
                 let mut nested_stylesheet = StyleSheetParser::new(input, &mut nested_parser);
                 let mut rules = Vec::new();
+                let mut errors = Vec::new();
 
                 for result in &mut nested_stylesheet {
-                    if let Ok(CssRule::Style(rule)) = result {
-                        rules.push(rule);
+                    match result {
+                        Ok(CssRule::Style(rule)) => rules.push(rule),
+                        Ok(CssRule::AtRule(_)) => {}
+                        Err((err, slice)) => errors.push((
+                            format!("{} (near `{}`)", err, slice.trim()),
+                            SourceLocation::new(err.location.line + 1, err.location.column),
+                        )),
                     }
                 }
+                self.errors.append(&mut nested_parser.errors);
+                self.errors.append(&mut errors);
 
                 let media_rule = MediaRule { query, rules };
                 Ok(CssRule::AtRule(ParsedAtRule::Media(media_rule)))
@@ -392,9 +445,12 @@ fn at_rule_error<'i>(
     input.new_custom_error(RuleParseError::UnsupportedAtRule(message.to_string()))
 }
 
+/// Разбирает блок деклараций; неверные декларации отбрасываются и
+/// записываются в `errors`, остальные остаются в силе.
 fn parse_declarations_from_parser(
     parser: &mut Parser<'_, '_>,
-) -> Result<Vec<Property>, PropertyParseError> {
+    errors: &mut Vec<(String, SourceLocation)>,
+) -> Vec<Property> {
     let mut declarations = Vec::new();
 
     'declarations: while !parser.is_exhausted() {
@@ -402,6 +458,7 @@ fn parse_declarations_from_parser(
         if parser.is_exhausted() {
             break;
         }
+        let start = parser.current_source_location();
 
         let name =
             match parser.try_parse(|input| input.expect_ident().map(|ident| ident.to_string())) {
@@ -417,43 +474,29 @@ fn parse_declarations_from_parser(
             continue;
         }
 
-        let value_start = parser.state();
-        #[allow(unused_assignments)]
-        let mut value_end: Option<ParserState> = None;
-
-        loop {
-            let before = parser.state();
-            match parser.next_including_whitespace_and_comments() {
-                Ok(Token::Semicolon) => {
-                    value_end = Some(before);
-                    break;
-                }
-                Ok(_) => {}
-                Err(_) => {
-                    value_end = Some(parser.state());
-                    break;
-                }
-            }
-        }
-
-        let Some(end_state) = value_end else {
-            continue 'declarations;
-        };
-
-        let raw_value = parser
-            .slice(value_start.position()..end_state.position())
-            .trim()
-            .to_string();
+        // Значение целиком, включая вложенные блоки функций вроде `var(...)`
+        let value_start = parser.position();
+        let _ = parser.parse_until_before(cssparser::Delimiter::Semicolon, |input| {
+            while input.next_including_whitespace_and_comments().is_ok() {}
+            Ok::<(), cssparser::ParseError<'_, ()>>(())
+        });
+        let raw_value = parser.slice_from(value_start).trim().to_string();
+        let _ = parser.next();
 
         if raw_value.is_empty() {
             continue 'declarations;
         }
 
-        let mut parsed = properties::parse_property(&name, raw_value.trim())?;
-        declarations.append(&mut parsed);
+        match properties::parse_property(&name, raw_value.trim()) {
+            Ok(mut parsed) => declarations.append(&mut parsed),
+            Err(err) => errors.push((
+                format!("{err} (near `{name}: {raw_value}`)"),
+                SourceLocation::new(start.line + 1, start.column),
+            )),
+        }
     }
 
-    Ok(declarations)
+    declarations
 }
 
 fn skip_until_semicolon(parser: &mut Parser<'_, '_>) {
@@ -6,7 +6,9 @@ use cssparser::ToCss;
 use cssparser::{Parser, ParserInput, Token};
 use thiserror::Error;
 
+use super::calc::{self, CalcExpr, CalcType};
 use super::color::{self, Color};
+use super::variables;
 
 /// Нормализованная декларация CSS (один property/value).
 #[derive(Debug, Clone)]
@@ -59,6 +61,38 @@ pub fn parse_property(name: &str, raw_value: &str) -> Result<Vec<Property>, Prop
         return Err(PropertyParseError::EmptyValue(name_lower));
     }
 
+    // Пользовательские свойства чувствительны к регистру и не разбираются
+    if variables::is_custom_property(property_name) {
+        return Ok(vec![Property {
+            name: property_name.to_string(),
+            value: value_part.to_string(),
+            important,
+        }]);
+    }
+    // Значение с `var()` проверяется только после подстановки
+    if variables::contains_var(value_part) {
+        let longhands = match name_lower.as_str() {
+            "margin" | "padding" => ["-top", "-right", "-bottom", "-left"]
+                .iter()
+                .map(|suffix| format!("{name_lower}{suffix}"))
+                .collect(),
+            _ => vec![name_lower.clone()],
+        };
+        let value = if longhands.len() > 1 {
+            variables::pending_longhand(&name_lower, value_part)
+        } else {
+            value_part.to_string()
+        };
+        return Ok(longhands
+            .into_iter()
+            .map(|name| Property {
+                name,
+                value: value.clone(),
+                important,
+            })
+            .collect());
+    }
+
     let mut declarations = match name_lower.as_str() {
         "color" | "background-color" => vec![Property {
             name: name_lower.clone(),
@@ -171,7 +205,7 @@ fn parse_color(value: &str) -> Result<String, PropertyParseError> {
     }
 }
 
-fn format_color(color: Color) -> String {
+pub(crate) fn format_color(color: Color) -> String {
     let alpha = f32::from(color.a) / 255.0;
     format!(
         "rgba({}, {}, {}, {})",
@@ -268,7 +302,7 @@ fn parse_length_component(
     match token {
         Ok(Token::Dimension { value, unit, .. }) => {
             let unit_lower = unit.as_ref().to_ascii_lowercase();
-            if matches!(unit_lower.as_str(), "px" | "em" | "vh" | "vw") {
+            if calc::is_length_unit(&unit_lower) {
                 Ok(format!("{}{}", format_float(value), unit_lower))
             } else {
                 Err(PropertyParseError::UnsupportedUnit(unit_lower))
@@ -277,6 +311,21 @@ fn parse_length_component(
         Ok(Token::Percentage { unit_value, .. }) => {
             Ok(format!("{}%", format_float(unit_value * 100.0)))
         }
+        Ok(Token::Function(name)) if calc::is_math_function(&name) => {
+            let expr = parser
+                .parse_nested_block(|input| CalcExpr::parse_function(&name, input))
+                .map_err(|_| {
+                    PropertyParseError::InvalidValue("math function".into(), name.to_string())
+                })?;
+            if expr.calc_type() == Some(CalcType::Length) {
+                Ok(expr.simplify().to_string())
+            } else {
+                Err(PropertyParseError::InvalidValue(
+                    "length".into(),
+                    expr.to_string(),
+                ))
+            }
+        }
         Ok(Token::Number { value, .. }) if value.abs() <= f32::EPSILON => Ok("0".to_string()),
         Ok(Token::Number { value, .. }) => Err(PropertyParseError::InvalidValue(
             "number".into(),
@@ -349,7 +398,7 @@ fn parse_display(value: &str) -> Result<String, PropertyParseError> {
     Ok(ident)
 }
 
-fn format_float(value: f32) -> String {
+pub(crate) fn format_float(value: f32) -> String {
     if (value - value.round()).abs() <= f32::EPSILON {
         (value.round() as i32).to_string()
     } else {
@@ -0,0 +1,325 @@
+//! Сбор таблиц стилей документа: `<style>`, `<link rel="stylesheet">` и `@import`.
+//!
+//! Внешние таблицы разрешаются относительно базового URL документа (а `@import` —
+//! относительно URL импортирующей таблицы), загружаются параллельно через
+//! [`ResourceLoader`] и склеиваются в порядке документа, чтобы каскад
+//! учитывал их так же, как браузер. Перед склейкой относительные `url(...)`
+//! каждой таблицы переписываются в абсолютные от её собственного URL.
+
+use cssparser::{Delimiter, ParseError, Parser, ParserInput, Token};
+use futures_util::future::{BoxFuture, join_all};
+
+use crate::css::media_queries::{MediaType, media_list_matches};
+use crate::dom::Document;
+use crate::network::Url;
+use crate::resource_loader::{Resource, ResourceLoader, ResourceRequest};
+
+/// Максимальная глубина вложенности `@import` (защита от циклов)
+const MAX_IMPORT_DEPTH: usize = 8;
+
+/// Источник таблицы стилей в порядке появления в документе
+#[derive(Debug, Clone, PartialEq)]
+pub enum StylesheetSource {
+    /// Содержимое `<style>`
+    Inline(String),
+    /// `<link rel="stylesheet" href=...>` — `href` ещё не разрешён
+    External { href: String },
+}
+
+/// Одно правило `@import` из начала таблицы стилей
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRule {
+    pub url: String,
+    /// Список медиа-запросов после URL (`None` — для всех устройств)
+    pub media: Option<String>,
+}
+
+/// Параметры устройства, по которым фильтруются атрибуты `media` и `@import`
+#[derive(Debug, Clone)]
+pub struct MediaEnvironment {
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+    pub media_type: MediaType,
+}
+
+impl MediaEnvironment {
+    /// Подходит ли список медиа-запросов текущему устройству
+    pub fn matches(&self, media: Option<&str>) -> bool {
+        media.is_none_or(|list| {
+            media_list_matches(
+                list,
+                self.viewport_width,
+                self.viewport_height,
+                self.media_type.clone(),
+            )
+        })
+    }
+}
+
+/// Собирает `<style>` и `<link rel="stylesheet">` в порядке документа.
+///
+/// Элементы, чей атрибут `media` не подходит устройству, пропускаются.
+pub fn collect_stylesheet_sources(
+    dom: &Document,
+    environment: &MediaEnvironment,
+) -> Vec<StylesheetSource> {
+    let mut ids: Vec<usize> = dom
+        .nodes
+        .iter()
+        .filter(|(_, node)| matches!(node.tag_name(), Some("style" | "link")))
+        .map(|(&id, _)| id)
+        .collect();
+    // Идентификаторы узлов выдаются в порядке разбора документа
+    ids.sort_unstable();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let node = &dom.nodes[&id];
+            if !environment.matches(node.attributes.get("media").map(String::as_str)) {
+                return None;
+            }
+
+            match node.tag_name()? {
+                "style" => {
+                    let content = dom.get_text_content(id);
+                    (!content.is_empty()).then_some(StylesheetSource::Inline(content))
+                }
+                _ => {
+                    let rel = node.attributes.get("rel")?.to_ascii_lowercase();
+                    let mut keywords = rel.split_ascii_whitespace();
+                    // Альтернативные таблицы по умолчанию не применяются
+                    if !keywords.clone().any(|keyword| keyword == "stylesheet")
+                        || keywords.any(|keyword| keyword == "alternate")
+                    {
+                        return None;
+                    }
+                    let href = node.attributes.get("href")?.trim();
+                    (!href.is_empty()).then(|| StylesheetSource::External {
+                        href: href.to_string(),
+                    })
+                }
+            }
+        })
+        .collect()
+}
+
+/// Отделяет `@charset` и `@import` в начале таблицы от остальных правил.
+///
+/// По спецификации `@import` действует только до первого другого правила,
+/// поэтому сканирование останавливается на нём; остаток возвращается как есть.
+pub fn split_imports(css: &str) -> (Vec<ImportRule>, &str) {
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+    let mut imports = Vec::new();
+
+    loop {
+        let start = parser.state();
+        let keyword = match parser.next() {
+            Ok(Token::AtKeyword(name)) => name.to_ascii_lowercase(),
+            Ok(Token::CDO | Token::CDC) => continue,
+            _ => {
+                parser.reset(&start);
+                break;
+            }
+        };
+
+        match keyword.as_str() {
+            "charset" => {
+                let _ = parser.parse_until_after(Delimiter::Semicolon, skip_rest);
+            }
+            "import" => {
+                let rule = parser.parse_until_after(Delimiter::Semicolon, |input| {
+                    let url = input.expect_url_or_string()?.to_string();
+                    let media_start = input.position();
+                    skip_rest(input)?;
+                    let media = input.slice_from(media_start).trim();
+                    Ok(ImportRule {
+                        url,
+                        media: (!media.is_empty()).then(|| media.to_string()),
+                    })
+                });
+                if let Ok(rule) = rule {
+                    imports.push(rule);
+                }
+            }
+            _ => {
+                parser.reset(&start);
+                break;
+            }
+        }
+    }
+
+    let rest = parser.position().byte_index();
+    (imports, &css[rest..])
+}
+
+fn skip_rest<'i>(input: &mut Parser<'i, '_>) -> Result<(), ParseError<'i, ()>> {
+    while input.next().is_ok() {}
+    Ok(())
+}
+
+/// Переписывает относительные `url(...)` таблицы в абсолютные от `base`.
+///
+/// Таблицы склеиваются в одну строку, и без этого `@font-face src` или
+/// `background-image` из импортированной таблицы разрешались бы от документа.
+/// Ссылки на фрагмент (`url(#id)`) и неразрешимые значения не меняются.
+pub fn absolutize_urls(css: &str, base: &Url) -> String {
+    let mut input = ParserInput::new(css);
+    let mut parser = Parser::new(&mut input);
+    let mut replacements = Vec::new();
+    collect_urls(&mut parser, base, &mut replacements);
+
+    let mut result = String::with_capacity(css.len());
+    let mut last = 0;
+    for (start, end, url) in replacements {
+        result.push_str(&css[last..start]);
+        result.push_str("url(\"");
+        for ch in url.chars() {
+            if matches!(ch, '"' | '\\') {
+                result.push('\\');
+            }
+            result.push(ch);
+        }
+        result.push_str("\")");
+        last = end;
+    }
+    result.push_str(&css[last..]);
+    result
+}
+
+fn collect_urls<'i>(
+    parser: &mut Parser<'i, '_>,
+    base: &Url,
+    replacements: &mut Vec<(usize, usize, String)>,
+) {
+    loop {
+        let start = parser.position().byte_index();
+        let value = match parser.next_including_whitespace_and_comments() {
+            Ok(Token::UnquotedUrl(value)) => Some(value.to_string()),
+            Ok(Token::Function(name)) if name.eq_ignore_ascii_case("url") => parser
+                .parse_nested_block(|input| {
+                    let value = input.expect_string()?.to_string();
+                    Ok::<_, ParseError<'i, ()>>(value)
+                })
+                .ok(),
+            Ok(
+                Token::Function(_)
+                | Token::ParenthesisBlock
+                | Token::SquareBracketBlock
+                | Token::CurlyBracketBlock,
+            ) => {
+                let _ = parser.parse_nested_block(|input| {
+                    collect_urls(input, base, replacements);
+                    Ok::<_, ParseError<'i, ()>>(())
+                });
+                None
+            }
+            Ok(_) => None,
+            Err(_) => break,
+        };
+
+        if let Some(value) = value
+            && !value.is_empty()
+            && !value.starts_with('#')
+            && let Ok(url) = base.join(&value)
+        {
+            replacements.push((start, parser.position().byte_index(), url.to_string()));
+        }
+    }
+}
+
+/// Загружает внешние таблицы и раскрывает `@import`.
+///
+/// Возвращает тексты таблиц в порядке документа; импортированные таблицы
+/// стоят перед правилами импортирующей. Ошибки загрузки не прерывают
+/// обработку — недоступная таблица просто пропускается.
+pub async fn load_stylesheets(
+    loader: &ResourceLoader,
+    base_url: &Url,
+    sources: Vec<StylesheetSource>,
+    environment: &MediaEnvironment,
+) -> Vec<String> {
+    let urls: Vec<Option<Url>> = sources
+        .iter()
+        .map(|source| match source {
+            StylesheetSource::External { href } => base_url
+                .join(href)
+                .inspect_err(|err| tracing::warn!("Cannot resolve stylesheet URL: {err}"))
+                .ok(),
+            StylesheetSource::Inline(_) => None,
+        })
+        .collect();
+    let requests = urls.iter().flatten().cloned().map(ResourceRequest::Css);
+    let mut fetched = loader.fetch_all(requests.collect()).await.into_iter();
+
+    let mut sheets = Vec::with_capacity(sources.len());
+    for (source, url) in sources.into_iter().zip(urls) {
+        let sheet = match (source, url) {
+            (StylesheetSource::Inline(css), _) => Some((base_url.clone(), css)),
+            (StylesheetSource::External { .. }, Some(url)) => match fetched.next().flatten() {
+                Some(Resource::Css(url, css)) => Some((url, css)),
+                _ => {
+                    tracing::warn!("Failed to load stylesheet {url}");
+                    None
+                }
+            },
+            (StylesheetSource::External { .. }, None) => None,
+        };
+        if let Some((url, css)) = sheet {
+            sheets.push(expand_imports(loader, url, css, environment, 0));
+        }
+    }
+
+    join_all(sheets).await
+}
+
+fn expand_imports<'a>(
+    loader: &'a ResourceLoader,
+    url: Url,
+    css: String,
+    environment: &'a MediaEnvironment,
+    depth: usize,
+) -> BoxFuture<'a, String> {
+    Box::pin(async move {
+        let (imports, rest) = split_imports(&css);
+        let rest = absolutize_urls(rest, &url);
+        if imports.is_empty() {
+            return rest;
+        }
+        if depth >= MAX_IMPORT_DEPTH {
+            tracing::warn!("@import nesting is too deep in {url}, imports ignored");
+            return rest;
+        }
+
+        let urls: Vec<Url> = imports
+            .into_iter()
+            .filter(|import| environment.matches(import.media.as_deref()))
+            .filter_map(|import| url.join(&import.url).ok())
+            .collect();
+        let fetched = loader
+            .fetch_all(urls.iter().cloned().map(ResourceRequest::Css).collect())
+            .await;
+
+        let nested = fetched
+            .into_iter()
+            .zip(urls)
+            .filter_map(|(resource, import_url)| match resource {
+                Some(Resource::Css(import_url, css)) => Some(expand_imports(
+                    loader,
+                    import_url,
+                    css,
+                    environment,
+                    depth + 1,
+                )),
+                _ => {
+                    tracing::warn!("Failed to load imported stylesheet {import_url}");
+                    None
+                }
+            });
+
+        let mut combined = join_all(nested).await.join("\n");
+        combined.push('\n');
+        combined.push_str(&rest);
+        combined
+    })
+}
@@ -0,0 +1,248 @@
+//! Пользовательские свойства (`--name`) и подстановка `var()`.
+//!
+//! Пользовательские свойства наследуются всегда и хранятся без разбора. `var()`
+//! подставляется при вычислении значения элемента: сначала в его собственных
+//! пользовательских свойствах (циклы делают все свойства цикла недействительными),
+//! затем в обычных свойствах, которые после подстановки разбираются заново.
+//! Значение, которое не удалось подставить или разобрать, недействительно в
+//! момент вычисления и ведёт себя как `unset`, то есть просто отбрасывается.
+//!
+//! Shorthand с `var()` нельзя развернуть при разборе, поэтому его longhand-и
+//! получают отложенное значение ([`pending_longhand`]) и участвуют в каскаде
+//! наравне с остальными; развёртывание повторяется после подстановки.
+
+use std::collections::{HashMap, HashSet};
+
+use super::properties::parse_property;
+
+/// Вычисленные пользовательские свойства элемента: имя → значение после подстановки
+pub type CustomProperties = HashMap<String, String>;
+
+/// Префикс отложенного значения longhand-а: `<префикс><shorthand> <значение>`
+const PENDING_PREFIX: &str = "pending-substitution:";
+
+/// Является ли имя пользовательским свойством (`--brand-color`)
+pub fn is_custom_property(name: &str) -> bool {
+    name.starts_with("--")
+}
+
+/// Содержит ли значение ссылку `var()`
+pub fn contains_var(value: &str) -> bool {
+    find_var(value).is_some()
+}
+
+/// Отложенное значение longhand-а из shorthand-а с `var()`
+pub(crate) fn pending_longhand(shorthand: &str, value: &str) -> String {
+    format!("{PENDING_PREFIX}{shorthand} {value}")
+}
+
+/// Разбирает отложенное значение на shorthand и его исходное значение
+fn parse_pending(value: &str) -> Option<(&str, &str)> {
+    value.strip_prefix(PENDING_PREFIX)?.split_once(' ')
+}
+
+/// Вычисляет пользовательские свойства элемента по унаследованным и объявленным.
+///
+/// `inherit` и `unset` берут значение родителя, `initial` и недействительные
+/// значения удаляют свойство.
+pub fn resolve_custom_properties(
+    inherited: &CustomProperties,
+    declared: &HashMap<String, String>,
+) -> CustomProperties {
+    let mut resolver = Resolver {
+        inherited,
+        declared,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+        cyclic: HashSet::new(),
+    };
+    let names: Vec<&String> = declared.keys().collect();
+    for name in names {
+        resolver.resolve(name);
+    }
+
+    let mut properties = inherited.clone();
+    for (name, value) in resolver.resolved {
+        match value {
+            Some(value) => properties.insert(name, value),
+            None => properties.remove(&name),
+        };
+    }
+    properties
+}
+
+/// Подставляет `var()` в обычные свойства элемента и разбирает их заново.
+///
+/// Недействительные после подстановки свойства удаляются.
+pub fn substitute_properties(styles: &mut HashMap<String, String>, custom: &CustomProperties) {
+    let pending: Vec<String> = styles
+        .iter()
+        .filter(|(name, value)| !is_custom_property(name) && contains_var(value))
+        .map(|(name, _)| name.clone())
+        .collect();
+    // Развёрнутые shorthand-ы: одно значение на все их longhand-и
+    let mut expanded: HashMap<(String, String), HashMap<String, String>> = HashMap::new();
+
+    for name in pending {
+        let Some(value) = styles.remove(&name) else {
+            continue;
+        };
+        let computed = match parse_pending(&value) {
+            Some((shorthand, raw)) => expanded
+                .entry((shorthand.to_string(), raw.to_string()))
+                .or_insert_with(|| compute(shorthand, raw, custom))
+                .get(&name)
+                .cloned(),
+            None => compute(&name, &value, custom).remove(&name),
+        };
+        if let Some(computed) = computed {
+            styles.insert(name, computed);
+        }
+    }
+}
+
+/// Подставляет `var()` в значение и разбирает его как свойство `name`
+fn compute(name: &str, value: &str, custom: &CustomProperties) -> HashMap<String, String> {
+    substitute(value, &mut |reference| custom.get(reference).cloned())
+        .and_then(|value| parse_property(name, &value).ok())
+        .map(|declarations| {
+            declarations
+                .into_iter()
+                .map(|declaration| (declaration.name, declaration.value))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Вычисление пользовательских свойств одного элемента с поиском циклов
+struct Resolver<'a> {
+    inherited: &'a CustomProperties,
+    declared: &'a HashMap<String, String>,
+    /// `None` — свойство недействительно
+    resolved: HashMap<String, Option<String>>,
+    /// Свойства, которые вычисляются сейчас, от внешнего к внутреннему
+    stack: Vec<String>,
+    cyclic: HashSet<String>,
+}
+
+impl Resolver<'_> {
+    fn resolve(&mut self, name: &str) -> Option<String> {
+        let Some(value) = self.declared.get(name) else {
+            return self.inherited.get(name).cloned();
+        };
+        if let Some(resolved) = self.resolved.get(name) {
+            return resolved.clone();
+        }
+        if let Some(start) = self.stack.iter().position(|entry| entry == name) {
+            // Цикл: недействительны все его участники, даже с fallback
+            self.cyclic.extend(self.stack[start..].iter().cloned());
+            return None;
+        }
+
+        let keyword = value.trim().to_ascii_lowercase();
+        let computed = match keyword.as_str() {
+            "initial" => None,
+            "inherit" | "unset" => self.inherited.get(name).cloned(),
+            _ => {
+                self.stack.push(name.to_string());
+                let value = value.clone();
+                let computed = substitute(&value, &mut |reference| self.resolve(reference));
+                self.stack.pop();
+                computed.filter(|_| !self.cyclic.contains(name))
+            }
+        };
+        self.resolved.insert(name.to_string(), computed.clone());
+        computed
+    }
+}
+
+/// Заменяет все `var(--name, fallback)` значениями из `lookup`.
+///
+/// Fallback подставляется, только если свойства нет; `None` — ссылка на
+/// отсутствующее свойство без fallback или синтаксическая ошибка.
+pub fn substitute(value: &str, lookup: &mut dyn FnMut(&str) -> Option<String>) -> Option<String> {
+    let mut output = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = find_var(rest) {
+        output.push_str(&rest[..start]);
+        let arguments_start = start + "var(".len();
+        let end = arguments_start + closing_parenthesis(&rest[arguments_start..])?;
+        let arguments = &rest[arguments_start..end];
+
+        let (name, fallback) = match top_level_comma(arguments) {
+            Some(comma) => (&arguments[..comma], Some(&arguments[comma + 1..])),
+            None => (arguments, None),
+        };
+        let name = name.trim();
+        if !is_custom_property(name) {
+            return None;
+        }
+        let replacement = match lookup(name) {
+            Some(replacement) => replacement,
+            None => substitute(fallback?.trim(), lookup)?,
+        };
+        output.push_str(&replacement);
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    Some(output)
+}
+
+/// Позиция `var(` вне строк; регистр имени функции не важен
+fn find_var(value: &str) -> Option<usize> {
+    let bytes = value.as_bytes();
+    let mut quote = None;
+    for (index, &byte) in bytes.iter().enumerate() {
+        match quote {
+            Some(open) if byte == open => quote = None,
+            Some(_) => {}
+            None if byte == b'"' || byte == b'\'' => quote = Some(byte),
+            None => {
+                let is_var = bytes[index..]
+                    .get(..4)
+                    .is_some_and(|candidate| candidate.eq_ignore_ascii_case(b"var("));
+                let starts_word = index == 0 || !is_name_byte(bytes[index - 1]);
+                if is_var && starts_word {
+                    return Some(index);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn is_name_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || byte >= 0x80
+}
+
+/// Позиция `)`, закрывающей уже открытую скобку, с учётом вложенности и строк
+fn closing_parenthesis(value: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, byte) in value.bytes().enumerate() {
+        match (quote, byte) {
+            (Some(open), _) if byte == open => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (None, b'(') => depth += 1,
+            (None, b')') if depth == 0 => return Some(index),
+            (None, b')') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Первая запятая вне скобок: отделяет имя свойства от fallback
+fn top_level_comma(arguments: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, byte) in arguments.bytes().enumerate() {
+        match byte {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
@@ -9,4 +9,4 @@ pub mod serialization;
 
 // Публичные экспорты
 pub use document::Document;
-pub use node::{ElementState, Node};
+pub use node::{Dirty, ElementState, Node};
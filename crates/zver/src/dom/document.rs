@@ -1,7 +1,9 @@
-use super::node::{ElementState, Node};
+use super::node::{Dirty, ElementState, Node};
 use crate::css::selectors::PseudoElement;
+use crate::network::Url;
 use scraper::Html;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone)]
 pub struct Document {
@@ -10,6 +12,12 @@ pub struct Document {
     pub(super) next_id: usize,
     pub(super) html: Option<Html>,
     pub(super) pseudo_children: HashMap<usize, HashMap<PseudoElement, usize>>,
+    /// URL, с которого загружен документ
+    pub(super) url: Url,
+    /// Узлы, изменённые после последнего пересчёта стилей и layout
+    pub(super) dirty: HashMap<usize, Dirty>,
+    /// Кэш [`Document::base_url`]; сбрасывается при смене URL и изменениях `<base>`
+    pub(super) base_url: OnceLock<Url>,
 }
 
 // SAFETY: Document can be safely sent between threads because:
@@ -31,9 +39,75 @@ impl Document {
             next_id: 0,
             html: None,
             pseudo_children: HashMap::new(),
+            url: Url::about_blank(),
+            dirty: HashMap::new(),
+            base_url: OnceLock::new(),
         }
     }
 
+    /// URL документа (`about:blank`, пока ничего не загружено)
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn set_url(&mut self, url: Url) {
+        self.url = url;
+        self.base_url.take();
+    }
+
+    /// Базовый URL для относительных ссылок.
+    ///
+    /// Берётся из первого `<base href>` документа, иначе совпадает с URL документа.
+    pub fn base_url(&self) -> Url {
+        self.base_url
+            .get_or_init(|| {
+                self.first_base_href()
+                    .and_then(|href| self.url.join(href).ok())
+                    .unwrap_or_else(|| self.url.clone())
+            })
+            .clone()
+    }
+
+    /// `href` первого `<base>` с этим атрибутом в порядке обхода дерева
+    fn first_base_href(&self) -> Option<&str> {
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(id) = stack.pop() {
+            let Some(node) = self.nodes.get(&id) else {
+                continue;
+            };
+            if node.tag_name() == Some("base")
+                && let Some(href) = node.attributes.get("href")
+            {
+                return Some(href);
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        None
+    }
+
+    /// Сбрасывает кэш базового URL, если в поддереве узла есть `<base>`
+    pub(super) fn invalidate_base_url_in(&mut self, node_id: usize) {
+        if self.base_url.get().is_none() {
+            return;
+        }
+        let mut stack = vec![node_id];
+        while let Some(id) = stack.pop() {
+            let Some(node) = self.nodes.get(&id) else {
+                continue;
+            };
+            if node.tag_name() == Some("base") {
+                self.base_url.take();
+                return;
+            }
+            stack.extend(&node.children);
+        }
+    }
+
+    /// Разрешает `href`/`src` относительно базового URL документа
+    pub fn resolve_url(&self, reference: &str) -> Option<Url> {
+        self.base_url().join(reference).ok()
+    }
+
     pub fn attribute(&self, node_id: usize, name: &str) -> Option<String> {
         let node = self.nodes.get(&node_id)?;
         node.attributes.get(name).cloned()
@@ -67,15 +141,54 @@ impl Document {
             .get_mut(&node_id)
             .ok_or_else(|| format!("Node {} not found", node_id))?;
 
-        if enabled {
-            node.element_state.insert(state);
-        } else {
-            node.element_state.remove(state);
+        let previous = node.element_state;
+        node.element_state.set(state, enabled);
+        if node.element_state != previous {
+            self.mark_dirty(node_id, Dirty::STYLE);
         }
 
         Ok(())
     }
 
+    /// Помечает поддерево узла для пересчёта в [`crate::Zver::update`]
+    pub fn mark_dirty(&mut self, node_id: usize, dirty: Dirty) {
+        *self.dirty.entry(node_id).or_default() |= dirty;
+    }
+
+    /// Есть ли изменения, ещё не учтённые в стилях и layout
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Забирает накопленные изменения, сбрасывая их
+    pub fn take_dirty(&mut self) -> HashMap<usize, Dirty> {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Подключён ли узел к корню документа
+    pub fn is_connected(&self, node_id: usize) -> bool {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            if Some(id) == self.root {
+                return true;
+            }
+            current = self.nodes.get(&id).and_then(|node| node.parent);
+        }
+        false
+    }
+
+    /// Является ли `node_id` потомком `ancestor_id` (или им самим)
+    pub fn is_inclusive_descendant(&self, node_id: usize, ancestor_id: usize) -> bool {
+        let mut current = Some(node_id);
+        while let Some(id) = current {
+            if id == ancestor_id {
+                return true;
+            }
+            current = self.nodes.get(&id).and_then(|node| node.parent);
+        }
+        false
+    }
+
     pub fn get_text_content(&self, node_id: usize) -> String {
         let mut text = String::new();
         self.collect_text_recursive(node_id, &mut text);
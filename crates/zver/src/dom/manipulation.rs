@@ -1,5 +1,5 @@
 use super::document::Document;
-use super::node::{ElementState, Node};
+use super::node::{Dirty, ElementState, Node};
 use std::collections::HashMap;
 
 impl Document {
@@ -25,6 +25,19 @@ impl Document {
 
     /// Добавляет дочерний элемент к родителю
     pub fn append_child(&mut self, parent_id: usize, child_id: usize) -> Result<(), String> {
+        self.invalidate_base_url_in(child_id);
+
+        // Перенос узла: сначала отсоединяем его от прежнего родителя
+        let previous_parent = self.nodes.get(&child_id).and_then(|node| node.parent);
+        if let Some(previous_parent) = previous_parent
+            && previous_parent != parent_id
+        {
+            if let Some(node) = self.nodes.get_mut(&previous_parent) {
+                node.children.retain(|&id| id != child_id);
+            }
+            self.mark_dirty(previous_parent, Dirty::STYLE);
+        }
+
         // Обновляем внутренние связи
         if let Some(parent_node) = self.nodes.get_mut(&parent_id)
             && !parent_node.children.contains(&child_id)
@@ -36,11 +49,15 @@ impl Document {
             child_node.parent = Some(parent_id);
         }
 
+        // Новый ребёнок может изменить стили соседей (`:last-child`, `+`, `~`)
+        self.mark_dirty(parent_id, Dirty::STYLE);
         Ok(())
     }
 
     /// Удаляет дочерний элемент
     pub fn remove_child(&mut self, parent_id: usize, child_id: usize) -> Result<(), String> {
+        self.invalidate_base_url_in(child_id);
+
         // Обновляем внутренние связи
         if let Some(parent_node) = self.nodes.get_mut(&parent_id) {
             parent_node.children.retain(|&id| id != child_id);
@@ -51,6 +68,7 @@ impl Document {
         }
 
         self.remove_all_pseudo_children(child_id);
+        self.mark_dirty(parent_id, Dirty::STYLE);
         Ok(())
     }
 
@@ -65,7 +83,13 @@ impl Document {
             return Err(format!("Node {} is not an element", node_id));
         }
 
-        node.attributes.insert(name.to_string(), value.to_string());
+        if node.attributes.get(name).map(String::as_str) != Some(value) {
+            node.attributes.insert(name.to_string(), value.to_string());
+            if name == "href" && node.tag_name() == Some("base") {
+                self.base_url.take();
+            }
+            self.mark_dirty(node_id, Dirty::STYLE);
+        }
         Ok(())
     }
 
@@ -83,9 +107,24 @@ impl Document {
             .map(|node| node.children.clone())
             .unwrap_or_default();
 
-        // Удаляем старых детей
+        // Без элементов среди детей и при неизменной `:empty` достаточно пересчитать геометрию
+        let mut had_elements = false;
+        let mut was_empty = true;
+        for &child_id in &children_to_remove {
+            if let Some(child) = self.nodes.get(&child_id) {
+                had_elements |= child.is_element();
+                was_empty &= !child.is_element()
+                    && child
+                        .text_content
+                        .as_deref()
+                        .is_none_or(|t| t.trim().is_empty());
+            }
+        }
+
+        // Удаляем старых детей вместе с их поддеревьями
         for child_id in children_to_remove {
-            self.nodes.remove(&child_id);
+            self.invalidate_base_url_in(child_id);
+            self.remove_subtree(child_id);
         }
 
         // Создаем новый текстовый узел
@@ -106,17 +145,30 @@ impl Document {
         self.nodes.insert(text_node_id, text_node);
 
         if let Some(node) = self.nodes.get_mut(&node_id) {
-            let old_children = std::mem::take(&mut node.children);
-            for child_id in old_children {
-                if let Some(child_node) = self.nodes.get_mut(&child_id) {
-                    child_node.parent = None;
-                }
-            }
+            node.children = vec![text_node_id];
         }
 
+        let dirty = if had_elements || was_empty != text.trim().is_empty() {
+            Dirty::STYLE
+        } else {
+            Dirty::LAYOUT
+        };
+        self.mark_dirty(node_id, dirty);
         Ok(())
     }
 
+    /// Удаляет узел и всех его потомков (включая псевдоэлементы) из документа
+    fn remove_subtree(&mut self, node_id: usize) {
+        let mut stack = vec![node_id];
+        while let Some(id) = stack.pop() {
+            self.remove_all_pseudo_children(id);
+            self.dirty.remove(&id);
+            if let Some(node) = self.nodes.remove(&id) {
+                stack.extend(node.children);
+            }
+        }
+    }
+
     /// Получает имя тега элемента
     pub fn get_tag_name(&self, node_id: usize) -> Option<String> {
         self.nodes
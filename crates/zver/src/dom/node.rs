@@ -15,6 +15,17 @@ bitflags! {
     }
 }
 
+bitflags! {
+    /// Что нужно пересчитать для поддерева узла после изменения DOM.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct Dirty: u8 {
+        /// Каскад: атрибуты, состояния или состав детей
+        const STYLE = 1 << 0;
+        /// Только геометрия: изменился текст
+        const LAYOUT = 1 << 1;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Node {
     pub id: usize,
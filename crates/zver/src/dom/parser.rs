@@ -1,16 +1,19 @@
 use super::document::Document;
 use super::node::{ElementState, Node};
+use crate::error::ZverResult;
 use scraper::Html;
 use std::collections::HashMap;
 
 impl Document {
-    pub async fn parse_html(&mut self, html_str: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn parse_html(&mut self, html_str: &str) -> ZverResult<()> {
         let html = Html::parse_document(html_str);
 
         self.nodes.clear();
         self.root = None;
         self.next_id = 0;
         self.pseudo_children.clear();
+        self.dirty.clear();
+        self.base_url.take();
 
         // Строим дерево из scraper's Html
         let root_id = self.build_tree_from_html(&html);
@@ -12,7 +12,10 @@ pub fn serialize_node(dom: &Document, node_id: usize, html: &mut String) {
     if let Some(node) = dom.nodes.get(&node_id) {
         if let Some(tag) = &node.tag_name {
             html.push_str(&format!("<{}", tag));
-            for (name, value) in &node.attributes {
+            // Атрибуты по имени: вывод не зависит от порядка в HashMap
+            let mut attributes: Vec<_> = node.attributes.iter().collect();
+            attributes.sort();
+            for (name, value) in attributes {
                 html.push_str(&format!(r#" {}="{}""#, name, value));
             }
             html.push('>');
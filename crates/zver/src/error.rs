@@ -0,0 +1,192 @@
+//! Ошибки конвейера движка.
+//!
+//! [`ZverError`] разделяет сбои по стадиям (сеть, разбор, скрипты, layout,
+//! рендеринг), чтобы встраивающее приложение могло показать точную страницу
+//! ошибки и повторить только восстановимые операции.
+
+use std::fmt;
+
+use crate::network::UrlError;
+
+/// Результат операций движка
+pub type ZverResult<T> = Result<T, ZverError>;
+
+/// Позиция в исходном тексте; строки и столбцы считаются с 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// URL или имя источника, если известно
+    pub source: Option<String>,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl SourceLocation {
+    pub fn new(line: u32, column: u32) -> Self {
+        Self {
+            source: None,
+            line,
+            column,
+        }
+    }
+
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "{}:", source)?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Что именно не удалось разобрать
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseTarget {
+    Html,
+    Css,
+    Url,
+}
+
+impl fmt::Display for ParseTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Html => "HTML",
+            Self::Css => "CSS",
+            Self::Url => "URL",
+        })
+    }
+}
+
+/// Сбой загрузки ресурса
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkError {
+    #[error("unsupported URL scheme: {0}")]
+    UnsupportedScheme(String),
+    #[error("malformed data URL: {0}")]
+    MalformedDataUrl(String),
+    #[error("HTTP error: {status} for {url}")]
+    Http { url: String, status: u16 },
+    #[error("request to {url} timed out")]
+    Timeout { url: String },
+    #[error("request to {url} failed: {source}")]
+    Request {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("failed to read {url}: {source}")]
+    Io {
+        url: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl NetworkError {
+    pub(crate) fn request(url: impl Into<String>, source: reqwest::Error) -> Self {
+        let url = url.into();
+        if source.is_timeout() {
+            Self::Timeout { url }
+        } else {
+            Self::Request { url, source }
+        }
+    }
+
+    /// Может ли повторный запрос завершиться успешно
+    pub fn is_recoverable(&self) -> bool {
+        match self {
+            Self::Timeout { .. } => true,
+            Self::Http { status, .. } => *status == 408 || *status == 429 || *status >= 500,
+            Self::Request { source, .. } => source.is_connect() || source.is_request(),
+            Self::Io { source, .. } => matches!(
+                source.kind(),
+                std::io::ErrorKind::Interrupted | std::io::ErrorKind::TimedOut
+            ),
+            Self::UnsupportedScheme(_) | Self::MalformedDataUrl(_) => false,
+        }
+    }
+}
+
+/// Ошибка движка с указанием стадии конвейера
+#[derive(Debug, thiserror::Error)]
+pub enum ZverError {
+    #[error("network error: {0}")]
+    Network(#[from] NetworkError),
+
+    #[error("{target} parse error{}: {message}", at(location))]
+    Parse {
+        target: ParseTarget,
+        message: String,
+        location: Option<SourceLocation>,
+    },
+
+    #[error("script error{}: {message}", at(location))]
+    Script {
+        message: String,
+        location: Option<SourceLocation>,
+    },
+
+    #[error("layout error: {0}")]
+    Layout(String),
+
+    #[error("render error: {0}")]
+    Render(String),
+}
+
+impl ZverError {
+    /// Ошибка JavaScript; позиция извлекается из сообщения Boa (`at line N, col M`)
+    pub fn script(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let location = script_location(&message);
+        Self::Script { message, location }
+    }
+
+    /// Стоит ли повторять операцию (таймауты, 5xx, обрыв соединения)
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Self::Network(error) if error.is_recoverable())
+    }
+
+    /// Позиция в исходном тексте для ошибок разбора и скриптов
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            Self::Parse { location, .. } | Self::Script { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl From<UrlError> for ZverError {
+    fn from(error: UrlError) -> Self {
+        Self::Parse {
+            target: ParseTarget::Url,
+            message: error.to_string(),
+            location: None,
+        }
+    }
+}
+
+fn at(location: &Option<SourceLocation>) -> String {
+    location
+        .as_ref()
+        .map(|location| format!(" at {}", location))
+        .unwrap_or_default()
+}
+
+fn script_location(message: &str) -> Option<SourceLocation> {
+    let (_, rest) = message.rsplit_once("line ")?;
+    let (line, rest) = rest.split_once(", col")?;
+    let column: String = rest
+        .trim_start_matches(['.', ' ', ':'])
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    Some(SourceLocation::new(
+        line.trim().parse().ok()?,
+        column.parse().ok()?,
+    ))
+}
@@ -0,0 +1,516 @@
+//! Ввод пользователя: события указателя и клавиатуры от окна браузера.
+//!
+//! [`Zver::dispatch_input`] находит цель через hit testing, обновляет состояния
+//! элементов (`:hover`, `:active`, `:focus`) и рассылает DOM-события с
+//! координатами и модификаторами, после чего изменения применяются как в
+//! [`Zver::run_until_idle`].
+
+use crate::Zver;
+use crate::dom::{Document, ElementState, Node};
+use crate::error::ZverResult;
+use crate::js::events::{DOCUMENT_TARGET, DispatchOutcome, EventInit, Modifiers};
+use crate::layout::{Overflow, ScrollState};
+use crate::navigation::NavigationRequest;
+
+/// Шаг прокрутки стрелками, в CSS-пикселях
+const ARROW_SCROLL: f32 = 40.0;
+/// Доля видимой области, на которую прокручивают PageUp/PageDown и пробел
+const PAGE_SCROLL: f32 = 0.875;
+
+/// Сдвиг прокрутки по осям в зависимости от состояния контейнера
+type ScrollDelta = fn(&ScrollState) -> (f32, f32);
+
+/// Кнопка мыши
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    Primary,
+    Middle,
+    Secondary,
+}
+
+impl MouseButton {
+    /// Значение `MouseEvent.button`
+    fn index(self) -> i16 {
+        match self {
+            MouseButton::Primary => 0,
+            MouseButton::Middle => 1,
+            MouseButton::Secondary => 2,
+        }
+    }
+
+    /// Бит в `MouseEvent.buttons`
+    fn mask(self) -> u16 {
+        match self {
+            MouseButton::Primary => 1,
+            MouseButton::Secondary => 2,
+            MouseButton::Middle => 4,
+        }
+    }
+}
+
+/// Событие ввода; координаты — в CSS-пикселях относительно viewport
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    PointerMove {
+        x: f32,
+        y: f32,
+        modifiers: Modifiers,
+    },
+    PointerDown {
+        x: f32,
+        y: f32,
+        button: MouseButton,
+        modifiers: Modifiers,
+    },
+    PointerUp {
+        x: f32,
+        y: f32,
+        button: MouseButton,
+        modifiers: Modifiers,
+    },
+    /// Указатель покинул страницу: `:hover` снимается
+    PointerLeave,
+    /// Прокрутка колесом; положительный `delta_y` — вниз
+    Wheel {
+        x: f32,
+        y: f32,
+        delta_x: f32,
+        delta_y: f32,
+        modifiers: Modifiers,
+    },
+    /// `key` — значение `KeyboardEvent.key` (`"a"`, `"Enter"`),
+    /// `code` — физическая клавиша (`"KeyA"`)
+    KeyDown {
+        key: String,
+        code: String,
+        modifiers: Modifiers,
+    },
+    KeyUp {
+        key: String,
+        code: String,
+        modifiers: Modifiers,
+    },
+}
+
+/// Итог обработки [`InputEvent`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InputOutcome {
+    /// Элемент, получивший событие (для клавиш — элемент в фокусе)
+    pub target: Option<usize>,
+    /// После нажатия и отпускания основной кнопки разослан `click`
+    pub clicked: bool,
+    /// Слушатель вызвал `preventDefault()`; при отпускании кнопки — у `click`, если он был
+    pub default_prevented: bool,
+    /// Переход по ссылке или отправка формы, которые не отменил ни `click`,
+    /// ни `submit`; выполнить его — дело окна ([`Zver::navigate`])
+    pub navigation: Option<NavigationRequest>,
+}
+
+/// Состояние ввода между событиями
+#[derive(Debug, Default)]
+pub(crate) struct InputState {
+    hovered: Option<usize>,
+    focused: Option<usize>,
+    /// Элемент и кнопка последнего нажатия: по ним определяется `click`
+    pressed: Option<(usize, MouseButton)>,
+    /// Нажатые кнопки (`MouseEvent.buttons`)
+    buttons: u16,
+}
+
+impl Zver {
+    /// Обрабатывает событие ввода так, как это сделал бы браузер.
+    ///
+    /// - перемещение указателя обновляет `:hover` у цели и её предков и
+    ///   рассылает `mouseout`/`mouseover` при смене цели, затем `mousemove`;
+    /// - нажатие основной кнопки включает `:active`, рассылает `mousedown` и,
+    ///   если он не отменён, переводит фокус (`blur`/`focus`, `:focus`) на
+    ///   ближайший фокусируемый элемент;
+    /// - отпускание рассылает `mouseup`, затем `click` общему предку элементов
+    ///   нажатия и отпускания;
+    /// - колесо рассылает `wheel`, клавиши — `keydown`/`keyup` элементу в
+    ///   фокусе или `document`.
+    ///
+    /// Неотменённые `wheel` и клавиши прокрутки (стрелки, PageUp/PageDown,
+    /// пробел, Home/End вне полей формы) прокручивают ближайший подходящий
+    /// контейнер или viewport ([`Zver::scroll`]).
+    ///
+    /// События всплывают и отменяемы; итог сообщает, был ли `click` и вызван ли
+    /// `preventDefault()`. Неотменённый `click` по ссылке или кнопке submit и
+    /// Enter в поле формы дают [`InputOutcome::navigation`].
+    pub async fn dispatch_input(&self, event: InputEvent) -> ZverResult<InputOutcome> {
+        let outcome = match event {
+            InputEvent::PointerMove { x, y, modifiers } => {
+                let buttons = self.input_state().buttons;
+                let init = pointer_init(x, y, 0, buttons, modifiers);
+                let target = self.element_at(x, y).await;
+                self.update_hover(target, &init).await;
+
+                let path = self.element_path(target).await;
+                let dispatched = self.fire(&path, "mousemove", &init).await;
+                InputOutcome {
+                    target,
+                    default_prevented: dispatched.default_prevented,
+                    ..InputOutcome::default()
+                }
+            }
+            InputEvent::PointerDown {
+                x,
+                y,
+                button,
+                modifiers,
+            } => {
+                let target = self.element_at(x, y).await;
+                let buttons = {
+                    let mut state = self.input_state();
+                    state.buttons |= button.mask();
+                    state.pressed = target.map(|id| (id, button));
+                    state.buttons
+                };
+                let init = pointer_init(x, y, button.index(), buttons, modifiers);
+                self.update_hover(target, &init).await;
+
+                let path = self.element_path(target).await;
+                if button == MouseButton::Primary {
+                    self.set_state(&path, ElementState::ACTIVE, true).await;
+                }
+                let dispatched = self.fire(&path, "mousedown", &init).await;
+                if button == MouseButton::Primary && !dispatched.default_prevented {
+                    let focusable = {
+                        let dom = self.dom.read().await;
+                        path.iter().copied().find(|&id| is_focusable(&dom, id))
+                    };
+                    self.focus(focusable).await;
+                }
+                InputOutcome {
+                    target,
+                    default_prevented: dispatched.default_prevented,
+                    ..InputOutcome::default()
+                }
+            }
+            InputEvent::PointerUp {
+                x,
+                y,
+                button,
+                modifiers,
+            } => {
+                let (pressed, buttons) = {
+                    let mut state = self.input_state();
+                    state.buttons &= !button.mask();
+                    let pressed = match state.pressed {
+                        Some((id, pressed_button)) if pressed_button == button => {
+                            state.pressed = None;
+                            Some(id)
+                        }
+                        _ => None,
+                    };
+                    (pressed, state.buttons)
+                };
+                let init = pointer_init(x, y, button.index(), buttons, modifiers);
+                let target = self.element_at(x, y).await;
+                self.update_hover(target, &init).await;
+
+                let pressed_path = self.element_path(pressed).await;
+                if button == MouseButton::Primary {
+                    self.set_state(&pressed_path, ElementState::ACTIVE, false)
+                        .await;
+                }
+                let path = self.element_path(target).await;
+                let mut dispatched = self.fire(&path, "mouseup", &init).await;
+
+                // click получает общий предок элементов нажатия и отпускания
+                let click_target = (button == MouseButton::Primary)
+                    .then(|| path.iter().position(|id| pressed_path.contains(id)))
+                    .flatten();
+                let mut navigation = None;
+                if let Some(index) = click_target {
+                    dispatched = self.fire(&path[index..], "click", &init).await;
+                    if !dispatched.default_prevented {
+                        navigation = self.activation_behavior(&path[index..]).await;
+                    }
+                }
+                InputOutcome {
+                    target,
+                    clicked: click_target.is_some(),
+                    default_prevented: dispatched.default_prevented,
+                    navigation,
+                }
+            }
+            InputEvent::PointerLeave => {
+                self.update_hover(None, &pointer_init(0.0, 0.0, 0, 0, Modifiers::default()))
+                    .await;
+                InputOutcome::default()
+            }
+            InputEvent::Wheel {
+                x,
+                y,
+                delta_x,
+                delta_y,
+                modifiers,
+            } => {
+                let buttons = self.input_state().buttons;
+                let init = EventInit {
+                    delta_x,
+                    delta_y,
+                    ..pointer_init(x, y, 0, buttons, modifiers)
+                };
+                let target = self.element_at(x, y).await;
+                let path = self.element_path(target).await;
+                let dispatched = self.fire(&path, "wheel", &init).await;
+                if !dispatched.default_prevented {
+                    self.scroll_nearest(&path, |_| (delta_x, delta_y)).await;
+                }
+                InputOutcome {
+                    target,
+                    default_prevented: dispatched.default_prevented,
+                    ..InputOutcome::default()
+                }
+            }
+            InputEvent::KeyDown {
+                key,
+                code,
+                modifiers,
+            } => {
+                let enter = key == "Enter";
+                let scroll = scroll_delta(&key, modifiers);
+                let mut outcome = self.key_event("keydown", key, code, modifiers).await;
+                if let Some(delta) = scroll
+                    && !outcome.default_prevented
+                    && !self.focus_in_form_control().await
+                {
+                    let path = self.element_path(outcome.target).await;
+                    self.scroll_nearest(&path, delta).await;
+                }
+                // Enter в поле ввода неявно отправляет его форму
+                if enter
+                    && !outcome.default_prevented
+                    && let Some(field) = outcome.target
+                {
+                    outcome.navigation = self.implicit_submission(field).await;
+                }
+                outcome
+            }
+            InputEvent::KeyUp {
+                key,
+                code,
+                modifiers,
+            } => self.key_event("keyup", key, code, modifiers).await,
+        };
+
+        self.run_until_idle().await?;
+        Ok(outcome)
+    }
+
+    /// `keydown`/`keyup` элементу в фокусе, без фокуса — `document`
+    async fn key_event(
+        &self,
+        event_type: &str,
+        key: String,
+        code: String,
+        modifiers: Modifiers,
+    ) -> InputOutcome {
+        let init = EventInit {
+            bubbles: true,
+            cancelable: true,
+            key,
+            code,
+            modifiers,
+            ..EventInit::default()
+        };
+        let target = self.focused_element();
+        let mut path = self.element_path(target).await;
+        if path.is_empty() {
+            path.push(DOCUMENT_TARGET);
+        }
+        let dispatched = self.fire(&path, event_type, &init).await;
+        InputOutcome {
+            target,
+            default_prevented: dispatched.default_prevented,
+            ..InputOutcome::default()
+        }
+    }
+
+    /// Элемент в фокусе после последнего [`Zver::dispatch_input`]
+    pub fn focused_element(&self) -> Option<usize> {
+        self.input_state().focused
+    }
+
+    fn input_state(&self) -> std::sync::MutexGuard<'_, InputState> {
+        self.input
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Ближайший элемент под точкой (для текста — его родитель)
+    async fn element_at(&self, x: f32, y: f32) -> Option<usize> {
+        let dom = self.dom.read().await;
+        let layout = self.layout.read().await;
+        crate::render::hit_test::hit_test(&layout, &dom, x, y)?.element(&dom)
+    }
+
+    /// Элемент и его предки-элементы, от самого глубокого к корню
+    pub(crate) async fn element_path(&self, node_id: Option<usize>) -> Vec<usize> {
+        let Some(node_id) = node_id else {
+            return Vec::new();
+        };
+        let dom = self.dom.read().await;
+        std::iter::successors(Some(node_id), |id| dom.nodes.get(id)?.parent)
+            .filter(|id| dom.nodes.get(id).is_some_and(Node::is_element))
+            .collect()
+    }
+
+    async fn set_state(&self, path: &[usize], state: ElementState, enabled: bool) {
+        let mut dom = self.dom.write().await;
+        for &id in path {
+            let _ = dom.set_element_state(id, state, enabled);
+        }
+    }
+
+    /// Переносит `:hover` на новую цель и рассылает `mouseout`/`mouseover`
+    async fn update_hover(&self, target: Option<usize>, init: &EventInit) {
+        let previous = std::mem::replace(&mut self.input_state().hovered, target);
+        if previous == target {
+            return;
+        }
+
+        let old_path = self.element_path(previous).await;
+        let new_path = self.element_path(target).await;
+        {
+            let mut dom = self.dom.write().await;
+            for &id in old_path.iter().filter(|id| !new_path.contains(id)) {
+                let _ = dom.set_element_state(id, ElementState::HOVER, false);
+            }
+            for &id in &new_path {
+                let _ = dom.set_element_state(id, ElementState::HOVER, true);
+            }
+        }
+
+        let mut js_engine = self.js.write().await;
+        js_engine.dispatch_dom_event(&old_path, "mouseout", init);
+        js_engine.dispatch_dom_event(&new_path, "mouseover", init);
+    }
+
+    /// Переводит фокус: `blur` старому элементу, `focus` новому
+    async fn focus(&self, next: Option<usize>) {
+        let previous = std::mem::replace(&mut self.input_state().focused, next);
+        if previous == next {
+            return;
+        }
+
+        {
+            let mut dom = self.dom.write().await;
+            if let Some(id) = previous {
+                let _ = dom.set_element_state(id, ElementState::FOCUS, false);
+            }
+            if let Some(id) = next {
+                let _ = dom.set_element_state(id, ElementState::FOCUS, true);
+            }
+        }
+
+        let mut js_engine = self.js.write().await;
+        if let Some(id) = previous {
+            js_engine.dispatch_dom_event(&[id], "blur", &EventInit::default());
+        }
+        if let Some(id) = next {
+            js_engine.dispatch_dom_event(&[id], "focus", &EventInit::default());
+        }
+    }
+
+    /// Прокручивает ближайший к цели контейнер с `overflow: scroll|auto`,
+    /// который может сдвинуться на `delta`; если такого нет — viewport
+    async fn scroll_nearest(
+        &self,
+        path: &[usize],
+        delta: impl Fn(&ScrollState) -> (f32, f32),
+    ) -> bool {
+        let viewport = self.scroll.viewport();
+        let containers: Vec<usize> = {
+            let layout = self.layout.read().await;
+            let styles = layout.resolved_styles();
+            path.iter()
+                .copied()
+                .filter(|id| {
+                    styles.get(id).is_some_and(|style| {
+                        [style.overflow.x, style.overflow.y]
+                            .iter()
+                            .any(|axis| matches!(axis, Overflow::Scroll | Overflow::Auto))
+                    })
+                })
+                .chain(viewport)
+                .collect()
+        };
+        containers.into_iter().any(|id| {
+            self.scroll.get(id).is_some_and(|state| {
+                let (dx, dy) = delta(&state);
+                self.scroll.scroll_by(id, dx, dy)
+            })
+        })
+    }
+
+    /// В фокусе поле или кнопка формы: клавиши достаются им, а не прокрутке
+    async fn focus_in_form_control(&self) -> bool {
+        let Some(focused) = self.focused_element() else {
+            return false;
+        };
+        let dom = self.dom.read().await;
+        matches!(
+            dom.nodes.get(&focused).and_then(Node::tag_name),
+            Some("input" | "textarea" | "select" | "button")
+        )
+    }
+
+    async fn fire(&self, path: &[usize], event_type: &str, init: &EventInit) -> DispatchOutcome {
+        self.js
+            .write()
+            .await
+            .dispatch_dom_event(path, event_type, init)
+    }
+}
+
+/// Всплывающее отменяемое событие мыши
+fn pointer_init(x: f32, y: f32, button: i16, buttons: u16, modifiers: Modifiers) -> EventInit {
+    EventInit {
+        bubbles: true,
+        cancelable: true,
+        client_x: x,
+        client_y: y,
+        button,
+        buttons,
+        modifiers,
+        ..EventInit::default()
+    }
+}
+
+/// Прокрутка клавишей; шаг страницы зависит от видимой области контейнера
+fn scroll_delta(key: &str, modifiers: Modifiers) -> Option<ScrollDelta> {
+    let delta: ScrollDelta = match key {
+        "ArrowDown" => |_| (0.0, ARROW_SCROLL),
+        "ArrowUp" => |_| (0.0, -ARROW_SCROLL),
+        "ArrowRight" => |_| (ARROW_SCROLL, 0.0),
+        "ArrowLeft" => |_| (-ARROW_SCROLL, 0.0),
+        "PageDown" => |state| (0.0, state.client_height * PAGE_SCROLL),
+        "PageUp" => |state| (0.0, -state.client_height * PAGE_SCROLL),
+        " " if modifiers.shift => |state| (0.0, -state.client_height * PAGE_SCROLL),
+        " " => |state| (0.0, state.client_height * PAGE_SCROLL),
+        "Home" => |state| (0.0, -state.scroll_y),
+        "End" => |state| (0.0, state.max_scroll_y() - state.scroll_y),
+        _ => return None,
+    };
+    Some(delta)
+}
+
+/// Может ли элемент получить фокус щелчком
+fn is_focusable(document: &Document, node_id: usize) -> bool {
+    let Some(node) = document.nodes.get(&node_id) else {
+        return false;
+    };
+    if node.attributes.contains_key("tabindex") {
+        return true;
+    }
+    match node.tag_name() {
+        Some("a") => node.attributes.contains_key("href"),
+        Some("button" | "input" | "select" | "textarea") => {
+            !node.attributes.contains_key("disabled")
+        }
+        _ => false,
+    }
+}
@@ -3,8 +3,15 @@ mod console;
 mod document;
 mod element;
 mod engine;
+mod event_object;
 pub mod events;
+mod location;
+pub mod modules;
+pub mod scripts;
+mod scroll;
 mod timers;
+mod window;
 
 // Публичные экспорты
+pub use console::{ConsoleLevel, ConsoleLog, ConsoleMessage};
 pub use engine::{JSEngine, JSValue};
@@ -1,51 +1,113 @@
 use boa_engine::{
     Context, JsValue, NativeFunction, js_string, object::ObjectInitializer, property::Attribute,
 };
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
-/// Инициализирует глобальный объект console с методами log, error, warn
-pub fn init_console(context: &mut Context) {
-    // console.log
-    let console_log = NativeFunction::from_fn_ptr(|_this, args, _context| {
-        for (i, arg) in args.iter().enumerate() {
-            if i > 0 {
-                print!(" ");
-            }
-            print!("{}", arg.display());
+/// Уровень сообщения консоли
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    Log,
+    Warn,
+    Error,
+}
+
+impl ConsoleLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
         }
-        println!();
-        Ok(JsValue::undefined())
-    });
-
-    // console.error
-    let console_error = NativeFunction::from_fn_ptr(|_this, args, _context| {
-        eprint!("ERROR: ");
-        for (i, arg) in args.iter().enumerate() {
-            if i > 0 {
-                eprint!(" ");
-            }
-            eprint!("{}", arg.display());
+    }
+}
+
+/// Сообщение `console.log`/`warn`/`error`: аргументы через пробел
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    pub text: String,
+}
+
+/// Журнал консоли страницы. Клоны разделяют одно состояние.
+///
+/// По умолчанию сообщения также печатаются: `log` — в stdout, `warn` и
+/// `error` — в stderr с префиксом.
+#[derive(Debug, Clone)]
+pub struct ConsoleLog {
+    messages: Arc<Mutex<Vec<ConsoleMessage>>>,
+    echo: Arc<AtomicBool>,
+}
+
+impl Default for ConsoleLog {
+    fn default() -> Self {
+        Self {
+            messages: Arc::default(),
+            echo: Arc::new(AtomicBool::new(true)),
         }
-        eprintln!();
-        Ok(JsValue::undefined())
-    });
-
-    // console.warn
-    let console_warn = NativeFunction::from_fn_ptr(|_this, args, _context| {
-        eprint!("WARN: ");
-        for (i, arg) in args.iter().enumerate() {
-            if i > 0 {
-                eprint!(" ");
+    }
+}
+
+impl ConsoleLog {
+    fn messages(&self) -> std::sync::MutexGuard<'_, Vec<ConsoleMessage>> {
+        self.messages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Печатать ли сообщения в stdout/stderr, а не только сохранять
+    pub fn set_echo(&self, echo: bool) {
+        self.echo.store(echo, Ordering::Relaxed);
+    }
+
+    /// Сообщения в порядке вызова
+    pub fn entries(&self) -> Vec<ConsoleMessage> {
+        self.messages().clone()
+    }
+
+    /// Забирает накопленные сообщения
+    pub fn take(&self) -> Vec<ConsoleMessage> {
+        std::mem::take(&mut *self.messages())
+    }
+
+    fn push(&self, level: ConsoleLevel, text: String) {
+        if self.echo.load(Ordering::Relaxed) {
+            match level {
+                ConsoleLevel::Log => println!("{}", text),
+                ConsoleLevel::Warn => eprintln!("WARN: {}", text),
+                ConsoleLevel::Error => eprintln!("ERROR: {}", text),
             }
-            eprint!("{}", arg.display());
         }
-        eprintln!();
-        Ok(JsValue::undefined())
-    });
+        self.messages().push(ConsoleMessage { level, text });
+    }
+}
+
+/// Инициализирует глобальный объект console с методами log, error, warn
+pub fn init_console(context: &mut Context, log: ConsoleLog) {
+    let method = |level: ConsoleLevel| {
+        let log = log.clone();
+        // SAFETY: The closure captures only the thread-safe ConsoleLog handle
+        unsafe {
+            NativeFunction::from_closure(move |_this, args, _context| {
+                let text = args
+                    .iter()
+                    .map(|arg| match arg.as_string() {
+                        // Строки печатаются без кавычек, как в браузере
+                        Some(text) => text.to_std_string_escaped(),
+                        None => arg.display().to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                log.push(level, text);
+                Ok(JsValue::undefined())
+            })
+        }
+    };
 
     let console = ObjectInitializer::new(context)
-        .function(console_log, js_string!("log"), 0)
-        .function(console_error, js_string!("error"), 0)
-        .function(console_warn, js_string!("warn"), 0)
+        .function(method(ConsoleLevel::Log), js_string!("log"), 0)
+        .function(method(ConsoleLevel::Error), js_string!("error"), 0)
+        .function(method(ConsoleLevel::Warn), js_string!("warn"), 0)
         .build();
 
     let _ = context.register_global_property(
@@ -4,14 +4,16 @@ use boa_engine::{
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use super::element::create_element_object;
-use super::events::EventRegistry;
+use super::element::{create_element_object, event_listener_methods};
+use super::events::{DOCUMENT_TARGET, EventRegistry, WINDOW_TARGET};
+use crate::layout::ScrollOffsets;
 
 /// Инициализирует глобальный объект document с методами querySelector, getElementById, createElement
 pub fn init_document(
     context: &mut Context,
     dom_ref: Arc<RwLock<super::super::dom::Document>>,
     event_registry: EventRegistry,
+    scroll: ScrollOffsets,
 ) {
     // document.querySelector
     // SAFETY: NativeFunction::from_closure is unsafe because:
@@ -22,6 +24,7 @@ pub fn init_document(
     let query_selector_fn = {
         let dom_ref = dom_ref.clone();
         let event_registry = event_registry.clone();
+        let scroll = scroll.clone();
         unsafe {
             NativeFunction::from_closure(move |_this, args, context| {
                 if let Some(selector_js) = args.first()
@@ -36,6 +39,7 @@ pub fn init_document(
                             first_id,
                             dom_ref.clone(),
                             event_registry.clone(),
+                            &scroll,
                         );
                         return Ok(JsValue::from(element));
                     }
@@ -51,6 +55,7 @@ pub fn init_document(
     let get_element_by_id_fn = {
         let dom_ref = dom_ref.clone();
         let event_registry = event_registry.clone();
+        let scroll = scroll.clone();
         unsafe {
             NativeFunction::from_closure(move |_this, args, context| {
                 if let Some(id_js) = args.first()
@@ -65,6 +70,7 @@ pub fn init_document(
                         node_id,
                         dom_ref.clone(),
                         event_registry.clone(),
+                        &scroll,
                     );
                     return Ok(JsValue::from(element));
                 }
@@ -77,6 +83,7 @@ pub fn init_document(
     let create_elem_fn = {
         let dom_ref = dom_ref.clone();
         let event_registry = event_registry.clone();
+        let scroll = scroll.clone();
         unsafe {
             NativeFunction::from_closure(move |_this, args, context| {
                 if let Some(tag_js) = args.first()
@@ -91,6 +98,7 @@ pub fn init_document(
                                 node_id,
                                 dom_ref.clone(),
                                 event_registry.clone(),
+                                &scroll,
                             );
                             return Ok(JsValue::from(element));
                         }
@@ -105,10 +113,14 @@ pub fn init_document(
         }
     };
 
+    let (add_listener_fn, remove_listener_fn) =
+        event_listener_methods(event_registry.clone(), Some(DOCUMENT_TARGET));
     let document = ObjectInitializer::new(context)
         .function(query_selector_fn, js_string!("querySelector"), 0)
         .function(get_element_by_id_fn, js_string!("getElementById"), 0)
         .function(create_elem_fn, js_string!("createElement"), 1)
+        .function(add_listener_fn, js_string!("addEventListener"), 2)
+        .function(remove_listener_fn, js_string!("removeEventListener"), 2)
         .build();
 
     let _ = context.register_global_property(
@@ -117,3 +129,26 @@ pub fn init_document(
         Attribute::WRITABLE | Attribute::ENUMERABLE | Attribute::CONFIGURABLE,
     );
 }
+
+/// Делает глобальный объект доступным как `window` с подпиской на события окна
+pub fn init_window(context: &mut Context, event_registry: EventRegistry) {
+    let (add_listener_fn, remove_listener_fn) =
+        event_listener_methods(event_registry, Some(WINDOW_TARGET));
+    let _ = context.register_global_builtin_callable(
+        js_string!("addEventListener"),
+        2,
+        add_listener_fn,
+    );
+    let _ = context.register_global_builtin_callable(
+        js_string!("removeEventListener"),
+        2,
+        remove_listener_fn,
+    );
+
+    let global = context.global_object();
+    let _ = context.register_global_property(
+        js_string!("window"),
+        global,
+        Attribute::WRITABLE | Attribute::ENUMERABLE | Attribute::CONFIGURABLE,
+    );
+}
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::events::{EventRegistry, EventType};
+use crate::layout::ScrollOffsets;
 
 /// Создает JS объект Element с методами для работы с DOM
 pub fn create_element_object(
@@ -12,6 +13,7 @@ pub fn create_element_object(
     node_id: usize,
     dom_ref: Arc<RwLock<super::super::dom::Document>>,
     event_registry: EventRegistry,
+    scroll: &ScrollOffsets,
 ) -> boa_engine::object::JsObject {
     let dom_ref_clone = dom_ref.clone();
 
@@ -208,32 +210,57 @@ pub fn create_element_object(
         }
     };
 
+    let (add_event_listener_fn, remove_event_listener_fn) =
+        event_listener_methods(event_registry, None);
+
+    let mut element = ObjectInitializer::new(context);
+    element
+        .property(
+            js_string!("nodeId"),
+            JsValue::from(node_id as f64),
+            Attribute::READONLY,
+        )
+        .function(append_child_fn, js_string!("appendChild"), 1)
+        .function(remove_child_fn, js_string!("removeChild"), 1)
+        .function(set_attr_fn, js_string!("setAttribute"), 2)
+        .function(get_attr_fn, js_string!("getAttribute"), 1)
+        .function(get_text_fn, js_string!("getTextContent"), 0)
+        .function(set_text_fn, js_string!("setTextContent"), 1)
+        .function(tag_name_fn, js_string!("getTagName"), 0)
+        .function(add_event_listener_fn, js_string!("addEventListener"), 2)
+        .function(
+            remove_event_listener_fn,
+            js_string!("removeEventListener"),
+            2,
+        );
+    super::scroll::define_element_scroll(&mut element, node_id, scroll);
+    element.build()
+}
+
+/// Методы addEventListener/removeEventListener.
+///
+/// Для элементов цель берётся из `this.nodeId`; `target` фиксирует её для
+/// `document` и `window`, у которых нет узла в DOM.
+pub(super) fn event_listener_methods(
+    event_registry: EventRegistry,
+    target: Option<usize>,
+) -> (NativeFunction, NativeFunction) {
+    // SAFETY: The closures capture only the EventRegistry. Listener objects stored in it
+    // are not traced, so boa_gc counts them as roots until they are removed
     // addEventListener
     let add_event_listener_fn = {
         let event_registry = event_registry.clone();
         unsafe {
             NativeFunction::from_closure(move |this, args, context| {
-                if let Some(elem_obj) = this.as_object()
-                    && let Ok(node_id_val) = elem_obj.get(js_string!("nodeId"), context)
-                    && let Some(node_id) = node_id_val.as_number()
+                if let Some(node_id) = listener_target(this, target, context)
                     && let Some(event_type_js) = args.first()
                     && let Some(event_type_str) = event_type_js.as_string()
                     && let Some(callback_val) = args.get(1)
                 {
                     let event_type = EventType::parse(&event_type_str.to_std_string_escaped());
 
-                    // Конвертируем callback в строку кода
-                    let callback_code = if let Some(s) = callback_val.as_string() {
-                        s.to_std_string_escaped()
-                    } else if callback_val.is_callable() {
-                        match callback_val.to_string(context) {
-                            Ok(s) => s.to_std_string_escaped(),
-                            Err(_) => {
-                                eprintln!("Failed to convert event callback to string");
-                                return Ok(JsValue::undefined());
-                            }
-                        }
-                    } else {
+                    // Функция или объект с handleEvent; null и примитивы игнорируются
+                    let Some(callback) = callback_val.as_object() else {
                         return Ok(JsValue::undefined());
                     };
 
@@ -245,7 +272,7 @@ pub fn create_element_object(
                         .and_then(|v| v.as_boolean())
                         .unwrap_or(false);
 
-                    event_registry.add_listener(node_id as usize, event_type, callback_code, once);
+                    event_registry.add_listener(node_id, event_type, callback, once);
 
                     return Ok(JsValue::undefined());
                 }
@@ -258,57 +285,32 @@ pub fn create_element_object(
     };
 
     // removeEventListener
-    let remove_event_listener_fn = {
-        let event_registry = event_registry.clone();
-        unsafe {
-            NativeFunction::from_closure(move |this, args, context| {
-                if let Some(elem_obj) = this.as_object()
-                    && let Ok(node_id_val) = elem_obj.get(js_string!("nodeId"), context)
-                    && let Some(node_id) = node_id_val.as_number()
-                    && let Some(event_type_js) = args.first()
-                    && let Some(event_type_str) = event_type_js.as_string()
-                    && let Some(callback_val) = args.get(1)
-                {
-                    let event_type = EventType::parse(&event_type_str.to_std_string_escaped());
-
-                    let callback_code = if let Some(s) = callback_val.as_string() {
-                        s.to_std_string_escaped()
-                    } else if callback_val.is_callable() {
-                        match callback_val.to_string(context) {
-                            Ok(s) => s.to_std_string_escaped(),
-                            Err(_) => return Ok(JsValue::undefined()),
-                        }
-                    } else {
-                        return Ok(JsValue::undefined());
-                    };
-
-                    event_registry.remove_listener(node_id as usize, &event_type, &callback_code);
+    let remove_event_listener_fn = unsafe {
+        NativeFunction::from_closure(move |this, args, context| {
+            if let Some(node_id) = listener_target(this, target, context)
+                && let Some(event_type_js) = args.first()
+                && let Some(event_type_str) = event_type_js.as_string()
+                && let Some(callback_val) = args.get(1)
+            {
+                let event_type = EventType::parse(&event_type_str.to_std_string_escaped());
 
-                    return Ok(JsValue::undefined());
+                if let Some(callback) = callback_val.as_object() {
+                    event_registry.remove_listener(node_id, &event_type, &callback);
                 }
-                Ok(JsValue::undefined())
-            })
-        }
+
+                return Ok(JsValue::undefined());
+            }
+            Ok(JsValue::undefined())
+        })
     };
 
-    ObjectInitializer::new(context)
-        .property(
-            js_string!("nodeId"),
-            JsValue::from(node_id as f64),
-            Attribute::READONLY,
-        )
-        .function(append_child_fn, js_string!("appendChild"), 1)
-        .function(remove_child_fn, js_string!("removeChild"), 1)
-        .function(set_attr_fn, js_string!("setAttribute"), 2)
-        .function(get_attr_fn, js_string!("getAttribute"), 1)
-        .function(get_text_fn, js_string!("getTextContent"), 0)
-        .function(set_text_fn, js_string!("setTextContent"), 1)
-        .function(tag_name_fn, js_string!("getTagName"), 0)
-        .function(add_event_listener_fn, js_string!("addEventListener"), 2)
-        .function(
-            remove_event_listener_fn,
-            js_string!("removeEventListener"),
-            2,
-        )
-        .build()
+    (add_event_listener_fn, remove_event_listener_fn)
+}
+
+fn listener_target(this: &JsValue, target: Option<usize>, context: &mut Context) -> Option<usize> {
+    if target.is_some() {
+        return target;
+    }
+    let node_id = this.as_object()?.get(js_string!("nodeId"), context).ok()?;
+    node_id.as_number().map(|id| id as usize)
 }
@@ -1,13 +1,29 @@
-use boa_engine::{Context, Source};
+use boa_engine::builtins::promise::PromiseState;
+use boa_engine::{Context, JsObject, JsResult, JsValue, Module, Source, js_string};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
-use super::console;
+use super::console::{self, ConsoleLog};
 use super::document;
-use super::events::EventRegistry;
-use super::events::EventType;
-use super::timers::{self, PendingCallback};
+use super::element;
+use super::event_object;
+use super::events::{
+    DOCUMENT_TARGET, DispatchOutcome, EventInit, EventRegistry, EventType, WINDOW_TARGET,
+};
+use super::location;
+use super::modules::UrlModuleLoader;
+use super::scroll;
+use super::timers::{self, TimerCallback, TimerQueue};
+use super::window::{self, WindowInfo};
+use crate::clock::Clock;
+use crate::error::{ZverError, ZverResult};
+use crate::layout::ScrollOffsets;
+use crate::network::Url;
 
 /// Типы значений JavaScript
 #[derive(Debug, Clone)]
@@ -24,39 +40,66 @@ pub enum JSValue {
 pub struct JSEngine {
     context: Context,
     dom_ref: Option<Arc<RwLock<super::super::dom::Document>>>,
-    timers: Arc<Mutex<HashMap<u32, tokio::task::JoinHandle<()>>>>,
-    next_timer_id: Arc<Mutex<u32>>,
-    pending_callbacks: Arc<Mutex<HashMap<u32, PendingCallback>>>,
+    /// Очередь setTimeout/setInterval, общая с глобальными функциями контекста
+    timers: Rc<RefCell<TimerQueue>>,
     event_registry: EventRegistry,
+    /// URL документа для `location`
+    location: Url,
+    /// Исходники модулей для `import`, общий с текущим контекстом
+    module_loader: Rc<UrlModuleLoader>,
+    /// Часы страницы для таймеров, `Date` и `performance`
+    clock: Clock,
+    /// Момент загрузки страницы — ноль для `performance.now()`
+    time_origin: Duration,
+    /// Размеры окна и `navigator`
+    window: WindowInfo,
+    /// Прокрутка страницы для `scrollTop` и `window.scrollY`
+    scroll: ScrollOffsets,
+    /// Сообщения `console`, общие для всех страниц движка
+    console: ConsoleLog,
 }
 
 impl JSEngine {
     pub fn new() -> Self {
-        let mut context = Context::default();
-
-        // Инициализируем глобальные объекты
-        console::init_console(&mut context);
-
-        let timers = Arc::new(Mutex::new(HashMap::new()));
-        let next_timer_id = Arc::new(Mutex::new(1));
-        let pending_callbacks = Arc::new(Mutex::new(HashMap::new()));
-
-        // Всегда инициализируем таймеры
-        timers::init_timers(
-            &mut context,
-            timers.clone(),
-            next_timer_id.clone(),
-            pending_callbacks.clone(),
-        );
+        Self::with_clock(Clock::real())
+    }
 
-        Self {
-            context,
+    /// Создаёт движок, чьи таймеры, `Date` и `performance` идут по `clock`
+    pub fn with_clock(clock: Clock) -> Self {
+        let module_loader = Rc::new(UrlModuleLoader::default());
+        let mut engine = Self {
+            context: new_context(module_loader.clone(), &clock),
             dom_ref: None,
-            timers,
-            next_timer_id,
-            pending_callbacks,
+            timers: Rc::new(RefCell::new(TimerQueue::new(clock.clone()))),
             event_registry: EventRegistry::new(),
+            location: Url::about_blank(),
+            module_loader,
+            time_origin: clock.now(),
+            clock,
+            window: WindowInfo::default(),
+            scroll: ScrollOffsets::new(),
+            console: ConsoleLog::default(),
+        };
+
+        // Инициализируем глобальные объекты
+        engine.init_globals();
+        engine
+    }
+
+    fn init_globals(&mut self) {
+        console::init_console(&mut self.context, self.console.clone());
+
+        // Всегда инициализируем таймеры и window
+        timers::init_timers(&mut self.context, self.timers.clone());
+        timers::init_performance(&mut self.context, self.clock.clone(), self.time_origin);
+        document::init_window(&mut self.context, self.event_registry.clone());
+        window::init_window_info(&mut self.context, &self.window);
+        scroll::init_window_scroll(&mut self.context, self.scroll.clone());
+
+        if self.dom_ref.is_some() {
+            self.init_document();
         }
+        location::init_location(&mut self.context, &self.location);
     }
 
     pub fn with_dom(mut self, dom: Arc<RwLock<super::super::dom::Document>>) -> Self {
@@ -65,103 +108,295 @@ impl JSEngine {
         self
     }
 
+    /// Подключает прокрутку, общую с layout ([`crate::layout::LayoutEngine::scroll_offsets`])
+    pub fn with_scroll(mut self, scroll: ScrollOffsets) -> Self {
+        self.scroll = scroll;
+        scroll::init_window_scroll(&mut self.context, self.scroll.clone());
+        self.init_document();
+        self
+    }
+
     fn init_document(&mut self) {
         if let Some(dom_ref) = &self.dom_ref {
             document::init_document(
                 &mut self.context,
                 dom_ref.clone(),
                 self.event_registry.clone(),
+                self.scroll.clone(),
             );
         }
     }
 
-    /// Выполняет готовые callbacks из setTimeout
-    pub fn tick_timers(&mut self) -> usize {
-        timers::tick_timers(
-            &mut self.context,
-            self.pending_callbacks.clone(),
-            self.timers.clone(),
-        )
+    /// Устанавливает URL документа, видимый скриптам как `location`
+    pub fn set_location(&mut self, url: &Url) {
+        self.location = url.clone();
+        location::init_location(&mut self.context, url);
     }
 
-    /// Dispatch event на узел DOM
-    pub fn dispatch_event(&mut self, node_id: usize, event_type: &str) -> usize {
-        let event_type = EventType::parse(event_type);
-        let listeners = self.event_registry.get_listeners(node_id, &event_type);
+    /// Задаёт `navigator.userAgent` и `navigator.language` (по Accept-Language)
+    pub fn set_navigator(&mut self, user_agent: &str, accept_language: &str) {
+        self.window.user_agent = user_agent.to_string();
+        self.window.language = window::primary_language(accept_language);
+        window::init_window_info(&mut self.context, &self.window);
+    }
+
+    /// Задаёт `innerWidth`, `innerHeight` и `devicePixelRatio`
+    pub fn set_viewport(&mut self, width: f32, height: f32, device_pixel_ratio: f32) {
+        self.window.inner_width = width;
+        self.window.inner_height = height;
+        self.window.device_pixel_ratio = device_pixel_ratio;
+        window::set_viewport(&mut self.context, &self.window);
+    }
 
+    /// Выполняет готовые callbacks из setTimeout/setInterval
+    pub fn tick_timers(&mut self) -> usize {
+        let now = self.now();
         let mut executed = 0;
-        for listener in listeners {
-            let exec_code = if listener.callback_code.starts_with("function")
-                || listener.callback_code.contains("=>")
-            {
-                format!("({})()", listener.callback_code)
-            } else {
-                listener.callback_code.clone()
-            };
-
-            match self.execute(&exec_code) {
-                Ok(_) => {
-                    executed += 1;
-                    #[cfg(debug_assertions)]
-                    println!("Executed event listener for {}", event_type.as_str());
-                }
-                Err(e) => {
-                    eprintln!("Error executing event listener: {:?}", e);
-                }
+        while self.run_next_timer(now) {
+            executed += 1;
+        }
+        executed
+    }
+
+    /// Текущее время по часам страницы, по которому срабатывают таймеры
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Часы страницы
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Журнал `console.log`/`warn`/`error`
+    pub fn console(&self) -> &ConsoleLog {
+        &self.console
+    }
+
+    /// Время срабатывания ближайшего таймера.
+    ///
+    /// Требует `&mut self`: очередь не синхронизирована и не должна читаться
+    /// параллельно из-под `RwLock::read`.
+    pub fn next_timer_at(&mut self) -> Option<Duration> {
+        self.timer_queue().next_fire_time()
+    }
+
+    /// Выполняет самый ранний таймер, сработавший к `until`, и затем микрозадачи.
+    ///
+    /// Если таймер назначен на будущее, часы страницы переводятся на момент его
+    /// срабатывания. Возвращает `false`, если таких таймеров нет.
+    pub fn run_next_timer(&mut self, until: Duration) -> bool {
+        let Some((timer_id, callback)) = self.timer_queue().pop_due(until) else {
+            return false;
+        };
+        self.clock.advance_to(callback.fire_time);
+
+        let result = match &callback.callback {
+            TimerCallback::Function { function, args } => {
+                // Callback кадра получает его временную метку, как performance.now()
+                let args = if callback.animation_frame {
+                    let timestamp = callback.fire_time.saturating_sub(self.time_origin);
+                    vec![JsValue::from(timestamp.as_secs_f64() * 1000.0)]
+                } else {
+                    args.clone()
+                };
+                function
+                    .call(&JsValue::undefined(), &args, &mut self.context)
+                    .map(|_| ())
+                    .map_err(|e| ZverError::script(e.to_string()))
+            }
+            TimerCallback::Code(code) => self.execute(code).map(|_| ()),
+        };
+        match result {
+            Ok(()) => {
+                tracing::debug!("Executed timer {} callback", timer_id);
+            }
+            Err(e) => {
+                eprintln!("Error executing timer {} callback: {}", timer_id, e);
             }
         }
+        self.run_microtasks();
+        true
+    }
 
-        // Удаляем once listeners после выполнения
-        if executed > 0 {
-            self.event_registry
-                .remove_once_listeners(node_id, &event_type);
+    /// Выполняет накопившиеся микрозадачи (реакции промисов)
+    pub fn run_microtasks(&mut self) {
+        if let Err(e) = self.context.run_jobs() {
+            eprintln!("Error in microtask: {}", e);
         }
+    }
 
-        executed
+    fn timer_queue(&mut self) -> std::cell::RefMut<'_, TimerQueue> {
+        self.timers.borrow_mut()
     }
 
-    /// Resets the JavaScript context for a new page load
-    /// This prevents "duplicate lexical declaration" errors when const/let variables are redeclared
-    pub fn reset_context(&mut self) {
-        tracing::debug!("Resetting JavaScript context");
+    /// Рассылает событие документа: сначала `document`, затем `window`
+    pub fn dispatch_document_event(&mut self, event_type: &str) -> usize {
+        self.dispatch_event(DOCUMENT_TARGET, event_type)
+            + self.dispatch_event(WINDOW_TARGET, event_type)
+    }
 
-        // Create a new context to avoid conflicts with previous declarations
-        self.context = Context::default();
+    /// Рассылает событие окна (`load`, `resize`)
+    pub fn dispatch_window_event(&mut self, event_type: &str) -> usize {
+        self.dispatch_event(WINDOW_TARGET, event_type)
+    }
+
+    /// Dispatch event на узел DOM без всплытия; возвращает число выполненных слушателей
+    pub fn dispatch_event(&mut self, node_id: usize, event_type: &str) -> usize {
+        self.dispatch_dom_event(&[node_id], event_type, &EventInit::default())
+            .listeners
+    }
+
+    /// Рассылает событие с данными `init` по пути `path`: цель, затем её предки.
+    ///
+    /// Всплывающее событие после предков получают `document` и `window`;
+    /// `stopPropagation()` прерывает всплытие. Слушатели видят объект события
+    /// как аргумент и как глобальный `event`; после каждого слушателя
+    /// выполняются микрозадачи.
+    pub fn dispatch_dom_event(
+        &mut self,
+        path: &[usize],
+        event_type: &str,
+        init: &EventInit,
+    ) -> DispatchOutcome {
+        let mut outcome = DispatchOutcome::default();
+        let Some(&target) = path.first() else {
+            return outcome;
+        };
+        let mut chain = if init.bubbles {
+            path.to_vec()
+        } else {
+            vec![target]
+        };
+        if init.bubbles {
+            for global_target in [DOCUMENT_TARGET, WINDOW_TARGET] {
+                if !chain.contains(&global_target) {
+                    chain.push(global_target);
+                }
+            }
+        }
+
+        let kind = EventType::parse(event_type);
+        let target_value = self.event_target_object(target);
+        let event =
+            event_object::create_event_object(&mut self.context, event_type, target_value, init);
+        let global = self.context.global_object();
+        let _ = global.set(js_string!("event"), event.clone(), false, &mut self.context);
+
+        for node_id in chain {
+            let listeners = self.event_registry.get_listeners(node_id, &kind);
+            if listeners.is_empty() {
+                continue;
+            }
+            let current_target = self.event_target_object(node_id);
+            let _ = event.set(
+                js_string!("currentTarget"),
+                current_target.clone(),
+                false,
+                &mut self.context,
+            );
+
+            let mut executed = 0;
+            for listener in listeners {
+                let result = self.call_listener(&listener.callback, &current_target, &event);
+                self.run_microtasks();
+                match result {
+                    Ok(_) => {
+                        executed += 1;
+                        tracing::debug!("Executed event listener for {}", kind.as_str());
+                    }
+                    Err(e) => {
+                        eprintln!("Error executing event listener: {:?}", e);
+                    }
+                }
+            }
 
-        // Re-initialize global objects
-        console::init_console(&mut self.context);
+            // Удаляем once listeners после выполнения
+            if executed > 0 {
+                self.event_registry.remove_once_listeners(node_id, &kind);
+            }
+            outcome.listeners += executed;
+
+            if event_object::event_flag(&event, "cancelBubble", &mut self.context) {
+                break;
+            }
+        }
 
-        // Re-initialize timers and document APIs
-        timers::init_timers(
+        outcome.default_prevented =
+            event_object::event_flag(&event, "defaultPrevented", &mut self.context);
+        let _ = global.set(
+            js_string!("event"),
+            JsValue::undefined(),
+            false,
             &mut self.context,
-            self.timers.clone(),
-            self.next_timer_id.clone(),
-            self.pending_callbacks.clone(),
         );
+        outcome
+    }
 
-        if self.dom_ref.is_some() {
-            self.init_document();
+    /// Вызывает слушателя: функцию с `this` = `currentTarget` или `handleEvent` объекта
+    fn call_listener(
+        &mut self,
+        callback: &JsObject,
+        current_target: &JsValue,
+        event: &JsObject,
+    ) -> JsResult<JsValue> {
+        let args = [JsValue::from(event.clone())];
+        if callback.is_callable() {
+            return callback.call(current_target, &args, &mut self.context);
         }
-
-        // Clear timers and callbacks from previous page
-        if let Ok(mut timers) = self.timers.lock() {
-            for (timer_id, handle) in timers.drain() {
-                tracing::debug!("Aborting timer {}", timer_id);
-                handle.abort();
-            }
+        let handle_event = callback.get(js_string!("handleEvent"), &mut self.context)?;
+        match handle_event.as_callable() {
+            Some(method) => method.call(&callback.clone().into(), &args, &mut self.context),
+            None => Ok(JsValue::undefined()),
         }
+    }
 
-        if let Ok(mut callbacks) = self.pending_callbacks.lock() {
-            let count = callbacks.len();
-            callbacks.clear();
-            tracing::debug!("Cleared {} pending callbacks", count);
+    /// JS-объект цели события: элемент, `document` или `window`
+    fn event_target_object(&mut self, node_id: usize) -> JsValue {
+        let global = self.context.global_object();
+        match node_id {
+            WINDOW_TARGET => global.into(),
+            DOCUMENT_TARGET => global
+                .get(js_string!("document"), &mut self.context)
+                .unwrap_or_default(),
+            _ => match &self.dom_ref {
+                Some(dom_ref) => element::create_element_object(
+                    &mut self.context,
+                    node_id,
+                    dom_ref.clone(),
+                    self.event_registry.clone(),
+                    &self.scroll,
+                )
+                .into(),
+                None => JsValue::null(),
+            },
         }
+    }
+
+    /// Resets the JavaScript context for a new page load
+    /// This prevents "duplicate lexical declaration" errors when const/let variables are redeclared
+    pub fn reset_context(&mut self) {
+        tracing::debug!("Resetting JavaScript context");
+
+        // Create a new context to avoid conflicts with previous declarations
+        // Модули привязаны к контексту, поэтому загрузчик тоже создаётся заново
+        self.module_loader = Rc::new(UrlModuleLoader::default());
+        self.context = new_context(self.module_loader.clone(), &self.clock);
 
-        // Reset event registry
+        // Reset event registry before document APIs capture it
         self.event_registry = EventRegistry::new();
+
+        // Re-initialize global objects, timers and document APIs;
+        // performance.now() of the new page starts from zero
+        self.time_origin = self.clock.now();
+        self.init_globals();
+
+        // Clear timers from previous page
+        let count = self.timer_queue().clear();
+        tracing::debug!("Cleared {} pending timers", count);
     }
 
-    pub fn execute(&mut self, code: &str) -> Result<JSValue, Box<dyn std::error::Error>> {
+    pub fn execute(&mut self, code: &str) -> ZverResult<JSValue> {
         let source = Source::from_bytes(code);
         match self.context.eval(source) {
             Ok(js_value) => {
@@ -182,11 +417,52 @@ impl JSEngine {
                     Ok(JSValue::Undefined)
                 }
             }
-            Err(e) => Err(format!("JavaScript error: {:?}", e).into()),
+            Err(e) => Err(ZverError::script(e.to_string())),
+        }
+    }
+
+    /// Регистрирует исходник модуля, доступный для `import` по этому URL.
+    ///
+    /// Зависимости загружаются заранее (см. [`super::modules::fetch_module_graph`]),
+    /// так как Boa запрашивает их синхронно во время исполнения.
+    pub fn register_module_source(&mut self, url: Url, code: String) {
+        self.module_loader.insert_source(url, code);
+    }
+
+    /// Исполняет код как ES-модуль (`<script type="module">`).
+    ///
+    /// Модуль получает собственную область видимости и strict mode;
+    /// относительные `import` разрешаются от URL документа.
+    pub fn execute_module(&mut self, code: &str) -> ZverResult<JSValue> {
+        let url = self.location.clone();
+        self.execute_module_at(code, &url)
+    }
+
+    /// Исполняет ES-модуль, загруженный по `url`: от него разрешаются его `import`
+    pub fn execute_module_at(&mut self, code: &str, url: &Url) -> ZverResult<JSValue> {
+        let source = Source::from_bytes(code).with_path(Path::new(url.as_str()));
+        let module = Module::parse(source, None, &mut self.context)
+            .map_err(|e| ZverError::script(e.to_string()))?;
+        let promise = module.load_link_evaluate(&mut self.context);
+        self.context
+            .run_jobs()
+            .map_err(|e| ZverError::script(e.to_string()))?;
+
+        match promise.state() {
+            PromiseState::Rejected(reason) => Err(ZverError::script(reason.display().to_string())),
+            _ => Ok(JSValue::Undefined),
         }
     }
 }
 
+fn new_context(module_loader: Rc<UrlModuleLoader>, clock: &Clock) -> Context {
+    Context::builder()
+        .module_loader(module_loader)
+        .clock(Rc::new(clock.clone()))
+        .build()
+        .expect("default JavaScript context must build")
+}
+
 impl Default for JSEngine {
     fn default() -> Self {
         Self::new()
@@ -196,12 +472,14 @@ impl Default for JSEngine {
 // SAFETY: JSEngine can be safely sent between threads because:
 // 1. boa_engine::Context is designed to be Send
 // 2. Arc<RwLock<Document>> is explicitly Send
-// 3. Arc<Mutex<HashMap>> and Arc<Mutex<u32>> are Send
-// 4. All access is protected by RwLock in the parent Zver struct
+// 3. All access is protected by RwLock in the parent Zver struct
+// 4. The Rc module loader, timer queue, event registry and clock handle are shared only with
+//    the owned Context and move together with it; the clock state itself is behind Arc<Mutex<>>
 unsafe impl Send for JSEngine {}
 
 // SAFETY: JSEngine can be shared between threads (behind Arc<RwLock<>>) because:
 // 1. All mutations to Context require exclusive lock (RwLock::write)
-// 2. Timers HashMap is protected by its own Mutex
-// 3. No interior mutability without proper synchronization
+// 2. The timer queue and event registry (Rc<RefCell<>>) are only touched through &mut self
+//    or from JS running in the Context, which also requires &mut self
+// 3. No other interior mutability without proper synchronization
 unsafe impl Sync for JSEngine {}
@@ -0,0 +1,79 @@
+use boa_engine::{
+    Context, JsString, JsValue, NativeFunction, js_string, object::JsObject,
+    object::ObjectInitializer, property::Attribute,
+};
+
+use super::events::EventInit;
+
+/// Создаёт объект события, передаваемый слушателям.
+///
+/// `preventDefault()` выставляет `defaultPrevented` только у отменяемых событий,
+/// `stopPropagation()` — `cancelBubble`, который проверяет диспетчер между целями.
+pub fn create_event_object(
+    context: &mut Context,
+    event_type: &str,
+    target: JsValue,
+    init: &EventInit,
+) -> JsObject {
+    let prevent_default = NativeFunction::from_fn_ptr(|this, _args, context| {
+        if let Some(event) = this.as_object()
+            && event.get(js_string!("cancelable"), context)?.to_boolean()
+        {
+            event.set(js_string!("defaultPrevented"), true, false, context)?;
+        }
+        Ok(JsValue::undefined())
+    });
+    let stop_propagation = NativeFunction::from_fn_ptr(|this, _args, context| {
+        if let Some(event) = this.as_object() {
+            event.set(js_string!("cancelBubble"), true, false, context)?;
+        }
+        Ok(JsValue::undefined())
+    });
+
+    let attributes = Attribute::all();
+    let mut event = ObjectInitializer::new(context);
+    event
+        .property(js_string!("type"), JsString::from(event_type), attributes)
+        .property(js_string!("target"), target.clone(), attributes)
+        .property(js_string!("currentTarget"), target, attributes)
+        .property(js_string!("bubbles"), init.bubbles, attributes)
+        .property(js_string!("cancelable"), init.cancelable, attributes)
+        .property(js_string!("defaultPrevented"), false, attributes)
+        .property(js_string!("cancelBubble"), false, attributes)
+        .property(js_string!("button"), init.button, attributes)
+        .property(js_string!("buttons"), init.buttons, attributes)
+        .property(
+            js_string!("key"),
+            JsString::from(init.key.as_str()),
+            attributes,
+        )
+        .property(
+            js_string!("code"),
+            JsString::from(init.code.as_str()),
+            attributes,
+        )
+        .property(js_string!("altKey"), init.modifiers.alt, attributes)
+        .property(js_string!("ctrlKey"), init.modifiers.ctrl, attributes)
+        .property(js_string!("shiftKey"), init.modifiers.shift, attributes)
+        .property(js_string!("metaKey"), init.modifiers.meta, attributes)
+        .function(prevent_default, js_string!("preventDefault"), 0)
+        .function(stop_propagation, js_string!("stopPropagation"), 0);
+    for (name, value) in [
+        ("clientX", init.client_x),
+        ("clientY", init.client_y),
+        ("pageX", init.client_x),
+        ("pageY", init.client_y),
+        ("deltaX", init.delta_x),
+        ("deltaY", init.delta_y),
+    ] {
+        event.property(JsString::from(name), f64::from(value), attributes);
+    }
+    event.build()
+}
+
+/// Читает логический флаг объекта события (`defaultPrevented`, `cancelBubble`)
+pub fn event_flag(event: &JsObject, name: &str, context: &mut Context) -> bool {
+    event
+        .get(JsString::from(name), context)
+        .is_ok_and(|value| value.to_boolean())
+}
@@ -1,5 +1,50 @@
+use boa_engine::JsObject;
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::rc::Rc;
+
+/// Цель событий `document`: у документа нет собственного узла в DOM
+pub const DOCUMENT_TARGET: usize = usize::MAX;
+/// Цель событий `window`
+pub const WINDOW_TARGET: usize = usize::MAX - 1;
+
+/// Клавиши-модификаторы события ввода
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Modifiers {
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub meta: bool,
+}
+
+/// Данные DOM-события, видимые слушателям через объект события
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EventInit {
+    /// Всплывает ли событие к предкам цели, `document` и `window`
+    pub bubbles: bool,
+    /// Может ли `preventDefault()` отменить действие по умолчанию
+    pub cancelable: bool,
+    pub client_x: f32,
+    pub client_y: f32,
+    /// Кнопка мыши: 0 — основная, 1 — средняя, 2 — вторичная
+    pub button: i16,
+    /// Нажатые кнопки битовой маской: 1 — основная, 2 — вторичная, 4 — средняя
+    pub buttons: u16,
+    pub key: String,
+    pub code: String,
+    pub delta_x: f32,
+    pub delta_y: f32,
+    pub modifiers: Modifiers,
+}
+
+/// Итог диспетчеризации события
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DispatchOutcome {
+    /// Число выполненных слушателей
+    pub listeners: usize,
+    /// Слушатель вызвал `preventDefault()` у отменяемого события
+    pub default_prevented: bool,
+}
 
 /// Типы DOM событий
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -54,10 +99,11 @@ impl EventType {
     }
 }
 
-/// Event listener - хранит код callback'а
+/// Event listener - хранит сам callback, чтобы он вызывался со своим замыканием
 #[derive(Debug, Clone)]
 pub struct EventListener {
-    pub callback_code: String,
+    /// Функция или объект с методом `handleEvent`
+    pub callback: JsObject,
     pub once: bool,
 }
 
@@ -74,18 +120,21 @@ impl EventTarget {
         }
     }
 
-    pub fn add_listener(&mut self, event_type: EventType, callback_code: String, once: bool) {
-        let listener = EventListener {
-            callback_code,
-            once,
-        };
-
-        self.listeners.entry(event_type).or_default().push(listener);
+    /// Добавляет слушателя; повторная регистрация того же callback'а игнорируется
+    pub fn add_listener(&mut self, event_type: EventType, callback: JsObject, once: bool) {
+        let listeners = self.listeners.entry(event_type).or_default();
+        if listeners
+            .iter()
+            .any(|l| JsObject::equals(&l.callback, &callback))
+        {
+            return;
+        }
+        listeners.push(EventListener { callback, once });
     }
 
-    pub fn remove_listener(&mut self, event_type: &EventType, callback_code: &str) {
+    pub fn remove_listener(&mut self, event_type: &EventType, callback: &JsObject) {
         if let Some(listeners) = self.listeners.get_mut(event_type) {
-            listeners.retain(|l| l.callback_code != callback_code);
+            listeners.retain(|l| !JsObject::equals(&l.callback, callback));
         }
     }
 
@@ -100,16 +149,19 @@ impl EventTarget {
     }
 }
 
-/// Event registry - глобальное хранилище слушателей для всех узлов
+/// Event registry - глобальное хранилище слушателей для всех узлов.
+///
+/// Слушатели — объекты контекста Boa, поэтому реестр, как и контекст,
+/// живёт в одном потоке вместе с [`super::JSEngine`].
 #[derive(Debug, Clone, Default)]
 pub struct EventRegistry {
-    targets: Arc<Mutex<HashMap<usize, EventTarget>>>,
+    targets: Rc<RefCell<HashMap<usize, EventTarget>>>,
 }
 
 impl EventRegistry {
     pub fn new() -> Self {
         Self {
-            targets: Arc::new(Mutex::new(HashMap::new())),
+            targets: Rc::new(RefCell::new(HashMap::new())),
         }
     }
 
@@ -117,44 +169,36 @@ impl EventRegistry {
         &self,
         node_id: usize,
         event_type: EventType,
-        callback_code: String,
+        callback: JsObject,
         once: bool,
     ) {
-        if let Ok(mut targets) = self.targets.lock() {
-            let target = targets.entry(node_id).or_insert_with(EventTarget::new);
-            target.add_listener(event_type, callback_code, once);
-        }
+        let mut targets = self.targets.borrow_mut();
+        let target = targets.entry(node_id).or_default();
+        target.add_listener(event_type, callback, once);
     }
 
-    pub fn remove_listener(&self, node_id: usize, event_type: &EventType, callback_code: &str) {
-        if let Ok(mut targets) = self.targets.lock()
-            && let Some(target) = targets.get_mut(&node_id)
-        {
-            target.remove_listener(event_type, callback_code);
+    pub fn remove_listener(&self, node_id: usize, event_type: &EventType, callback: &JsObject) {
+        if let Some(target) = self.targets.borrow_mut().get_mut(&node_id) {
+            target.remove_listener(event_type, callback);
         }
     }
 
     pub fn get_listeners(&self, node_id: usize, event_type: &EventType) -> Vec<EventListener> {
-        if let Ok(targets) = self.targets.lock()
-            && let Some(target) = targets.get(&node_id)
-            && let Some(listeners) = target.get_listeners(event_type)
-        {
-            return listeners.clone();
-        }
-        Vec::new()
+        self.targets
+            .borrow()
+            .get(&node_id)
+            .and_then(|target| target.get_listeners(event_type))
+            .cloned()
+            .unwrap_or_default()
     }
 
     pub fn remove_once_listeners(&self, node_id: usize, event_type: &EventType) {
-        if let Ok(mut targets) = self.targets.lock()
-            && let Some(target) = targets.get_mut(&node_id)
-        {
+        if let Some(target) = self.targets.borrow_mut().get_mut(&node_id) {
             target.remove_once_listeners(event_type);
         }
     }
 
     pub fn clear_node(&self, node_id: usize) {
-        if let Ok(mut targets) = self.targets.lock() {
-            targets.remove(&node_id);
-        }
+        self.targets.borrow_mut().remove(&node_id);
     }
 }
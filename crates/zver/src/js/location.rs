@@ -0,0 +1,66 @@
+use boa_engine::{
+    Context, JsString, JsValue, NativeFunction, js_string, object::ObjectInitializer,
+    property::Attribute,
+};
+
+use crate::network::Url;
+
+/// Инициализирует глобальный объект `location` по URL документа.
+///
+/// Свойства только для чтения: навигация через присваивание `location.href`
+/// пока не поддерживается.
+pub fn init_location(context: &mut Context, url: &Url) {
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => String::new(),
+    };
+    let search = url.query().map(|query| format!("?{query}"));
+    let hash = url.fragment().map(|fragment| format!("#{fragment}"));
+
+    let href = JsString::from(url.as_str());
+    let to_string = NativeFunction::from_copy_closure_with_captures(
+        |_this, _args, href, _context| Ok(JsValue::from(href.clone())),
+        href.clone(),
+    );
+
+    let readonly = Attribute::READONLY | Attribute::ENUMERABLE;
+    let location = ObjectInitializer::new(context)
+        .property(js_string!("href"), href, readonly)
+        .property(
+            js_string!("protocol"),
+            JsString::from(format!("{}:", url.scheme())),
+            readonly,
+        )
+        .property(js_string!("host"), JsString::from(host), readonly)
+        .property(
+            js_string!("hostname"),
+            JsString::from(url.host_str().unwrap_or_default()),
+            readonly,
+        )
+        .property(
+            js_string!("port"),
+            JsString::from(url.port().map(|port| port.to_string()).unwrap_or_default()),
+            readonly,
+        )
+        .property(js_string!("pathname"), JsString::from(url.path()), readonly)
+        .property(
+            js_string!("search"),
+            JsString::from(search.unwrap_or_default()),
+            readonly,
+        )
+        .property(
+            js_string!("hash"),
+            JsString::from(hash.unwrap_or_default()),
+            readonly,
+        )
+        .property(js_string!("origin"), JsString::from(url.origin()), readonly)
+        .function(to_string, js_string!("toString"), 0)
+        .build();
+
+    let _ = context.register_global_property(
+        js_string!("location"),
+        location,
+        Attribute::WRITABLE | Attribute::ENUMERABLE | Attribute::CONFIGURABLE,
+    );
+}
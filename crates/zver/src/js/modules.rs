@@ -0,0 +1,188 @@
+//! Загрузка ES-модулей для `import`.
+//!
+//! Boa запрашивает зависимости синхронно во время `load_link_evaluate`, а сеть
+//! асинхронна и живёт за собственным lock, поэтому граф модулей сначала
+//! выкачивается через [`NetworkEngine`] ([`fetch_module_graph`]), а загрузчик
+//! только разбирает уже полученные исходники. Путь модуля в Boa хранит его
+//! абсолютный URL, относительно которого разрешаются вложенные `import`.
+
+use boa_engine::module::{ModuleLoader, Referrer};
+use boa_engine::{Context, JsNativeError, JsObject, JsResult, JsString, Module, Source, js_string};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::network::{NetworkEngine, Response, Url};
+
+/// Предел числа модулей в графе одной страницы
+const MAX_MODULES: usize = 256;
+
+/// Загрузчик модулей по URL из заранее полученных исходников
+#[derive(Debug, Default)]
+pub struct UrlModuleLoader {
+    sources: RefCell<HashMap<Url, String>>,
+    /// Разобранные модули: повторный `import` того же URL возвращает тот же экземпляр
+    modules: RefCell<HashMap<Url, Module>>,
+}
+
+impl UrlModuleLoader {
+    /// Регистрирует исходник модуля, доступный для `import` по этому URL
+    pub fn insert_source(&self, url: Url, code: String) {
+        self.sources.borrow_mut().insert(url, code);
+    }
+
+    fn load(
+        &self,
+        referrer: &Referrer,
+        specifier: &str,
+        context: &mut Context,
+    ) -> JsResult<Module> {
+        let base = referrer
+            .path()
+            .and_then(|path| path.to_str())
+            .and_then(|path| Url::parse(path).ok());
+        let url = resolve_specifier(specifier, base.as_ref()).ok_or_else(|| {
+            JsNativeError::typ()
+                .with_message(format!("Cannot resolve module specifier '{specifier}'"))
+        })?;
+
+        if let Some(module) = self.modules.borrow().get(&url) {
+            return Ok(module.clone());
+        }
+
+        let code = self.sources.borrow().get(&url).cloned().ok_or_else(|| {
+            JsNativeError::typ().with_message(format!("Failed to load module {url}"))
+        })?;
+        let path = Path::new(url.as_str());
+        let module = Module::parse(Source::from_bytes(&code).with_path(path), None, context)
+            .map_err(|err| {
+                JsNativeError::syntax()
+                    .with_message(format!("Cannot parse module {url}"))
+                    .with_cause(err)
+            })?;
+        self.modules.borrow_mut().insert(url, module.clone());
+        Ok(module)
+    }
+}
+
+impl ModuleLoader for UrlModuleLoader {
+    async fn load_imported_module(
+        self: Rc<Self>,
+        referrer: Referrer,
+        specifier: JsString,
+        context: &RefCell<&mut Context>,
+    ) -> JsResult<Module> {
+        let specifier = specifier.to_std_string_escaped();
+        self.load(&referrer, &specifier, &mut context.borrow_mut())
+    }
+
+    fn init_import_meta(
+        self: Rc<Self>,
+        import_meta: &JsObject,
+        module: &Module,
+        context: &mut Context,
+    ) {
+        if let Some(path) = module.path().and_then(|path| path.to_str()) {
+            let _ = import_meta.set(js_string!("url"), js_string!(path), false, context);
+        }
+    }
+}
+
+/// Разрешает спецификатор `import` относительно URL импортирующего модуля.
+///
+/// Как в браузере, «голые» имена пакетов (`import "lodash"`) не поддерживаются.
+pub fn resolve_specifier(specifier: &str, base: Option<&Url>) -> Option<Url> {
+    let relative = ["/", "./", "../"]
+        .iter()
+        .any(|prefix| specifier.starts_with(prefix));
+    if relative {
+        base?.join(specifier).ok()
+    } else {
+        Url::parse(specifier)
+            .ok()
+            .filter(|_| specifier.contains(':'))
+    }
+}
+
+/// Статические и динамические спецификаторы модуля: `from "x"`, `import "x"`, `import("x")`.
+///
+/// Это грубый лексический проход без полного разбора: лишнее совпадение внутри
+/// строки или комментария приведёт лишь к ненужной загрузке.
+pub fn module_specifiers(code: &str) -> Vec<String> {
+    let bytes = code.as_bytes();
+    let mut specifiers = Vec::new();
+
+    for keyword in ["import", "from"] {
+        let mut offset = 0;
+        while let Some(found) = code[offset..].find(keyword) {
+            let start = offset + found;
+            let end = start + keyword.len();
+            offset = end;
+
+            let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+            let bounded = (start == 0 || !is_ident(bytes[start - 1]))
+                && bytes.get(end).is_none_or(|&b| !is_ident(b));
+            if !bounded {
+                continue;
+            }
+
+            let mut rest = code[end..].trim_start();
+            if keyword == "import"
+                && let Some(call) = rest.strip_prefix('(')
+            {
+                rest = call.trim_start();
+            }
+            let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+                continue;
+            };
+            if let Some(len) = rest[1..].find(quote) {
+                specifiers.push(rest[1..1 + len].to_string());
+            }
+        }
+    }
+
+    specifiers
+}
+
+/// Выкачивает граф модулей, на который ссылаются корневые скрипты.
+///
+/// `roots` — пары (URL, относительно которого разрешаются импорты; код).
+/// Загрузка идёт по уровням, каждый уровень — одним [`NetworkEngine::fetch_many`].
+/// Недоступные модули пропускаются: ошибку сообщит сам `import` при исполнении.
+pub async fn fetch_module_graph(
+    network: &mut NetworkEngine,
+    roots: &[(Url, String)],
+) -> HashMap<Url, String> {
+    let mut sources = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue: VecDeque<(Url, String)> = roots.iter().cloned().collect();
+
+    while !queue.is_empty() {
+        let mut level = Vec::new();
+        for (base, code) in queue.drain(..) {
+            for specifier in module_specifiers(&code) {
+                if let Some(url) = resolve_specifier(&specifier, Some(&base))
+                    && seen.len() < MAX_MODULES
+                    && seen.insert(url.clone())
+                {
+                    level.push(url);
+                }
+            }
+        }
+
+        let results = network.fetch_many(&level).await;
+        for (url, result) in level.into_iter().zip(results) {
+            match result.and_then(Response::error_for_status) {
+                Ok(response) => {
+                    let code = response.text();
+                    queue.push_back((url.clone(), code.clone()));
+                    sources.insert(url, code);
+                }
+                Err(err) => tracing::warn!("Failed to load module {}: {}", url, err),
+            }
+        }
+    }
+
+    sources
+}
@@ -0,0 +1,120 @@
+//! Сбор `<script>` документа и порядок их исполнения.
+//!
+//! Каждый скрипт исполняется отдельной единицей, поэтому ошибка в одном не
+//! мешает остальным. Порядок повторяет HTML: сначала блокирующие скрипты в
+//! порядке документа, затем `defer` (и модули без `async`), затем `async`.
+
+use crate::dom::Document;
+
+/// Вид скрипта по атрибуту `type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    /// Обычный скрипт в глобальной области видимости
+    Classic,
+    /// `type="module"`: strict mode и собственная область видимости
+    Module,
+}
+
+/// Момент исполнения относительно разбора документа.
+///
+/// Порядок вариантов совпадает с порядком исполнения групп.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ScriptTiming {
+    /// Исполняется сразу, блокируя разбор
+    Blocking,
+    /// Исполняется после разбора, в порядке документа
+    Defer,
+    /// Исполняется по готовности, не дожидаясь остальных
+    Async,
+}
+
+/// Источник кода скрипта
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptSource {
+    Inline(String),
+    /// `src` как записан в разметке, ещё не разрешён относительно документа
+    External {
+        src: String,
+    },
+}
+
+/// Скрипт документа, готовый к загрузке и исполнению
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptElement {
+    pub node_id: usize,
+    pub kind: ScriptKind,
+    pub timing: ScriptTiming,
+    pub source: ScriptSource,
+}
+
+/// Собирает исполняемые `<script>` в порядке документа.
+///
+/// Скрипты с неизвестным `type` (шаблоны, JSON) и `nomodule` пропускаются.
+pub fn collect_scripts(dom: &Document) -> Vec<ScriptElement> {
+    let mut ids = dom.select_ids("script");
+    ids.sort_unstable();
+    ids.dedup();
+
+    ids.into_iter()
+        .filter_map(|node_id| {
+            let node = dom.nodes.get(&node_id)?;
+            let has = |name: &str| node.attributes.contains_key(name);
+
+            let kind = script_kind(node.attributes.get("type").map(String::as_str))?;
+            if kind == ScriptKind::Classic && has("nomodule") {
+                return None;
+            }
+
+            let source = match node.attributes.get("src") {
+                Some(src) if !src.trim().is_empty() => ScriptSource::External {
+                    src: src.trim().to_string(),
+                },
+                _ => {
+                    let content = dom.get_text_content(node_id);
+                    if content.trim().is_empty() {
+                        return None;
+                    }
+                    ScriptSource::Inline(content)
+                }
+            };
+
+            // defer/async у встроенных классических скриптов игнорируются
+            let external = matches!(source, ScriptSource::External { .. });
+            let timing = match kind {
+                ScriptKind::Module if has("async") => ScriptTiming::Async,
+                ScriptKind::Module => ScriptTiming::Defer,
+                ScriptKind::Classic if external && has("async") => ScriptTiming::Async,
+                ScriptKind::Classic if external && has("defer") => ScriptTiming::Defer,
+                ScriptKind::Classic => ScriptTiming::Blocking,
+            };
+
+            Some(ScriptElement {
+                node_id,
+                kind,
+                timing,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Переставляет скрипты в порядок исполнения (внутри группы — порядок документа)
+pub fn execution_order(mut scripts: Vec<ScriptElement>) -> Vec<ScriptElement> {
+    scripts.sort_by_key(|script| script.timing);
+    scripts
+}
+
+fn script_kind(script_type: Option<&str>) -> Option<ScriptKind> {
+    let script_type = script_type.unwrap_or_default().trim().to_ascii_lowercase();
+    match script_type.as_str() {
+        "module" => Some(ScriptKind::Module),
+        ""
+        | "text/javascript"
+        | "application/javascript"
+        | "text/ecmascript"
+        | "application/ecmascript"
+        | "application/x-javascript"
+        | "text/jscript" => Some(ScriptKind::Classic),
+        _ => None,
+    }
+}
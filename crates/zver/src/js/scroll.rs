@@ -0,0 +1,182 @@
+//! Прокрутка для скриптов: `scrollTop`/`scrollLeft` и размеры прокрутки
+//! элементов, `window.scrollX`/`scrollY`, `scrollTo()` и `scrollBy()`.
+//!
+//! Новое смещение сразу видно скриптам, а в layout и кадре оно появляется
+//! после [`crate::Zver::update`], который рассылает и события `scroll`.
+
+use boa_engine::{
+    Context, JsResult, JsValue, NativeFunction, js_string, object::ObjectInitializer,
+    object::builtins::JsFunction, property::Attribute, property::PropertyDescriptor,
+};
+
+use crate::layout::{ScrollOffsets, ScrollState};
+
+/// Размер из состояния прокрутки
+type Getter = fn(&ScrollState) -> f32;
+
+const ELEMENT_EXTENTS: [(&str, Getter); 4] = [
+    ("scrollWidth", |state| state.scroll_width),
+    ("scrollHeight", |state| state.scroll_height),
+    ("clientWidth", |state| state.client_width),
+    ("clientHeight", |state| state.client_height),
+];
+
+/// Добавляет элементу `scrollTop`, `scrollLeft`, `scrollWidth`, `scrollHeight`,
+/// `clientWidth` и `clientHeight`.
+///
+/// Обычные элементы не прокручиваются: смещение у них ноль, а запись игнорируется.
+pub fn define_element_scroll(
+    element: &mut ObjectInitializer<'_>,
+    node_id: usize,
+    scroll: &ScrollOffsets,
+) {
+    let attribute = Attribute::ENUMERABLE | Attribute::CONFIGURABLE;
+
+    for (name, vertical) in [("scrollLeft", false), ("scrollTop", true)] {
+        let get = offset_getter(
+            element.context(),
+            scroll.clone(),
+            move |_| Some(node_id),
+            vertical,
+        );
+        let set = offset_setter(
+            element.context(),
+            scroll.clone(),
+            move |_| Some(node_id),
+            vertical,
+        );
+        element.accessor(js_string!(name), Some(get), Some(set), attribute);
+    }
+    for (name, getter) in ELEMENT_EXTENTS {
+        let scroll = scroll.clone();
+        // SAFETY: The closure captures only the thread-safe ScrollOffsets handle
+        let get = unsafe {
+            NativeFunction::from_closure(move |_this, _args, _context| {
+                let state = scroll.get(node_id).unwrap_or_default();
+                Ok(JsValue::from(getter(&state) as f64))
+            })
+        }
+        .to_js_function(element.context().realm());
+        element.accessor(js_string!(name), Some(get), None, attribute);
+    }
+}
+
+/// Инициализирует прокрутку окна: `scrollX`/`pageXOffset`, `scrollY`/`pageYOffset`,
+/// `scrollTo(x, y)` и `scrollBy(dx, dy)` (также с `{ left, top }`)
+pub fn init_window_scroll(context: &mut Context, scroll: ScrollOffsets) {
+    let global = context.global_object();
+    let viewport = |scroll: &ScrollOffsets| scroll.viewport();
+
+    for (names, vertical) in [
+        (["scrollX", "pageXOffset"], false),
+        (["scrollY", "pageYOffset"], true),
+    ] {
+        for name in names {
+            let get = offset_getter(context, scroll.clone(), viewport, vertical);
+            let _ = global.define_property_or_throw(
+                js_string!(name),
+                PropertyDescriptor::builder()
+                    .get(get)
+                    .enumerable(true)
+                    .configurable(true),
+                context,
+            );
+        }
+    }
+
+    for (name, relative) in [("scrollTo", false), ("scroll", false), ("scrollBy", true)] {
+        let scroll = scroll.clone();
+        // SAFETY: The closure captures only the thread-safe ScrollOffsets handle
+        let scroll_fn = unsafe {
+            NativeFunction::from_closure(move |_this, args, context| {
+                let Some(node_id) = scroll.viewport() else {
+                    return Ok(JsValue::undefined());
+                };
+                let (x, y) = scroll_arguments(args, context)?;
+                if relative {
+                    scroll.scroll_by(node_id, x.unwrap_or(0.0), y.unwrap_or(0.0));
+                } else {
+                    let (current_x, current_y) = scroll.offset(node_id);
+                    scroll.scroll_to(node_id, x.unwrap_or(current_x), y.unwrap_or(current_y));
+                }
+                Ok(JsValue::undefined())
+            })
+        };
+        let _ = context.register_global_builtin_callable(js_string!(name), 2, scroll_fn);
+    }
+}
+
+/// Геттер смещения по оси; `target` выбирает контейнер — элемент или viewport
+fn offset_getter(
+    context: &mut Context,
+    scroll: ScrollOffsets,
+    target: impl Fn(&ScrollOffsets) -> Option<usize> + 'static,
+    vertical: bool,
+) -> JsFunction {
+    // SAFETY: The closure captures only the thread-safe ScrollOffsets handle
+    unsafe {
+        NativeFunction::from_closure(move |_this, _args, _context| {
+            let (x, y) = target(&scroll).map_or((0.0, 0.0), |node_id| scroll.offset(node_id));
+            Ok(JsValue::from(if vertical { y } else { x } as f64))
+        })
+    }
+    .to_js_function(context.realm())
+}
+
+/// Сеттер смещения по оси; другая ось сохраняется
+fn offset_setter(
+    context: &mut Context,
+    scroll: ScrollOffsets,
+    target: impl Fn(&ScrollOffsets) -> Option<usize> + 'static,
+    vertical: bool,
+) -> JsFunction {
+    // SAFETY: The closure captures only the thread-safe ScrollOffsets handle
+    unsafe {
+        NativeFunction::from_closure(move |_this, args, context| {
+            let value = args
+                .first()
+                .cloned()
+                .unwrap_or_default()
+                .to_number(context)?;
+            if let Some(node_id) = target(&scroll)
+                && value.is_finite()
+            {
+                let (x, y) = scroll.offset(node_id);
+                let value = value as f32;
+                if vertical {
+                    scroll.scroll_to(node_id, x, value);
+                } else {
+                    scroll.scroll_to(node_id, value, y);
+                }
+            }
+            Ok(JsValue::undefined())
+        })
+    }
+    .to_js_function(context.realm())
+}
+
+/// Аргументы `scrollTo(x, y)` или `scrollTo({ left, top })`; `None` — ось не задана
+fn scroll_arguments(
+    args: &[JsValue],
+    context: &mut Context,
+) -> JsResult<(Option<f32>, Option<f32>)> {
+    let number = |value: JsValue, context: &mut Context| -> JsResult<Option<f32>> {
+        if value.is_undefined() {
+            return Ok(None);
+        }
+        let number = value.to_number(context)?;
+        Ok(number.is_finite().then_some(number as f32))
+    };
+
+    match args.first().and_then(JsValue::as_object) {
+        Some(options) => {
+            let left = options.get(js_string!("left"), context)?;
+            let top = options.get(js_string!("top"), context)?;
+            Ok((number(left, context)?, number(top, context)?))
+        }
+        None => Ok((
+            number(args.first().cloned().unwrap_or_default(), context)?,
+            number(args.get(1).cloned().unwrap_or_default(), context)?,
+        )),
+    }
+}
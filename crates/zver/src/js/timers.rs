@@ -1,173 +1,261 @@
-use boa_engine::{Context, JsValue, NativeFunction, js_string};
+use boa_engine::{
+    Context, JsObject, JsString, JsValue, NativeFunction, js_string, object::ObjectInitializer,
+    property::Attribute,
+};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::rc::Rc;
+use std::time::Duration;
 
-/// Pending callback для setTimeout
+use crate::clock::Clock;
+
+/// Минимальный период `setInterval`, иначе нулевой интервал зацикливает очередь
+const MIN_INTERVAL: Duration = Duration::from_millis(1);
+/// Период кадров `requestAnimationFrame` (60 Гц)
+const FRAME_INTERVAL: Duration = Duration::from_nanos(16_666_667);
+
+/// Что выполнить по срабатыванию таймера
+#[derive(Debug, Clone)]
+pub enum TimerCallback {
+    /// Функция, вызываемая с дополнительными аргументами `setTimeout(fn, delay, ...args)`
+    Function {
+        function: JsObject,
+        args: Vec<JsValue>,
+    },
+    /// Строка кода (`setTimeout("code", delay)`), исполняемая в глобальной области
+    Code(String),
+}
+
+/// Запланированный callback из setTimeout/setInterval/requestAnimationFrame
 #[derive(Debug, Clone)]
 pub struct PendingCallback {
-    pub code: String,
-    pub fire_time: std::time::Instant,
+    pub callback: TimerCallback,
+    /// Время срабатывания по часам страницы
+    pub fire_time: Duration,
+    /// Период повтора для setInterval
+    pub interval: Option<Duration>,
+    /// Callback кадра: вызывается с временной меткой кадра
+    pub animation_frame: bool,
+    /// Порядок постановки: таймеры с одинаковым временем срабатывают по нему
+    seq: u64,
 }
 
-/// Инициализирует глобальную функцию setTimeout
-pub fn init_timers(
-    context: &mut Context,
-    timers: Arc<Mutex<HashMap<u32, tokio::task::JoinHandle<()>>>>,
-    next_id: Arc<Mutex<u32>>,
-    pending_callbacks: Arc<Mutex<HashMap<u32, PendingCallback>>>,
-) {
-    // setTimeout
-    // SAFETY: The closure captures Arc<Mutex<>> which are thread-safe
-    // The spawned tokio task is independent and doesn't access JSEngine state
-    // Timer handles are stored separately and can be safely accessed
-    let set_timeout_fn = unsafe {
-        NativeFunction::from_closure(move |_this, args, context| {
-            // Поддерживаем две формы: setTimeout("code", delay) и setTimeout(function, delay)
-            if let Some(callback_val) = args.first() {
-                let delay_ms = args.get(1).and_then(|v| v.as_number()).unwrap_or(0.0) as u64;
-
-                // Конвертируем callback в строку кода
-                let callback_code = if let Some(s) = callback_val.as_string() {
-                    // Строка - это уже код
-                    s.to_std_string_escaped()
-                } else if callback_val.is_callable() {
-                    // Функция - вызываем её toString()
-                    match callback_val.to_string(context) {
-                        Ok(s) => s.to_std_string_escaped(),
-                        Err(_) => {
-                            eprintln!("Failed to convert callback to string");
-                            return Ok(JsValue::undefined());
-                        }
-                    }
-                } else {
-                    return Ok(JsValue::undefined());
-                };
+/// Очередь таймеров страницы, идущая по [`Clock`]
+#[derive(Debug)]
+pub struct TimerQueue {
+    clock: Clock,
+    pending: HashMap<u32, PendingCallback>,
+    next_id: u32,
+    next_seq: u64,
+}
 
-                let timer_id = {
-                    let mut timer_id_lock = match next_id.lock() {
-                        Ok(guard) => guard,
-                        Err(poisoned) => {
-                            #[cfg(debug_assertions)]
-                            eprintln!("Warning: Timer ID mutex was poisoned, recovering");
-                            poisoned.into_inner()
-                        }
-                    };
-                    let id = *timer_id_lock;
-                    *timer_id_lock += 1;
-                    id
-                };
+impl TimerQueue {
+    pub fn new(clock: Clock) -> Self {
+        Self {
+            clock,
+            pending: HashMap::new(),
+            next_id: 1,
+            next_seq: 0,
+        }
+    }
 
-                // Сохраняем callback для последующего выполнения
-                let fire_time =
-                    std::time::Instant::now() + std::time::Duration::from_millis(delay_ms);
-                if let Ok(mut pending) = pending_callbacks.lock() {
-                    pending.insert(
-                        timer_id,
-                        PendingCallback {
-                            code: callback_code,
-                            fire_time,
-                        },
-                    );
-                }
-
-                // Планируем таймер
-                #[allow(unused_variables)]
-                let pending_clone = pending_callbacks.clone();
-                let handle = tokio::spawn(async move {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-                    // Таймер сработал - callback будет выполнен в следующем tick_timers()
-                    #[cfg(debug_assertions)]
-                    {
-                        if let Ok(pending) = pending_clone.lock()
-                            && pending.contains_key(&timer_id)
-                        {
-                            println!("Timer {} ready to fire after {}ms", timer_id, delay_ms);
-                        }
-                    }
-                });
-
-                if let Ok(mut timers_lock) = timers.lock() {
-                    timers_lock.insert(timer_id, handle);
-                } else {
-                    #[cfg(debug_assertions)]
-                    eprintln!(
-                        "Warning: Failed to acquire timers lock, timer {} may not be tracked",
-                        timer_id
-                    );
-                }
-
-                return Ok(JsValue::from(timer_id as f64));
-            }
-            Ok(JsValue::undefined())
-        })
-    };
+    /// Текущее время страницы
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
 
-    let _ = context.register_global_builtin_callable(js_string!("setTimeout"), 0, set_timeout_fn);
-}
+    /// Планирует callback и возвращает id таймера
+    pub fn schedule(&mut self, callback: TimerCallback, delay: Duration, repeat: bool) -> u32 {
+        let fire_time = self.now() + delay;
+        let interval = repeat.then(|| delay.max(MIN_INTERVAL));
+        self.insert(callback, fire_time, interval, false)
+    }
 
-/// Выполняет готовые callbacks из setTimeout
-pub fn tick_timers(
-    context: &mut Context,
-    pending_callbacks: Arc<Mutex<HashMap<u32, PendingCallback>>>,
-    timers: Arc<Mutex<HashMap<u32, tokio::task::JoinHandle<()>>>>,
-) -> usize {
-    let now = std::time::Instant::now();
-    let mut executed = 0;
-
-    // Собираем callbacks, которые нужно выполнить
-    let ready_callbacks: Vec<(u32, String)> = {
-        let pending = match pending_callbacks.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                eprintln!("Warning: pending_callbacks mutex poisoned");
-                poisoned.into_inner()
-            }
+    /// Планирует callback на ближайший следующий кадр.
+    ///
+    /// Кадры выровнены по сетке [`FRAME_INTERVAL`], поэтому все callbacks,
+    /// запрошенные до кадра, выполняются вместе, а запрошенные из callback'а
+    /// кадра — уже в следующем.
+    pub fn schedule_frame(&mut self, function: JsObject) -> u32 {
+        let frame = FRAME_INTERVAL.as_nanos();
+        let next = (self.now().as_nanos() / frame + 1) * frame;
+        let fire_time = Duration::from_nanos(next as u64);
+        let callback = TimerCallback::Function {
+            function,
+            args: Vec::new(),
         };
+        self.insert(callback, fire_time, None, true)
+    }
 
-        pending
-            .iter()
-            .filter(|(_, cb)| cb.fire_time <= now)
-            .map(|(id, cb)| (*id, cb.code.clone()))
-            .collect()
-    };
+    fn insert(
+        &mut self,
+        callback: TimerCallback,
+        fire_time: Duration,
+        interval: Option<Duration>,
+        animation_frame: bool,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.next_seq += 1;
+        self.pending.insert(
+            id,
+            PendingCallback {
+                callback,
+                fire_time,
+                interval,
+                animation_frame,
+                seq: self.next_seq,
+            },
+        );
+        id
+    }
 
-    // Выполняем callbacks и удаляем их из pending
-    for (timer_id, code) in ready_callbacks {
-        // Пытаемся выполнить как код или как вызов функции
-        let exec_code = if code.starts_with("function") || code.contains("=>") {
-            format!("({})()", code)
-        } else {
-            code.clone()
-        };
+    pub fn cancel(&mut self, id: u32) {
+        self.pending.remove(&id);
+    }
 
-        match execute_code(context, &exec_code) {
-            Ok(_) => {
-                executed += 1;
-                #[cfg(debug_assertions)]
-                println!("Executed timer {} callback", timer_id);
-            }
-            Err(e) => {
-                eprintln!("Error executing timer {} callback: {:?}", timer_id, e);
-            }
-        }
+    pub fn clear(&mut self) -> usize {
+        let count = self.pending.len();
+        self.pending.clear();
+        count
+    }
 
-        // Удаляем выполненный callback
-        if let Ok(mut pending) = pending_callbacks.lock() {
-            pending.remove(&timer_id);
-        }
+    /// Время срабатывания ближайшего таймера
+    pub fn next_fire_time(&self) -> Option<Duration> {
+        self.pending.values().map(|cb| cb.fire_time).min()
+    }
 
-        // Удаляем завершённый таймер
-        if let Ok(mut timers_lock) = timers.lock() {
-            timers_lock.remove(&timer_id);
+    /// Извлекает самый ранний таймер, сработавший к `until`.
+    ///
+    /// Интервалы сразу перепланируются от момента срабатывания, так что
+    /// `clearInterval` внутри callback'а отменяет следующий запуск.
+    pub fn pop_due(&mut self, until: Duration) -> Option<(u32, PendingCallback)> {
+        let (&id, _) = self
+            .pending
+            .iter()
+            .filter(|(_, cb)| cb.fire_time <= until)
+            .min_by_key(|(_, cb)| (cb.fire_time, cb.seq))?;
+
+        let callback = self.pending.remove(&id)?;
+        if let Some(interval) = callback.interval {
+            self.next_seq += 1;
+            let mut next = callback.clone();
+            next.fire_time = callback.fire_time.max(self.now()) + interval;
+            next.seq = self.next_seq;
+            self.pending.insert(id, next);
         }
+        Some((id, callback))
+    }
+}
+
+/// Инициализирует глобальные setTimeout, setInterval, clearTimeout и clearInterval
+pub fn init_timers(context: &mut Context, timers: Rc<RefCell<TimerQueue>>) {
+    for (name, repeat) in [("setTimeout", false), ("setInterval", true)] {
+        // SAFETY: The closure captures only Rc<RefCell<TimerQueue>>. Callables stored in the
+        // queue are not traced, so boa_gc counts them as roots: they stay alive until the
+        // timer fires or is cancelled
+        let schedule_fn = {
+            let timers = timers.clone();
+            unsafe {
+                NativeFunction::from_closure(move |_this, args, _context| {
+                    // Поддерживаем две формы: setTimeout("code", delay) и setTimeout(function, delay)
+                    let Some(callback_val) = args.first() else {
+                        return Ok(JsValue::undefined());
+                    };
+                    let delay_ms = args
+                        .get(1)
+                        .and_then(|v| v.as_number())
+                        .filter(|ms| ms.is_finite() && *ms > 0.0)
+                        .unwrap_or(0.0);
+
+                    // Функция хранится как объект: при вызове она видит своё замыкание
+                    let callback = if let Some(function) = callback_val.as_callable() {
+                        TimerCallback::Function {
+                            function: function.clone(),
+                            args: args.iter().skip(2).cloned().collect(),
+                        }
+                    } else if let Some(s) = callback_val.as_string() {
+                        TimerCallback::Code(s.to_std_string_escaped())
+                    } else {
+                        return Ok(JsValue::undefined());
+                    };
+
+                    let delay = Duration::from_secs_f64(delay_ms / 1000.0);
+                    let timer_id = timers.borrow_mut().schedule(callback, delay, repeat);
+
+                    Ok(JsValue::from(timer_id as f64))
+                })
+            }
+        };
+        let _ = context.register_global_builtin_callable(JsString::from(name), 0, schedule_fn);
     }
 
-    executed
+    for name in ["clearTimeout", "clearInterval"] {
+        // SAFETY: Same reasoning as the scheduling functions above
+        let clear_fn = {
+            let timers = timers.clone();
+            unsafe {
+                NativeFunction::from_closure(move |_this, args, _context| {
+                    if let Some(id) = args.first().and_then(|v| v.as_number()) {
+                        timers.borrow_mut().cancel(id as u32);
+                    }
+                    Ok(JsValue::undefined())
+                })
+            }
+        };
+        let _ = context.register_global_builtin_callable(JsString::from(name), 1, clear_fn);
+    }
+
+    // requestAnimationFrame / cancelAnimationFrame
+    // SAFETY: Same reasoning as the scheduling functions above
+    let request_frame_fn = {
+        let timers = timers.clone();
+        unsafe {
+            NativeFunction::from_closure(move |_this, args, _context| {
+                let Some(function) = args.first().and_then(JsValue::as_callable) else {
+                    return Ok(JsValue::undefined());
+                };
+                let frame_id = timers.borrow_mut().schedule_frame(function.clone());
+                Ok(JsValue::from(frame_id as f64))
+            })
+        }
+    };
+    let cancel_frame_fn = unsafe {
+        NativeFunction::from_closure(move |_this, args, _context| {
+            if let Some(id) = args.first().and_then(|v| v.as_number()) {
+                timers.borrow_mut().cancel(id as u32);
+            }
+            Ok(JsValue::undefined())
+        })
+    };
+    let _ = context.register_global_builtin_callable(
+        js_string!("requestAnimationFrame"),
+        1,
+        request_frame_fn,
+    );
+    let _ = context.register_global_builtin_callable(
+        js_string!("cancelAnimationFrame"),
+        1,
+        cancel_frame_fn,
+    );
 }
 
-/// Вспомогательная функция для выполнения JS кода
-fn execute_code(context: &mut Context, code: &str) -> Result<JsValue, boa_engine::JsError> {
-    use boa_engine::Source;
-    let source = Source::from_bytes(code);
-    context.eval(source)
+/// Инициализирует `performance.now()`: миллисекунды от `time_origin` по часам страницы
+pub fn init_performance(context: &mut Context, clock: Clock, time_origin: Duration) {
+    // SAFETY: The closure captures only the thread-safe Clock handle
+    let now_fn = unsafe {
+        NativeFunction::from_closure(move |_this, _args, _context| {
+            let elapsed = clock.now().saturating_sub(time_origin);
+            Ok(JsValue::from(elapsed.as_secs_f64() * 1000.0))
+        })
+    };
+
+    let performance = ObjectInitializer::new(context)
+        .function(now_fn, js_string!("now"), 0)
+        .build();
+    let _ = context.register_global_property(
+        js_string!("performance"),
+        performance,
+        Attribute::WRITABLE | Attribute::ENUMERABLE | Attribute::CONFIGURABLE,
+    );
 }
@@ -0,0 +1,79 @@
+use boa_engine::{
+    Context, JsString, JsValue, js_string, object::ObjectInitializer, property::Attribute,
+};
+
+/// Параметры окна и браузера, видимые скриптам
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub inner_width: f32,
+    pub inner_height: f32,
+    pub device_pixel_ratio: f32,
+    pub user_agent: String,
+    /// Основной язык (первый из Accept-Language)
+    pub language: String,
+}
+
+impl Default for WindowInfo {
+    fn default() -> Self {
+        Self {
+            inner_width: 800.0,
+            inner_height: 600.0,
+            device_pixel_ratio: 1.0,
+            user_agent: crate::network::DEFAULT_USER_AGENT.to_string(),
+            language: primary_language(crate::network::DEFAULT_ACCEPT_LANGUAGE),
+        }
+    }
+}
+
+/// Первый язык из значения Accept-Language: `"ru-RU,ru;q=0.9"` → `"ru-RU"`
+pub fn primary_language(accept_language: &str) -> String {
+    accept_language
+        .split(',')
+        .next()
+        .and_then(|tag| tag.split(';').next())
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .unwrap_or("en-US")
+        .to_string()
+}
+
+/// Инициализирует `navigator` и размеры окна (`innerWidth`, `innerHeight`, `devicePixelRatio`)
+pub fn init_window_info(context: &mut Context, info: &WindowInfo) {
+    let readonly = Attribute::READONLY | Attribute::ENUMERABLE;
+    let navigator = ObjectInitializer::new(context)
+        .property(
+            js_string!("userAgent"),
+            JsString::from(info.user_agent.as_str()),
+            readonly,
+        )
+        .property(
+            js_string!("language"),
+            JsString::from(info.language.as_str()),
+            readonly,
+        )
+        .build();
+    let _ = context.register_global_property(
+        js_string!("navigator"),
+        navigator,
+        Attribute::WRITABLE | Attribute::ENUMERABLE | Attribute::CONFIGURABLE,
+    );
+
+    set_viewport(context, info);
+}
+
+/// Обновляет размеры окна после изменения viewport
+pub fn set_viewport(context: &mut Context, info: &WindowInfo) {
+    let global = context.global_object();
+    for (name, value) in [
+        ("innerWidth", info.inner_width),
+        ("innerHeight", info.inner_height),
+        ("devicePixelRatio", info.device_pixel_ratio),
+    ] {
+        let _ = global.set(
+            JsString::from(name),
+            JsValue::from(value as f64),
+            false,
+            context,
+        );
+    }
+}
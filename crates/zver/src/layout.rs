@@ -3,12 +3,18 @@ mod builder;
 mod engine;
 mod metrics;
 pub mod render;
+pub mod scroll;
+pub mod snapshot;
 pub mod styles;
 pub mod taffy_integration;
+pub mod text_measure;
 pub mod types;
 
 // Публичные экспорты
 pub use engine::LayoutEngine;
 pub use metrics::{FontMetrics, TextMeasureContext};
 pub use render::*;
+pub use scroll::{ScrollOffsets, ScrollState};
+pub use snapshot::LayoutSnapshot;
+pub use text_measure::{FontChain, FontRun, LineBox, TextLayout, TextMeasurer};
 pub use types::*;
@@ -1,21 +1,75 @@
+use crate::css::calc::CalcContext;
 use crate::css::{PseudoStyle, selectors::PseudoElement};
 use crate::dom::Document;
 use crate::layout::metrics::TextMeasureContext;
 use crate::layout::styles::apply_default_tag_styles;
-use crate::layout::types::{ComputedStyle, Display};
+use crate::layout::taffy_integration::LayoutTree;
+use crate::layout::text_measure::TextMeasurer;
+use crate::layout::types::{
+    CalcProperty, ComputedStyle, DEFAULT_FONT_SIZE, Display, FontStyle, FontWeight,
+};
 use std::collections::HashMap;
 use taffy::prelude::*;
 
 /// Вспомогательная структура для построения Taffy дерева
 pub struct TreeBuilder<'a> {
-    pub taffy: &'a mut TaffyTree<Option<TextMeasureContext>>,
+    pub taffy: &'a mut LayoutTree,
     pub node_mapping: &'a mut HashMap<usize, NodeId>,
     pub resolved_styles: &'a mut HashMap<usize, ComputedStyle>,
     pub viewport_width: f32,
     pub viewport_height: f32,
+    pub text_measurer: &'a TextMeasurer,
+}
+
+/// Общие для документа данные для относительных единиц
+pub struct UnitContext<'a> {
+    /// Вычисленный `font-size` корневого элемента (`rem`)
+    pub root_font_size: f32,
+    pub viewport_width: f32,
+    pub viewport_height: f32,
+    /// Метрики шрифтов для `ch` и `ex`
+    pub text_measurer: &'a TextMeasurer,
+}
+
+impl UnitContext<'_> {
+    /// Контекст вычисления длин в шрифте `style` размера `font_size`
+    fn calc_context(&self, style: Option<&ComputedStyle>, font_size: f32) -> CalcContext {
+        let font = TextMeasureContext {
+            content: String::new(),
+            font_family: style.and_then(|style| style.font_family.clone()),
+            font_size,
+            font_weight: style.map_or(FontWeight::Normal, |style| style.font_weight),
+            font_style: style.map_or(FontStyle::Normal, |style| style.font_style),
+        };
+        let (ch_width, ex_height) = self.text_measurer.font_units(&font);
+        CalcContext {
+            percent_basis: None,
+            font_size,
+            root_font_size: self.root_font_size,
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+            ch_width,
+            ex_height,
+        }
+    }
 }
 
 impl<'a> TreeBuilder<'a> {
+    /// Контекст единиц для узла: `rem` корня от начального размера шрифта
+    fn unit_context(&self, document: &Document, dom_node_id: usize) -> UnitContext<'a> {
+        let root_font_size = document
+            .root
+            .filter(|&root| root != dom_node_id)
+            .and_then(|root| self.resolved_styles.get(&root))
+            .map_or(DEFAULT_FONT_SIZE, |style| style.font_size);
+        UnitContext {
+            root_font_size,
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+            text_measurer: self.text_measurer,
+        }
+    }
+
     /// Рекурсивно строит узел Taffy дерева
     pub fn build_node_recursive(
         &mut self,
@@ -30,10 +84,16 @@ impl<'a> TreeBuilder<'a> {
         let mut computed_style = ComputedStyle::from_css_properties(&node_styles);
 
         if let Some(node) = document.nodes.get(&dom_node_id) {
+            let author_display = computed_style.display;
             apply_default_tag_styles(&mut computed_style, &node.tag_name);
+            // Заданный автором `display` сильнее умолчаний тега
+            if node_styles.contains_key("display") {
+                computed_style.display = author_display;
+            }
         }
 
-        inherit_computed_style(&mut computed_style, parent_style);
+        let units = self.unit_context(document, dom_node_id);
+        inherit_computed_style(&mut computed_style, parent_style, &units);
 
         // Текстовые узлы рассматриваем как inline по умолчанию
         if document
@@ -50,17 +110,21 @@ impl<'a> TreeBuilder<'a> {
         if document.root == Some(dom_node_id) {
             computed_style.width = crate::layout::types::Size::Px(self.viewport_width);
             computed_style.height = crate::layout::types::Size::Px(self.viewport_height);
+            computed_style.calc.retain(|(property, _)| {
+                !matches!(property, CalcProperty::Width | CalcProperty::Height)
+            });
             computed_style.display = Display::Block;
         }
 
+        // Стиль скрытого элемента тоже вычислен — он нужен дампам и проверкам видимости
+        self.resolved_styles
+            .insert(dom_node_id, computed_style.clone());
+
         // Пропускаем элементы с display: none
         if matches!(computed_style.display, Display::None) {
             return None;
         }
 
-        self.resolved_styles
-            .insert(dom_node_id, computed_style.clone());
-
         // Пропускаем служебные теги
         if let Some(node) = document.nodes.get(&dom_node_id)
             && let Some(tag) = &node.tag_name
@@ -89,6 +153,7 @@ impl<'a> TreeBuilder<'a> {
             if node.tag_name.is_none() {
                 Some(TextMeasureContext {
                     content: node.text_content.clone().unwrap_or_default(),
+                    font_family: computed_style.font_family.clone(),
                     font_size: computed_style.font_size,
                     font_weight: computed_style.font_weight,
                     font_style: computed_style.font_style,
@@ -156,17 +221,12 @@ impl<'a> TreeBuilder<'a> {
         self.flush_inline_group(&mut inline_group, &mut taffy_children);
 
         // Создаем Taffy узел
-        let taffy_node_id = if context.is_some() {
-            self.taffy
-                .new_leaf_with_context(computed_style.to_taffy_style(), context)
-                .ok()?
-        } else if taffy_children.is_empty() {
-            self.taffy.new_leaf(computed_style.to_taffy_style()).ok()?
-        } else {
-            self.taffy
-                .new_with_children(computed_style.to_taffy_style(), &taffy_children)
-                .ok()?
-        };
+        let taffy_node_id = self.taffy.new_node(
+            computed_style.to_taffy_style(),
+            &computed_style.calc,
+            context,
+            &taffy_children,
+        );
 
         self.node_mapping.insert(dom_node_id, taffy_node_id);
         Some((taffy_node_id, node_display))
@@ -187,7 +247,7 @@ impl<'a> TreeBuilder<'a> {
             ..Default::default()
         };
 
-        self.taffy.new_with_children(style, children).ok()
+        Some(self.taffy.new_node(style, &[], None, children))
     }
 
     fn push_layout_child(
@@ -236,7 +296,8 @@ impl<'a> TreeBuilder<'a> {
         if !pseudo_style.properties.contains_key("display") {
             computed_style.display = Display::Inline;
         }
-        inherit_computed_style(&mut computed_style, Some(parent_style));
+        let units = self.unit_context(document, owner_id);
+        inherit_computed_style(&mut computed_style, Some(parent_style), &units);
 
         if matches!(computed_style.display, Display::None) {
             return None;
@@ -250,18 +311,18 @@ impl<'a> TreeBuilder<'a> {
 
         let context = text_content.map(|content| TextMeasureContext {
             content,
+            font_family: computed_style.font_family.clone(),
             font_size: computed_style.font_size,
             font_weight: computed_style.font_weight,
             font_style: computed_style.font_style,
         });
 
-        let taffy_node_id = if let Some(ctx) = context {
-            self.taffy
-                .new_leaf_with_context(computed_style.to_taffy_style(), Some(ctx))
-                .ok()?
-        } else {
-            self.taffy.new_leaf(computed_style.to_taffy_style()).ok()?
-        };
+        let taffy_node_id = self.taffy.new_node(
+            computed_style.to_taffy_style(),
+            &computed_style.calc,
+            context,
+            &[],
+        );
 
         self.resolved_styles
             .insert(pseudo_node_id, computed_style.clone());
@@ -271,8 +332,16 @@ impl<'a> TreeBuilder<'a> {
     }
 }
 
-/// Применяет наследование стилей от родителя
-pub fn inherit_computed_style(style: &mut ComputedStyle, parent: Option<&ComputedStyle>) {
+/// Применяет наследование стилей от родителя и вычисляет относительные единицы.
+///
+/// `em`, `ex`, `ch` и проценты в `font-size` считаются от шрифта родителя, в
+/// остальных длинах — от шрифта самого элемента. Длины с процентами остаются
+/// в [`ComputedStyle::calc`] до layout.
+pub fn inherit_computed_style(
+    style: &mut ComputedStyle,
+    parent: Option<&ComputedStyle>,
+    units: &UnitContext<'_>,
+) {
     if let Some(parent) = parent {
         if style.color.is_none() {
             style.color = parent.color.clone();
@@ -280,8 +349,8 @@ pub fn inherit_computed_style(style: &mut ComputedStyle, parent: Option<&Compute
         if style.background_color.is_none() {
             style.background_color = parent.background_color.clone();
         }
-        if (style.font_size - 16.0).abs() < f32::EPSILON {
-            style.font_size = parent.font_size;
+        if style.font_family.is_none() {
+            style.font_family = parent.font_family.clone();
         }
         if matches!(style.font_weight, crate::layout::types::FontWeight::Normal) {
             style.font_weight = parent.font_weight;
@@ -289,5 +358,34 @@ pub fn inherit_computed_style(style: &mut ComputedStyle, parent: Option<&Compute
         if matches!(style.font_style, crate::layout::types::FontStyle::Normal) {
             style.font_style = parent.font_style;
         }
+        if style.pointer_events.is_none() {
+            style.pointer_events = parent.pointer_events;
+        }
+    }
+
+    let parent_font_size = parent.map_or(DEFAULT_FONT_SIZE, |parent| parent.font_size);
+    style.font_size = match &style.font_size_value {
+        Some(value) => {
+            let mut context = units.calc_context(parent, parent_font_size);
+            context.percent_basis = Some(parent_font_size);
+            value
+                .eval(&context)
+                .filter(|size| *size >= 0.0)
+                .unwrap_or(parent_font_size)
+        }
+        None => parent_font_size,
+    };
+
+    if style.calc.is_empty() {
+        return;
+    }
+    let context = units.calc_context(Some(style), style.font_size);
+    for (property, expr) in std::mem::take(&mut style.calc) {
+        let expr = expr.resolve_units(&context);
+        if expr.has_percentage() {
+            style.calc.push((property, expr));
+        } else if let Some(value) = expr.eval(&context) {
+            property.set(style, value);
+        }
     }
 }
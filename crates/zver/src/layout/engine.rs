@@ -1,9 +1,12 @@
+use crate::css::fonts::LoadedFont;
 use crate::css::{PseudoStyle, selectors::PseudoElement};
 use crate::dom::Document;
 use crate::layout::builder::TreeBuilder;
-use crate::layout::metrics::{FontMetrics, TextMeasureContext, text_measure_function};
 use crate::layout::render::RenderInfo;
-use crate::layout::types::{ComputedStyle, LayoutResult};
+use crate::layout::scroll::ScrollOffsets;
+use crate::layout::taffy_integration::LayoutTree;
+use crate::layout::text_measure::{TextLayout, TextMeasurer};
+use crate::layout::types::{ComputedStyle, Display, LayoutResult};
 use std::collections::HashMap;
 use taffy::prelude::*;
 
@@ -12,17 +15,24 @@ pub struct LayoutEngine {
     viewport_height: f32,
 
     // Taffy layout engine с поддержкой текстовых контекстов
-    taffy: TaffyTree<Option<TextMeasureContext>>,
+    taffy: LayoutTree,
 
     // Кеширование результатов
     root_node: Option<NodeId>,
     node_mapping: HashMap<usize, NodeId>, // DOM ID -> Taffy NodeId
     layout_cache: HashMap<usize, LayoutResult>, // Результаты layout по DOM ID
     resolved_styles: HashMap<usize, ComputedStyle>,
+
+    // Измерение текста по шрифтам и line box'ы текстовых узлов
+    text_measurer: TextMeasurer,
+    text_layouts: HashMap<usize, TextLayout>,
+
+    // Смещения scroll-контейнеров, общие с вводом и скриптами
+    scroll: ScrollOffsets,
 }
 
 // SAFETY: LayoutEngine can be safely sent between threads because:
-// 1. TaffyTree is designed to be Send (contains no thread-local data)
+// 1. LayoutTree contains no thread-local data; calc() handles are plain indices
 // 2. All access is protected by RwLock in the parent Zver struct
 // 3. HashMap and Vec are Send when their contents are Send
 // 4. TextMeasureContext and LayoutResult are plain data structures
@@ -39,23 +49,71 @@ impl LayoutEngine {
         Self {
             viewport_width,
             viewport_height,
-            taffy: TaffyTree::new(),
+            taffy: LayoutTree::new(),
             root_node: None,
             node_mapping: HashMap::new(),
             layout_cache: HashMap::new(),
             resolved_styles: HashMap::new(),
+            text_measurer: TextMeasurer::default(),
+            text_layouts: HashMap::new(),
+            scroll: ScrollOffsets::new(),
         }
     }
 
+    /// Смещения прокрутки, с которыми извлекаются позиции узлов
+    pub fn scroll_offsets(&self) -> &ScrollOffsets {
+        &self.scroll
+    }
+
+    /// Сдвигает узлы на текущую прокрутку без пересчёта Taffy.
+    ///
+    /// Вызывается после [`ScrollOffsets::scroll_to`]: размеры не меняются,
+    /// меняются только позиции потомков прокрученных контейнеров.
+    pub fn apply_scroll(&mut self, document: &Document) -> &HashMap<usize, LayoutResult> {
+        self.extract_and_cache_results(document);
+        &self.layout_cache
+    }
+
+    /// Прокручивается ли содержимое узла: корень документа — это viewport
+    pub fn is_scroll_container(&self, document: &Document, node_id: usize) -> bool {
+        document.root == Some(node_id)
+            || self
+                .resolved_styles
+                .get(&node_id)
+                .is_some_and(ComputedStyle::is_scroll_container)
+    }
+
+    /// Задаёт загруженные @font-face шрифты для измерения текста
+    pub fn set_fonts(&mut self, fonts: Vec<LoadedFont>) {
+        self.text_measurer = TextMeasurer::new(fonts);
+    }
+
+    /// Измеритель текста, которым считался последний layout
+    pub fn text_measurer(&self) -> &TextMeasurer {
+        &self.text_measurer
+    }
+
+    /// Возвращает размеры viewport, для которого считается layout
+    pub fn viewport_size(&self) -> (f32, f32) {
+        (self.viewport_width, self.viewport_height)
+    }
+
+    /// Меняет размеры viewport; применяются при следующем [`LayoutEngine::compute_layout`]
+    pub fn set_viewport(&mut self, width: f32, height: f32) {
+        self.viewport_width = width;
+        self.viewport_height = height;
+    }
+
     /// Сбрасывает состояние при изменении DOM/CSS
     pub fn invalidate(&mut self) {
         if let Some(root) = self.root_node.take() {
-            let _ = self.taffy.remove(root);
+            self.taffy.remove(root);
         }
         self.taffy.clear();
         self.node_mapping.clear();
         self.layout_cache.clear();
         self.resolved_styles.clear();
+        self.text_layouts.clear();
     }
 
     /// Вычисляет layout с использованием Taffy
@@ -83,16 +141,166 @@ impl LayoutEngine {
         self.layout_cache.clone()
     }
 
+    /// Пересчитывает layout после изменения поддеревьев `roots`.
+    ///
+    /// Taffy-узлы остальной части дерева сохраняются вместе с кешем Taffy:
+    /// заново строятся только поддеревья изменённых элементов. Если элемент
+    /// сменил inline/block, стал `display: none` или ещё не раскладывался,
+    /// перестраивается его родитель; изменение корня — полный пересчёт.
+    pub fn relayout(
+        &mut self,
+        document: &Document,
+        styles: &HashMap<usize, HashMap<String, String>>,
+        pseudo_styles: &HashMap<usize, HashMap<PseudoElement, PseudoStyle>>,
+        roots: &[usize],
+    ) -> HashMap<usize, LayoutResult> {
+        if self.root_node.is_none() {
+            return self.compute_layout(document, styles, pseudo_styles);
+        }
+
+        // Поддерево, вложенное в другой корень, перестроится вместе с ним
+        let mut pending: Vec<usize> = roots
+            .iter()
+            .copied()
+            .filter(|&node_id| {
+                document.is_connected(node_id)
+                    && !roots.iter().any(|&other| {
+                        other != node_id && document.is_inclusive_descendant(node_id, other)
+                    })
+            })
+            .collect();
+        pending.sort_unstable();
+        pending.dedup();
+
+        while let Some(node_id) = pending.pop() {
+            if let Err(parent_id) = self.rebuild_subtree(document, styles, pseudo_styles, node_id) {
+                match parent_id {
+                    Some(parent_id) => pending.push(parent_id),
+                    None => return self.compute_layout(document, styles, pseudo_styles),
+                }
+            }
+        }
+
+        self.compute_taffy_layouts();
+        self.extract_and_cache_results(document);
+        self.layout_cache.clone()
+    }
+
+    /// Заменяет Taffy-поддерево элемента новым.
+    ///
+    /// `Err` означает, что перестраивать нужно родителя (`None` — корень документа).
+    fn rebuild_subtree(
+        &mut self,
+        document: &Document,
+        styles: &HashMap<usize, HashMap<String, String>>,
+        pseudo_styles: &HashMap<usize, HashMap<PseudoElement, PseudoStyle>>,
+        node_id: usize,
+    ) -> Result<(), Option<usize>> {
+        let parent_id = document.nodes.get(&node_id).and_then(|node| node.parent);
+        if document.root == Some(node_id) {
+            return Err(None);
+        }
+        let escalate = Err(parent_id);
+
+        let (Some(&old_node), Some(old_display)) = (
+            self.node_mapping.get(&node_id),
+            self.resolved_styles
+                .get(&node_id)
+                .map(|style| style.display),
+        ) else {
+            return escalate;
+        };
+        let Some(taffy_parent) = self.taffy.parent(old_node) else {
+            return escalate;
+        };
+        let parent_style = parent_id
+            .and_then(|id| self.resolved_styles.get(&id))
+            .cloned();
+
+        // Старое поддерево запоминаем до построения нового: маппинг будет перезаписан
+        let mut removed = Vec::new();
+        let mut stack = vec![old_node];
+        while let Some(node) = stack.pop() {
+            removed.push(node);
+            stack.extend_from_slice(self.taffy.children(node));
+        }
+
+        let mut builder = TreeBuilder {
+            taffy: &mut self.taffy,
+            node_mapping: &mut self.node_mapping,
+            resolved_styles: &mut self.resolved_styles,
+            viewport_width: self.viewport_width,
+            viewport_height: self.viewport_height,
+            text_measurer: &self.text_measurer,
+        };
+        let rebuilt = builder.build_node_recursive(
+            document,
+            node_id,
+            styles,
+            pseudo_styles,
+            parent_style.as_ref(),
+        );
+
+        let is_inline = |display: Display| matches!(display, Display::Inline);
+        let index = self
+            .taffy
+            .children(taffy_parent)
+            .iter()
+            .position(|&child| child == old_node);
+        match (rebuilt, index) {
+            (Some((new_node, display)), Some(index))
+                if is_inline(display) == is_inline(old_display) =>
+            {
+                self.taffy
+                    .replace_child_at_index(taffy_parent, index, new_node);
+                for node in &removed {
+                    self.taffy.remove(*node);
+                }
+                self.prune_mapping(&removed);
+                Ok(())
+            }
+            (rebuilt, _) => {
+                // Ничего не заменили: убираем недостроенное и строим родителя
+                if let Some((new_node, _)) = rebuilt {
+                    let mut stack = vec![new_node];
+                    while let Some(node) = stack.pop() {
+                        stack.extend_from_slice(self.taffy.children(node));
+                        self.taffy.remove(node);
+                        removed.push(node);
+                    }
+                }
+                self.prune_mapping(&removed);
+                escalate
+            }
+        }
+    }
+
+    /// Убирает из маппинга ссылки на удалённые Taffy-узлы
+    fn prune_mapping(&mut self, removed: &[NodeId]) {
+        let removed: std::collections::HashSet<NodeId> = removed.iter().copied().collect();
+        self.node_mapping.retain(|_, node| !removed.contains(node));
+    }
+
     /// Получает результат layout для конкретного узла
     pub fn get_layout_result(&self, node_id: usize) -> Option<LayoutResult> {
         self.layout_cache.get(&node_id).copied()
     }
 
+    /// Taffy-узел, которым представлен DOM-узел в текущем дереве
+    pub fn taffy_node(&self, node_id: usize) -> Option<NodeId> {
+        self.node_mapping.get(&node_id).copied()
+    }
+
     /// Получает все результаты layout
     pub fn get_all_layout_results(&self) -> &HashMap<usize, LayoutResult> {
         &self.layout_cache
     }
 
+    /// Строки текстового узла в том виде, в котором их измерил layout
+    pub fn text_layout(&self, node_id: usize) -> Option<&TextLayout> {
+        self.text_layouts.get(&node_id)
+    }
+
     /// Возвращает карту вычисленных стилей после применения каскада и наследования
     pub fn resolved_styles(&self) -> &HashMap<usize, ComputedStyle> {
         &self.resolved_styles
@@ -107,7 +315,7 @@ impl LayoutEngine {
     ) -> Option<NodeId> {
         // Очищаем старое состояние
         if let Some(root) = self.root_node.take() {
-            let _ = self.taffy.remove(root);
+            self.taffy.remove(root);
         }
         self.taffy.clear();
         self.node_mapping.clear();
@@ -122,6 +330,7 @@ impl LayoutEngine {
             resolved_styles: &mut self.resolved_styles,
             viewport_width: self.viewport_width,
             viewport_height: self.viewport_height,
+            text_measurer: &self.text_measurer,
         };
 
         let (taffy_root, _) =
@@ -130,32 +339,16 @@ impl LayoutEngine {
         Some(taffy_root)
     }
 
-    /// Вычисляет layout с измерением текста
+    /// Вычисляет layout с измерением текста; `calc()` вычисляет Taffy
     fn compute_taffy_layouts(&mut self) {
         if let Some(root) = self.root_node {
-            let font_metrics = FontMetrics::default();
-
-            // Вычисляем layout, учитывая измерение текста
-            let _ = self.taffy.compute_layout_with_measure(
+            self.taffy.compute_layout(
                 root,
                 taffy::Size {
                     width: taffy::AvailableSpace::Definite(self.viewport_width),
                     height: taffy::AvailableSpace::Definite(self.viewport_height),
                 },
-                |known_dimensions, available_space, _node_id, node_context, _style| {
-                    if let Some(text_ctx) = node_context.as_ref().and_then(|opt| opt.as_ref()) {
-                        // Это текстовый узел - измеряем текст
-                        text_measure_function(
-                            known_dimensions,
-                            available_space,
-                            Some(text_ctx),
-                            &font_metrics,
-                        )
-                    } else {
-                        // Это обычный элемент - используем заданные размеры (если есть)
-                        known_dimensions.unwrap_or(taffy::Size::ZERO)
-                    }
-                },
+                &self.text_measurer,
             );
         }
     }
@@ -163,12 +356,17 @@ impl LayoutEngine {
     /// Извлекает результаты layout из Taffy и кеширует их
     fn extract_and_cache_results(&mut self, document: &Document) {
         self.layout_cache.clear();
+        self.text_layouts.clear();
 
         if let Some(root_id) = document.root
             && let Some(&taffy_root) = self.node_mapping.get(&root_id)
         {
             self.extract_node_layout(document, root_id, taffy_root, 0.0, 0.0);
         }
+        let scroll = self.scroll.clone();
+        scroll.retain(document.root, |node_id| {
+            self.layout_cache.contains_key(&node_id) && self.is_scroll_container(document, node_id)
+        });
     }
 
     fn extract_node_layout(
@@ -179,9 +377,22 @@ impl LayoutEngine {
         parent_x: f32,
         parent_y: f32,
     ) {
-        if let Ok(layout) = self.taffy.layout(taffy_id) {
+        if let Some(&layout) = self.taffy.layout(taffy_id) {
             let abs_x = parent_x + layout.location.x;
             let abs_y = parent_y + layout.location.y;
+            // content_size у Taffy — размер переполняющего содержимого, а не content box
+            let content_width = (layout.size.width
+                - layout.border.left
+                - layout.border.right
+                - layout.padding.left
+                - layout.padding.right)
+                .max(0.0);
+            let content_height = (layout.size.height
+                - layout.border.top
+                - layout.border.bottom
+                - layout.padding.top
+                - layout.padding.bottom)
+                .max(0.0);
 
             let layout_result = LayoutResult {
                 node_id: dom_node_id,
@@ -191,11 +402,36 @@ impl LayoutEngine {
                 height: layout.size.height,
                 content_x: abs_x + layout.border.left + layout.padding.left,
                 content_y: abs_y + layout.border.top + layout.padding.top,
-                content_width: layout.content_size.width,
-                content_height: layout.content_size.height,
+                content_width,
+                content_height,
             };
             self.layout_cache.insert(dom_node_id, layout_result);
 
+            // Переносим текст по итоговой ширине — те же строки, что дали высоту листа
+            if let Some(text_ctx) = self.taffy.context(taffy_id) {
+                let text_layout = self
+                    .text_measurer
+                    .layout_text(text_ctx, Some(content_width));
+                self.text_layouts.insert(dom_node_id, text_layout);
+            }
+
+            // Потомки scroll-контейнера сдвигаются на его прокрутку
+            let (scroll_x, scroll_y) = if self.is_scroll_container(document, dom_node_id) {
+                let client = (
+                    (layout.size.width - layout.border.left - layout.border.right).max(0.0),
+                    (layout.size.height - layout.border.top - layout.border.bottom).max(0.0),
+                );
+                // content_size отсчитывается от внешнего края рамки
+                let scroll = (
+                    layout.content_size.width - layout.border.left,
+                    layout.content_size.height - layout.border.top,
+                );
+                self.scroll.set_extents(dom_node_id, client, scroll)
+            } else {
+                (0.0, 0.0)
+            };
+            let (child_x, child_y) = (abs_x - scroll_x, abs_y - scroll_y);
+
             if let Some(dom_node) = document.nodes.get(&dom_node_id) {
                 for &child_dom_id in &dom_node.children {
                     if let Some(&child_taffy_id) = self.node_mapping.get(&child_dom_id) {
@@ -203,8 +439,8 @@ impl LayoutEngine {
                             document,
                             child_dom_id,
                             child_taffy_id,
-                            abs_x,
-                            abs_y,
+                            child_x,
+                            child_y,
                         );
                     }
                 }
@@ -216,8 +452,8 @@ impl LayoutEngine {
                             document,
                             pseudo_dom_id,
                             pseudo_taffy_id,
-                            abs_x,
-                            abs_y,
+                            child_x,
+                            child_y,
                         );
                     }
                 }
@@ -263,7 +499,11 @@ impl LayoutEngine {
             render_list.push(RenderInfo {
                 layout: *layout_result,
                 node: dom_node.clone(),
-                z_index: 0, // TODO: вычислить z-index
+                z_index: self
+                    .resolved_styles
+                    .get(&dom_node_id)
+                    .and_then(|style| style.z_index)
+                    .unwrap_or(0),
             });
 
             // Рекурсивно обрабатываем детей
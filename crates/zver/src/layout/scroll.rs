@@ -0,0 +1,158 @@
+//! Прокрутка: смещения scroll-контейнеров.
+//!
+//! Scroll-контейнер — элемент с `overflow` не `visible` и корень документа,
+//! который прокручивается как viewport. Смещения общие для layout (позиции
+//! потомков сдвигаются на них), ввода (колесо, клавиши) и скриптов
+//! (`scrollTop`, `window.scrollY`); изменения применяются в [`crate::Zver::update`],
+//! который перерисовывает кадр и рассылает события `scroll`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Прокрутка одного контейнера, в CSS-пикселях
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScrollState {
+    pub scroll_x: f32,
+    pub scroll_y: f32,
+    /// Видимая область: padding box без рамок
+    pub client_width: f32,
+    pub client_height: f32,
+    /// Размер содержимого вместе с переполнением, не меньше видимой области
+    pub scroll_width: f32,
+    pub scroll_height: f32,
+}
+
+impl ScrollState {
+    pub fn max_scroll_x(&self) -> f32 {
+        (self.scroll_width - self.client_width).max(0.0)
+    }
+
+    pub fn max_scroll_y(&self) -> f32 {
+        (self.scroll_height - self.client_height).max(0.0)
+    }
+
+    /// Смещение, ограниченное допустимым диапазоном
+    fn clamped(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x.clamp(0.0, self.max_scroll_x()),
+            y.clamp(0.0, self.max_scroll_y()),
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct Scrollers {
+    containers: HashMap<usize, ScrollState>,
+    /// Корень документа — прокрутка viewport
+    viewport: Option<usize>,
+    /// Контейнеры, прокрученные после последнего [`ScrollOffsets::take_changed`]
+    changed: Vec<usize>,
+}
+
+/// Смещения scroll-контейнеров страницы. Клоны разделяют одно состояние.
+#[derive(Debug, Clone, Default)]
+pub struct ScrollOffsets {
+    state: Arc<Mutex<Scrollers>>,
+}
+
+impl ScrollOffsets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, Scrollers> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Узел, прокрутка которого — прокрутка viewport
+    pub fn viewport(&self) -> Option<usize> {
+        self.state().viewport
+    }
+
+    /// Прокрутка контейнера; `None`, если узел не scroll-контейнер
+    pub fn get(&self, node_id: usize) -> Option<ScrollState> {
+        self.state().containers.get(&node_id).copied()
+    }
+
+    /// Текущее смещение; у обычных элементов — ноль
+    pub fn offset(&self, node_id: usize) -> (f32, f32) {
+        self.get(node_id)
+            .map_or((0.0, 0.0), |state| (state.scroll_x, state.scroll_y))
+    }
+
+    /// Прокручивает контейнер к точке, ограничивая её содержимым.
+    ///
+    /// Возвращает `true`, если смещение изменилось; новая позиция видна в
+    /// layout после [`crate::Zver::update`].
+    pub fn scroll_to(&self, node_id: usize, x: f32, y: f32) -> bool {
+        let mut state = self.state();
+        let Some(container) = state.containers.get_mut(&node_id) else {
+            return false;
+        };
+        let (x, y) = container.clamped(x, y);
+        if x == container.scroll_x && y == container.scroll_y {
+            return false;
+        }
+        container.scroll_x = x;
+        container.scroll_y = y;
+        if !state.changed.contains(&node_id) {
+            state.changed.push(node_id);
+        }
+        true
+    }
+
+    /// Сдвигает прокрутку контейнера на `(dx, dy)`
+    pub fn scroll_by(&self, node_id: usize, dx: f32, dy: f32) -> bool {
+        let (x, y) = self.offset(node_id);
+        self.scroll_to(node_id, x + dx, y + dy)
+    }
+
+    /// Есть ли прокрутка, ещё не применённая к layout
+    pub fn has_changes(&self) -> bool {
+        !self.state().changed.is_empty()
+    }
+
+    /// Забирает прокрученные контейнеры в порядке прокрутки
+    pub fn take_changed(&self) -> Vec<usize> {
+        std::mem::take(&mut self.state().changed)
+    }
+
+    /// Забывает прокрутку прежней страницы
+    pub fn reset(&self) {
+        *self.state() = Scrollers::default();
+    }
+
+    /// Обновляет размеры контейнера после layout и возвращает его смещение,
+    /// ограниченное новым содержимым
+    pub(crate) fn set_extents(
+        &self,
+        node_id: usize,
+        client: (f32, f32),
+        scroll: (f32, f32),
+    ) -> (f32, f32) {
+        let mut state = self.state();
+        let container = state.containers.entry(node_id).or_default();
+        container.client_width = client.0;
+        container.client_height = client.1;
+        container.scroll_width = scroll.0.max(client.0);
+        container.scroll_height = scroll.1.max(client.1);
+        (container.scroll_x, container.scroll_y) =
+            container.clamped(container.scroll_x, container.scroll_y);
+        (container.scroll_x, container.scroll_y)
+    }
+
+    /// Оставляет только контейнеры, для которых `keep` вернул `true`
+    pub(crate) fn retain(&self, viewport: Option<usize>, keep: impl Fn(usize) -> bool) {
+        let mut state = self.state();
+        state.viewport = viewport;
+        state.containers.retain(|&node_id, _| keep(node_id));
+        let Scrollers {
+            containers,
+            changed,
+            ..
+        } = &mut *state;
+        changed.retain(|node_id| containers.contains_key(node_id));
+    }
+}
@@ -0,0 +1,563 @@
+//! Стабильный текстовый снимок дерева боксов для snapshot-тестов.
+//!
+//! [`LayoutSnapshot`] обходит DOM в порядке документа и не содержит id узлов,
+//! поэтому снимки разных запусков сравниваются построчно. Формат — строка на
+//! бокс, вложенность отступом в два пробела:
+//!
+//! ```text
+//! html block 0,0 800x600 content 0,0 800x600
+//!   body block 8,8 784x18 content 8,8 784x18
+//!     p#intro.lead block 8,8 784x18 content 8,8 784x18
+//!       #text 8,8 45.5x18
+//!         "Hello" 8,8 45.5x18
+//! ```
+//!
+//! Элемент: `tag#id.class`, значение `display`, border box и content box.
+//! Текстовый узел: его бокс и строки после переноса в кавычках. Координаты
+//! абсолютные, округлены до сотых. Узлы без layout (`display: none`) и
+//! текст из одних пробелов не попадают в снимок.
+
+use std::fmt::{self, Write as _};
+
+use super::engine::LayoutEngine;
+use super::types::{Display, LayoutResult};
+use crate::dom::Document;
+
+/// Прямоугольник в CSS-пикселях
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BoxRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl BoxRect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Отличается ли хоть одна координата больше чем на `tolerance`
+    fn differs(&self, other: &BoxRect, tolerance: f32) -> bool {
+        [
+            (self.x, other.x),
+            (self.y, other.y),
+            (self.width, other.width),
+            (self.height, other.height),
+        ]
+        .into_iter()
+        .any(|(a, b)| (a - b).abs() > tolerance)
+    }
+}
+
+impl fmt::Display for BoxRect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{},{} {}x{}",
+            Number(self.x),
+            Number(self.y),
+            Number(self.width),
+            Number(self.height)
+        )
+    }
+}
+
+/// Строка текста после переноса
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextRun {
+    pub text: String,
+    pub rect: BoxRect,
+}
+
+/// Чем является бокс
+#[derive(Debug, Clone, PartialEq)]
+pub enum SnapshotKind {
+    Element {
+        /// `tag#id.class`
+        label: String,
+        display: Display,
+        content: BoxRect,
+    },
+    Text {
+        runs: Vec<TextRun>,
+    },
+}
+
+/// Бокс снимка: border box и дочерние боксы в порядке документа
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotBox {
+    pub kind: SnapshotKind,
+    pub rect: BoxRect,
+    pub children: Vec<SnapshotBox>,
+}
+
+impl SnapshotBox {
+    /// `tag#id.class` элемента или `#text`
+    pub fn label(&self) -> &str {
+        match &self.kind {
+            SnapshotKind::Element { label, .. } => label,
+            SnapshotKind::Text { .. } => "#text",
+        }
+    }
+}
+
+/// Различие двух снимков
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotDifference {
+    /// Путь к боксу: `html > body > div#main`
+    pub path: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for SnapshotDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: expected `{}`, got `{}`",
+            self.path, self.expected, self.actual
+        )
+    }
+}
+
+/// Ошибка разбора текстового снимка
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("line {line}: {message}")]
+pub struct SnapshotParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Дерево боксов в порядке документа; см. описание модуля
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LayoutSnapshot {
+    pub roots: Vec<SnapshotBox>,
+}
+
+impl LayoutSnapshot {
+    /// Снимает текущий layout документа
+    pub fn capture(dom: &Document, layout: &LayoutEngine) -> Self {
+        Self {
+            roots: dom
+                .root
+                .map(|root| capture_node(dom, layout, root))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Разбирает снимок, записанный через [`fmt::Display`]
+    pub fn parse(source: &str) -> Result<Self, SnapshotParseError> {
+        // Открытые боксы от корня до текущей глубины
+        let mut stack: Vec<SnapshotBox> = Vec::new();
+        let mut roots = Vec::new();
+
+        for (index, line) in source.lines().enumerate() {
+            let error = |message: &str| SnapshotParseError {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let content = line.trim_start_matches(' ');
+            let indent = line.len() - content.len();
+            if indent % 2 != 0 {
+                return Err(error("indentation must be a multiple of two spaces"));
+            }
+            let depth = indent / 2;
+
+            if content.starts_with('"') {
+                // Строка текста принадлежит текстовому узлу уровнем выше
+                let (text, rest) =
+                    parse_quoted(content).ok_or_else(|| error("unterminated string"))?;
+                let rect = parse_rect(rest.trim()).ok_or_else(|| error("expected x,y WxH"))?;
+                let owner = (depth == stack.len())
+                    .then(|| stack.last_mut())
+                    .flatten()
+                    .ok_or_else(|| error("text run outside of #text"))?;
+                let SnapshotKind::Text { runs } = &mut owner.kind else {
+                    return Err(error("text run outside of #text"));
+                };
+                runs.push(TextRun { text, rect });
+                continue;
+            }
+
+            if depth > stack.len() {
+                return Err(error("unexpected indentation"));
+            }
+            close_boxes(&mut stack, &mut roots, depth);
+            stack.push(parse_box(content).ok_or_else(|| {
+                error("expected `LABEL DISPLAY x,y WxH content x,y WxH` or `#text x,y WxH`")
+            })?);
+        }
+        close_boxes(&mut stack, &mut roots, 0);
+        Ok(Self { roots })
+    }
+
+    /// Различия с ожидаемым снимком; координаты сравниваются с допуском
+    /// `tolerance`, остальное — точно
+    pub fn diff(&self, expected: &LayoutSnapshot, tolerance: f32) -> Vec<SnapshotDifference> {
+        let mut differences = Vec::new();
+        diff_boxes(
+            &self.roots,
+            &expected.roots,
+            "",
+            tolerance,
+            &mut differences,
+        );
+        differences
+    }
+
+    /// Совпадает ли с ожидаемым снимком в пределах `tolerance`
+    pub fn matches(&self, expected: &LayoutSnapshot, tolerance: f32) -> bool {
+        self.diff(expected, tolerance).is_empty()
+    }
+}
+
+impl fmt::Display for LayoutSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_box(f: &mut fmt::Formatter<'_>, node: &SnapshotBox, depth: usize) -> fmt::Result {
+            let indent = "  ".repeat(depth);
+            writeln!(f, "{indent}{}", box_line(node))?;
+            if let SnapshotKind::Text { runs } = &node.kind {
+                for run in runs {
+                    writeln!(f, "{indent}  {}", run_line(run))?;
+                }
+            }
+            node.children
+                .iter()
+                .try_for_each(|child| write_box(f, child, depth + 1))
+        }
+
+        self.roots.iter().try_for_each(|root| write_box(f, root, 0))
+    }
+}
+
+/// Боксы узла: его собственный или, если у узла нет layout, боксы потомков
+fn capture_node(dom: &Document, layout: &LayoutEngine, node_id: usize) -> Vec<SnapshotBox> {
+    let Some(node) = dom.nodes.get(&node_id) else {
+        return Vec::new();
+    };
+    let children: Vec<SnapshotBox> = node
+        .children
+        .iter()
+        .flat_map(|&child| capture_node(dom, layout, child))
+        .collect();
+    let Some(result) = layout.get_layout_result(node_id) else {
+        return children;
+    };
+    let rect = BoxRect::new(result.x, result.y, result.width, result.height);
+
+    let kind = if let Some(tag) = node.tag_name() {
+        let display = layout
+            .resolved_styles()
+            .get(&node_id)
+            .map_or(Display::Block, |style| style.display);
+        SnapshotKind::Element {
+            label: element_label(dom, node_id, tag),
+            display,
+            content: content_rect(&result),
+        }
+    } else {
+        let runs: Vec<TextRun> = layout
+            .text_layout(node_id)
+            .map(|text| {
+                text.lines
+                    .iter()
+                    .map(|line| TextRun {
+                        text: line.text.clone(),
+                        rect: BoxRect::new(
+                            result.content_x + line.x,
+                            result.content_y + line.y,
+                            line.width,
+                            line.height,
+                        ),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if runs.iter().all(|run| run.text.trim().is_empty()) {
+            return Vec::new();
+        }
+        SnapshotKind::Text { runs }
+    };
+    vec![SnapshotBox {
+        kind,
+        rect,
+        children,
+    }]
+}
+
+fn content_rect(result: &LayoutResult) -> BoxRect {
+    BoxRect::new(
+        result.content_x,
+        result.content_y,
+        result.content_width,
+        result.content_height,
+    )
+}
+
+fn element_label(dom: &Document, node_id: usize, tag: &str) -> String {
+    let mut label = tag.to_string();
+    if let Some(node) = dom.nodes.get(&node_id) {
+        if let Some(id) = node.attributes.get("id") {
+            let _ = write!(label, "#{id}");
+        }
+        if let Some(class) = node.attributes.get("class") {
+            for class in class.split_whitespace() {
+                let _ = write!(label, ".{class}");
+            }
+        }
+    }
+    label
+}
+
+fn display_name(display: Display) -> &'static str {
+    match display {
+        Display::Block => "block",
+        Display::Inline => "inline",
+        Display::None => "none",
+        Display::Flex => "flex",
+        Display::Grid => "grid",
+    }
+}
+
+fn parse_display(name: &str) -> Option<Display> {
+    Some(match name {
+        "block" => Display::Block,
+        "inline" => Display::Inline,
+        "none" => Display::None,
+        "flex" => Display::Flex,
+        "grid" => Display::Grid,
+        _ => return None,
+    })
+}
+
+fn box_line(node: &SnapshotBox) -> String {
+    match &node.kind {
+        SnapshotKind::Element {
+            label,
+            display,
+            content,
+        } => format!(
+            "{label} {} {} content {content}",
+            display_name(*display),
+            node.rect
+        ),
+        SnapshotKind::Text { .. } => format!("#text {}", node.rect),
+    }
+}
+
+fn run_line(run: &TextRun) -> String {
+    format!("{} {}", quote(&run.text), run.rect)
+}
+
+/// Число, округлённое до сотых, без лишних нулей
+struct Number(f32);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rounded = (self.0 * 100.0).round() / 100.0;
+        // -0 и 0 пишутся одинаково
+        write!(f, "{}", if rounded == 0.0 { 0.0 } else { rounded })
+    }
+}
+
+fn parse_box(content: &str) -> Option<SnapshotBox> {
+    let tokens: Vec<&str> = content.split(' ').collect();
+    match tokens.as_slice() {
+        ["#text", position, size] => Some(SnapshotBox {
+            kind: SnapshotKind::Text { runs: Vec::new() },
+            rect: parse_rect(&format!("{position} {size}"))?,
+            children: Vec::new(),
+        }),
+        [
+            label,
+            display,
+            position,
+            size,
+            "content",
+            content_position,
+            content_size,
+        ] => Some(SnapshotBox {
+            kind: SnapshotKind::Element {
+                label: label.to_string(),
+                display: parse_display(display)?,
+                content: parse_rect(&format!("{content_position} {content_size}"))?,
+            },
+            rect: parse_rect(&format!("{position} {size}"))?,
+            children: Vec::new(),
+        }),
+        _ => None,
+    }
+}
+
+/// `x,y WxH`
+fn parse_rect(text: &str) -> Option<BoxRect> {
+    let (position, size) = text.split_once(' ')?;
+    let (x, y) = position.split_once(',')?;
+    let (width, height) = size.split_once('x')?;
+    Some(BoxRect::new(
+        x.parse().ok()?,
+        y.parse().ok()?,
+        width.parse().ok()?,
+        height.parse().ok()?,
+    ))
+}
+
+/// Закрывает боксы глубже `depth`, присоединяя их к родителям
+fn close_boxes(stack: &mut Vec<SnapshotBox>, roots: &mut Vec<SnapshotBox>, depth: usize) {
+    while stack.len() > depth {
+        let node = stack.pop().expect("stack is longer than depth");
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    }
+}
+
+/// Строка в кавычках с экранированием `\"`, `\\`, `\n`, `\r`, `\t` и `\u{..}`
+fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{{{:x}}}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Обратное к [`quote`]: текст и остаток строки после закрывающей кавычки
+fn parse_quoted(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut unquoted = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((unquoted, &text[index + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => unquoted.push('\n'),
+                'r' => unquoted.push('\r'),
+                't' => unquoted.push('\t'),
+                'u' => {
+                    let rest = &text[index + 3..];
+                    let hex = rest.strip_prefix('{')?.split('}').next()?;
+                    unquoted.push(char::from_u32(u32::from_str_radix(hex, 16).ok()?)?);
+                    // `{`, цифры и `}`
+                    for _ in 0..hex.len() + 2 {
+                        chars.next();
+                    }
+                }
+                escaped => unquoted.push(escaped),
+            },
+            c => unquoted.push(c),
+        }
+    }
+    None
+}
+
+fn diff_boxes(
+    actual: &[SnapshotBox],
+    expected: &[SnapshotBox],
+    parent: &str,
+    tolerance: f32,
+    differences: &mut Vec<SnapshotDifference>,
+) {
+    let path = |node: &SnapshotBox| {
+        if parent.is_empty() {
+            node.label().to_string()
+        } else {
+            format!("{parent} > {}", node.label())
+        }
+    };
+
+    for (actual, expected) in actual.iter().zip(expected) {
+        let node_path = path(expected);
+        if actual.label() != expected.label() {
+            differences.push(SnapshotDifference {
+                path: node_path,
+                expected: box_line(expected),
+                actual: box_line(actual),
+            });
+            // Разные элементы: их потомков сравнивать бессмысленно
+            continue;
+        }
+
+        let same = match (&actual.kind, &expected.kind) {
+            (
+                SnapshotKind::Element {
+                    display, content, ..
+                },
+                SnapshotKind::Element {
+                    display: expected_display,
+                    content: expected_content,
+                    ..
+                },
+            ) => display == expected_display && !content.differs(expected_content, tolerance),
+            (
+                SnapshotKind::Text { runs },
+                SnapshotKind::Text {
+                    runs: expected_runs,
+                },
+            ) => {
+                runs.len() == expected_runs.len()
+                    && runs.iter().zip(expected_runs).all(|(run, expected)| {
+                        run.text == expected.text && !run.rect.differs(&expected.rect, tolerance)
+                    })
+            }
+            _ => false,
+        };
+        if !same || actual.rect.differs(&expected.rect, tolerance) {
+            let describe = |node: &SnapshotBox| match &node.kind {
+                SnapshotKind::Text { runs } => std::iter::once(box_line(node))
+                    .chain(runs.iter().map(run_line))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                SnapshotKind::Element { .. } => box_line(node),
+            };
+            differences.push(SnapshotDifference {
+                path: node_path.clone(),
+                expected: describe(expected),
+                actual: describe(actual),
+            });
+        }
+        diff_boxes(
+            &actual.children,
+            &expected.children,
+            &node_path,
+            tolerance,
+            differences,
+        );
+    }
+
+    let missing = expected.iter().skip(actual.len());
+    let extra = actual.iter().skip(expected.len());
+    for node in missing {
+        differences.push(SnapshotDifference {
+            path: path(node),
+            expected: box_line(node),
+            actual: "nothing".to_string(),
+        });
+    }
+    for node in extra {
+        differences.push(SnapshotDifference {
+            path: path(node),
+            expected: "nothing".to_string(),
+            actual: box_line(node),
+        });
+    }
+}
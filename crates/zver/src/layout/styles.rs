@@ -1,4 +1,5 @@
 use super::types::*;
+use crate::css::calc::CalcExpr;
 use std::collections::HashMap;
 
 /// Применяет стили по умолчанию для HTML тегов
@@ -7,32 +8,32 @@ pub fn apply_default_tag_styles(style: &mut ComputedStyle, tag_name: &Option<Str
         match tag.as_str() {
             // Заголовки
             "h1" => {
-                style.font_size = 32.0;
+                default_font_size(style, 32.0);
                 style.font_weight = FontWeight::Bold;
                 style.display = Display::Block;
             }
             "h2" => {
-                style.font_size = 24.0;
+                default_font_size(style, 24.0);
                 style.font_weight = FontWeight::Bold;
                 style.display = Display::Block;
             }
             "h3" => {
-                style.font_size = 19.0;
+                default_font_size(style, 19.0);
                 style.font_weight = FontWeight::Bold;
                 style.display = Display::Block;
             }
             "h4" => {
-                style.font_size = 16.0;
+                default_font_size(style, 16.0);
                 style.font_weight = FontWeight::Bold;
                 style.display = Display::Block;
             }
             "h5" => {
-                style.font_size = 13.0;
+                default_font_size(style, 13.0);
                 style.font_weight = FontWeight::Bold;
                 style.display = Display::Block;
             }
             "h6" => {
-                style.font_size = 11.0;
+                default_font_size(style, 11.0);
                 style.font_weight = FontWeight::Bold;
                 style.display = Display::Block;
             }
@@ -78,6 +79,14 @@ pub fn apply_default_tag_styles(style: &mut ComputedStyle, tag_name: &Option<Str
     }
 }
 
+/// Размер шрифта тега, если автор не задал свой
+fn default_font_size(style: &mut ComputedStyle, size: f32) {
+    if style.font_size_value.is_none() {
+        style.font_size = size;
+        style.font_size_value = Some(CalcExpr::Length(size, "px".to_string()));
+    }
+}
+
 /// Применяет CSS стили к ComputedStyle
 pub fn apply_css_styles(style: &mut ComputedStyle, css_styles: &HashMap<String, String>) {
     for (property, value) in css_styles {
@@ -1 +1,432 @@
+use crate::css::calc::{CalcContext, CalcExpr};
+use crate::layout::metrics::TextMeasureContext;
+use crate::layout::text_measure::{TextMeasurer, text_measure_function};
+use crate::layout::types::CalcProperty;
+use taffy::prelude::*;
+use taffy::tree::{LayoutInput, LayoutOutput};
+use taffy::{
+    Cache, CacheTree, RunMode, compute_block_layout, compute_cached_layout, compute_flexbox_layout,
+    compute_grid_layout, compute_hidden_layout, compute_leaf_layout, compute_root_layout,
+    round_layout,
+};
 
+/// Узел дерева layout
+struct Node {
+    style: Style,
+    /// Текст листа, который измеряется при layout
+    context: Option<TextMeasureContext>,
+    children: Vec<NodeId>,
+    parent: Option<NodeId>,
+    /// Слоты [`LayoutTree::calc`], на которые ссылается стиль узла
+    calc_slots: Vec<usize>,
+    cache: Cache,
+    unrounded_layout: Layout,
+    final_layout: Layout,
+}
+
+/// Дерево Taffy с вычислением `calc()`.
+///
+/// Высокоуровневый `TaffyTree` не вычисляет `calc()`, поэтому дерево
+/// реализует трейты Taffy само: стиль хранит ссылку на выражение, а Taffy
+/// вызывает [`taffy::LayoutPartialTree::resolve_calc_value`] с базой процентов
+/// от containing block узла.
+#[derive(Default)]
+pub struct LayoutTree {
+    nodes: Vec<Option<Node>>,
+    free_nodes: Vec<usize>,
+    /// Выражения `calc()` стилей; индекс слота зашит в указатель значения Taffy
+    calc: Vec<Option<(CalcProperty, CalcExpr)>>,
+    free_calc: Vec<usize>,
+}
+
+impl LayoutTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Создаёт узел со стилем `style`, в который подставляются выражения `calc`
+    pub fn new_node(
+        &mut self,
+        mut style: Style,
+        calc: &[(CalcProperty, CalcExpr)],
+        context: Option<TextMeasureContext>,
+        children: &[NodeId],
+    ) -> NodeId {
+        let calc_slots: Vec<usize> = calc
+            .iter()
+            .map(|(property, expr)| {
+                let slot = self.store_calc(*property, expr.clone());
+                property.apply(&mut style, calc_handle(slot));
+                slot
+            })
+            .collect();
+
+        let node = Node {
+            style,
+            context,
+            children: children.to_vec(),
+            parent: None,
+            calc_slots,
+            cache: Cache::new(),
+            unrounded_layout: Layout::with_order(0),
+            final_layout: Layout::with_order(0),
+        };
+        let id = match self.free_nodes.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                NodeId::from(index)
+            }
+            None => {
+                self.nodes.push(Some(node));
+                NodeId::from(self.nodes.len() - 1)
+            }
+        };
+        for &child in children {
+            if let Some(child) = self.node_mut(child) {
+                child.parent = Some(id);
+            }
+        }
+        id
+    }
+
+    /// Удаляет узел: отцепляет его от родителя, дети остаются без родителя
+    pub fn remove(&mut self, id: NodeId) {
+        let Some(node) = self.nodes.get_mut(usize::from(id)).and_then(Option::take) else {
+            return;
+        };
+        self.free_nodes.push(usize::from(id));
+        for slot in node.calc_slots {
+            self.calc[slot] = None;
+            self.free_calc.push(slot);
+        }
+        if let Some(parent) = node.parent
+            && let Some(parent_node) = self.node_mut(parent)
+        {
+            parent_node.children.retain(|&child| child != id);
+            self.mark_dirty(parent);
+        }
+        for child in node.children {
+            if let Some(child) = self.node_mut(child) {
+                child.parent = None;
+            }
+        }
+    }
+
+    /// Удаляет все узлы
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free_nodes.clear();
+        self.calc.clear();
+        self.free_calc.clear();
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id)?.parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        self.node(id).map_or(&[], |node| node.children.as_slice())
+    }
+
+    /// Округлённый результат последнего layout
+    pub fn layout(&self, id: NodeId) -> Option<&Layout> {
+        self.node(id).map(|node| &node.final_layout)
+    }
+
+    /// Текст, который измеряется в листе
+    pub fn context(&self, id: NodeId) -> Option<&TextMeasureContext> {
+        self.node(id)?.context.as_ref()
+    }
+
+    /// Ставит `child` на место ребёнка `index`; прежний ребёнок остаётся без родителя
+    pub fn replace_child_at_index(&mut self, parent: NodeId, index: usize, child: NodeId) {
+        let Some(old_child) = self
+            .node_mut(parent)
+            .and_then(|node| node.children.get_mut(index))
+            .map(|slot| std::mem::replace(slot, child))
+        else {
+            return;
+        };
+        if let Some(old_child) = self.node_mut(old_child) {
+            old_child.parent = None;
+        }
+        if let Some(child) = self.node_mut(child) {
+            child.parent = Some(parent);
+        }
+        self.mark_dirty(parent);
+    }
+
+    /// Вычисляет и округляет layout дерева `root`, измеряя текст `measurer`
+    pub fn compute_layout(
+        &mut self,
+        root: NodeId,
+        available_space: Size<AvailableSpace>,
+        measurer: &TextMeasurer,
+    ) {
+        let mut view = LayoutView {
+            tree: self,
+            measurer,
+        };
+        compute_root_layout(&mut view, root, available_space);
+        round_layout(&mut view, root);
+    }
+
+    /// Сбрасывает кеш узла и его предков
+    fn mark_dirty(&mut self, id: NodeId) {
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let Some(node) = self.node_mut(id) else {
+                break;
+            };
+            node.cache.clear();
+            current = node.parent;
+        }
+    }
+
+    fn store_calc(&mut self, property: CalcProperty, expr: CalcExpr) -> usize {
+        match self.free_calc.pop() {
+            Some(slot) => {
+                self.calc[slot] = Some((property, expr));
+                slot
+            }
+            None => {
+                self.calc.push(Some((property, expr)));
+                self.calc.len() - 1
+            }
+        }
+    }
+
+    /// Значение выражения по указателю из стиля Taffy; `basis` — база процентов
+    fn resolve_calc(&self, handle: *const (), basis: f32) -> f32 {
+        let Some(Some((property, expr))) = self.calc.get(calc_slot(handle)) else {
+            return 0.0;
+        };
+        let context = CalcContext {
+            percent_basis: Some(basis),
+            ..CalcContext::default()
+        };
+        let value = expr.eval(&context).unwrap_or(0.0);
+        if property.allows_negative() {
+            value
+        } else {
+            value.max(0.0)
+        }
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node> {
+        self.nodes.get(usize::from(id))?.as_ref()
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node> {
+        self.nodes.get_mut(usize::from(id))?.as_mut()
+    }
+
+    fn node_ref(&self, id: NodeId) -> &Node {
+        self.node(id).expect("узел layout удалён")
+    }
+
+    fn node_ref_mut(&mut self, id: NodeId) -> &mut Node {
+        self.node_mut(id).expect("узел layout удалён")
+    }
+}
+
+/// Указатель, которым Taffy ссылается на выражение: младшие 3 бита Taffy
+/// занимает под тег, а нулевой указатель недопустим
+fn calc_handle(slot: usize) -> *const () {
+    std::ptr::without_provenance((slot + 1) << 3)
+}
+
+fn calc_slot(handle: *const ()) -> usize {
+    (handle.addr() >> 3).wrapping_sub(1)
+}
+
+/// Дерево на время одного layout вместе с измерителем текста
+struct LayoutView<'a> {
+    tree: &'a mut LayoutTree,
+    measurer: &'a TextMeasurer,
+}
+
+impl taffy::TraversePartialTree for LayoutView<'_> {
+    type ChildIter<'a>
+        = std::iter::Copied<std::slice::Iter<'a, NodeId>>
+    where
+        Self: 'a;
+
+    fn child_ids(&self, parent: NodeId) -> Self::ChildIter<'_> {
+        self.tree.node_ref(parent).children.iter().copied()
+    }
+
+    fn child_count(&self, parent: NodeId) -> usize {
+        self.tree.node_ref(parent).children.len()
+    }
+
+    fn get_child_id(&self, parent: NodeId, index: usize) -> NodeId {
+        self.tree.node_ref(parent).children[index]
+    }
+}
+
+impl taffy::TraverseTree for LayoutView<'_> {}
+
+impl taffy::LayoutPartialTree for LayoutView<'_> {
+    type CoreContainerStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    type CustomIdent = String;
+
+    fn get_core_container_style(&self, node: NodeId) -> Self::CoreContainerStyle<'_> {
+        &self.tree.node_ref(node).style
+    }
+
+    fn set_unrounded_layout(&mut self, node: NodeId, layout: &Layout) {
+        self.tree.node_ref_mut(node).unrounded_layout = *layout;
+    }
+
+    fn resolve_calc_value(&self, val: *const (), basis: f32) -> f32 {
+        self.tree.resolve_calc(val, basis)
+    }
+
+    fn compute_child_layout(&mut self, node: NodeId, inputs: LayoutInput) -> LayoutOutput {
+        // Предок с `display: none` скрывает всё поддерево
+        if inputs.run_mode == RunMode::PerformHiddenLayout {
+            return compute_hidden_layout(self, node);
+        }
+
+        compute_cached_layout(self, node, inputs, |view, node, inputs| {
+            let display = view.tree.node_ref(node).style.display;
+            let has_children = view.child_count(node) > 0;
+            match (display, has_children) {
+                (Display::None, _) => compute_hidden_layout(view, node),
+                (Display::Block, true) => compute_block_layout(view, node, inputs),
+                (Display::Flex, true) => compute_flexbox_layout(view, node, inputs),
+                (Display::Grid, true) => compute_grid_layout(view, node, inputs),
+                (_, false) => {
+                    let tree = &*view.tree;
+                    let measurer = view.measurer;
+                    let leaf = tree.node_ref(node);
+                    compute_leaf_layout(
+                        inputs,
+                        &leaf.style,
+                        |handle, basis| tree.resolve_calc(handle, basis),
+                        |known_dimensions, available_space| match &leaf.context {
+                            Some(text) => text_measure_function(
+                                known_dimensions,
+                                available_space,
+                                Some(text),
+                                measurer,
+                            ),
+                            // Пустой элемент: только заданные размеры
+                            None => known_dimensions.unwrap_or(Size::ZERO),
+                        },
+                    )
+                }
+            }
+        })
+    }
+}
+
+impl CacheTree for LayoutView<'_> {
+    fn cache_get(
+        &self,
+        node: NodeId,
+        known_dimensions: Size<Option<f32>>,
+        available_space: Size<AvailableSpace>,
+        run_mode: RunMode,
+    ) -> Option<LayoutOutput> {
+        self.tree
+            .node_ref(node)
+            .cache
+            .get(known_dimensions, available_space, run_mode)
+    }
+
+    fn cache_store(
+        &mut self,
+        node: NodeId,
+        known_dimensions: Size<Option<f32>>,
+        available_space: Size<AvailableSpace>,
+        run_mode: RunMode,
+        layout_output: LayoutOutput,
+    ) {
+        self.tree.node_ref_mut(node).cache.store(
+            known_dimensions,
+            available_space,
+            run_mode,
+            layout_output,
+        );
+    }
+
+    fn cache_clear(&mut self, node: NodeId) {
+        self.tree.node_ref_mut(node).cache.clear();
+    }
+}
+
+impl taffy::LayoutBlockContainer for LayoutView<'_> {
+    type BlockContainerStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    type BlockItemStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    fn get_block_container_style(&self, node: NodeId) -> Self::BlockContainerStyle<'_> {
+        &self.tree.node_ref(node).style
+    }
+
+    fn get_block_child_style(&self, child: NodeId) -> Self::BlockItemStyle<'_> {
+        &self.tree.node_ref(child).style
+    }
+}
+
+impl taffy::LayoutFlexboxContainer for LayoutView<'_> {
+    type FlexboxContainerStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    type FlexboxItemStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    fn get_flexbox_container_style(&self, node: NodeId) -> Self::FlexboxContainerStyle<'_> {
+        &self.tree.node_ref(node).style
+    }
+
+    fn get_flexbox_child_style(&self, child: NodeId) -> Self::FlexboxItemStyle<'_> {
+        &self.tree.node_ref(child).style
+    }
+}
+
+impl taffy::LayoutGridContainer for LayoutView<'_> {
+    type GridContainerStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    type GridItemStyle<'a>
+        = &'a Style
+    where
+        Self: 'a;
+
+    fn get_grid_container_style(&self, node: NodeId) -> Self::GridContainerStyle<'_> {
+        &self.tree.node_ref(node).style
+    }
+
+    fn get_grid_child_style(&self, child: NodeId) -> Self::GridItemStyle<'_> {
+        &self.tree.node_ref(child).style
+    }
+}
+
+impl taffy::RoundTree for LayoutView<'_> {
+    fn get_unrounded_layout(&self, node: NodeId) -> Layout {
+        self.tree.node_ref(node).unrounded_layout
+    }
+
+    fn set_final_layout(&mut self, node: NodeId, layout: &Layout) {
+        self.tree.node_ref_mut(node).final_layout = *layout;
+    }
+}
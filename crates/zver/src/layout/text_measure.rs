@@ -0,0 +1,394 @@
+//! Измерение текста по реальным метрикам глифов.
+//!
+//! Ширина строк считается по advance и кернингу fontdue-шрифтов, подобранных среди
+//! загруженных @font-face (или встроенного DejaVu Sans). Каждый символ измеряется
+//! первым шрифтом цепочки `font-family`, в котором есть его глиф, а перенос выполняется
+//! по возможностям разрыва UAX #14. Итоговые line box'ы вместе с отрезками по шрифтам
+//! сохраняются в [`TextLayout`], чтобы paint рисовал ровно то, что измерил layout.
+
+use crate::css::fonts::{self, FallbackFont, LoadedFont};
+use crate::layout::metrics::{FontMetrics, TextMeasureContext};
+use crate::layout::types::{FontStyle, FontWeight};
+use std::sync::Arc;
+use unicode_width::UnicodeWidthChar;
+use xi_unicode::LineBreakIterator;
+
+/// Допуск при сравнении ширины строки с доступным местом (ошибки округления f32)
+const FIT_EPSILON: f32 = 0.01;
+
+/// Одна строка текста после переноса. Координаты относительно content box узла.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    pub text: String,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Базовая линия относительно верха content box
+    pub baseline: f32,
+    /// Отрезки строки по шрифтам, которые покрывают их символы
+    pub runs: Vec<FontRun>,
+}
+
+/// Отрезок строки, измеренный и рисуемый одним шрифтом цепочки fallback
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontRun {
+    pub text: String,
+    /// Смещение от начала строки
+    pub x: f32,
+    pub width: f32,
+    /// Семейство шрифта отрезка; `None` — встроенный шрифт
+    pub family: Option<String>,
+}
+
+/// Результат переноса текстового узла на строки
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<LineBox>,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Вертикальные метрики строки для шрифта и размера
+#[derive(Debug, Clone, Copy)]
+struct LineMetrics {
+    line_height: f32,
+    baseline: f32,
+}
+
+/// Сегмент между соседними возможностями разрыва строки
+struct Segment<'t> {
+    text: &'t str,
+    /// Ширина без завершающих пробелов (они «висят» за краем строки)
+    width: f32,
+    /// Ширина вместе с завершающими пробелами
+    advance: f32,
+}
+
+/// Цепочка шрифтов контекста: семейства из `font-family`, затем встроенный
+#[derive(Debug, Clone, Default)]
+pub struct FontChain {
+    fonts: Vec<FallbackFont>,
+}
+
+impl FontChain {
+    /// Основной шрифт — первый доступный в цепочке
+    pub fn primary(&self) -> Option<&fontdue::Font> {
+        self.fonts.first().map(|fallback| fallback.font.as_ref())
+    }
+
+    /// Первый шрифт цепочки с глифом для `ch`; `None`, если глифа нет ни в одном
+    fn covering(&self, ch: char) -> Option<usize> {
+        self.fonts
+            .iter()
+            .position(|fallback| fallback.font.lookup_glyph_index(ch) != 0)
+    }
+
+    /// Делит текст на отрезки по покрывающему шрифту. Пробел остаётся в текущем
+    /// отрезке, если его шрифт пробел покрывает, чтобы не дробить текст по словам.
+    fn split<'t>(&self, text: &'t str) -> Vec<(&'t str, Option<usize>)> {
+        let mut pieces = Vec::new();
+        let mut start = 0;
+        let mut current: Option<Option<usize>> = None;
+        for (index, ch) in text.char_indices() {
+            let face = match current {
+                Some(Some(face))
+                    if ch.is_whitespace() && self.fonts[face].font.lookup_glyph_index(ch) != 0 =>
+                {
+                    Some(face)
+                }
+                _ => self.covering(ch),
+            };
+            if let Some(previous) = current
+                && previous != face
+            {
+                pieces.push((&text[start..index], previous));
+                start = index;
+            }
+            current = Some(face);
+        }
+        if let Some(face) = current {
+            pieces.push((&text[start..], face));
+        }
+        pieces
+    }
+
+    /// Семейство шрифта отрезка; символы без глифа рисуются основным шрифтом
+    fn family(&self, face: Option<usize>) -> Option<String> {
+        face.or((!self.fonts.is_empty()).then_some(0))
+            .and_then(|face| self.fonts[face].family.clone())
+    }
+}
+
+/// Измеритель текста на основе загруженных шрифтов
+#[derive(Debug, Clone, Default)]
+pub struct TextMeasurer {
+    fonts: Vec<LoadedFont>,
+    fallback: FontMetrics,
+}
+
+impl TextMeasurer {
+    pub fn new(fonts: Vec<LoadedFont>) -> Self {
+        Self {
+            fonts,
+            fallback: FontMetrics::default(),
+        }
+    }
+
+    pub fn fonts(&self) -> &[LoadedFont] {
+        &self.fonts
+    }
+
+    /// Подбирает основной шрифт для контекста; `None`, если недоступен даже встроенный
+    pub fn font_for(&self, ctx: &TextMeasureContext) -> Option<Arc<fontdue::Font>> {
+        self.font_chain(ctx)
+            .fonts
+            .into_iter()
+            .next()
+            .map(|fallback| fallback.font)
+    }
+
+    /// Цепочка шрифтов для посимвольного fallback
+    pub fn font_chain(&self, ctx: &TextMeasureContext) -> FontChain {
+        let fonts = fonts::fallback_fonts(
+            &self.fonts,
+            ctx.font_family.as_deref(),
+            match ctx.font_weight {
+                FontWeight::Normal => fonts::FontWeight::Normal,
+                FontWeight::Bold => fonts::FontWeight::Bold,
+            },
+            match ctx.font_style {
+                FontStyle::Normal => fonts::FontStyle::Normal,
+                FontStyle::Italic => fonts::FontStyle::Italic,
+            },
+        );
+        FontChain { fonts }
+    }
+
+    /// Ширина строки с учётом кернинга внутри отрезков одного шрифта
+    pub fn text_width(&self, chain: &FontChain, text: &str, size: f32) -> f32 {
+        chain
+            .split(text)
+            .into_iter()
+            .map(|(piece, face)| {
+                let font = face.map(|face| chain.fonts[face].font.as_ref());
+                self.run_width(font, piece, size)
+            })
+            .sum()
+    }
+
+    /// Отрезки строки по шрифтам с их смещениями и ширинами
+    pub fn font_runs(&self, chain: &FontChain, text: &str, size: f32) -> Vec<FontRun> {
+        let mut x = 0.0;
+        chain
+            .split(text)
+            .into_iter()
+            .map(|(piece, face)| {
+                let font = face.map(|face| chain.fonts[face].font.as_ref());
+                let width = self.run_width(font, piece, size);
+                let run = FontRun {
+                    text: piece.to_string(),
+                    x,
+                    width,
+                    family: chain.family(face),
+                };
+                x += width;
+                run
+            })
+            .collect()
+    }
+
+    /// Ширина отрезка одним шрифтом; `None` — глифа нет ни в одном шрифте цепочки
+    fn run_width(&self, font: Option<&fontdue::Font>, text: &str, size: f32) -> f32 {
+        match font {
+            Some(font) => {
+                let mut width = 0.0;
+                let mut previous = None;
+                for ch in text.chars() {
+                    if let Some(prev) = previous {
+                        width += font.horizontal_kern(prev, ch, size).unwrap_or(0.0);
+                    }
+                    width += font.metrics(ch, size).advance_width;
+                    previous = Some(ch);
+                }
+                width
+            }
+            // Без глифа — эвристика, но широкие символы (CJK) занимают две ячейки
+            None => text
+                .chars()
+                .map(|ch| ch.width().unwrap_or(0) as f32 * size * self.fallback.char_width)
+                .sum(),
+        }
+    }
+
+    /// Единицы `ch` и `ex` шрифта контекста: advance глифа `0` и высота `x`.
+    ///
+    /// Если глифа нет или он пустой, берётся `0.5em`, как разрешает CSS Values.
+    pub fn font_units(&self, ctx: &TextMeasureContext) -> (f32, f32) {
+        let fallback = ctx.font_size * 0.5;
+        let Some(font) = self.font_for(ctx) else {
+            return (fallback, fallback);
+        };
+        let glyph =
+            |ch: char| (font.lookup_glyph_index(ch) != 0).then(|| font.metrics(ch, ctx.font_size));
+        let ch = glyph('0')
+            .map(|metrics| metrics.advance_width)
+            .filter(|width| *width > 0.0)
+            .unwrap_or(fallback);
+        let ex = glyph('x')
+            .map(|metrics| metrics.bounds.ymin + metrics.bounds.height)
+            .filter(|height| *height > 0.0)
+            .unwrap_or(fallback);
+        (ch, ex)
+    }
+
+    /// Переносит текст по UAX #14 в пределах `max_width` (`None` — без ограничения)
+    pub fn layout_text(&self, ctx: &TextMeasureContext, max_width: Option<f32>) -> TextLayout {
+        let text = collapse_whitespace(&ctx.content);
+        if text.is_empty() {
+            return TextLayout::default();
+        }
+
+        let chain = self.font_chain(ctx);
+        let metrics = self.line_metrics(chain.primary(), ctx.font_size);
+        let segments = self.segments(&chain, &text, ctx.font_size);
+        let limit = max_width.unwrap_or(f32::INFINITY) + FIT_EPSILON;
+
+        let mut layout = TextLayout::default();
+        let mut start = 0;
+        let mut end = 0;
+        let mut line_width = 0.0;
+        let mut line_advance = 0.0;
+
+        for segment in &segments {
+            // Сегмент, не влезающий целиком, переносится; слишком длинное слово переполняет строку
+            if end > start && line_advance + segment.width > limit {
+                let line = text[start..end].trim_end();
+                let runs = self.font_runs(&chain, line, ctx.font_size);
+                layout.push_line(line, line_width, runs, metrics);
+                start = end;
+                line_advance = 0.0;
+            }
+            line_width = line_advance + segment.width;
+            line_advance += segment.advance;
+            end += segment.text.len();
+        }
+        let line = text[start..end].trim_end();
+        let runs = self.font_runs(&chain, line, ctx.font_size);
+        layout.push_line(line, line_width, runs, metrics);
+
+        layout
+    }
+
+    /// Размер текстового листа для Taffy (min-/max-content и перенос при известной ширине)
+    pub fn measure(
+        &self,
+        known_dimensions: taffy::Size<Option<f32>>,
+        available_space: taffy::Size<taffy::AvailableSpace>,
+        ctx: &TextMeasureContext,
+    ) -> taffy::Size<f32> {
+        let width = known_dimensions
+            .width
+            .unwrap_or_else(|| match available_space.width {
+                taffy::AvailableSpace::MinContent => self.min_content_width(ctx),
+                taffy::AvailableSpace::MaxContent => self.layout_text(ctx, None).width,
+                taffy::AvailableSpace::Definite(available) => {
+                    self.layout_text(ctx, Some(available)).width
+                }
+            });
+
+        let height = known_dimensions
+            .height
+            .unwrap_or_else(|| self.layout_text(ctx, Some(width)).height);
+
+        taffy::Size { width, height }
+    }
+
+    /// Ширина самого длинного неразрывного фрагмента
+    fn min_content_width(&self, ctx: &TextMeasureContext) -> f32 {
+        let text = collapse_whitespace(&ctx.content);
+        let chain = self.font_chain(ctx);
+        self.segments(&chain, &text, ctx.font_size)
+            .iter()
+            .map(|segment| segment.width)
+            .fold(0.0, f32::max)
+    }
+
+    fn segments<'t>(&self, chain: &FontChain, text: &'t str, size: f32) -> Vec<Segment<'t>> {
+        let mut segments = Vec::new();
+        let mut last = 0;
+        for (index, _) in LineBreakIterator::new(text) {
+            if index <= last {
+                continue;
+            }
+            let piece = &text[last..index];
+            segments.push(Segment {
+                text: piece,
+                width: self.text_width(chain, piece.trim_end(), size),
+                advance: self.text_width(chain, piece, size),
+            });
+            last = index;
+        }
+        segments
+    }
+
+    fn line_metrics(&self, font: Option<&fontdue::Font>, size: f32) -> LineMetrics {
+        let line_height = size * self.fallback.char_height;
+        let (ascent, descent) = font
+            .and_then(|font| font.horizontal_line_metrics(size))
+            .map(|metrics| (metrics.ascent, metrics.descent))
+            .unwrap_or((size * 0.8, -size * 0.2));
+        // Половина интерлиньяжа распределяется над и под глифами (CSS 2.1, 10.8.1)
+        let half_leading = (line_height - (ascent - descent)) / 2.0;
+        LineMetrics {
+            line_height,
+            baseline: half_leading + ascent,
+        }
+    }
+}
+
+impl TextLayout {
+    fn push_line(&mut self, text: &str, width: f32, runs: Vec<FontRun>, metrics: LineMetrics) {
+        let y = self.lines.len() as f32 * metrics.line_height;
+        self.lines.push(LineBox {
+            text: text.to_string(),
+            x: 0.0,
+            y,
+            width,
+            height: metrics.line_height,
+            baseline: y + metrics.baseline,
+            runs,
+        });
+        self.width = self.width.max(width);
+        self.height = y + metrics.line_height;
+    }
+}
+
+/// Схлопывает пробельные символы как `white-space: normal`
+pub fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Функция измерения текста для Taffy
+pub fn text_measure_function(
+    known_dimensions: taffy::Size<Option<f32>>,
+    available_space: taffy::Size<taffy::AvailableSpace>,
+    node_context: Option<&TextMeasureContext>,
+    measurer: &TextMeasurer,
+) -> taffy::Size<f32> {
+    // Если размеры уже известны и положительные, возвращаем их
+    if let taffy::Size {
+        width: Some(width),
+        height: Some(height),
+    } = known_dimensions
+        && width > 0.0
+        && height > 0.0
+    {
+        return taffy::Size { width, height };
+    }
+
+    match node_context {
+        Some(ctx) => measurer.measure(known_dimensions, available_space, ctx),
+        None => taffy::Size::ZERO,
+    }
+}
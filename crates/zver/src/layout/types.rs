@@ -1,7 +1,11 @@
+use crate::css::calc::{CalcContext, CalcExpr, CalcType};
 use std::collections::HashMap;
 use taffy::geometry;
 use taffy::style::{AlignItems, FlexDirection, JustifyContent};
 
+/// Начальное значение `font-size` (`medium`)
+pub const DEFAULT_FONT_SIZE: f32 = 16.0;
+
 /// Результат layout вычисления от Taffy
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LayoutResult {
@@ -68,7 +72,12 @@ pub struct ComputedStyle {
     pub height: Size,
     pub background_color: Option<String>,
     pub color: Option<String>,
+    pub font_family: Option<String>,
+    /// Вычисленный размер шрифта в пикселях
     pub font_size: f32,
+    /// Заданный `font-size`; `None` — наследуется. Относительные значения
+    /// вычисляются от шрифта родителя при наследовании
+    pub font_size_value: Option<CalcExpr>,
     pub font_weight: FontWeight,
     pub font_style: FontStyle,
     pub list_style_type: ListStyleType,
@@ -85,6 +94,7 @@ pub struct ComputedStyle {
     pub margin: taffy::geometry::Rect<taffy::style::LengthPercentageAuto>,
     pub padding: taffy::geometry::Rect<taffy::style::LengthPercentage>,
     pub border: taffy::geometry::Rect<taffy::style::LengthPercentage>,
+    pub border_color: Option<String>,
 
     // Grid properties - simplified for now, will be implemented later
     pub grid_template_rows: Vec<taffy::style::TrackSizingFunction>,
@@ -100,6 +110,19 @@ pub struct ComputedStyle {
 
     // Aspect ratio
     pub aspect_ratio: Option<f32>,
+
+    // Paint properties
+    pub z_index: Option<i32>,
+    pub opacity: f32,
+    /// `overflow-x`/`overflow-y`; если одна ось не `visible`, `visible` другой становится `auto`
+    pub overflow: geometry::Point<Overflow>,
+    /// `None` — не задано, наследуется от родителя
+    pub pointer_events: Option<PointerEvents>,
+
+    /// Длины с относительными единицами и выражения `calc()` с процентами.
+    /// Единицы вычисляются при наследовании, проценты — при layout, когда
+    /// известен containing block
+    pub calc: Vec<(CalcProperty, CalcExpr)>,
 }
 
 impl Default for ComputedStyle {
@@ -111,7 +134,9 @@ impl Default for ComputedStyle {
             height: Size::Auto,
             background_color: None,
             color: None,
-            font_size: 16.0,
+            font_family: None,
+            font_size: DEFAULT_FONT_SIZE,
+            font_size_value: None,
             font_weight: FontWeight::Normal,
             font_style: FontStyle::Normal,
             list_style_type: ListStyleType::None,
@@ -128,6 +153,7 @@ impl Default for ComputedStyle {
             margin: taffy::geometry::Rect::zero(),
             padding: taffy::geometry::Rect::zero(),
             border: taffy::geometry::Rect::zero(),
+            border_color: None,
 
             // Grid properties
             grid_template_rows: Vec::new(),
@@ -143,6 +169,16 @@ impl Default for ComputedStyle {
 
             // Aspect ratio
             aspect_ratio: None,
+
+            // Paint properties
+            z_index: None,
+            opacity: 1.0,
+            overflow: geometry::Point {
+                x: Overflow::Visible,
+                y: Overflow::Visible,
+            },
+            pointer_events: None,
+            calc: Vec::new(),
         }
     }
 }
@@ -153,6 +189,24 @@ impl ComputedStyle {
         let mut style = ComputedStyle::default();
 
         for (property, value) in properties {
+            let targets = CalcProperty::longhands(property);
+            // Ширина рамки не принимает проценты — такое объявление недействительно
+            if targets.iter().any(|target| !target.accepts_percentage())
+                && CalcExpr::parse(value)
+                    .or_else(|| CalcExpr::parse_value(value))
+                    .is_some_and(|expr| expr.has_percentage())
+            {
+                continue;
+            }
+            if !targets.is_empty()
+                && let Some(expr) = deferred_length(value)
+            {
+                style
+                    .calc
+                    .extend(targets.iter().map(|&target| (target, expr.clone())));
+                continue;
+            }
+
             match property.as_str() {
                 "display" => {
                     style.display = match value.as_str() {
@@ -191,7 +245,7 @@ impl ComputedStyle {
                     style.max_height = Size::parse(value);
                 }
                 "aspect-ratio" => {
-                    if let Ok(ratio) = value.parse::<f32>() {
+                    if let Some(ratio) = parse_number(value) {
                         style.aspect_ratio = Some(ratio);
                     }
                 }
@@ -201,9 +255,17 @@ impl ComputedStyle {
                 "color" => {
                     style.color = Some(value.clone());
                 }
+                "font-family" => {
+                    style.font_family = Some(value.clone());
+                }
                 "font-size" => {
-                    if let Ok(size) = value.replace("px", "").parse::<f32>() {
-                        style.font_size = size;
+                    if let Some(size) = parse_font_size(value) {
+                        if size.is_absolute() {
+                            style.font_size = size
+                                .eval(&CalcContext::default())
+                                .unwrap_or(style.font_size);
+                        }
+                        style.font_size_value = Some(size);
                     }
                 }
                 "font-weight" => {
@@ -258,12 +320,12 @@ impl ComputedStyle {
                     });
                 }
                 "flex-grow" => {
-                    if let Ok(grow) = value.parse::<f32>() {
+                    if let Some(grow) = parse_number(value) {
                         style.flex_grow = grow;
                     }
                 }
                 "flex-shrink" => {
-                    if let Ok(shrink) = value.parse::<f32>() {
+                    if let Some(shrink) = parse_number(value) {
                         style.flex_shrink = shrink;
                     }
                 }
@@ -282,6 +344,8 @@ impl ComputedStyle {
                         height: gap_val,
                     };
                 }
+                "row-gap" => style.gap.height = parse_length_percentage(value),
+                "column-gap" => style.gap.width = parse_length_percentage(value),
                 // Margin properties
                 "margin" => {
                     let val = parse_length_percentage_auto(value);
@@ -313,7 +377,17 @@ impl ComputedStyle {
                 "padding-bottom" => style.padding.bottom = parse_length_percentage(value),
 
                 // Border properties
-                "border" => {
+                "border" | "border-left" | "border-right" | "border-top" | "border-bottom" => {
+                    let (width, color) = parse_border_shorthand(value);
+                    if let Some(width) = width {
+                        let sides = CalcProperty::longhands(&format!("{property}-width"));
+                        style.set_length(sides, width);
+                    }
+                    if color.is_some() {
+                        style.border_color = color;
+                    }
+                }
+                "border-width" => {
                     let val = parse_length_percentage(value);
                     style.border = taffy::geometry::Rect {
                         left: val,
@@ -322,17 +396,41 @@ impl ComputedStyle {
                         bottom: val,
                     };
                 }
-                "border-left" | "border-left-width" => {
-                    style.border.left = parse_length_percentage(value)
+                "border-color" => {
+                    style.border_color = Some(value.clone());
                 }
-                "border-right" | "border-right-width" => {
-                    style.border.right = parse_length_percentage(value)
+                "border-left-width" => style.border.left = parse_length_percentage(value),
+                "border-right-width" => style.border.right = parse_length_percentage(value),
+                "border-top-width" => style.border.top = parse_length_percentage(value),
+                "border-bottom-width" => style.border.bottom = parse_length_percentage(value),
+
+                // Paint properties
+                "z-index" => {
+                    style.z_index = value.trim().parse::<i32>().ok();
                 }
-                "border-top" | "border-top-width" => {
-                    style.border.top = parse_length_percentage(value)
+                "opacity" => {
+                    let value = value.trim();
+                    let opacity = match value.strip_suffix('%') {
+                        Some(percent) => percent.parse::<f32>().map(|p| p / 100.0),
+                        None => value.parse::<f32>(),
+                    };
+                    if let Ok(opacity) = opacity {
+                        style.opacity = opacity.clamp(0.0, 1.0);
+                    }
+                }
+                "pointer-events" => {
+                    style.pointer_events = match value.trim() {
+                        "none" => Some(PointerEvents::None),
+                        "auto" => Some(PointerEvents::Auto),
+                        _ => style.pointer_events,
+                    };
                 }
-                "border-bottom" | "border-bottom-width" => {
-                    style.border.bottom = parse_length_percentage(value)
+                "overflow" => {
+                    let mut values = value.split_whitespace().map(Overflow::parse);
+                    if let Some(Some(x)) = values.next() {
+                        let y = values.next().flatten().unwrap_or(x);
+                        style.overflow = geometry::Point { x, y };
+                    }
                 }
 
                 // Grid properties
@@ -353,9 +451,49 @@ impl ComputedStyle {
             }
         }
 
+        // Полные свойства по осям уточняют сокращение `overflow`
+        if let Some(x) = properties
+            .get("overflow-x")
+            .and_then(|v| Overflow::parse(v.trim()))
+        {
+            style.overflow.x = x;
+        }
+        if let Some(y) = properties
+            .get("overflow-y")
+            .and_then(|v| Overflow::parse(v.trim()))
+        {
+            style.overflow.y = y;
+        }
+        match (style.overflow.x, style.overflow.y) {
+            (Overflow::Visible, Overflow::Visible) => {}
+            (Overflow::Visible, _) => style.overflow.x = Overflow::Auto,
+            (_, Overflow::Visible) => style.overflow.y = Overflow::Auto,
+            _ => {}
+        }
+
         style
     }
 
+    /// Записывает длину в свойства `targets`: абсолютную сразу, с
+    /// относительными единицами — через [`ComputedStyle::calc`]
+    fn set_length(&mut self, targets: &[CalcProperty], length: CalcExpr) {
+        match length.eval(&CalcContext::default()) {
+            Some(value) if length.is_absolute() => {
+                for target in targets {
+                    target.set(self, value);
+                }
+            }
+            _ => self
+                .calc
+                .extend(targets.iter().map(|&target| (target, length.clone()))),
+        }
+    }
+
+    /// Прокручивается ли содержимое элемента (`overflow` не `visible`)
+    pub fn is_scroll_container(&self) -> bool {
+        self.overflow.x != Overflow::Visible || self.overflow.y != Overflow::Visible
+    }
+
     /// Конвертирует в Taffy Style для layout-движка
     pub fn to_taffy_style(&self) -> taffy::Style {
         use taffy::style::*;
@@ -387,6 +525,13 @@ impl ComputedStyle {
         style.padding = self.padding;
         style.border = self.border;
 
+        // Переполнение: полосы прокрутки рисует окно, места в layout они не занимают
+        style.overflow = taffy::geometry::Point {
+            x: self.overflow.x.to_taffy(),
+            y: self.overflow.y.to_taffy(),
+        };
+        style.scrollbar_width = 0.0;
+
         // Flex properties
         if matches!(self.display, crate::layout::types::Display::Flex) {
             style.flex_direction = self.flex_direction;
@@ -441,6 +586,44 @@ pub enum Position {
     Absolute,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    #[default]
+    Visible,
+    Hidden,
+    Scroll,
+    Auto,
+}
+
+impl Overflow {
+    /// Значение `overflow`; `clip` обрезает так же, как `hidden`
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "visible" => Some(Overflow::Visible),
+            "hidden" | "clip" => Some(Overflow::Hidden),
+            "scroll" => Some(Overflow::Scroll),
+            "auto" => Some(Overflow::Auto),
+            _ => None,
+        }
+    }
+
+    fn to_taffy(self) -> taffy::style::Overflow {
+        match self {
+            Overflow::Visible => taffy::style::Overflow::Visible,
+            Overflow::Hidden => taffy::style::Overflow::Hidden,
+            Overflow::Scroll | Overflow::Auto => taffy::style::Overflow::Scroll,
+        }
+    }
+}
+
+/// Может ли элемент быть целью событий указателя
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointerEvents {
+    #[default]
+    Auto,
+    None,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Size {
     Auto,
@@ -451,6 +634,9 @@ pub enum Size {
 impl Size {
     /// Парсит размер из CSS значения
     pub fn parse(value: &str) -> Self {
+        if let Some(expr) = CalcExpr::parse(value) {
+            return absolute_calc_length(&expr).map_or(Size::Auto, Size::Px);
+        }
         if value == "auto" {
             Size::Auto
         } else if let Some(px_value) = value.strip_suffix("px") {
@@ -480,6 +666,201 @@ impl Size {
     }
 }
 
+/// Свойство layout, которое может задаваться выражением `calc()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalcProperty {
+    Width,
+    Height,
+    MinWidth,
+    MinHeight,
+    MaxWidth,
+    MaxHeight,
+    MarginTop,
+    MarginRight,
+    MarginBottom,
+    MarginLeft,
+    PaddingTop,
+    PaddingRight,
+    PaddingBottom,
+    PaddingLeft,
+    RowGap,
+    ColumnGap,
+    BorderTopWidth,
+    BorderRightWidth,
+    BorderBottomWidth,
+    BorderLeftWidth,
+}
+
+impl CalcProperty {
+    /// Свойства, которые задаёт декларация `name`: сокращения `gap` и
+    /// `border-width` раскрываются; пустой список — свойство не из этого набора
+    pub fn longhands(name: &str) -> &'static [Self] {
+        match name {
+            "width" => &[Self::Width],
+            "height" => &[Self::Height],
+            "min-width" => &[Self::MinWidth],
+            "min-height" => &[Self::MinHeight],
+            "max-width" => &[Self::MaxWidth],
+            "max-height" => &[Self::MaxHeight],
+            "margin-top" => &[Self::MarginTop],
+            "margin-right" => &[Self::MarginRight],
+            "margin-bottom" => &[Self::MarginBottom],
+            "margin-left" => &[Self::MarginLeft],
+            "padding-top" => &[Self::PaddingTop],
+            "padding-right" => &[Self::PaddingRight],
+            "padding-bottom" => &[Self::PaddingBottom],
+            "padding-left" => &[Self::PaddingLeft],
+            "row-gap" => &[Self::RowGap],
+            "column-gap" => &[Self::ColumnGap],
+            "gap" => &[Self::RowGap, Self::ColumnGap],
+            "border-top-width" => &[Self::BorderTopWidth],
+            "border-right-width" => &[Self::BorderRightWidth],
+            "border-bottom-width" => &[Self::BorderBottomWidth],
+            "border-left-width" => &[Self::BorderLeftWidth],
+            "border-width" => &[
+                Self::BorderTopWidth,
+                Self::BorderRightWidth,
+                Self::BorderBottomWidth,
+                Self::BorderLeftWidth,
+            ],
+            _ => &[],
+        }
+    }
+
+    /// Записывает длину в пикселях в вычисленный стиль
+    pub fn set(self, style: &mut ComputedStyle, value: f32) {
+        let margin = taffy::style::LengthPercentageAuto::length(value);
+        let length = taffy::style::LengthPercentage::length(value.max(0.0));
+        match self {
+            Self::Width => style.width = Size::Px(value),
+            Self::Height => style.height = Size::Px(value),
+            Self::MinWidth => style.min_width = Size::Px(value),
+            Self::MinHeight => style.min_height = Size::Px(value),
+            Self::MaxWidth => style.max_width = Size::Px(value),
+            Self::MaxHeight => style.max_height = Size::Px(value),
+            Self::MarginTop => style.margin.top = margin,
+            Self::MarginRight => style.margin.right = margin,
+            Self::MarginBottom => style.margin.bottom = margin,
+            Self::MarginLeft => style.margin.left = margin,
+            Self::PaddingTop => style.padding.top = length,
+            Self::PaddingRight => style.padding.right = length,
+            Self::PaddingBottom => style.padding.bottom = length,
+            Self::PaddingLeft => style.padding.left = length,
+            Self::RowGap => style.gap.height = length,
+            Self::ColumnGap => style.gap.width = length,
+            Self::BorderTopWidth => style.border.top = length,
+            Self::BorderRightWidth => style.border.right = length,
+            Self::BorderBottomWidth => style.border.bottom = length,
+            Self::BorderLeftWidth => style.border.left = length,
+        }
+    }
+
+    /// Допустимы ли проценты: у `border-*-width` их нет
+    pub fn accepts_percentage(self) -> bool {
+        !matches!(
+            self,
+            Self::BorderTopWidth
+                | Self::BorderRightWidth
+                | Self::BorderBottomWidth
+                | Self::BorderLeftWidth
+        )
+    }
+
+    /// Может ли значение быть отрицательным; остальные свойства обрезаются до нуля
+    pub fn allows_negative(self) -> bool {
+        matches!(
+            self,
+            Self::MarginTop | Self::MarginRight | Self::MarginBottom | Self::MarginLeft
+        )
+    }
+
+    /// Ставит в стиль Taffy ссылку `handle` на выражение, которое Taffy
+    /// вычислит от containing block при layout
+    pub fn apply(self, style: &mut taffy::Style, handle: *const ()) {
+        let dimension = taffy::Dimension::calc(handle);
+        let margin = taffy::style::LengthPercentageAuto::calc(handle);
+        let length = taffy::style::LengthPercentage::calc(handle);
+        match self {
+            Self::Width => style.size.width = dimension,
+            Self::Height => style.size.height = dimension,
+            Self::MinWidth => style.min_size.width = dimension,
+            Self::MinHeight => style.min_size.height = dimension,
+            Self::MaxWidth => style.max_size.width = dimension,
+            Self::MaxHeight => style.max_size.height = dimension,
+            Self::MarginTop => style.margin.top = margin,
+            Self::MarginRight => style.margin.right = margin,
+            Self::MarginBottom => style.margin.bottom = margin,
+            Self::MarginLeft => style.margin.left = margin,
+            Self::PaddingTop => style.padding.top = length,
+            Self::PaddingRight => style.padding.right = length,
+            Self::PaddingBottom => style.padding.bottom = length,
+            Self::PaddingLeft => style.padding.left = length,
+            Self::RowGap => style.gap.height = length,
+            Self::ColumnGap => style.gap.width = length,
+            Self::BorderTopWidth => style.border.top = length,
+            Self::BorderRightWidth => style.border.right = length,
+            Self::BorderBottomWidth => style.border.bottom = length,
+            Self::BorderLeftWidth => style.border.left = length,
+        }
+    }
+}
+
+/// Длина, которую вычисляют наследование и layout: математическая функция
+/// или длина в относительных единицах
+fn deferred_length(value: &str) -> Option<CalcExpr> {
+    if let Some(expr) = CalcExpr::parse(value) {
+        return (expr.calc_type() == Some(CalcType::Length)).then_some(expr);
+    }
+    CalcExpr::parse_value(value)
+        .filter(|expr| matches!(expr, CalcExpr::Length(_, unit) if unit != "px"))
+}
+
+/// Длина из выражения без процентов; относительные единицы — по умолчаниям
+/// [`CalcContext`]
+fn absolute_calc_length(expr: &CalcExpr) -> Option<f32> {
+    if expr.calc_type() != Some(CalcType::Length) {
+        return None;
+    }
+    expr.eval(&CalcContext::default())
+}
+
+/// Значение `font-size`: длина, процент, выражение или ключевое слово.
+///
+/// Проценты, `larger` и `smaller` считаются от шрифта родителя
+fn parse_font_size(value: &str) -> Option<CalcExpr> {
+    let keyword = match value.trim().to_ascii_lowercase().as_str() {
+        "xx-small" => Some(9.0),
+        "x-small" => Some(10.0),
+        "small" => Some(13.0),
+        "medium" => Some(DEFAULT_FONT_SIZE),
+        "large" => Some(18.0),
+        "x-large" => Some(24.0),
+        "xx-large" => Some(32.0),
+        "xxx-large" => Some(48.0),
+        "larger" => return Some(CalcExpr::Percentage(120.0)),
+        "smaller" => return Some(CalcExpr::Percentage(100.0 / 1.2)),
+        _ => None,
+    };
+    if let Some(size) = keyword {
+        return Some(CalcExpr::Length(size, "px".to_string()));
+    }
+    CalcExpr::parse_value(value).filter(|expr| {
+        expr.calc_type() == Some(CalcType::Length)
+            && !matches!(expr, CalcExpr::Length(size, _) | CalcExpr::Percentage(size) if *size < 0.0)
+    })
+}
+
+/// Число или выражение `calc()` с числовым результатом
+fn parse_number(value: &str) -> Option<f32> {
+    match CalcExpr::parse(value) {
+        Some(expr) if expr.calc_type() == Some(CalcType::Number) => {
+            expr.eval(&CalcContext::default())
+        }
+        Some(_) => None,
+        None => value.trim().parse::<f32>().ok(),
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FontWeight {
     Normal,
@@ -524,6 +905,77 @@ fn parse_length_percentage(value: &str) -> taffy::style::LengthPercentage {
     taffy::style::LengthPercentage::length(0.0)
 }
 
+/// Разбирает шорткат `border` / `border-<side>`: `<width> || <style> || <color>`.
+///
+/// Возвращает ширину (с учётом `none`/`hidden`, обнуляющих рамку) и цвет, если они заданы.
+/// Относительные единицы ширины вычисляются при наследовании.
+fn parse_border_shorthand(value: &str) -> (Option<CalcExpr>, Option<String>) {
+    let px = |width: f32| CalcExpr::Length(width, "px".to_string());
+    let mut width = None;
+    let mut color = None;
+    let mut hidden = false;
+
+    for token in split_css_components(value) {
+        match token.to_ascii_lowercase().as_str() {
+            "none" | "hidden" => hidden = true,
+            "solid" | "dashed" | "dotted" | "double" | "groove" | "ridge" | "inset" | "outset" => {}
+            "thin" => width = Some(px(1.0)),
+            "medium" => width = Some(px(3.0)),
+            "thick" => width = Some(px(5.0)),
+            _ => match CalcExpr::parse_value(token) {
+                Some(CalcExpr::Number(number)) => width = Some(px(number)),
+                Some(expr)
+                    if expr.calc_type() == Some(CalcType::Length) && !expr.has_percentage() =>
+                {
+                    width = Some(expr);
+                }
+                _ => {
+                    if crate::css::color::parse_css_color(token).is_some() {
+                        color = Some(token.to_string());
+                    }
+                }
+            },
+        }
+    }
+
+    if hidden {
+        width = Some(px(0.0));
+    } else if width.is_none() && color.is_some() {
+        // Ширина по умолчанию для `border: solid red` — medium
+        width = Some(px(3.0));
+    }
+
+    (width, color)
+}
+
+/// Делит значение на компоненты по пробелам, не разрывая функции вроде `rgb(1, 2, 3)`.
+fn split_css_components(value: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+
+    for (index, ch) in value.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            c if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    parts.push(&value[begin..index]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(index);
+    }
+
+    if let Some(begin) = start {
+        parts.push(&value[begin..]);
+    }
+
+    parts
+}
+
 /// Парсит значение в LengthPercentageAuto
 fn parse_length_percentage_auto(value: &str) -> taffy::style::LengthPercentageAuto {
     let value = value.trim();
@@ -1,16 +1,33 @@
+pub mod builder;
+pub mod clock;
 pub mod css;
 pub mod dom;
+pub mod error;
+pub mod input;
 pub mod js;
 pub mod layout;
+pub mod navigation;
 pub mod network;
+pub mod reftest;
 pub mod render;
 pub mod resource_loader;
 
-use rayon::prelude::*;
+pub use builder::ZverBuilder;
+pub use error::{ZverError, ZverResult};
+
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{debug, instrument};
 
+/// Предел задач за один [`Zver::run_until_idle`]: бесконечная цепочка
+/// `setTimeout(f, 0)` иначе никогда не даст странице простаивать
+const MAX_IDLE_TASKS: usize = 10_000;
+
+/// Сколько раз подряд [`Zver::update`] перерисовывает кадр, пока слушатели
+/// `scroll` продолжают прокручивать страницу
+const MAX_SCROLL_PASSES: usize = 8;
+
 /// Главный интерфейс браузерного движка Zver.
 ///
 /// Координирует работу всех подсистем: DOM, CSS, Layout, Render, Network, JS.
@@ -56,6 +73,16 @@ pub struct Zver {
     pub js: Arc<RwLock<js::JSEngine>>,
     /// Resource loader (Lock Level 7)
     pub resource_loader: Arc<RwLock<resource_loader::ResourceLoader>>,
+    /// Часы страницы: общие для таймеров, `Date`, `performance` и CSS-анимаций.
+    /// Без RwLock — состояние синхронизировано внутри и не участвует в порядке блокировок
+    pub clock: clock::Clock,
+    /// Прокрутка документа и scroll-контейнеров, общая с layout и скриптами.
+    /// Как и часы, синхронизирована внутри; применяется в [`Zver::update`]
+    pub scroll: layout::ScrollOffsets,
+    /// Исполнять ли `<script>` при загрузке ([`ZverBuilder::javascript_enabled`])
+    javascript_enabled: bool,
+    /// Наведение, фокус и нажатые кнопки для [`Zver::dispatch_input`]
+    input: Arc<std::sync::Mutex<input::InputState>>,
 }
 
 impl Zver {
@@ -63,30 +90,37 @@ impl Zver {
     ///
     /// Синхронная обёртка над [`JSEngine::execute()`], использующая существующий RwLock.
     /// Не изменяет публичные контракты и модель блокировок.
-    pub fn eval_js(&self, code: &str) -> Result<crate::js::JSValue, String> {
+    pub fn eval_js(&self, code: &str) -> ZverResult<crate::js::JSValue> {
         // Используем существующий js: Arc<RwLock<JSEngine>>
         // Без изменения lock ordering: JS — самый высокий уровень.
         let mut js_engine = self.js.blocking_write();
-        js_engine.execute(code).map_err(|e| e.to_string())
+        js_engine.execute(code)
     }
     /// Создаёт новый экземпляр браузерного движка с настройками по умолчанию.
     ///
     /// # Возвращает
     ///
-    /// Новый экземпляр `Zver` с viewport размером 800x600.
+    /// Новый экземпляр `Zver` с viewport размером 800x600 и реальными часами.
     pub fn new() -> Self {
-        let dom = Arc::new(RwLock::new(dom::Document::new()));
-        let js = Arc::new(RwLock::new(js::JSEngine::new().with_dom(dom.clone())));
-
-        Self {
-            dom,
-            css: Arc::new(RwLock::new(css::StyleEngine::new())),
-            layout: Arc::new(RwLock::new(layout::LayoutEngine::new(800.0, 600.0))),
-            render: Arc::new(RwLock::new(render::RenderEngine::new())),
-            network: Arc::new(RwLock::new(network::NetworkEngine::new())),
-            js,
-            resource_loader: Arc::new(RwLock::new(resource_loader::ResourceLoader::new())),
-        }
+        Self::builder().build()
+    }
+
+    /// Построитель движка с настройками viewport, сети, JS и часов
+    pub fn builder() -> ZverBuilder {
+        ZverBuilder::new()
+    }
+
+    /// Создаёт движок, время страницы в котором идёт по `clock`.
+    ///
+    /// С [`clock::Clock::new_virtual`] время стоит, пока его не переведёт
+    /// [`Zver::advance`], и страница воспроизводится детерминированно.
+    pub fn with_clock(clock: clock::Clock) -> Self {
+        Self::builder().clock(clock).build()
+    }
+
+    /// Исполняются ли скрипты страницы
+    pub fn javascript_enabled(&self) -> bool {
+        self.javascript_enabled
     }
 
     /// Загружает и рендерит HTML документ по заданному URL.
@@ -96,13 +130,15 @@ impl Zver {
     /// 2. Загрузка HTML через сеть
     /// 3. Парсинг DOM
     /// 4. Извлечение и применение CSS
-    /// 5. Выполнение JavaScript
+    /// 5. Выполнение JavaScript и событие `DOMContentLoaded`
     /// 6. Вычисление layout
-    /// 7. Рендеринг
+    /// 7. Загрузка изображений и событие `load`
+    /// 8. Рендеринг и [`Zver::run_until_idle`] для уже готовых таймеров
     ///
     /// # Аргументы
     ///
-    /// * `url` - URL для загрузки. Поддерживаются схемы: `http://`, `https://`, `file://`
+    /// * `url` - URL для загрузки. Поддерживаются схемы: `http://`, `https://`, `file://`,
+    ///   `data:` и `about:blank`; строка без схемы считается путём к файлу
     ///
     /// # Примеры
     ///
@@ -127,47 +163,62 @@ impl Zver {
     ///
     /// Соблюдается безопасный порядок блокировок: DOM -> CSS -> Layout -> Render
     #[instrument(skip(self), fields(url = %url))]
-    pub async fn load_url(&self, url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn load_url(&self, url: &str) -> ZverResult<()> {
+        let response = {
+            let _span = tracing::debug_span!("fetch_html").entered();
+            let mut network = self.network.write().await;
+            network.fetch_response(&network::Url::parse(url)?).await?
+        };
+        self.load_response(response).await
+    }
+
+    /// Пайплайн [`Zver::load_url`] для уже полученного ответа с документом
+    async fn load_response(&self, response: network::Response) -> ZverResult<()> {
         // Инициализируем resource_loader при первом использовании
         {
             let mut loader = self.resource_loader.write().await;
             loader.init().await;
         }
 
-        let html = {
-            let _span = tracing::debug_span!("fetch_html").entered();
-            let mut network = self.network.write().await;
-            network.fetch(url).await?
-        };
+        // После перенаправлений документ живёт по итоговому URL
+        let url = response.url.clone();
+        let html = response.text();
 
-        {
+        // Новая страница открывается в начале
+        self.scroll.reset();
+
+        let base_url = {
             let _span = tracing::debug_span!("parse_dom").entered();
             let mut dom = self.dom.write().await;
             dom.parse_html(&html).await?;
-        }
+            dom.set_url(url.clone());
+            dom.base_url()
+        };
 
         let pseudo_contents = {
             let _span = tracing::debug_span!("process_css").entered();
-            // TODO(Phase 3): ��⨬���஢��� - ᮧ���� ��������� snapshot ��� CSS extraction
-            // ����� �ॡ���� clone() ��� rayon parallel processing ('static lifetime)
+            // TODO(Phase 3): оптимизировать - создать облегчённый snapshot для CSS extraction
             let dom_snapshot = self.dom.read().await.clone();
-            let mut css = self.css.write().await;
+            let environment = self.css.read().await.media_environment();
 
-            // ��ࠫ���쭮 ��������� CSS �� ��� <style> ⥣��
-            let css_contents: Vec<String> = find_style_nodes(&dom_snapshot, dom_snapshot.root)
-                .into_par_iter()
-                .map(|style_node_id| {
-                    let mut content = String::new();
-                    extract_css_from_single_node(&dom_snapshot, style_node_id, &mut content);
-                    content
-                })
-                .collect();
+            // <style>, <link rel="stylesheet"> и @import в порядке документа;
+            // внешние таблицы загружаются параллельно до захвата CSS lock
+            let sources = css::stylesheets::collect_stylesheet_sources(&dom_snapshot, &environment);
+            let sheets = {
+                let loader = self.resource_loader.read().await;
+                css::stylesheets::load_stylesheets(&loader, &base_url, sources, &environment).await
+            };
 
-            let combined_css = css_contents.join("\n");
-            css.parse_css(&combined_css)?;
+            // Каждая таблица разбирается отдельно: ошибка в одной не роняет страницу
+            let mut css = self.css.write().await;
+            css.parse_stylesheets(&sheets);
             css.apply_styles(&dom_snapshot)?;
 
-            debug!("Processed {} CSS rules", css.rules.len());
+            debug!(
+                "Processed {} CSS rules from {} stylesheets",
+                css.rules.len(),
+                sheets.len()
+            );
             css.pseudo_element_contents()
         };
 
@@ -176,31 +227,129 @@ impl Zver {
             dom.sync_pseudo_elements(&pseudo_contents);
         }
 
-        // Исполняем JavaScript из <script> тегов
-        // NOTE: Требуется clone() для изоляции - JS может мутировать DOM через callbacks
-        {
+        // Шрифты @font-face нужны до layout: от них зависят метрики текста
+        self.load_font_faces().await;
+
+        // Исполняем JavaScript из <script> тегов: каждый скрипт — отдельная единица,
+        // внешние загружаются параллельно через NetworkEngine до захвата JS lock.
+        // С выключенным JS скрипты не загружаются и не исполняются
+        if self.javascript_enabled {
             let _span = tracing::debug_span!("execute_js").entered();
-            let dom_snapshot = self.dom.read().await.clone();
+            let scripts = {
+                let dom = self.dom.read().await;
+                js::scripts::execution_order(js::scripts::collect_scripts(&dom))
+            };
+
+            let script_urls: Vec<network::Url> = scripts
+                .iter()
+                .filter_map(|script| match &script.source {
+                    js::scripts::ScriptSource::External { src } => base_url.join(src).ok(),
+                    js::scripts::ScriptSource::Inline(_) => None,
+                })
+                .collect();
+            let (fetched, modules) = {
+                let mut network = self.network.write().await;
+                let results = network.fetch_many(&script_urls).await;
+                let fetched: std::collections::HashMap<network::Url, Result<String, ZverError>> =
+                    script_urls
+                        .into_iter()
+                        .zip(results)
+                        .map(|(url, result)| {
+                            let result = result
+                                .and_then(network::Response::error_for_status)
+                                .map(|response| response.text())
+                                .map_err(Into::into);
+                            (url, result)
+                        })
+                        .collect();
+
+                // Зависимости модулей выкачиваются заранее: Boa запрашивает их синхронно
+                let module_roots: Vec<(network::Url, String)> = scripts
+                    .iter()
+                    .filter(|script| script.kind == js::scripts::ScriptKind::Module)
+                    .filter_map(|script| match &script.source {
+                        js::scripts::ScriptSource::Inline(code) => {
+                            Some((base_url.clone(), code.clone()))
+                        }
+                        js::scripts::ScriptSource::External { src } => {
+                            let url = base_url.join(src).ok()?;
+                            let code = fetched.get(&url)?.as_ref().ok()?.clone();
+                            Some((url, code))
+                        }
+                    })
+                    .collect();
+                let modules = js::modules::fetch_module_graph(&mut network, &module_roots).await;
+                (fetched, modules)
+            };
+
             let mut js_engine = self.js.write().await;
 
             // Reset JavaScript context to prevent "duplicate lexical declaration" errors
             // This is necessary because const/let declarations cannot be redeclared in the same scope
             js_engine.reset_context();
+            js_engine.set_location(&url);
+            for (module_url, code) in modules {
+                js_engine.register_module_source(module_url, code);
+            }
 
-            let mut js_content = String::new();
-            extract_js_from_dom(&dom_snapshot, dom_snapshot.root, &mut js_content);
+            for script in &scripts {
+                // URL скрипта нужен модулям: от него разрешаются их `import`
+                let (code, script_url) = match &script.source {
+                    js::scripts::ScriptSource::Inline(code) => (code.clone(), base_url.clone()),
+                    js::scripts::ScriptSource::External { src } => {
+                        let script_url = base_url.join(src).ok();
+                        match script_url.and_then(|url| Some((fetched.get(&url)?, url))) {
+                            Some((Ok(code), script_url)) => (code.clone(), script_url),
+                            Some((Err(err), _)) => {
+                                tracing::warn!("Failed to load script {}: {}", src, err);
+                                continue;
+                            }
+                            None => {
+                                tracing::warn!("Cannot resolve script URL {}", src);
+                                continue;
+                            }
+                        }
+                    }
+                };
 
-            if !js_content.is_empty() {
                 tracing::debug!(
-                    "Executing JavaScript code ({} chars): {}",
-                    js_content.len(),
-                    &js_content[..js_content.len().min(100)]
+                    "Executing {:?} script #{} ({} chars)",
+                    script.timing,
+                    script.node_id,
+                    code.len()
                 );
-
-                if let Err(e) = js_engine.execute(&js_content) {
-                    eprintln!("JavaScript execution error: {}", e);
+                let result = match script.kind {
+                    js::scripts::ScriptKind::Classic => js_engine.execute(&code),
+                    js::scripts::ScriptKind::Module => {
+                        js_engine.execute_module_at(&code, &script_url)
+                    }
+                };
+                // Ошибка одного скрипта не прерывает исполнение остальных
+                if let Err(e) = result {
+                    eprintln!(
+                        "JavaScript execution error in script #{}: {}",
+                        script.node_id, e
+                    );
                 }
+                // Контрольная точка микрозадач после каждого скрипта
+                js_engine.run_microtasks();
+            }
+
+            js_engine.dispatch_document_event("DOMContentLoaded");
+        }
+
+        // Скрипты могли изменить DOM: пересчитываем каскад затронутых поддеревьев,
+        // layout ниже всё равно строится целиком. CSS-анимации стартуют здесь же
+        {
+            let mut dom = self.dom.write().await;
+            let dirty = dom.take_dirty();
+            let mut css = self.css.write().await;
+            if !dirty.is_empty() {
+                let roots = style_roots(&dom, &css, &dirty);
+                css.restyle(&dom, &roots);
+                dom.sync_pseudo_elements(&css.pseudo_element_contents());
             }
+            css.sample_animations(self.clock.now());
         }
 
         // Вычисляем layout
@@ -212,8 +361,10 @@ impl Zver {
             let css_guard = self.css.read().await;
             let css_snapshot = css_guard.computed_styles.clone();
             let pseudo_snapshot = css_guard.pseudo_element_styles.clone();
+            let fonts = css_guard.fonts.clone();
             drop(css_guard);
             let mut layout = self.layout.write().await;
+            layout.set_fonts(fonts);
 
             // Берём DOM guard на время вычислений вместо полного clone()
             let dom_guard = self.dom.read().await;
@@ -223,84 +374,368 @@ impl Zver {
             debug!("Computed layout for {} nodes", layout_results.len());
         }
 
+        // Загружаем <img> через NetworkEngine до захвата Render lock
+        self.load_images().await;
+
+        // Подресурсы загружены; изменения DOM из обработчиков применит run_until_idle
+        self.js.write().await.dispatch_window_event("load");
+
         // Рендеринг
         // TODO(Phase 2): Создать RenderSnapshot вместо полного clone()
         // Render нужны только геометрия + тексты, не всё дерево
         {
             let _span = tracing::debug_span!("render").entered();
-            let layout = self.layout.read().await;
             let dom_snapshot = self.dom.read().await.clone();
+            let fonts = self.css.read().await.fonts.clone();
+            let layout = self.layout.read().await;
             let mut render = self.render.write().await;
-            render.paint(&layout, &dom_snapshot).await?;
+            render.paint(&layout, &dom_snapshot, &fonts).await?;
         }
+
+        self.run_until_idle().await?;
         Ok(())
     }
-}
 
-impl Default for Zver {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+    /// Загружает `<img src>`, которых ещё нет среди декодированных изображений.
+    ///
+    /// Вызывается при загрузке страницы и в [`Zver::update`] после изменений DOM,
+    /// так что изображения, добавленные скриптами, тоже появляются в кадре.
+    async fn load_images(&self) {
+        let _span = tracing::debug_span!("load_images").entered();
+        let mut image_urls: Vec<network::Url> = {
+            // Живые узлы, а не исходная разметка: скрипты могли добавить <img>
+            let dom = self.dom.read().await;
+            dom.nodes
+                .iter()
+                .filter(|(id, node)| {
+                    node.tag_name.as_deref() == Some("img") && dom.is_connected(**id)
+                })
+                .filter_map(|(_, node)| dom.resolve_url(node.attributes.get("src")?))
+                .collect()
+        };
+        image_urls.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        image_urls.dedup();
+        {
+            let render = self.render.read().await;
+            image_urls.retain(|url| !render.has_image(url.as_str()));
+        }
+        if image_urls.is_empty() {
+            return;
+        }
 
-fn find_style_nodes(dom: &dom::Document, node_id: Option<usize>) -> Vec<usize> {
-    match node_id {
-        Some(id) => {
-            let mut nodes = Vec::new();
-            if dom.nodes.get(&id).and_then(|node| node.tag_name.as_deref()) == Some("style") {
-                nodes.push(id);
+        let results = self.network.write().await.fetch_many(&image_urls).await;
+        let mut render = self.render.write().await;
+        for (url, result) in image_urls.iter().zip(results) {
+            match result.and_then(network::Response::error_for_status) {
+                Ok(response) => {
+                    render.load_image(url.as_str(), &response.body);
+                }
+                Err(err) => tracing::warn!("Failed to load image {}: {}", url, err),
             }
-            nodes.extend(dom.select_ids_from(id, "style"));
-            nodes
         }
-        None => dom.select_ids("style"),
     }
-}
 
-fn extract_css_from_single_node(dom: &dom::Document, node_id: usize, css_content: &mut String) {
-    let content = dom.get_text_content(node_id);
-    if !content.is_empty() {
-        css_content.push_str(&content);
-        if !content.ends_with('\n') {
-            css_content.push('\n');
+    /// Загружает файлы `url()` источников @font-face через NetworkEngine.
+    ///
+    /// Для каждого правила берётся первый источник, который удалось загрузить
+    /// и разобрать; правила без шрифта остаются на [`css::fonts::default_font`].
+    async fn load_font_faces(&self) {
+        let _span = tracing::debug_span!("load_fonts").entered();
+        let candidates: Vec<Vec<network::Url>> = {
+            let dom = self.dom.read().await;
+            let css = self.css.read().await;
+            css.fonts
+                .iter()
+                .map(|font| {
+                    font.face
+                        .sources
+                        .iter()
+                        .filter_map(|source| match source {
+                            css::fonts::FontSource::Url { url, .. } => dom.resolve_url(url),
+                            css::fonts::FontSource::Local { .. } => None,
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        let mut urls: Vec<network::Url> = candidates.iter().flatten().cloned().collect();
+        urls.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        urls.dedup();
+        if urls.is_empty() {
+            return;
+        }
+
+        let results = self.network.write().await.fetch_many(&urls).await;
+        let mut bodies = std::collections::HashMap::new();
+        for (url, result) in urls.iter().zip(results) {
+            match result.and_then(network::Response::error_for_status) {
+                Ok(response) => {
+                    bodies.insert(url.clone(), response.body);
+                }
+                Err(err) => tracing::warn!("Failed to load font {}: {}", url, err),
+            }
+        }
+
+        let mut css = self.css.write().await;
+        for (font, urls) in css.fonts.iter_mut().zip(&candidates) {
+            for url in urls {
+                let Some(body) = bodies.get(url) else {
+                    continue;
+                };
+                match font.load_from_bytes(body) {
+                    Ok(()) => break,
+                    Err(err) => tracing::warn!("Cannot decode font {}: {}", url, err),
+                }
+            }
         }
     }
-}
 
-#[allow(dead_code)]
-fn extract_css_from_dom(dom: &dom::Document, node_id: Option<usize>, css_content: &mut String) {
-    for style_id in find_style_nodes(dom, node_id) {
-        extract_css_from_single_node(dom, style_id, css_content);
+    /// Прокручивает цикл событий, пока не останется готовых задач.
+    ///
+    /// Выполняет микрозадачи и все таймеры, срок которых уже наступил, включая
+    /// поставленные по ходу (`setTimeout(f, 0)`). После каждой задачи изменения
+    /// DOM применяются через [`Zver::update`]. Таймеры из будущего остаются в
+    /// очереди — их запускает [`Zver::advance`].
+    ///
+    /// Возвращает число выполненных таймеров.
+    pub async fn run_until_idle(&self) -> ZverResult<usize> {
+        self.run_tasks(None).await
+    }
+
+    /// Сдвигает время страницы на `duration`, выполняя по порядку все таймеры
+    /// и кадры `requestAnimationFrame`, которые сработали бы за это время.
+    ///
+    /// Часы страницы переводятся к моменту срабатывания каждого таймера, поэтому
+    /// задержки внутри callback'ов отсчитываются детерминированно, без реального
+    /// ожидания. В конце CSS-анимации выбираются на итоговый момент и кадр
+    /// перерисовывается. Возвращает число выполненных таймеров.
+    pub async fn advance(&self, duration: Duration) -> ZverResult<usize> {
+        let target = self.clock.now() + duration;
+        let executed = self.run_tasks(Some(target)).await?;
+        self.clock.advance_to(target);
+        self.update().await?;
+        Ok(executed)
     }
-}
 
-fn extract_js_from_dom(dom: &dom::Document, node_id: Option<usize>, js_content: &mut String) {
-    let mut script_nodes = match node_id {
-        Some(id) => {
-            let mut nodes = Vec::new();
-            if dom.nodes.get(&id).and_then(|node| node.tag_name.as_deref()) == Some("script") {
-                nodes.push(id);
+    /// Один проход цикла событий: микрозадачи, затем таймеры по одному, каждый
+    /// со своими микрозадачами и шагом рендеринга.
+    ///
+    /// Без `deadline` выполняются таймеры, готовые к текущему моменту.
+    async fn run_tasks(&self, deadline: Option<Duration>) -> ZverResult<usize> {
+        self.js.write().await.run_microtasks();
+        self.update().await?;
+
+        let mut executed = 0;
+        loop {
+            // JS lock отпускается до update(): DOM захватывается раньше JS
+            let ran = {
+                let mut js_engine = self.js.write().await;
+                let until = deadline.unwrap_or_else(|| js_engine.now());
+                js_engine.run_next_timer(until)
+            };
+            if !ran {
+                break;
+            }
+            executed += 1;
+            self.update().await?;
+
+            if deadline.is_none() && executed >= MAX_IDLE_TASKS {
+                tracing::warn!("Event loop did not become idle after {} tasks", executed);
+                break;
             }
-            nodes.extend(dom.select_ids_from(id, "script"));
-            nodes
         }
-        None => dom.select_ids("script"),
-    };
+        Ok(executed)
+    }
 
-    script_nodes.sort_unstable();
-    script_nodes.dedup();
+    /// Применяет накопленные изменения DOM без повторной загрузки страницы.
+    ///
+    /// Изменения через [`dom::Document::append_child`], `set_attribute`,
+    /// `set_text_content` и `set_element_state` помечают узлы грязными. Здесь
+    /// каскад пересчитывается только для их поддеревьев, а в layout заново
+    /// строятся лишь Taffy-узлы элементов, чьи стили или содержимое изменились.
+    /// CSS-анимации выбираются на текущий момент [`Zver::clock`].
+    ///
+    /// Прокрутка через [`Zver::scroll`] сдвигает позиции без пересчёта Taffy,
+    /// после кадра прокрученные контейнеры получают `scroll`, а viewport —
+    /// `document` и `window`. Изменения из этих слушателей применяются здесь же.
+    ///
+    /// Возвращает `false`, если изменений не было и кадр не перерисовывался.
+    pub async fn update(&self) -> ZverResult<bool> {
+        let mut updated = false;
+        for _ in 0..MAX_SCROLL_PASSES {
+            let (rendered, scrolled) = self.update_rendering().await?;
+            updated |= rendered;
+            if scrolled.is_empty() {
+                break;
+            }
+            self.dispatch_scroll_events(&scrolled).await;
+        }
+        Ok(updated)
+    }
 
-    for script_id in script_nodes {
-        if let Some(src) = dom.attribute(script_id, "src") {
-            js_content.push_str(&format!("// External script: {src}\n"));
+    /// Один кадр [`Zver::update`]; возвращает, перерисован ли он, и
+    /// прокрученные с прошлого кадра контейнеры
+    async fn update_rendering(&self) -> ZverResult<(bool, Vec<usize>)> {
+        // Скрипты могли добавить <img> или сменить src; кадр и так перерисуется
+        if self.dom.read().await.is_dirty() {
+            self.load_images().await;
+        }
+
+        let mut dom = self.dom.write().await;
+        let dirty = dom.take_dirty();
+        let mut css = self.css.write().await;
+        let scrolled = self.scroll.take_changed();
+        if dirty.is_empty() && !css.has_animations() && scrolled.is_empty() {
+            return Ok((false, scrolled));
+        }
+
+        let mut changed = Vec::new();
+        if !dirty.is_empty() {
+            let roots = style_roots(&dom, &css, &dirty);
+            changed = css.restyle(&dom, &roots);
+            dom.sync_pseudo_elements(&css.pseudo_element_contents());
+        }
+        let animated = css.sample_animations(self.clock.now());
+        if dirty.is_empty() && animated.is_empty() && scrolled.is_empty() {
+            return Ok((false, scrolled));
+        }
+
+        let mut layout = self.layout.write().await;
+        if dirty.is_empty() && animated.is_empty() {
+            layout.apply_scroll(&dom);
         } else {
-            let content = dom.get_text_content(script_id);
-            if !content.is_empty() {
-                js_content.push_str(&content);
-                if !content.ends_with('\n') {
-                    js_content.push('\n');
-                }
+            let layout_roots: Vec<usize> = dirty
+                .keys()
+                .copied()
+                .chain(changed)
+                .chain(animated)
+                .collect();
+            layout.relayout(
+                &dom,
+                &css.computed_styles,
+                &css.pseudo_element_styles,
+                &layout_roots,
+            );
+        }
+
+        let mut render = self.render.write().await;
+        render.paint(&layout, &dom, &css.fonts).await?;
+        Ok((true, scrolled))
+    }
+
+    /// `scroll` прокрученным элементам (без всплытия) и, для viewport,
+    /// `document` со всплытием к `window`
+    async fn dispatch_scroll_events(&self, scrolled: &[usize]) {
+        let viewport = self.scroll.viewport();
+        let mut js_engine = self.js.write().await;
+        for &node_id in scrolled {
+            if Some(node_id) == viewport {
+                let init = js::events::EventInit {
+                    bubbles: true,
+                    ..Default::default()
+                };
+                js_engine.dispatch_dom_event(&[js::events::DOCUMENT_TARGET], "scroll", &init);
+            } else {
+                js_engine.dispatch_dom_event(&[node_id], "scroll", &Default::default());
             }
         }
     }
+
+    /// Меняет размер viewport и плотность пикселей, как при изменении размера окна.
+    ///
+    /// Новые размеры получают CSS, layout, рендер и скрипты (`innerWidth`,
+    /// `devicePixelRatio`). Каскад пересчитывается, только если изменился набор
+    /// подходящих @media правил; layout пересчитывается всегда, затем в `window`
+    /// приходит событие `resize`, а его изменения DOM применяются как в
+    /// [`Zver::run_until_idle`].
+    ///
+    /// Возвращает `true`, если изменились применимые @media правила.
+    pub async fn set_viewport(
+        &self,
+        width: f32,
+        height: f32,
+        device_pixel_ratio: f32,
+    ) -> ZverResult<bool> {
+        let (media_changed, dpr) = {
+            let mut dom = self.dom.write().await;
+            let mut css = self.css.write().await;
+            let media_changed = css.set_viewport(width, height);
+            if media_changed && let Some(root) = dom.root {
+                css.restyle(&dom, &[root]);
+                dom.sync_pseudo_elements(&css.pseudo_element_contents());
+            }
+
+            let mut layout = self.layout.write().await;
+            layout.set_viewport(width, height);
+            layout.compute_layout(&dom, &css.computed_styles, &css.pseudo_element_styles);
+
+            let mut render = self.render.write().await;
+            if device_pixel_ratio > 0.0 {
+                render.set_device_pixel_ratio(device_pixel_ratio);
+            }
+            render.paint(&layout, &dom, &css.fonts).await?;
+            (media_changed, render.device_pixel_ratio())
+        };
+
+        {
+            let mut js_engine = self.js.write().await;
+            js_engine.set_viewport(width, height, dpr);
+            js_engine.dispatch_window_event("resize");
+        }
+        self.run_until_idle().await?;
+        Ok(media_changed)
+    }
+
+    /// Возвращает верхний узел в точке `(x, y)` (CSS-пиксели) и цепочку его предков.
+    ///
+    /// Учитывает порядок наложения, `pointer-events`, обрезку `overflow` и строки
+    /// текста; см. [`render::hit_test::hit_test`].
+    pub async fn hit_test(&self, x: f32, y: f32) -> Option<render::HitTestResult> {
+        let dom = self.dom.read().await;
+        let layout = self.layout.read().await;
+        render::hit_test::hit_test(&layout, &dom, x, y)
+    }
+
+    /// Снимок дерева боксов в порядке документа для snapshot-тестов;
+    /// см. [`layout::snapshot`]
+    pub async fn layout_snapshot(&self) -> layout::LayoutSnapshot {
+        let dom = self.dom.read().await;
+        let layout = self.layout.read().await;
+        layout::LayoutSnapshot::capture(&dom, &layout)
+    }
+
+    /// Возвращает последний кадр, нарисованный программным растеризатором.
+    ///
+    /// Кадр растеризуется по запросу из display list последнего paint
+    /// ([`Zver::load_url`] или [`Zver::update`]); для сохранения используйте
+    /// [`render::Framebuffer::encode_png`] или [`render::Framebuffer::save_png`].
+    pub async fn screenshot(&self) -> Option<render::Framebuffer> {
+        self.render.read().await.frame().cloned()
+    }
+}
+
+/// Корни поддеревьев для пересчёта каскада по грязным узлам.
+///
+/// При селекторах `+`/`~` изменение элемента затрагивает следующих соседей,
+/// поэтому пересчитывается поддерево родителя.
+fn style_roots(
+    dom: &dom::Document,
+    css: &css::StyleEngine,
+    dirty: &std::collections::HashMap<usize, dom::Dirty>,
+) -> Vec<usize> {
+    let siblings = css.has_sibling_combinators();
+    dirty
+        .iter()
+        .filter(|(node_id, flags)| flags.contains(dom::Dirty::STYLE) && dom.is_connected(**node_id))
+        .map(|(&node_id, _)| {
+            let parent = dom.nodes.get(&node_id).and_then(|node| node.parent);
+            parent.filter(|_| siblings).unwrap_or(node_id)
+        })
+        .collect()
+}
+
+impl Default for Zver {
+    fn default() -> Self {
+        Self::new()
+    }
 }
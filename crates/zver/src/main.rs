@@ -0,0 +1,444 @@
+//! `zver` — загрузка страницы без окна: PNG-снимок и дампы DOM, стилей,
+//! layout, видимого текста и консоли для конвейеров и snapshot-тестов.
+//!
+//! Время страницы виртуальное: таймеры и анимации срабатывают только при
+//! `--wait`, поэтому повторный запуск даёт тот же результат.
+
+use std::fmt::Write as _;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use zver::Zver;
+use zver::clock::Clock;
+use zver::css::color::parse_css_color;
+use zver::dom::Document;
+use zver::dom::serialization::serialize_dom;
+use zver::layout::types::{ComputedStyle, FontWeight, Size};
+use zver::layout::{Display, LayoutEngine, LayoutSnapshot};
+
+const USAGE: &str = "\
+Usage: zver [OPTIONS] <URL|FILE>
+
+Loads a page headlessly and prints the requested dumps to stdout.
+
+Options:
+  --screenshot <FILE>  Save a PNG screenshot of the viewport
+  --dump <WHAT>        Print dom, styles, layout, text or console; repeatable
+  --width <PX>         Viewport width in CSS pixels [default: 800]
+  --height <PX>        Viewport height in CSS pixels [default: 600]
+  --dpr <RATIO>        Device pixel ratio of the screenshot [default: 1]
+  --wait <MS>          Advance page time by MS, running timers and animations
+  --no-js              Do not execute page scripts
+  -h, --help           Print this help
+
+Without --screenshot or --dump the serialized DOM is printed.";
+
+/// Что печатать после загрузки
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dump {
+    Dom,
+    Styles,
+    Layout,
+    Text,
+    Console,
+}
+
+impl Dump {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "dom" => Ok(Dump::Dom),
+            "styles" => Ok(Dump::Styles),
+            "layout" => Ok(Dump::Layout),
+            "text" => Ok(Dump::Text),
+            "console" => Ok(Dump::Console),
+            other => Err(format!(
+                "unknown dump '{other}': expected dom, styles, layout, text or console"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    url: String,
+    screenshot: Option<String>,
+    dumps: Vec<Dump>,
+    width: f32,
+    height: f32,
+    device_pixel_ratio: f32,
+    wait: Option<Duration>,
+    javascript: bool,
+}
+
+impl Options {
+    /// Разбирает аргументы; `Ok(None)` — запрошена справка
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut options = Options {
+            url: String::new(),
+            screenshot: None,
+            dumps: Vec::new(),
+            width: 800.0,
+            height: 600.0,
+            device_pixel_ratio: 1.0,
+            wait: None,
+            javascript: true,
+        };
+        let mut url = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // `--name=value` равносильно `--name value`
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .map(str::to_string)
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("{name} requires a value"))
+            };
+
+            match name.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--screenshot" => options.screenshot = Some(value()?),
+                "--dump" => options.dumps.push(Dump::parse(&value()?)?),
+                "--width" => options.width = positive(&name, &value()?)?,
+                "--height" => options.height = positive(&name, &value()?)?,
+                "--dpr" => options.device_pixel_ratio = positive(&name, &value()?)?,
+                "--wait" => {
+                    let millis: u64 = value()?
+                        .parse()
+                        .map_err(|_| format!("{name} expects milliseconds"))?;
+                    options.wait = Some(Duration::from_millis(millis));
+                }
+                "--no-js" => options.javascript = false,
+                flag if flag.starts_with('-') && flag.len() > 1 => {
+                    return Err(format!("unknown option '{flag}'"));
+                }
+                _ if url.is_some() => return Err(format!("unexpected argument '{arg}'")),
+                _ => url = Some(arg),
+            }
+        }
+
+        options.url = url.ok_or("missing URL or file")?;
+        if options.screenshot.is_none() && options.dumps.is_empty() {
+            options.dumps.push(Dump::Dom);
+        }
+        Ok(Some(options))
+    }
+}
+
+fn positive(name: &str, value: &str) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite() && *number > 0.0)
+        .ok_or_else(|| format!("{name} expects a positive number, got '{value}'"))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("zver: {message}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options).await {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("zver: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Загружает страницу и собирает вывод всех запрошенных дампов
+async fn run(options: &Options) -> Result<String, String> {
+    let engine = Zver::builder()
+        .viewport(options.width, options.height)
+        .device_pixel_ratio(options.device_pixel_ratio)
+        .javascript_enabled(options.javascript)
+        .clock(Clock::new_virtual())
+        .build();
+    // Сообщения консоли печатаются только дампом `console`, stdout остаётся чистым
+    engine.js.read().await.console().set_echo(false);
+
+    engine
+        .load_url(&options.url)
+        .await
+        .map_err(|err| format!("failed to load {}: {err}", options.url))?;
+    engine
+        .run_until_idle()
+        .await
+        .map_err(|err| err.to_string())?;
+    if let Some(wait) = options.wait {
+        engine.advance(wait).await.map_err(|err| err.to_string())?;
+    }
+
+    if let Some(path) = &options.screenshot {
+        let frame = engine
+            .screenshot()
+            .await
+            .ok_or("the page produced no frame")?;
+        frame
+            .save_png(path)
+            .map_err(|err| format!("failed to write {path}: {err}"))?;
+    }
+
+    let dom = engine.dom.read().await;
+    let layout = engine.layout.read().await;
+    let mut output = String::new();
+    for dump in &options.dumps {
+        match dump {
+            Dump::Dom => {
+                output.push_str(&serialize_dom(&dom));
+                output.push('\n');
+            }
+            Dump::Styles => dump_styles(&mut output, &dom, layout.resolved_styles()),
+            Dump::Layout => output.push_str(&LayoutSnapshot::capture(&dom, &layout).to_string()),
+            Dump::Text => dump_text(&mut output, &dom, &layout),
+            Dump::Console => {
+                for message in engine.js.read().await.console().entries() {
+                    let _ = writeln!(output, "[{}] {}", message.level.as_str(), message.text);
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Узлы документа в порядке обхода дерева
+fn tree_order(dom: &Document) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut stack: Vec<usize> = dom.root.into_iter().collect();
+    while let Some(node_id) = stack.pop() {
+        order.push(node_id);
+        if let Some(node) = dom.nodes.get(&node_id) {
+            stack.extend(node.children.iter().rev());
+        }
+    }
+    order
+}
+
+/// `tag#id.class` элемента
+fn label(dom: &Document, node_id: usize) -> Option<String> {
+    let node = dom.nodes.get(&node_id)?;
+    let mut label = node.tag_name()?.to_string();
+    if let Some(id) = node.attributes.get("id") {
+        let _ = write!(label, "#{id}");
+    }
+    if let Some(class) = node.attributes.get("class") {
+        for class in class.split_whitespace() {
+            let _ = write!(label, ".{class}");
+        }
+    }
+    Some(label)
+}
+
+/// Вычисленные значения свойств каждого элемента, с которыми работает layout:
+/// единицы и наследование уже применены. Печатаются свойства, отличные от
+/// начальных, по алфавиту
+fn dump_styles(
+    output: &mut String,
+    dom: &Document,
+    styles: &std::collections::HashMap<usize, ComputedStyle>,
+) {
+    let initial = computed_properties(&ComputedStyle::default());
+    for node_id in tree_order(dom) {
+        let (Some(label), Some(style)) = (label(dom, node_id), styles.get(&node_id)) else {
+            continue;
+        };
+        let mut properties = computed_properties(style);
+        properties.retain(|property| !initial.contains(property));
+        properties.sort();
+        let _ = writeln!(output, "{label} {{");
+        for (name, value) in properties {
+            let _ = writeln!(output, "  {name}: {value};");
+        }
+        let _ = writeln!(output, "}}");
+    }
+}
+
+/// Свойства [`ComputedStyle`] с сериализованными значениями
+fn computed_properties(style: &ComputedStyle) -> Vec<(String, String)> {
+    let mut properties: Vec<(String, String)> = [
+        ("display", keyword(style.display)),
+        ("position", keyword(style.position)),
+        ("width", size(style.width)),
+        ("height", size(style.height)),
+        ("min-width", size(style.min_width)),
+        ("min-height", size(style.min_height)),
+        ("max-width", size(style.max_width)),
+        ("max-height", size(style.max_height)),
+        ("margin-top", length(style.margin.top.into_raw())),
+        ("margin-right", length(style.margin.right.into_raw())),
+        ("margin-bottom", length(style.margin.bottom.into_raw())),
+        ("margin-left", length(style.margin.left.into_raw())),
+        ("padding-top", length(style.padding.top.into_raw())),
+        ("padding-right", length(style.padding.right.into_raw())),
+        ("padding-bottom", length(style.padding.bottom.into_raw())),
+        ("padding-left", length(style.padding.left.into_raw())),
+        ("border-top-width", length(style.border.top.into_raw())),
+        ("border-right-width", length(style.border.right.into_raw())),
+        (
+            "border-bottom-width",
+            length(style.border.bottom.into_raw()),
+        ),
+        ("border-left-width", length(style.border.left.into_raw())),
+        ("row-gap", length(style.gap.height.into_raw())),
+        ("column-gap", length(style.gap.width.into_raw())),
+        ("color", color(style.color.as_deref())),
+        ("background-color", color(style.background_color.as_deref())),
+        ("border-color", color(style.border_color.as_deref())),
+        (
+            "font-family",
+            style
+                .font_family
+                .clone()
+                .unwrap_or_else(|| "initial".into()),
+        ),
+        ("font-size", format!("{}px", style.font_size)),
+        (
+            "font-weight",
+            match style.font_weight {
+                FontWeight::Normal => "400".into(),
+                FontWeight::Bold => "700".into(),
+            },
+        ),
+        ("font-style", keyword(style.font_style)),
+        ("list-style-type", keyword(style.list_style_type)),
+        ("flex-direction", keyword(style.flex_direction)),
+        (
+            "flex-wrap",
+            match style.flex_wrap {
+                taffy::style::FlexWrap::NoWrap => "nowrap".into(),
+                wrap => keyword(wrap),
+            },
+        ),
+        ("flex-grow", style.flex_grow.to_string()),
+        ("flex-shrink", style.flex_shrink.to_string()),
+        (
+            "justify-content",
+            style.justify_content.map_or("normal".into(), keyword),
+        ),
+        (
+            "align-items",
+            style.align_items.map_or("normal".into(), keyword),
+        ),
+        (
+            "align-self",
+            style.align_self.map_or("auto".into(), keyword),
+        ),
+        (
+            "aspect-ratio",
+            style
+                .aspect_ratio
+                .map_or("auto".into(), |ratio| ratio.to_string()),
+        ),
+        (
+            "z-index",
+            style.z_index.map_or("auto".into(), |z| z.to_string()),
+        ),
+        ("opacity", style.opacity.to_string()),
+        ("overflow-x", keyword(style.overflow.x)),
+        ("overflow-y", keyword(style.overflow.y)),
+        (
+            "pointer-events",
+            style.pointer_events.map_or("auto".into(), keyword),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value))
+    .collect();
+
+    // Длины с процентами вычисляются только при layout — печатается выражение
+    for (property, expr) in &style.calc {
+        let name = keyword(property);
+        if let Some(entry) = properties.iter_mut().find(|(known, _)| *known == name) {
+            entry.1 = expr.to_string();
+        }
+    }
+    properties
+}
+
+/// Ключевое слово CSS из имени варианта: `SpaceBetween` → `space-between`
+fn keyword(value: impl std::fmt::Debug) -> String {
+    let mut keyword = String::new();
+    for ch in format!("{value:?}").chars() {
+        if ch.is_ascii_uppercase() && !keyword.is_empty() {
+            keyword.push('-');
+        }
+        keyword.push(ch.to_ascii_lowercase());
+    }
+    keyword
+}
+
+fn size(value: Size) -> String {
+    match value {
+        Size::Auto => "auto".into(),
+        Size::Px(px) => format!("{px}px"),
+        Size::Percent(fraction) => format!("{}%", fraction * 100.0),
+    }
+}
+
+fn length(value: taffy::style::CompactLength) -> String {
+    match value.tag() {
+        taffy::style::CompactLength::LENGTH_TAG => format!("{}px", value.value()),
+        taffy::style::CompactLength::PERCENT_TAG => format!("{}%", value.value() * 100.0),
+        _ => "auto".into(),
+    }
+}
+
+/// Цвет как `rgb()`/`rgba()`; нераспознанное значение печатается как есть
+fn color(value: Option<&str>) -> String {
+    let Some(value) = value else {
+        return "initial".into();
+    };
+    match parse_css_color(value) {
+        Some(color) if color.a == 255 => format!("rgb({}, {}, {})", color.r, color.g, color.b),
+        Some(color) => format!(
+            "rgba({}, {}, {}, {})",
+            color.r,
+            color.g,
+            color.b,
+            (color.a as f32 / 255.0 * 100.0).round() / 100.0
+        ),
+        None => value.to_string(),
+    }
+}
+
+/// Видимый текст: строки текстовых узлов, получивших layout
+fn dump_text(output: &mut String, dom: &Document, layout: &LayoutEngine) {
+    let hidden = |node_id: usize| {
+        std::iter::successors(Some(node_id), |id| dom.nodes.get(id)?.parent).any(|id| {
+            layout
+                .resolved_styles()
+                .get(&id)
+                .is_some_and(|style| style.display == Display::None)
+        })
+    };
+    for node_id in tree_order(dom) {
+        if let Some(text) = layout.text_layout(node_id)
+            && !hidden(node_id)
+        {
+            for line in text
+                .lines
+                .iter()
+                .filter(|line| !line.text.trim().is_empty())
+            {
+                let _ = writeln!(output, "{}", line.text.trim());
+            }
+        }
+    }
+}
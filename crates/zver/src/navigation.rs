@@ -0,0 +1,508 @@
+//! Переходы по ссылкам и отправка форм.
+//!
+//! Действие по умолчанию у `click` по `<a href>` и у `submit` формы
+//! превращается в [`NavigationRequest`]: куда перейти, каким методом и в каком
+//! окне. Загружает запрос [`Zver::navigate`], а окно с вкладками решает, где
+//! открыть переход с [`NavigationTarget::Blank`].
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::Zver;
+use crate::dom::Document;
+use crate::error::ZverResult;
+use crate::js::events::EventInit;
+use crate::network::{RequestBody, Url};
+
+/// Где открыть переход
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationTarget {
+    /// Текущая вкладка: без `target`, `_self`, `_parent` или `_top`
+    Current,
+    /// Новая вкладка: `_blank` или имя окна
+    Blank,
+}
+
+/// Переход, который должен выполнить браузер
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavigationRequest {
+    /// Абсолютный URL; для GET-формы уже со строкой запроса
+    pub url: Url,
+    /// Тело POST-запроса; `None` — GET
+    pub body: Option<RequestBody>,
+    pub target: NavigationTarget,
+}
+
+/// Кодирование тела формы (`enctype`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormEncoding {
+    /// `application/x-www-form-urlencoded`, значение по умолчанию
+    UrlEncoded,
+    /// `multipart/form-data`
+    Multipart,
+    /// `text/plain`
+    TextPlain,
+}
+
+impl FormEncoding {
+    /// Разбирает `enctype`; неизвестное значение означает urlencoded
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "multipart/form-data" => Self::Multipart,
+            "text/plain" => Self::TextPlain,
+            _ => Self::UrlEncoded,
+        }
+    }
+}
+
+impl Zver {
+    /// Загружает документ по запросу перехода: GET — как [`Zver::load_url`],
+    /// POST — отправляя тело через [`crate::network::NetworkEngine::post`].
+    ///
+    /// `target` запроса не учитывается: новую вкладку для
+    /// [`NavigationTarget::Blank`] открывает вызывающий.
+    pub async fn navigate(&self, request: &NavigationRequest) -> ZverResult<()> {
+        let Some(body) = &request.body else {
+            return self.load_url(request.url.as_str()).await;
+        };
+        let response = {
+            let mut network = self.network.write().await;
+            network.post(&request.url, body).await?
+        };
+        self.load_response(response).await
+    }
+
+    /// Действие по умолчанию неотменённого `click`: переход по ближайшей
+    /// ссылке или отправка формы ближайшей кнопкой submit на пути события
+    pub(crate) async fn activation_behavior(&self, path: &[usize]) -> Option<NavigationRequest> {
+        enum Activation {
+            Link(NavigationRequest),
+            Submit { form: usize, submitter: usize },
+        }
+
+        let activation = {
+            let dom = self.dom.read().await;
+            path.iter().find_map(|&id| {
+                if let Some(request) = link_navigation(&dom, id) {
+                    Some(Activation::Link(request))
+                } else if is_submit_button(&dom, id) {
+                    let form = form_owner(&dom, id)?;
+                    Some(Activation::Submit {
+                        form,
+                        submitter: id,
+                    })
+                } else {
+                    None
+                }
+            })?
+        };
+        match activation {
+            Activation::Link(request) => Some(request),
+            Activation::Submit { form, submitter } => self.submit_form(form, Some(submitter)).await,
+        }
+    }
+
+    /// Неявная отправка по Enter в поле ввода: от имени первой кнопки submit
+    /// формы; форма с выключенной кнопкой по умолчанию не отправляется
+    pub(crate) async fn implicit_submission(&self, field: usize) -> Option<NavigationRequest> {
+        let (form, submitter) = {
+            let dom = self.dom.read().await;
+            let node = dom.nodes.get(&field)?;
+            if node.tag_name() != Some("input")
+                || !is_text_field(&input_type(&dom, field))
+                || is_disabled(&dom, field)
+            {
+                return None;
+            }
+            let form = form_owner(&dom, field)?;
+            let submitter = tree_order(&dom)
+                .into_iter()
+                .find(|&id| is_submit_control(&dom, id) && form_owner(&dom, id) == Some(form));
+            if submitter.is_some_and(|id| is_disabled(&dom, id)) {
+                return None;
+            }
+            (form, submitter)
+        };
+        self.submit_form(form, submitter).await
+    }
+
+    /// Рассылает отменяемый `submit` и, если его не отменили, собирает запрос
+    async fn submit_form(
+        &self,
+        form: usize,
+        submitter: Option<usize>,
+    ) -> Option<NavigationRequest> {
+        let init = EventInit {
+            bubbles: true,
+            cancelable: true,
+            ..EventInit::default()
+        };
+        let path = self.element_path(Some(form)).await;
+        if self
+            .js
+            .write()
+            .await
+            .dispatch_dom_event(&path, "submit", &init)
+            .default_prevented
+        {
+            return None;
+        }
+        let dom = self.dom.read().await;
+        form_submission(&dom, form, submitter)
+    }
+}
+
+/// Переход по `<a href>`/`<area href>`; `None`, если `node_id` не ссылка
+pub fn link_navigation(document: &Document, node_id: usize) -> Option<NavigationRequest> {
+    let node = document.nodes.get(&node_id)?;
+    if !matches!(node.tag_name(), Some("a" | "area")) {
+        return None;
+    }
+    let url = document.resolve_url(node.attributes.get("href")?)?;
+    Some(NavigationRequest {
+        url,
+        body: None,
+        target: navigation_target(node.attributes.get("target").map(String::as_str)),
+    })
+}
+
+/// Запрос отправки формы `form` от имени `submitter` (кнопки или `None`).
+///
+/// `formaction`, `formmethod`, `formenctype` и `formtarget` кнопки
+/// перекрывают атрибуты формы. GET заменяет строку запроса `action`,
+/// POST кодирует поля по `enctype`. `method="dialog"` не даёт перехода.
+pub fn form_submission(
+    document: &Document,
+    form: usize,
+    submitter: Option<usize>,
+) -> Option<NavigationRequest> {
+    let attribute = |submitter_name: &str, form_name: &str| {
+        submitter
+            .and_then(|id| document.attribute(id, submitter_name))
+            .or_else(|| document.attribute(form, form_name))
+    };
+
+    let action = attribute("formaction", "action").filter(|action| !action.trim().is_empty());
+    let url = match action {
+        Some(action) => document.resolve_url(&action)?,
+        None => document.url().clone(),
+    };
+    let target = navigation_target(attribute("formtarget", "target").as_deref());
+    let entries = form_data(document, form, submitter);
+
+    match attribute("formmethod", "method")
+        .map(|method| method.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("dialog") => None,
+        Some("post") => {
+            let encoding = attribute("formenctype", "enctype")
+                .map_or(FormEncoding::UrlEncoded, |value| {
+                    FormEncoding::parse(&value)
+                });
+            Some(NavigationRequest {
+                url,
+                body: Some(encode_form(&entries, encoding)),
+                target,
+            })
+        }
+        _ => Some(NavigationRequest {
+            url: url.with_query(Some(&encode_urlencoded(&entries))),
+            body: None,
+            target,
+        }),
+    }
+}
+
+/// Пары имя–значение полей формы в порядке документа.
+///
+/// Пропускаются поля без имени, выключенные (в том числе внутри выключенного
+/// `<fieldset>`), неотмеченные флажки и переключатели, а также кнопки, кроме
+/// `submitter`. Значения берутся из атрибутов разметки: `value`, `checked`,
+/// `selected` и текста `<textarea>`. Выбор файлов не поддерживается, поэтому
+/// `<input type="file">` не отправляется.
+pub fn form_data(
+    document: &Document,
+    form: usize,
+    submitter: Option<usize>,
+) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    for id in tree_order(document) {
+        let Some(node) = document.nodes.get(&id) else {
+            continue;
+        };
+        let Some(tag) = node.tag_name() else {
+            continue;
+        };
+        if !matches!(tag, "input" | "button" | "select" | "textarea")
+            || form_owner(document, id) != Some(form)
+            || is_disabled(document, id)
+        {
+            continue;
+        }
+        let Some(name) = node.attributes.get("name").filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        let value = || node.attributes.get("value").cloned().unwrap_or_default();
+
+        match tag {
+            "button" => {
+                if Some(id) == submitter {
+                    entries.push((name.clone(), value()));
+                }
+            }
+            "textarea" => entries.push((name.clone(), document.get_text_content(id))),
+            "select" => {
+                for option in selected_options(document, id) {
+                    entries.push((name.clone(), option_value(document, option)));
+                }
+            }
+            _ => match input_type(document, id).as_str() {
+                "submit" | "reset" | "button" | "file" => {
+                    if Some(id) == submitter {
+                        entries.push((name.clone(), value()));
+                    }
+                }
+                // Координаты щелчка по кнопке-картинке не отслеживаются
+                "image" => {
+                    if Some(id) == submitter {
+                        entries.push((format!("{name}.x"), "0".to_string()));
+                        entries.push((format!("{name}.y"), "0".to_string()));
+                    }
+                }
+                "checkbox" | "radio" => {
+                    if node.attributes.contains_key("checked") {
+                        let value = node
+                            .attributes
+                            .get("value")
+                            .cloned()
+                            .unwrap_or_else(|| "on".to_string());
+                        entries.push((name.clone(), value));
+                    }
+                }
+                _ => entries.push((name.clone(), value())),
+            },
+        }
+    }
+    entries
+}
+
+/// `application/x-www-form-urlencoded`: пробел — `+`, остальное percent-encoding
+pub fn encode_urlencoded(entries: &[(String, String)]) -> String {
+    url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(entries)
+        .finish()
+}
+
+/// `multipart/form-data` с заданной границей (RFC 7578)
+pub fn encode_multipart(entries: &[(String, String)], boundary: &str) -> Vec<u8> {
+    let mut body = String::new();
+    for (name, value) in entries {
+        body.push_str(&format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+            escape_multipart_name(name),
+            normalize_newlines(value)
+        ));
+    }
+    body.push_str(&format!("--{boundary}--\r\n"));
+    body.into_bytes()
+}
+
+/// Тело POST-запроса с подходящим `Content-Type`
+pub fn encode_form(entries: &[(String, String)], encoding: FormEncoding) -> RequestBody {
+    match encoding {
+        FormEncoding::UrlEncoded => RequestBody {
+            content_type: "application/x-www-form-urlencoded".to_string(),
+            data: encode_urlencoded(entries).into_bytes(),
+        },
+        FormEncoding::Multipart => {
+            let boundary = multipart_boundary();
+            RequestBody {
+                content_type: format!("multipart/form-data; boundary={boundary}"),
+                data: encode_multipart(entries, &boundary),
+            }
+        }
+        FormEncoding::TextPlain => RequestBody {
+            content_type: "text/plain;charset=UTF-8".to_string(),
+            data: entries
+                .iter()
+                .map(|(name, value)| format!("{name}={value}\r\n"))
+                .collect::<String>()
+                .into_bytes(),
+        },
+    }
+}
+
+/// Форма, которой принадлежит поле: по атрибуту `form` — первая форма
+/// документа с таким `id` в порядке дерева, иначе ближайшая форма-предок
+pub fn form_owner(document: &Document, node_id: usize) -> Option<usize> {
+    let node = document.nodes.get(&node_id)?;
+    if let Some(form_id) = node.attributes.get("form") {
+        return tree_order(document).into_iter().find(|id| {
+            document.nodes.get(id).is_some_and(|candidate| {
+                candidate.tag_name() == Some("form")
+                    && candidate.attributes.get("id") == Some(form_id)
+            })
+        });
+    }
+    std::iter::successors(node.parent, |&id| document.nodes.get(&id)?.parent)
+        .find(|id| document.nodes.get(id).and_then(|node| node.tag_name()) == Some("form"))
+}
+
+fn navigation_target(target: Option<&str>) -> NavigationTarget {
+    match target.map(str::trim) {
+        None | Some("") => NavigationTarget::Current,
+        Some(name)
+            if ["_self", "_parent", "_top"]
+                .iter()
+                .any(|keyword| name.eq_ignore_ascii_case(keyword)) =>
+        {
+            NavigationTarget::Current
+        }
+        Some(_) => NavigationTarget::Blank,
+    }
+}
+
+/// Тип `<input>` в нижнем регистре, по умолчанию `text`
+fn input_type(document: &Document, node_id: usize) -> String {
+    document
+        .attribute(node_id, "type")
+        .map(|kind| kind.trim().to_ascii_lowercase())
+        .filter(|kind| !kind.is_empty())
+        .unwrap_or_else(|| "text".to_string())
+}
+
+/// Отправляет ли элемент форму при активации
+fn is_submit_control(document: &Document, node_id: usize) -> bool {
+    match document
+        .nodes
+        .get(&node_id)
+        .and_then(|node| node.tag_name())
+    {
+        Some("button") => document
+            .attribute(node_id, "type")
+            .is_none_or(|kind| kind.trim().eq_ignore_ascii_case("submit")),
+        Some("input") => matches!(input_type(document, node_id).as_str(), "submit" | "image"),
+        _ => false,
+    }
+}
+
+fn is_submit_button(document: &Document, node_id: usize) -> bool {
+    is_submit_control(document, node_id) && !is_disabled(document, node_id)
+}
+
+/// Поля, в которых Enter отправляет форму
+fn is_text_field(kind: &str) -> bool {
+    matches!(
+        kind,
+        "text"
+            | "search"
+            | "url"
+            | "tel"
+            | "email"
+            | "password"
+            | "number"
+            | "date"
+            | "month"
+            | "week"
+            | "time"
+            | "datetime-local"
+    )
+}
+
+/// Выключено ли поле атрибутом `disabled` у себя или у `<fieldset>`-предка
+fn is_disabled(document: &Document, node_id: usize) -> bool {
+    std::iter::successors(Some(node_id), |&id| document.nodes.get(&id)?.parent).any(|id| {
+        document.nodes.get(&id).is_some_and(|node| {
+            (id == node_id || node.tag_name() == Some("fieldset"))
+                && node.attributes.contains_key("disabled")
+        })
+    })
+}
+
+/// Выбранные `<option>` списка; у одиночного списка без `selected` — первый
+fn selected_options(document: &Document, select: usize) -> Vec<usize> {
+    let options: Vec<usize> = descendants(document, select)
+        .into_iter()
+        .filter(|id| document.nodes.get(id).and_then(|node| node.tag_name()) == Some("option"))
+        .filter(|&id| !document.nodes[&id].attributes.contains_key("disabled"))
+        .collect();
+    let selected: Vec<usize> = options
+        .iter()
+        .copied()
+        .filter(|id| document.nodes[id].attributes.contains_key("selected"))
+        .collect();
+    if document.nodes[&select].attributes.contains_key("multiple") {
+        selected
+    } else {
+        // Выбран последний отмеченный, как при разборе HTML
+        selected
+            .last()
+            .or(options.first())
+            .copied()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// `value` опции, иначе её текст со схлопнутыми пробелами
+fn option_value(document: &Document, option: usize) -> String {
+    document.attribute(option, "value").unwrap_or_else(|| {
+        document
+            .get_text_content(option)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    })
+}
+
+/// Узлы документа в порядке обхода дерева
+fn tree_order(document: &Document) -> Vec<usize> {
+    document
+        .root
+        .map(|root| {
+            let mut nodes = vec![root];
+            nodes.extend(descendants(document, root));
+            nodes
+        })
+        .unwrap_or_default()
+}
+
+/// Потомки узла в порядке обхода дерева
+fn descendants(document: &Document, node_id: usize) -> Vec<usize> {
+    let mut result = Vec::new();
+    let mut stack: Vec<usize> = document
+        .nodes
+        .get(&node_id)
+        .map(|node| node.children.iter().rev().copied().collect())
+        .unwrap_or_default();
+    while let Some(id) = stack.pop() {
+        result.push(id);
+        if let Some(node) = document.nodes.get(&id) {
+            stack.extend(node.children.iter().rev());
+        }
+    }
+    result
+}
+
+/// Переводы строк значения в CRLF, как требует multipart/form-data
+fn normalize_newlines(value: &str) -> String {
+    value
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\n', "\r\n")
+}
+
+/// Кавычки и переводы строк в имени поля кодируются, чтобы не разорвать заголовок
+fn escape_multipart_name(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Граница частей multipart, уникальная для каждой отправки
+fn multipart_boundary() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos());
+    format!("----ZverFormBoundary{:x}", nanos)
+}
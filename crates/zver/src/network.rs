@@ -1,5 +1,18 @@
+pub mod cache;
+mod directory;
+pub mod mime;
+pub mod response;
+pub mod url;
+
+pub use self::cache::{CacheStatus, HttpCache};
+pub use self::response::Response;
+pub use self::url::{DataUrl, Url, UrlError};
+
+use self::cache::{CacheLookup, CachedResponse};
+use crate::error::{NetworkError, ZverResult};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 /// Simple HTTP log entry for DevTools
 #[derive(Debug, Clone)]
@@ -7,12 +20,108 @@ pub struct NetworkLogEntry {
     pub url: String,
     pub status: String,
     pub source: String,
+    /// Как запрос обслужен кэшем
+    pub cache: CacheStatus,
+    /// HTTP-статус; `None`, если ответа нет
+    pub status_code: Option<u16>,
+    pub content_type: Option<String>,
+    /// Размер тела в байтах
+    pub size: usize,
+}
+
+/// User-Agent движка по умолчанию
+pub const DEFAULT_USER_AGENT: &str = "Zver/0.1.0 (Rust Browser Engine)";
+/// Accept-Language по умолчанию
+pub const DEFAULT_ACCEPT_LANGUAGE: &str = "en-US,en;q=0.9";
+
+const ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+/// Заголовки каждого запроса; от них зависят записи кэша с `Vary`.
+///
+/// `Accept-Encoding` выставляет сам reqwest: заданный вручную, он отключил бы
+/// автоматическую распаковку gzip/deflate/br.
+fn request_headers(accept_language: &str) -> [(&'static str, &str); 2] {
+    [("accept", ACCEPT), ("accept-language", accept_language)]
+}
+
+/// Сетевые настройки, общие для [`NetworkEngine`] и
+/// [`crate::resource_loader::ResourceLoader`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConfig {
+    pub user_agent: String,
+    pub accept_language: String,
+    /// Предел на весь запрос, включая чтение тела
+    pub timeout: Duration,
+    pub connect_timeout: Duration,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            accept_language: DEFAULT_ACCEPT_LANGUAGE.to_string(),
+            timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Основа HTTP-клиента: User-Agent, Accept-Language и таймауты
+    pub fn client_builder(&self) -> reqwest::ClientBuilder {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Ok(value) = reqwest::header::HeaderValue::from_str(&self.accept_language) {
+            headers.insert(reqwest::header::ACCEPT_LANGUAGE, value);
+        }
+        reqwest::Client::builder()
+            .user_agent(self.user_agent.as_str())
+            .default_headers(headers)
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+    }
+}
+
+/// Тело запроса с его `Content-Type`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestBody {
+    /// Например `application/x-www-form-urlencoded` или
+    /// `multipart/form-data; boundary=...`
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Результат загрузки до обновления кэша и журнала
+enum FetchOutcome {
+    /// Локальный ресурс из кэша сессии
+    LocalHit(Response),
+    /// Локальный ресурс (file, data, about:blank), прочитанный заново
+    Local(Response),
+    /// Свежая запись HTTP-кэша
+    Fresh(CachedResponse),
+    /// Сервер подтвердил устаревшую запись
+    NotModified {
+        entry: CachedResponse,
+        response: Response,
+    },
+    /// Перепроверка не удалась, и запись разрешено отдать устаревшей
+    Stale(CachedResponse),
+    Http(Response),
+}
+
+/// Состояние кэша для URL перед загрузкой
+enum Lookup {
+    Local(Option<Response>),
+    Http(CacheLookup),
 }
 
 #[derive(Debug, Clone)]
 pub struct NetworkEngine {
-    cache: HashMap<String, String>,
+    /// Кэш сессии для file:, data: и about:blank
+    cache: HashMap<String, Response>,
+    http_cache: HttpCache,
     client: reqwest::Client,
+    /// Значение Accept-Language: участвует в сопоставлении `Vary`
+    accept_language: String,
     logs: Vec<NetworkLogEntry>,
 }
 
@@ -20,8 +129,13 @@ impl NetworkEngine {
     const MAX_LOG_ENTRIES: usize = 256;
 
     pub fn new() -> Self {
-        let client = reqwest::Client::builder()
-            .user_agent("Zver/0.1.0 (Rust Browser Engine)")
+        Self::with_config(&NetworkConfig::default())
+    }
+
+    /// Движок с заданными User-Agent, Accept-Language и таймаутами
+    pub fn with_config(config: &NetworkConfig) -> Self {
+        let client = config
+            .client_builder()
             // Полноценная настройка HTTP/2
             .http2_prior_knowledge()
             .http2_adaptive_window(true)
@@ -35,13 +149,10 @@ impl NetworkEngine {
             // Connection pooling
             .pool_max_idle_per_host(10)
             .pool_idle_timeout(Duration::from_secs(90))
-            // Таймауты
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
-            .read_timeout(Duration::from_secs(30))
+            .read_timeout(config.timeout)
             // Редиректы
             .redirect(reqwest::redirect::Policy::limited(10))
-            // Компрессия включена автоматически при наличии features
+            // Ответы gzip/deflate/br распаковываются reqwest (features в Cargo.toml)
             // Производительность
             .tcp_nodelay(true)
             .tcp_keepalive(Duration::from_secs(60))
@@ -49,27 +160,68 @@ impl NetworkEngine {
             .unwrap_or_else(|e| {
                 eprintln!("Failed to build reqwest client with full config: {}", e);
                 eprintln!("Falling back to default client configuration");
-                reqwest::Client::builder()
-                    .timeout(Duration::from_secs(30))
+                config
+                    .client_builder()
                     .build()
                     .expect("Failed to build even basic HTTP client - this is a critical error")
             });
 
+        Self::with_client(client).with_accept_language(&config.accept_language)
+    }
+
+    /// Движок с собственным HTTP-клиентом (прокси, HTTP/1.1, тестовые серверы)
+    pub fn with_client(client: reqwest::Client) -> Self {
         Self {
             cache: HashMap::new(),
+            http_cache: HttpCache::new(),
             client,
+            accept_language: DEFAULT_ACCEPT_LANGUAGE.to_string(),
             logs: Vec::new(),
         }
     }
 
-    fn record_network_event(&mut self, url: &str, status: String, source: String) {
+    /// Задаёт Accept-Language запросов (по умолчанию [`DEFAULT_ACCEPT_LANGUAGE`])
+    pub fn with_accept_language(mut self, accept_language: &str) -> Self {
+        self.accept_language = accept_language.to_string();
+        self
+    }
+
+    /// Хранит HTTP-кэш на диске в `dir`, не превышая `max_bytes`.
+    ///
+    /// Записи в памяти сбрасываются; уже сохранённые на диске файлы
+    /// подхватываются при следующих запросах.
+    pub fn enable_disk_cache(&mut self, dir: impl Into<PathBuf>, max_bytes: u64) {
+        self.http_cache = HttpCache::with_disk_store(dir, max_bytes);
+    }
+
+    fn record_network_event(&mut self, url: &str, result: Result<&Response, String>) {
         if self.logs.len() >= Self::MAX_LOG_ENTRIES {
             self.logs.remove(0);
         }
-        self.logs.push(NetworkLogEntry {
-            url: url.to_string(),
-            status,
-            source,
+        let source = Self::categorize_resource(url).to_string();
+        self.logs.push(match result {
+            Ok(response) => NetworkLogEntry {
+                url: url.to_string(),
+                status: if response.is_success() {
+                    "OK".to_string()
+                } else {
+                    format!("HTTP {}", response.status)
+                },
+                source,
+                cache: response.cache,
+                status_code: Some(response.status),
+                content_type: response.content_type().map(str::to_string),
+                size: response.body.len(),
+            },
+            Err(err) => NetworkLogEntry {
+                url: url.to_string(),
+                status: format!("ERR: {}", err),
+                source,
+                cache: CacheStatus::Miss,
+                status_code: None,
+                content_type: None,
+                size: 0,
+            },
         });
     }
 
@@ -94,94 +246,308 @@ impl NetworkEngine {
     }
 
     pub fn clear_cache_for_url(&mut self, url: &str) {
-        self.cache.remove(url);
+        let key = Url::parse(url).map_or_else(|_| url.to_string(), String::from);
+        self.cache.remove(&key);
+        self.http_cache.remove(&key);
     }
 
-    pub async fn fetch(&mut self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let source_label = Self::categorize_resource(url).to_string();
+    /// Загружает документ или ресурс; `url` может быть и путём к файлу.
+    pub async fn fetch(&mut self, url: &str) -> ZverResult<String> {
+        let url = Url::parse(url)?;
+        self.fetch_url(&url).await
+    }
 
-        if let Some(cached) = self.cache.get(url).cloned() {
-            self.record_network_event(url, "OK (cache)".to_string(), source_label.clone());
-            return Ok(cached);
-        }
+    /// Загружает уже разобранный URL (http(s), file, data, about:blank) как текст.
+    ///
+    /// Ответ со статусом вне 2xx считается ошибкой [`NetworkError::Http`].
+    pub async fn fetch_url(&mut self, url: &Url) -> ZverResult<String> {
+        Ok(self.fetch_response(url).await?.error_for_status()?.text())
+    }
+
+    /// Загружает ресурс со статусом, заголовками, итоговым URL и сырым телом.
+    ///
+    /// Ответ с любым HTTP-статусом возвращается как есть: что делать со
+    /// страницей 404 или 500, решает вызывающий.
+    pub async fn fetch_response(&mut self, url: &Url) -> ZverResult<Response> {
+        let lookup = self.lookup(url);
+        let outcome = self.load(url, lookup).await;
+        Ok(self.commit(url, outcome)?)
+    }
+
+    /// Загружает несколько ресурсов параллельно.
+    ///
+    /// Кэш и журнал обновляются так же, как при [`NetworkEngine::fetch`];
+    /// результаты возвращаются в порядке `urls`.
+    pub async fn fetch_many(&mut self, urls: &[Url]) -> Vec<Result<Response, NetworkError>> {
+        use futures_util::future::join_all;
+
+        let lookups: Vec<Lookup> = urls.iter().map(|url| self.lookup(url)).collect();
+        let engine = &*self;
+        let outcomes = join_all(
+            urls.iter()
+                .zip(lookups)
+                .map(|(url, lookup)| engine.load(url, lookup)),
+        )
+        .await;
+
+        urls.iter()
+            .zip(outcomes)
+            .map(|(url, outcome)| self.commit(url, outcome))
+            .collect()
+    }
 
-        let result = if url.starts_with("http://") || url.starts_with("https://") {
-            self.fetch_http_https(url).await
-        } else if url.starts_with("file://") {
-            self.fetch_file(url).await
+    /// Отправляет тело формы методом POST.
+    ///
+    /// Ответ не кэшируется, а запись для `url` сбрасывается: небезопасный
+    /// метод делает её устаревшей (RFC 9111, §4.4). У `file:` и `data:` тела
+    /// нет — ресурс читается как при GET.
+    pub async fn post(&mut self, url: &Url, body: &RequestBody) -> ZverResult<Response> {
+        let result = if url.is_http() {
+            self.post_http(url, body).await
         } else {
-            self.fetch_file(&format!("file://{}", url)).await
+            fetch_local(url).await
         };
-
+        self.http_cache.remove(url.as_str());
         match &result {
-            Ok(content) => {
-                self.cache.insert(url.to_string(), content.clone());
-                self.record_network_event(url, "OK".to_string(), source_label.clone());
+            Ok(response) => self.record_network_event(url.as_str(), Ok(response)),
+            Err(err) => self.record_network_event(url.as_str(), Err(err.to_string())),
+        }
+        Ok(result?)
+    }
+
+    fn lookup(&mut self, url: &Url) -> Lookup {
+        if url.is_http() {
+            let headers = request_headers(&self.accept_language);
+            Lookup::Http(
+                self.http_cache
+                    .lookup(url.as_str(), &headers, SystemTime::now()),
+            )
+        } else {
+            Lookup::Local(self.cache.get(url.as_str()).cloned())
+        }
+    }
+
+    /// Сетевая часть загрузки; не трогает состояние движка
+    async fn load(&self, url: &Url, lookup: Lookup) -> Result<FetchOutcome, NetworkError> {
+        match lookup {
+            Lookup::Local(Some(cached)) => Ok(FetchOutcome::LocalHit(cached)),
+            Lookup::Local(None) => Ok(FetchOutcome::Local(fetch_local(url).await?)),
+            Lookup::Http(CacheLookup::Fresh(entry)) => Ok(FetchOutcome::Fresh(entry)),
+            Lookup::Http(CacheLookup::Stale(entry)) => {
+                // Сетевая ошибка и 5xx — перепроверка не удалась
+                match self.fetch_http(url, Some(&entry)).await {
+                    Ok(response) if response.status == 304 => {
+                        Ok(FetchOutcome::NotModified { entry, response })
+                    }
+                    Ok(response) if response.status < 500 => Ok(FetchOutcome::Http(response)),
+                    _ if entry.may_serve_stale() => Ok(FetchOutcome::Stale(entry)),
+                    Ok(response) => Err(NetworkError::Http {
+                        url: url.to_string(),
+                        status: response.status,
+                    }),
+                    Err(err) => Err(err),
+                }
             }
-            Err(err) => {
-                self.record_network_event(url, format!("ERR: {}", err), source_label.clone());
+            Lookup::Http(CacheLookup::Miss) => {
+                Ok(FetchOutcome::Http(self.fetch_http(url, None).await?))
             }
         }
+    }
+
+    /// Обновляет кэш и журнал по результату загрузки
+    fn commit(
+        &mut self,
+        url: &Url,
+        outcome: Result<FetchOutcome, NetworkError>,
+    ) -> Result<Response, NetworkError> {
+        let now = SystemTime::now();
+
+        let response = match outcome {
+            Ok(FetchOutcome::LocalHit(response)) => Response {
+                cache: CacheStatus::Hit,
+                ..response
+            },
+            Ok(FetchOutcome::Local(response)) => {
+                if response.cache != CacheStatus::Bypass {
+                    self.cache.insert(url.to_string(), response.clone());
+                }
+                response
+            }
+            Ok(FetchOutcome::Fresh(entry)) => Response::from_cached(&entry, CacheStatus::Hit),
+            Ok(FetchOutcome::Stale(entry)) => Response::from_cached(&entry, CacheStatus::Stale),
+            Ok(FetchOutcome::NotModified {
+                mut entry,
+                response,
+            }) => {
+                entry.refresh(&response.headers, now);
+                let response = Response::from_cached(&entry, CacheStatus::Revalidated);
+                self.http_cache.store(entry);
+                response
+            }
+            Ok(FetchOutcome::Http(mut response)) => {
+                let mut entry = CachedResponse::new(
+                    url.as_str(),
+                    response.status,
+                    response.headers.clone(),
+                    response.body.clone(),
+                    &request_headers(&self.accept_language),
+                    now,
+                );
+                if response.url != *url {
+                    entry.final_url = Some(response.url.to_string());
+                }
+                if !self.http_cache.store(entry) {
+                    response.cache = CacheStatus::Bypass;
+                }
+                response
+            }
+            Err(err) => {
+                self.record_network_event(url.as_str(), Err(err.to_string()));
+                return Err(err);
+            }
+        };
+
+        self.record_network_event(url.as_str(), Ok(&response));
+        Ok(response)
+    }
 
-        result
+    async fn fetch_uncached(&self, url: &Url) -> Result<Response, NetworkError> {
+        if url.is_http() {
+            self.fetch_http(url, None).await
+        } else {
+            fetch_local(url).await
+        }
     }
 
     // Prefetch ресурсов асинхронно с использованием пула соединений
-    pub async fn prefetch_resources(
-        &self,
-        urls: Vec<String>,
-    ) -> Vec<Result<String, Box<dyn std::error::Error>>> {
+    pub async fn prefetch_resources(&self, urls: Vec<String>) -> Vec<ZverResult<String>> {
         use futures_util::future::join_all;
 
-        let futures = urls.into_iter().map(|url| {
-            let client = self.client.clone();
-            async move {
-                let result: Result<String, Box<dyn std::error::Error>> =
-                    if url.starts_with("http://") || url.starts_with("https://") {
-                        match client.get(&url).send().await {
-                            Ok(response) => response.text().await.map_err(|e| e.into()),
-                            Err(e) => Err(e.into()),
-                        }
-                    } else {
-                        // Для файлов используем tokio::fs
-                        tokio::fs::read_to_string(url.strip_prefix("file://").unwrap_or(&url))
-                            .await
-                            .map_err(|e| e.into())
-                    };
-                result
-            }
+        let futures = urls.into_iter().map(|url| async move {
+            let url = Url::parse(&url)?;
+            Ok(self.fetch_uncached(&url).await?.error_for_status()?.text())
         });
 
         join_all(futures).await
     }
 
-    async fn fetch_http_https(&self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let response = self
-            .client
-            .get(url)
-            .header(
-                reqwest::header::ACCEPT,
-                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
-            )
-            .header(reqwest::header::ACCEPT_LANGUAGE, "en-US,en;q=0.9")
-            .header(reqwest::header::ACCEPT_ENCODING, "gzip, deflate, br")
+    /// HTTP-запрос; при наличии `cached` он становится условным
+    async fn fetch_http(
+        &self,
+        url: &Url,
+        cached: Option<&CachedResponse>,
+    ) -> Result<Response, NetworkError> {
+        let mut request = self.client.get(url.as_str());
+        for (name, value) in request_headers(&self.accept_language) {
+            request = request.header(name, value);
+        }
+        if let Some(entry) = cached {
+            if let Some(etag) = entry.etag() {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = entry.last_modified() {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = request
             .send()
-            .await?;
+            .await
+            .map_err(|err| NetworkError::request(url.as_str(), err))?;
+        read_http_response(response)
+            .await
+            .map_err(|err| NetworkError::request(url.as_str(), err))
+    }
 
-        // Проверяем статус
-        let status = response.status();
-        if !status.is_success() {
-            return Err(format!("HTTP error: {} for {}", status, url).into());
+    async fn post_http(&self, url: &Url, body: &RequestBody) -> Result<Response, NetworkError> {
+        let mut request = self
+            .client
+            .post(url.as_str())
+            .header(reqwest::header::CONTENT_TYPE, body.content_type.as_str())
+            .body(body.data.clone());
+        for (name, value) in request_headers(&self.accept_language) {
+            request = request.header(name, value);
         }
-
-        let text = response.text().await?;
-        Ok(text)
+        let response = request
+            .send()
+            .await
+            .map_err(|err| NetworkError::request(url.as_str(), err))?;
+        read_http_response(response)
+            .await
+            .map_err(|err| NetworkError::request(url.as_str(), err))
     }
+}
 
-    async fn fetch_file(&self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
-        let path = url.strip_prefix("file://").unwrap_or(url);
-        let content = tokio::fs::read_to_string(path).await?;
-        Ok(content)
+/// Читает ответ reqwest целиком, сохраняя итоговый URL и заголовки
+pub(crate) async fn read_http_response(
+    response: reqwest::Response,
+) -> Result<Response, reqwest::Error> {
+    let url = Url::from(response.url().clone());
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+        })
+        .collect();
+    let body = response.bytes().await?.to_vec();
+    Ok(Response {
+        url,
+        status,
+        headers,
+        body,
+        cache: CacheStatus::Miss,
+    })
+}
+
+/// Загружает ресурс без сети: file:, data: или about:blank
+pub(crate) async fn fetch_local(url: &Url) -> Result<Response, NetworkError> {
+    match url.scheme() {
+        "file" => {
+            let io_error = |source| NetworkError::Io {
+                url: url.to_string(),
+                source,
+            };
+            let path = url.to_file_path().ok_or_else(|| {
+                io_error(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "not a local file URL",
+                ))
+            })?;
+            if tokio::fs::metadata(&path).await.map_err(io_error)?.is_dir() {
+                // Относительные ссылки индекса разрешаются только от URL со слешем
+                let url = url.as_directory();
+                let display_path =
+                    percent_encoding::percent_decode_str(url.path()).decode_utf8_lossy();
+                let html = directory::listing(&path, &display_path)
+                    .await
+                    .map_err(io_error)?;
+                // Индекс строится заново при каждом запросе, чтобы отражать новые файлы
+                return Ok(Response {
+                    cache: CacheStatus::Bypass,
+                    ..Response::local(url, Some("text/html;charset=utf-8"), html.into_bytes())
+                });
+            }
+            let body = tokio::fs::read(&path).await.map_err(io_error)?;
+            let content_type = mime::detect(url.file_name().as_deref(), &body);
+            Ok(Response::local(url.clone(), Some(content_type), body))
+        }
+        "data" => {
+            let data = url
+                .data()
+                .ok_or_else(|| NetworkError::MalformedDataUrl(url.to_string()))?;
+            Ok(Response::local(
+                url.clone(),
+                Some(&data.mime_type),
+                data.body,
+            ))
+        }
+        _ if url.is_about_blank() => Ok(Response::local(
+            url.clone(),
+            Some("text/html;charset=utf-8"),
+            Vec::new(),
+        )),
+        scheme => Err(NetworkError::UnsupportedScheme(scheme.to_string())),
     }
 }
 
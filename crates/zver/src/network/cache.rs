@@ -0,0 +1,518 @@
+//! HTTP-кэш по RFC 9111 (частный кэш браузера).
+//!
+//! Ответы хранятся вместе с заголовками: свежесть определяется по
+//! `Cache-Control: max-age`, `Expires` или эвристике по `Last-Modified`, а
+//! устаревшие записи перепроверяются условными запросами (`If-None-Match`,
+//! `If-Modified-Since`). Записи с `Vary` применяются только к запросам с теми
+//! же значениями заголовков. Необязательное хранилище на диске ограничено
+//! по размеру и вытесняет самые старые файлы.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Верхняя граница эвристической свежести (RFC 9111, 4.2.2)
+const MAX_HEURISTIC_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Как запрос был обслужен кэшем — отображается в журнале сети
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// Ответ получен из сети и сохранён
+    Miss,
+    /// Свежая запись отдана без обращения к сети
+    Hit,
+    /// Сервер подтвердил устаревшую запись ответом 304
+    Revalidated,
+    /// Перепроверить запись не удалось — отдана устаревшая
+    Stale,
+    /// Ответ получен из сети, но кэшировать его нельзя
+    Bypass,
+}
+
+impl fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Miss => "MISS",
+            Self::Hit => "HIT",
+            Self::Revalidated => "REVALIDATED",
+            Self::Stale => "STALE",
+            Self::Bypass => "BYPASS",
+        })
+    }
+}
+
+/// Разобранные директивы `Cache-Control`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    pub no_store: bool,
+    pub no_cache: bool,
+    pub must_revalidate: bool,
+    pub max_age: Option<u64>,
+}
+
+impl CacheControl {
+    pub fn parse(value: &str) -> Self {
+        let mut control = Self::default();
+        for directive in value.split(',') {
+            let (name, argument) = match directive.split_once('=') {
+                Some((name, argument)) => (name, Some(argument.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+            match name.trim().to_ascii_lowercase().as_str() {
+                "no-store" => control.no_store = true,
+                "no-cache" => control.no_cache = true,
+                "must-revalidate" => control.must_revalidate = true,
+                "max-age" => control.max_age = argument.and_then(|age| age.parse().ok()),
+                _ => {}
+            }
+        }
+        control
+    }
+}
+
+/// Результат поиска в кэше
+#[derive(Debug, Clone)]
+pub enum CacheLookup {
+    /// Запись свежая — можно отдавать без запроса
+    Fresh(CachedResponse),
+    /// Запись устарела, но у неё есть валидаторы для условного запроса
+    Stale(CachedResponse),
+    Miss,
+}
+
+/// Сохранённый HTTP-ответ
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedResponse {
+    pub url: String,
+    /// Итоговый URL, если запрос был перенаправлен
+    pub final_url: Option<String>,
+    pub status: u16,
+    /// Заголовки ответа; имена в нижнем регистре
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Момент получения ответа
+    pub stored_at: SystemTime,
+    /// Значения заголовков запроса, перечисленных в `Vary`
+    pub vary: Vec<(String, Option<String>)>,
+}
+
+impl CachedResponse {
+    /// Создаёт запись, запоминая заголовки запроса, от которых зависит ответ
+    pub fn new(
+        url: &str,
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+        request_headers: &[(&str, &str)],
+        now: SystemTime,
+    ) -> Self {
+        let mut response = Self {
+            url: url.to_string(),
+            final_url: None,
+            status,
+            headers: headers
+                .into_iter()
+                .map(|(name, value)| (name.to_ascii_lowercase(), value))
+                .collect(),
+            body,
+            stored_at: now,
+            vary: Vec::new(),
+        };
+        response.vary = response
+            .vary_names()
+            .into_iter()
+            .map(|name| {
+                let value = request_header(request_headers, &name).map(str::to_string);
+                (name, value)
+            })
+            .collect();
+        response
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn cache_control(&self) -> CacheControl {
+        self.header("cache-control")
+            .map(CacheControl::parse)
+            .unwrap_or_default()
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.header("etag")
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.header("last-modified")
+    }
+
+    pub fn has_validators(&self) -> bool {
+        self.etag().is_some() || self.last_modified().is_some()
+    }
+
+    /// Время жизни записи (RFC 9111, 4.2.1)
+    pub fn freshness_lifetime(&self) -> Duration {
+        if let Some(max_age) = self.cache_control().max_age {
+            return Duration::from_secs(max_age);
+        }
+
+        let date = self
+            .header("date")
+            .and_then(parse_http_date)
+            .unwrap_or(self.stored_at);
+        if let Some(expires) = self.header("expires") {
+            // Некорректный Expires означает «уже устарел»
+            return parse_http_date(expires)
+                .and_then(|expires| expires.duration_since(date).ok())
+                .unwrap_or_default();
+        }
+
+        // Эвристика: 10% времени с последнего изменения
+        self.last_modified()
+            .and_then(parse_http_date)
+            .and_then(|modified| date.duration_since(modified).ok())
+            .map(|since| (since / 10).min(MAX_HEURISTIC_LIFETIME))
+            .unwrap_or_default()
+    }
+
+    /// Текущий возраст записи с учётом заголовка `Age`
+    pub fn age(&self, now: SystemTime) -> Duration {
+        let initial = self
+            .header("age")
+            .and_then(|age| age.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+        initial + now.duration_since(self.stored_at).unwrap_or_default()
+    }
+
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        !self.cache_control().no_cache && self.age(now) < self.freshness_lifetime()
+    }
+
+    /// Можно ли отдать устаревшую запись, если перепроверить её не удалось
+    /// (RFC 9111, 4.2.4): `must-revalidate` и `no-cache` это запрещают
+    pub fn may_serve_stale(&self) -> bool {
+        let control = self.cache_control();
+        !control.must_revalidate && !control.no_cache
+    }
+
+    /// Подходит ли запись запросу с такими заголовками
+    pub fn matches_vary(&self, request_headers: &[(&str, &str)]) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| request_header(request_headers, name) == value.as_deref())
+    }
+
+    /// Обновляет запись по ответу 304: заголовки заменяются, тело остаётся
+    pub fn refresh(&mut self, headers: &[(String, String)], now: SystemTime) {
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            // Заголовки представления у 304 не передаются или не должны меняться
+            if matches!(name.as_str(), "content-length" | "content-encoding") {
+                continue;
+            }
+            self.headers.retain(|(header, _)| *header != name);
+            self.headers.push((name, value.clone()));
+        }
+        self.stored_at = now;
+    }
+
+    fn vary_names(&self) -> Vec<String> {
+        self.header("vary")
+            .map(|vary| {
+                vary.split(',')
+                    .map(|name| name.trim().to_ascii_lowercase())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Можно ли сохранять ответ в кэш
+    fn is_storable(&self) -> bool {
+        self.status == 200
+            && !self.cache_control().no_store
+            && !self.vary.iter().any(|(name, _)| name == "*")
+            // Запись без срока жизни и валидаторов бесполезна
+            && (self.has_validators() || !self.freshness_lifetime().is_zero())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let stored = self
+            .stored_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut head = format!(
+            "ZVER-CACHE 1\nurl {}\nstatus {}\nstored {}\n",
+            self.url, self.status, stored
+        );
+        if let Some(final_url) = &self.final_url {
+            head.push_str(&format!("redirect {}\n", final_url));
+        }
+        for (name, value) in &self.vary {
+            head.push_str(&format!(
+                "vary {}={}\n",
+                name,
+                value.as_deref().unwrap_or("\0")
+            ));
+        }
+        for (name, value) in &self.headers {
+            head.push_str(&format!("header {}: {}\n", name, value));
+        }
+        head.push('\n');
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let split = bytes.windows(2).position(|window| window == b"\n\n")?;
+        let head = std::str::from_utf8(&bytes[..split]).ok()?;
+        let mut lines = head.lines();
+        if lines.next()? != "ZVER-CACHE 1" {
+            return None;
+        }
+
+        let mut response = Self {
+            url: String::new(),
+            final_url: None,
+            status: 0,
+            headers: Vec::new(),
+            body: bytes[split + 2..].to_vec(),
+            stored_at: UNIX_EPOCH,
+            vary: Vec::new(),
+        };
+        for line in lines {
+            let (key, value) = line.split_once(' ')?;
+            match key {
+                "url" => response.url = value.to_string(),
+                "redirect" => response.final_url = Some(value.to_string()),
+                "status" => response.status = value.parse().ok()?,
+                "stored" => {
+                    response.stored_at = UNIX_EPOCH + Duration::from_secs(value.parse().ok()?)
+                }
+                "vary" => {
+                    let (name, value) = value.split_once('=')?;
+                    let value = (value != "\0").then(|| value.to_string());
+                    response.vary.push((name.to_string(), value));
+                }
+                "header" => {
+                    let (name, value) = value.split_once(": ")?;
+                    response.headers.push((name.to_string(), value.to_string()));
+                }
+                _ => {}
+            }
+        }
+        Some(response)
+    }
+}
+
+/// HTTP-кэш в памяти с необязательным хранилищем на диске
+#[derive(Debug, Clone, Default)]
+pub struct HttpCache {
+    entries: HashMap<String, CachedResponse>,
+    disk: Option<DiskStore>,
+}
+
+impl HttpCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Включает хранение на диске в каталоге `dir` не более `max_bytes` байт
+    pub fn with_disk_store(dir: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            entries: HashMap::new(),
+            disk: Some(DiskStore {
+                dir: dir.into(),
+                max_bytes,
+            }),
+        }
+    }
+
+    pub fn lookup(
+        &mut self,
+        url: &str,
+        request_headers: &[(&str, &str)],
+        now: SystemTime,
+    ) -> CacheLookup {
+        if !self.entries.contains_key(url)
+            && let Some(entry) = self.disk.as_ref().and_then(|disk| disk.read(url))
+        {
+            self.entries.insert(url.to_string(), entry);
+        }
+
+        match self.entries.get(url) {
+            Some(entry) if entry.matches_vary(request_headers) => {
+                if entry.is_fresh(now) {
+                    CacheLookup::Fresh(entry.clone())
+                } else if entry.has_validators() {
+                    CacheLookup::Stale(entry.clone())
+                } else {
+                    CacheLookup::Miss
+                }
+            }
+            _ => CacheLookup::Miss,
+        }
+    }
+
+    /// Сохраняет ответ; возвращает `false`, если кэшировать его нельзя.
+    ///
+    /// `no-store` заодно удаляет прежнюю запись для этого URL.
+    pub fn store(&mut self, response: CachedResponse) -> bool {
+        if !response.is_storable() {
+            if response.cache_control().no_store {
+                self.remove(&response.url);
+            }
+            return false;
+        }
+
+        if let Some(disk) = &self.disk {
+            disk.write(&response);
+        }
+        self.entries.insert(response.url.clone(), response);
+        true
+    }
+
+    pub fn remove(&mut self, url: &str) {
+        self.entries.remove(url);
+        if let Some(disk) = &self.disk {
+            disk.remove(url);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Файловое хранилище: по файлу на URL, вытеснение самых старых при переполнении
+#[derive(Debug, Clone)]
+struct DiskStore {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl DiskStore {
+    fn path(&self, url: &str) -> PathBuf {
+        // FNV-1a: стабильное имя файла между запусками
+        let hash = url.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        self.dir.join(format!("{hash:016x}.cache"))
+    }
+
+    fn read(&self, url: &str) -> Option<CachedResponse> {
+        let entry = CachedResponse::decode(&fs::read(self.path(url)).ok()?)?;
+        (entry.url == url).then_some(entry)
+    }
+
+    fn write(&self, response: &CachedResponse) {
+        let bytes = response.encode();
+        if bytes.len() as u64 > self.max_bytes {
+            return;
+        }
+        let path = self.path(&response.url);
+        if let Err(err) = fs::create_dir_all(&self.dir).and_then(|()| fs::write(&path, &bytes)) {
+            tracing::warn!("Failed to write cache entry {}: {}", path.display(), err);
+            return;
+        }
+        self.evict();
+    }
+
+    fn remove(&self, url: &str) {
+        let _ = fs::remove_file(self.path(url));
+    }
+
+    fn evict(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut files: Vec<(SystemTime, u64, PathBuf)> = entries
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "cache"))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+            })
+            .collect();
+
+        let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+        files.sort_by_key(|(modified, _, _)| *modified);
+        for (_, size, path) in files {
+            if total <= self.max_bytes {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+}
+
+fn request_header<'a>(headers: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| *value)
+}
+
+/// Разбирает дату в формате IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`)
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let mut parts = value.split_whitespace();
+    let _weekday = parts.next()?;
+    let day: u64 = parts.next()?.parse().ok()?;
+    let month = match parts.next()? {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut time = parts
+        .next()?
+        .split(':')
+        .map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+    if parts.next()? != "GMT" || !(1..=31).contains(&day) || hours > 23 || minutes > 59 {
+        return None;
+    }
+
+    // Число дней от 1970-01-01 (алгоритм days_from_civil)
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    let seconds = days * 86_400 + (hours * 3600 + minutes * 60 + seconds) as i64;
+    u64::try_from(seconds)
+        .ok()
+        .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds))
+}
@@ -0,0 +1,92 @@
+//! HTML-страница со списком файлов для `file://` URL каталога.
+
+use std::path::Path;
+
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+
+/// Символы, кодируемые в относительных ссылках на записи каталога.
+///
+/// `:` тоже кодируется: иначе `a:b.html` читался бы как URL со схемой `a:`
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b':')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+struct Entry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Строит страницу-индекс каталога: сначала подкаталоги, затем файлы, по имени.
+///
+/// Ссылки относительные, поэтому документ должен загружаться по URL со
+/// слешем на конце.
+pub async fn listing(path: &Path, display_path: &str) -> std::io::Result<String> {
+    let mut entries = Vec::new();
+    let mut read_dir = tokio::fs::read_dir(path).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        entries.push(Entry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+        });
+    }
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+    let title = format!("Index of {}", escape(display_path));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body {{ font-family: sans-serif; margin: 16px; }} \
+         li {{ margin: 2px 0; }} .size {{ color: #777777; }}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n<ul>\n"
+    );
+    if path.parent().is_some() {
+        html.push_str("<li><a href=\"../\">../</a></li>\n");
+    }
+    for entry in &entries {
+        let href = utf8_percent_encode(&entry.name, PATH_SEGMENT);
+        if entry.is_dir {
+            html.push_str(&format!(
+                "<li><a href=\"{href}/\">{}/</a></li>\n",
+                escape(&entry.name)
+            ));
+        } else {
+            html.push_str(&format!(
+                "<li><a href=\"{href}\">{}</a> <span class=\"size\">{}</span></li>\n",
+                escape(&entry.name),
+                format_size(entry.size)
+            ));
+        }
+    }
+    html.push_str("</ul>\n</body>\n</html>\n");
+    Ok(html)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
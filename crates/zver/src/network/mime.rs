@@ -0,0 +1,137 @@
+//! Определение MIME-типа локальных ресурсов.
+//!
+//! Сначала проверяется расширение файла, затем сигнатура содержимого
+//! (упрощённый алгоритм MIME Sniffing Standard): так изображения, шрифты и
+//! таблицы стилей без расширения или с неизвестным расширением получают
+//! правильный тип.
+
+/// Тип для двоичных данных неизвестного формата
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Сигнатура формата: (префикс, маска или `None`, MIME-тип)
+type Signature = (&'static [u8], Option<&'static [u8]>, &'static str);
+
+const SIGNATURES: &[Signature] = &[
+    (b"\x89PNG\r\n\x1a\n", None, "image/png"),
+    (b"\xff\xd8\xff", None, "image/jpeg"),
+    (b"GIF87a", None, "image/gif"),
+    (b"GIF89a", None, "image/gif"),
+    (
+        b"RIFF\0\0\0\0WEBPVP",
+        Some(b"\xff\xff\xff\xff\0\0\0\0\xff\xff\xff\xff\xff\xff"),
+        "image/webp",
+    ),
+    (b"BM", None, "image/bmp"),
+    (b"\0\0\x01\0", None, "image/x-icon"),
+    (b"wOFF", None, "font/woff"),
+    (b"wOF2", None, "font/woff2"),
+    (b"\0\x01\0\0", None, "font/ttf"),
+    (b"true", None, "font/ttf"),
+    (b"OTTO", None, "font/otf"),
+    (b"%PDF-", None, "application/pdf"),
+    (b"\x1f\x8b\x08", None, "application/gzip"),
+    (b"PK\x03\x04", None, "application/zip"),
+];
+
+/// Начала HTML-документа (без учёта регистра, после пробелов)
+const HTML_PATTERNS: &[&str] = &[
+    "<!doctype html",
+    "<html",
+    "<head",
+    "<body",
+    "<script",
+    "<iframe",
+    "<h1",
+    "<div",
+    "<font",
+    "<table",
+    "<a",
+    "<style",
+    "<title",
+    "<b",
+    "<br",
+    "<p",
+    "<!--",
+];
+
+/// MIME-тип по расширению имени файла
+pub fn from_extension(file_name: &str) -> Option<&'static str> {
+    let (_, extension) = file_name.rsplit_once('.')?;
+    Some(match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "xhtml" => "application/xhtml+xml",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" | "md" => "text/plain",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        _ => return None,
+    })
+}
+
+/// MIME-тип по содержимому; `None`, если формат не распознан
+pub fn sniff(body: &[u8]) -> Option<&'static str> {
+    let binary = SIGNATURES.iter().find(|(pattern, mask, _)| {
+        body.len() >= pattern.len()
+            && pattern.iter().enumerate().all(|(index, &byte)| {
+                let mask = mask.map_or(0xff, |mask| mask[index]);
+                body[index] & mask == byte & mask
+            })
+    });
+    if let Some((_, _, mime_type)) = binary {
+        return Some(mime_type);
+    }
+
+    let text = body.strip_prefix(b"\xef\xbb\xbf").unwrap_or(body);
+    let start = text.iter().position(|byte| !byte.is_ascii_whitespace())?;
+    let head = &text[start..text.len().min(start + 512)];
+    let lower = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    let is_html = HTML_PATTERNS.iter().any(|pattern| {
+        lower.strip_prefix(pattern).is_some_and(|rest| {
+            // Паттерн должен заканчиваться пробелом или `>`, как в спецификации
+            pattern.ends_with("--") || rest.starts_with([' ', '>', '\t', '\n', '\r'])
+        })
+    });
+    if is_html {
+        Some("text/html")
+    } else if lower.starts_with("<svg") || (lower.starts_with("<?xml") && lower.contains("<svg")) {
+        Some("image/svg+xml")
+    } else if lower.starts_with("<?xml") {
+        Some("application/xml")
+    } else {
+        None
+    }
+}
+
+/// Выглядит ли содержимое как текст (нет управляющих двоичных байтов)
+pub fn is_text(body: &[u8]) -> bool {
+    body.iter()
+        .take(1024)
+        .all(|&byte| !matches!(byte, 0x00..=0x08 | 0x0b | 0x0e..=0x1a | 0x1c..=0x1f))
+}
+
+/// Тип локального файла: расширение, затем сигнатура, затем текст/двоичные данные
+pub fn detect(file_name: Option<&str>, body: &[u8]) -> &'static str {
+    file_name
+        .and_then(from_extension)
+        .or_else(|| sniff(body))
+        .unwrap_or(if is_text(body) {
+            "text/plain"
+        } else {
+            OCTET_STREAM
+        })
+}
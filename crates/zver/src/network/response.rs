@@ -0,0 +1,176 @@
+//! Ответ на запрос ресурса: статус, заголовки, итоговый URL и сырое тело.
+//!
+//! Тело хранится байтами, поэтому через [`Response`] загружаются изображения
+//! и шрифты. Текст декодируется по правилам HTML: BOM, затем `charset` из
+//! `Content-Type`, затем `<meta charset>` (или `@charset` для CSS), иначе UTF-8.
+
+use encoding_rs::Encoding;
+
+use super::cache::{CacheStatus, CachedResponse};
+use super::url::Url;
+use crate::error::NetworkError;
+
+/// Сколько байт документа просматривается в поисках `<meta charset>`
+const META_PRESCAN_LIMIT: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    /// URL после всех перенаправлений
+    pub url: Url,
+    pub status: u16,
+    /// Заголовки ответа; имена в нижнем регистре
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Как ответ обслужен кэшем
+    pub cache: CacheStatus,
+}
+
+impl Response {
+    /// Ответ локального источника (файл, `data:`) с заданным MIME-типом
+    pub fn local(url: Url, content_type: Option<&str>, body: Vec<u8>) -> Self {
+        Self {
+            url,
+            status: 200,
+            headers: content_type
+                .map(|content_type| vec![("content-type".to_string(), content_type.to_string())])
+                .unwrap_or_default(),
+            body,
+            cache: CacheStatus::Miss,
+        }
+    }
+
+    pub(crate) fn from_cached(entry: &CachedResponse, cache: CacheStatus) -> Self {
+        let url = entry
+            .final_url
+            .as_deref()
+            .unwrap_or(&entry.url)
+            .parse()
+            .unwrap_or_else(|_| Url::about_blank());
+        Self {
+            url,
+            status: entry.status,
+            headers: entry.headers.clone(),
+            body: entry.body.clone(),
+            cache,
+        }
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+
+    /// MIME-тип без параметров в нижнем регистре (`text/html`)
+    pub fn mime_type(&self) -> Option<String> {
+        let essence = self.content_type()?.split(';').next()?.trim();
+        (!essence.is_empty()).then(|| essence.to_ascii_lowercase())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Ответ со статусом 2xx или ошибка [`NetworkError::Http`] — для
+    /// подресурсов, у которых тело страницы ошибки бесполезно
+    pub fn error_for_status(self) -> Result<Self, NetworkError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(NetworkError::Http {
+                url: self.url.to_string(),
+                status: self.status,
+            })
+        }
+    }
+
+    /// Кодировка, которой будет декодирован [`Response::text`]
+    pub fn encoding(&self) -> &'static Encoding {
+        if let Some((encoding, _)) = Encoding::for_bom(&self.body) {
+            return encoding;
+        }
+        if let Some(encoding) = self.content_type().and_then(charset_parameter) {
+            return encoding;
+        }
+        let sniffed = match self.mime_type().as_deref() {
+            Some("text/css") => css_charset(&self.body),
+            Some("text/html") | None => meta_charset(&self.body),
+            _ => None,
+        };
+        sniffed.unwrap_or(encoding_rs::UTF_8)
+    }
+
+    /// Тело как текст; BOM отбрасывается
+    pub fn text(&self) -> String {
+        let (text, _, _) = self.encoding().decode(&self.body);
+        text.into_owned()
+    }
+}
+
+/// `charset` из значения `Content-Type`
+pub fn charset_parameter(content_type: &str) -> Option<&'static Encoding> {
+    content_type.split(';').skip(1).find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("charset") {
+            return None;
+        }
+        Encoding::for_label(value.trim().trim_matches(['"', '\'']).as_bytes())
+    })
+}
+
+/// Упрощённый prescan HTML: `<meta charset>` и `<meta http-equiv content>`
+fn meta_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let head = &body[..body.len().min(META_PRESCAN_LIMIT)];
+    let head = String::from_utf8_lossy(head).to_ascii_lowercase();
+
+    head.match_indices("<meta").find_map(|(start, _)| {
+        let tag = &head[start..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        let value = attribute_value(tag, "charset").or_else(|| {
+            attribute_value(tag, "content")
+                .and_then(|content| Some(content.split_once("charset=")?.1))
+        })?;
+        let label = value.trim().trim_matches(['"', '\'', ';']);
+        // UTF-16 в meta означает UTF-8: тег уже прочитан как ASCII
+        Encoding::for_label(label.as_bytes()).map(|encoding| encoding.output_encoding())
+    })
+}
+
+/// `@charset "…";` в самом начале таблицы стилей
+fn css_charset(body: &[u8]) -> Option<&'static Encoding> {
+    let rest = body.strip_prefix(b"@charset \"")?;
+    let end = rest.iter().position(|&byte| byte == b'"')?;
+    rest[end..].starts_with(b"\";").then_some(())?;
+    Encoding::for_label(&rest[..end]).map(|encoding| encoding.output_encoding())
+}
+
+fn attribute_value<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut search = tag;
+    while let Some(position) = search.find(name) {
+        let preceded = search[..position]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_whitespace());
+        let rest = search[position + name.len()..].trim_start();
+        if preceded && let Some(value) = rest.strip_prefix('=') {
+            let value = value.trim_start();
+            return Some(match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let value = &value[1..];
+                    &value[..value.find(quote).unwrap_or(value.len())]
+                }
+                _ => value
+                    .split(|c: char| c.is_ascii_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default(),
+            });
+        }
+        search = &search[position + name.len()..];
+    }
+    None
+}
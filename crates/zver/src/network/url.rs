@@ -0,0 +1,241 @@
+//! Разобранный URL документа или ресурса.
+//!
+//! Обёртка над [`url::Url`] с правилами движка: строка без схемы считается
+//! путём в файловой системе, а `data:` и `about:blank` загружаются без сети.
+
+use base64::Engine as _;
+use percent_encoding::percent_decode_str;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Ошибки разбора URL
+#[derive(Debug, thiserror::Error)]
+pub enum UrlError {
+    #[error("invalid URL '{input}': {source}")]
+    Invalid {
+        input: String,
+        #[source]
+        source: url::ParseError,
+    },
+    #[error("path cannot be converted to a file URL: {0}")]
+    FilePath(PathBuf),
+}
+
+/// Содержимое `data:` URL (RFC 2397)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataUrl {
+    /// MIME-тип с параметрами, например `text/plain;charset=US-ASCII`
+    pub mime_type: String,
+    pub body: Vec<u8>,
+}
+
+/// Абсолютный URL
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Url(url::Url);
+
+impl Url {
+    /// Разбирает абсолютный URL или путь к файлу.
+    ///
+    /// `file://./page.html` и строки без схемы разрешаются относительно
+    /// текущего каталога.
+    pub fn parse(input: &str) -> Result<Self, UrlError> {
+        let input = input.trim();
+
+        if let Some(path) = input.strip_prefix("file://")
+            && !path.starts_with('/')
+            && !path.starts_with("localhost/")
+        {
+            return Self::from_file_path(path);
+        }
+
+        match url::Url::parse(input) {
+            // Однобуквенная «схема» — это буква диска Windows (`C:\page.html`)
+            Ok(url) if url.scheme().len() > 1 => Ok(Self(url)),
+            Ok(_) | Err(url::ParseError::RelativeUrlWithoutBase) => Self::from_file_path(input),
+            Err(source) => Err(UrlError::Invalid {
+                input: input.to_string(),
+                source,
+            }),
+        }
+    }
+
+    /// `file://` URL для пути; относительный путь берётся от текущего каталога
+    pub fn from_file_path(path: impl AsRef<Path>) -> Result<Self, UrlError> {
+        let path = path.as_ref();
+        let absolute =
+            std::path::absolute(path).map_err(|_| UrlError::FilePath(path.to_path_buf()))?;
+        url::Url::from_file_path(&absolute)
+            .map(Self)
+            .map_err(|()| UrlError::FilePath(absolute))
+    }
+
+    /// Пустой документ `about:blank`
+    pub fn about_blank() -> Self {
+        Self(url::Url::parse("about:blank").expect("about:blank is a valid URL"))
+    }
+
+    /// Разрешает относительную ссылку (`href`, `src`, `@import`) от этого URL
+    pub fn join(&self, reference: &str) -> Result<Self, UrlError> {
+        let reference = reference.trim();
+        self.0
+            .join(reference)
+            .map(Self)
+            .map_err(|source| UrlError::Invalid {
+                input: reference.to_string(),
+                source,
+            })
+    }
+
+    /// URL каталога: путь дополняется слешем, чтобы ссылки разрешались внутрь него
+    pub fn as_directory(&self) -> Self {
+        let mut url = self.0.clone();
+        if !url.path().ends_with('/') {
+            let path = format!("{}/", url.path());
+            url.set_path(&path);
+        }
+        Self(url)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Схема без двоеточия в нижнем регистре
+    pub fn scheme(&self) -> &str {
+        self.0.scheme()
+    }
+
+    pub fn host_str(&self) -> Option<&str> {
+        self.0.host_str()
+    }
+
+    /// Явно указанный порт (порт по умолчанию для схемы не возвращается)
+    pub fn port(&self) -> Option<u16> {
+        self.0.port()
+    }
+
+    pub fn path(&self) -> &str {
+        self.0.path()
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        self.0.query()
+    }
+
+    pub fn fragment(&self) -> Option<&str> {
+        self.0.fragment()
+    }
+
+    /// Тот же URL с заменённой строкой запроса (`None` убирает её)
+    pub fn with_query(&self, query: Option<&str>) -> Self {
+        let mut url = self.0.clone();
+        url.set_query(query);
+        Self(url)
+    }
+
+    /// Сериализованный origin: `https://example.com:8080` или `null`
+    pub fn origin(&self) -> String {
+        self.0.origin().ascii_serialization()
+    }
+
+    /// Последний сегмент пути без percent-encoding
+    pub fn file_name(&self) -> Option<String> {
+        let segment = self
+            .0
+            .path_segments()?
+            .rfind(|segment| !segment.is_empty())?;
+        Some(percent_decode_str(segment).decode_utf8_lossy().into_owned())
+    }
+
+    pub fn is_http(&self) -> bool {
+        matches!(self.scheme(), "http" | "https")
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.scheme() == "file"
+    }
+
+    pub fn is_about_blank(&self) -> bool {
+        self.scheme() == "about" && self.path() == "blank"
+    }
+
+    /// Путь в файловой системе для `file://` URL
+    pub fn to_file_path(&self) -> Option<PathBuf> {
+        if self.is_file() {
+            self.0.to_file_path().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Декодирует `data:` URL; `None` для других схем и некорректных данных
+    pub fn data(&self) -> Option<DataUrl> {
+        let content = self.as_str().strip_prefix("data:")?;
+        let content = content.split_once('#').map_or(content, |(data, _)| data);
+        let (header, payload) = content.split_once(',')?;
+
+        let header = header.trim();
+        let (mime_type, is_base64) = match header.len().checked_sub(";base64".len()) {
+            Some(split) if header[split..].eq_ignore_ascii_case(";base64") => {
+                (&header[..split], true)
+            }
+            _ => (header, false),
+        };
+        let mime_type = if mime_type.is_empty() {
+            "text/plain;charset=US-ASCII".to_string()
+        } else if mime_type.starts_with(';') {
+            format!("text/plain{mime_type}")
+        } else {
+            mime_type.to_string()
+        };
+
+        let decoded: Vec<u8> = percent_decode_str(payload).collect();
+        let body = if is_base64 {
+            let compact: Vec<u8> = decoded
+                .into_iter()
+                .filter(|byte| !byte.is_ascii_whitespace())
+                .collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(&compact)
+                .or_else(|_| base64::engine::general_purpose::STANDARD_NO_PAD.decode(&compact))
+                .ok()?
+        } else {
+            decoded
+        };
+
+        Some(DataUrl { mime_type, body })
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Url {
+    type Err = UrlError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::parse(input)
+    }
+}
+
+impl AsRef<str> for Url {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<url::Url> for Url {
+    fn from(url: url::Url) -> Self {
+        Self(url)
+    }
+}
+
+impl From<Url> for String {
+    fn from(url: Url) -> Self {
+        url.0.into()
+    }
+}
@@ -0,0 +1,291 @@
+//! Reftest-ы: сравнение кадров страницы с эталоном для регрессий layout.
+//!
+//! Тест и эталон рендерятся одинаково — программным растеризатором с
+//! виртуальными часами, — поэтому результат воспроизводим. Эталоном служит
+//! другая страница, описывающая ту же картинку проще, или сохранённый PNG.
+//! Список тестов задаётся [`Manifest`] в формате `reftest.list`.
+//!
+//! ```no_run
+//! # async fn run() {
+//! use zver::reftest::{Manifest, ReftestRunner};
+//!
+//! let manifest = Manifest::load("tests/reftests/reftest.list").unwrap();
+//! let report = ReftestRunner::new()
+//!     .output_dir("target/reftest-output")
+//!     .run(&manifest)
+//!     .await;
+//! assert!(report.passed(), "{report}");
+//! # }
+//! ```
+
+pub mod compare;
+pub mod manifest;
+
+pub use compare::{Comparison, Tolerance, compare, diff_image};
+pub use manifest::{Manifest, ReftestEntry, Relation};
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::ZverError;
+use crate::builder::ZverBuilder;
+use crate::clock::Clock;
+use crate::render::Framebuffer;
+
+/// Сбой reftest-а, не связанный с несовпадением кадров
+#[derive(Debug, thiserror::Error)]
+pub enum ReftestError {
+    #[error("{}:{line}: {message}", path.display())]
+    Manifest {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    #[error("failed to access {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to load {}: {source}", path.display())]
+    Load {
+        path: PathBuf,
+        #[source]
+        source: ZverError,
+    },
+    #[error("{} produced no frame", path.display())]
+    NoFrame { path: PathBuf },
+    #[error("invalid image {}: {message}", path.display())]
+    Image { path: PathBuf, message: String },
+}
+
+/// Результат одной строки манифеста
+#[derive(Debug)]
+pub struct ReftestResult {
+    pub entry: ReftestEntry,
+    /// Сравнение кадров или ошибка, помешавшая его выполнить
+    pub outcome: Result<Comparison, ReftestError>,
+    /// Сохранённые при провале кадры: `actual`, `expected` и `diff`
+    pub artifacts: Vec<PathBuf>,
+}
+
+impl ReftestResult {
+    /// Совпало ли сравнение с ожидаемым отношением
+    pub fn passed(&self) -> bool {
+        self.outcome
+            .as_ref()
+            .is_ok_and(|comparison| self.entry.accepts(comparison))
+    }
+}
+
+impl fmt::Display for ReftestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.passed() { "PASS" } else { "FAIL" };
+        write!(
+            f,
+            "{status} {} {} {}",
+            self.entry.test.display(),
+            self.entry.relation.as_str(),
+            self.entry.reference.display()
+        )?;
+        match &self.outcome {
+            Ok(comparison) if !comparison.same_size => write!(f, ": frame sizes differ")?,
+            Ok(comparison) => write!(
+                f,
+                ": {} pixels differ, max difference {}",
+                comparison.differing_pixels, comparison.max_difference
+            )?,
+            Err(error) => write!(f, ": {error}")?,
+        }
+        for artifact in &self.artifacts {
+            write!(f, "\n    {}", artifact.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Результаты прогона манифеста
+#[derive(Debug, Default)]
+pub struct ReftestReport {
+    pub results: Vec<ReftestResult>,
+}
+
+impl ReftestReport {
+    pub fn passed(&self) -> bool {
+        self.results.iter().all(ReftestResult::passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ReftestResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+impl fmt::Display for ReftestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{result}")?;
+        }
+        let failed = self.failures().count();
+        write!(f, "{} passed, {failed} failed", self.results.len() - failed)
+    }
+}
+
+/// Рендерит страницы и сравнивает их с эталонами.
+///
+/// Каждая страница получает новый движок из [`ZverBuilder`] с виртуальными
+/// часами; после загрузки выполняются все готовые задачи, включая
+/// `setTimeout(f, 0)`.
+#[derive(Debug, Clone)]
+pub struct ReftestRunner {
+    builder: ZverBuilder,
+    output_dir: Option<PathBuf>,
+    update_images: bool,
+}
+
+impl Default for ReftestRunner {
+    fn default() -> Self {
+        Self {
+            builder: ZverBuilder::new(),
+            output_dir: None,
+            update_images: false,
+        }
+    }
+}
+
+impl ReftestRunner {
+    /// Viewport 800x600, JS включён, файлы различий не сохраняются
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Настройки движка: viewport, плотность пикселей, JS; часы всегда виртуальные
+    pub fn builder(mut self, builder: ZverBuilder) -> Self {
+        self.builder = builder;
+        self
+    }
+
+    /// Каталог для кадров провалившихся тестов
+    pub fn output_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    /// Записывать PNG-эталоны текущими кадрами вместо сравнения; без этого
+    /// отсутствующий эталон — ошибка теста
+    pub fn update_images(mut self, update: bool) -> Self {
+        self.update_images = update;
+        self
+    }
+
+    /// Загружает страницу и возвращает её кадр
+    pub async fn render(&self, path: &Path) -> Result<Framebuffer, ReftestError> {
+        let absolute = std::path::absolute(path).map_err(|source| ReftestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let engine = self.builder.clone().clock(Clock::new_virtual()).build();
+        let load = async {
+            engine
+                .load_url(&format!("file://{}", absolute.display()))
+                .await?;
+            engine.run_until_idle().await
+        };
+        load.await.map_err(|source| ReftestError::Load {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        engine
+            .screenshot()
+            .await
+            .ok_or_else(|| ReftestError::NoFrame {
+                path: path.to_path_buf(),
+            })
+    }
+
+    /// Выполняет одну строку манифеста
+    pub async fn check(&self, entry: &ReftestEntry) -> ReftestResult {
+        let mut artifacts = Vec::new();
+        let outcome = self.compare_entry(entry, &mut artifacts).await;
+        ReftestResult {
+            entry: entry.clone(),
+            outcome,
+            artifacts,
+        }
+    }
+
+    /// Выполняет все строки манифеста по порядку
+    pub async fn run(&self, manifest: &Manifest) -> ReftestReport {
+        let mut report = ReftestReport::default();
+        for entry in &manifest.entries {
+            report.results.push(self.check(entry).await);
+        }
+        report
+    }
+
+    async fn compare_entry(
+        &self,
+        entry: &ReftestEntry,
+        artifacts: &mut Vec<PathBuf>,
+    ) -> Result<Comparison, ReftestError> {
+        let actual = self.render(&entry.test).await?;
+        let expected = if !entry.has_image_reference() {
+            self.render(&entry.reference).await?
+        } else if self.update_images {
+            save(&actual, &entry.reference)?;
+            actual.clone()
+        } else {
+            load_png(&entry.reference)?
+        };
+
+        let comparison = compare(&actual, &expected, entry.tolerance);
+        if !entry.accepts(&comparison)
+            && let Some(dir) = &self.output_dir
+        {
+            std::fs::create_dir_all(dir).map_err(|source| ReftestError::Io {
+                path: dir.clone(),
+                source,
+            })?;
+            let name = entry.name();
+            let diff = diff_image(&actual, &expected, entry.tolerance);
+            for (suffix, frame) in [
+                ("actual", &actual),
+                ("expected", &expected),
+                ("diff", &diff),
+            ] {
+                let path = dir.join(format!("{name}.{suffix}.png"));
+                save(frame, &path)?;
+                artifacts.push(path);
+            }
+        }
+        Ok(comparison)
+    }
+}
+
+fn save(frame: &Framebuffer, path: &Path) -> Result<(), ReftestError> {
+    let data = frame.encode_png().map_err(|error| ReftestError::Image {
+        path: path.to_path_buf(),
+        message: error.to_string(),
+    })?;
+    std::fs::write(path, data).map_err(|source| ReftestError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Читает PNG-эталон как RGBA
+pub fn load_png(path: &Path) -> Result<Framebuffer, ReftestError> {
+    let bytes = std::fs::read(path).map_err(|source| ReftestError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let image_error = |message: String| ReftestError::Image {
+        path: path.to_path_buf(),
+        message,
+    };
+    let image = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+        .map_err(|error| image_error(error.to_string()))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    Framebuffer::from_pixels(width, height, image.into_raw())
+        .ok_or_else(|| image_error("unexpected pixel data size".to_string()))
+}
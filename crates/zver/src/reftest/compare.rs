@@ -0,0 +1,115 @@
+//! Попиксельное сравнение кадров и изображение различий
+
+use crate::css::color::Color;
+use crate::render::Framebuffer;
+
+/// Допустимое расхождение, как `fuzzy` в web-platform-tests.
+///
+/// Пиксель совпадает, если ни один канал RGBA не отличается больше чем на
+/// `max_difference`; кадры совпадают, если различающихся пикселей не больше
+/// `max_pixels`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tolerance {
+    pub max_difference: u8,
+    pub max_pixels: usize,
+}
+
+impl Tolerance {
+    /// Точное совпадение
+    pub const EXACT: Self = Self::new(0, 0);
+
+    pub const fn new(max_difference: u8, max_pixels: usize) -> Self {
+        Self {
+            max_difference,
+            max_pixels,
+        }
+    }
+}
+
+/// Итог сравнения двух кадров
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comparison {
+    /// Пиксели, отличающиеся сильнее допуска; при разных размерах сюда
+    /// входят и пиксели, которых нет в одном из кадров
+    pub differing_pixels: usize,
+    /// Наибольшая разница каналов среди всех пикселей
+    pub max_difference: u8,
+    /// Совпадают ли размеры кадров
+    pub same_size: bool,
+}
+
+impl Comparison {
+    /// Укладываются ли различия в допуск; кадры разного размера не совпадают никогда
+    pub fn matches(&self, tolerance: Tolerance) -> bool {
+        self.same_size && self.differing_pixels <= tolerance.max_pixels
+    }
+}
+
+/// Сравнивает кадры попиксельно с учётом `tolerance.max_difference`
+pub fn compare(actual: &Framebuffer, expected: &Framebuffer, tolerance: Tolerance) -> Comparison {
+    let mut comparison = Comparison {
+        differing_pixels: 0,
+        max_difference: 0,
+        same_size: actual.width() == expected.width() && actual.height() == expected.height(),
+    };
+    let (width, height) = union_size(actual, expected);
+    for y in 0..height {
+        for x in 0..width {
+            let (a, b) = (actual.pixel(x, y), expected.pixel(x, y));
+            comparison.max_difference = comparison.max_difference.max(pixel_difference(a, b));
+            if differs(a, b, tolerance) {
+                comparison.differing_pixels += 1;
+            }
+        }
+    }
+    comparison
+}
+
+/// Изображение различий: совпавшие пиксели — бледная копия `actual`,
+/// различающиеся — красные
+pub fn diff_image(
+    actual: &Framebuffer,
+    expected: &Framebuffer,
+    tolerance: Tolerance,
+) -> Framebuffer {
+    let (width, height) = union_size(actual, expected);
+    let mut diff = Framebuffer::new(width, height);
+    diff.clear(Color::rgb(255, 255, 255));
+    for y in 0..height {
+        for x in 0..width {
+            let (a, b) = (actual.pixel(x, y), expected.pixel(x, y));
+            if differs(a, b, tolerance) {
+                diff.fill_rect(x as f32, y as f32, 1.0, 1.0, Color::rgb(255, 0, 0));
+            } else if let Some(color) = a {
+                let faded = Color::new(color.r, color.g, color.b, color.a / 4);
+                diff.fill_rect(x as f32, y as f32, 1.0, 1.0, faded);
+            }
+        }
+    }
+    diff
+}
+
+fn union_size(a: &Framebuffer, b: &Framebuffer) -> (u32, u32) {
+    (a.width().max(b.width()), a.height().max(b.height()))
+}
+
+/// Пиксель за пределами одного из кадров отличается при любом допуске
+fn differs(a: Option<Color>, b: Option<Color>, tolerance: Tolerance) -> bool {
+    a.is_none() || b.is_none() || pixel_difference(a, b) > tolerance.max_difference
+}
+
+/// Наибольшая разница каналов; пиксель за пределами кадра отличается на 255
+fn pixel_difference(a: Option<Color>, b: Option<Color>) -> u8 {
+    match (a, b) {
+        (Some(a), Some(b)) => [
+            a.r.abs_diff(b.r),
+            a.g.abs_diff(b.g),
+            a.b.abs_diff(b.b),
+            a.a.abs_diff(b.a),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0),
+        _ => u8::MAX,
+    }
+}
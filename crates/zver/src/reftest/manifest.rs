@@ -0,0 +1,164 @@
+//! Манифест reftest-ов в формате `reftest.list`.
+//!
+//! Каждая непустая строка — один тест, `#` начинает комментарий:
+//!
+//! ```text
+//! # тест и эталон должны выглядеть одинаково
+//! == flex-row.html flex-row-ref.html
+//! # ... или различаться
+//! != display-none.html display-none-notref.html
+//! # эталоном может быть сохранённый PNG; fuzzy(разница каналов, число пикселей)
+//! fuzzy(0-2,0-40) == borders.html borders.png
+//! ```
+//!
+//! Пути считаются от каталога манифеста. У `fuzzy` допускаются и диапазоны
+//! `min-max`, как в web-platform-tests; учитывается верхняя граница.
+
+use std::path::{Path, PathBuf};
+
+use super::ReftestError;
+use super::compare::{Comparison, Tolerance};
+
+/// Ожидаемое отношение теста к эталону
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    /// `==`: кадры совпадают в пределах допуска
+    Match,
+    /// `!=`: кадры различаются сильнее допуска
+    Mismatch,
+}
+
+impl Relation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Relation::Match => "==",
+            Relation::Mismatch => "!=",
+        }
+    }
+}
+
+/// Строка манифеста
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReftestEntry {
+    pub relation: Relation,
+    pub test: PathBuf,
+    /// HTML-страница или PNG-эталон
+    pub reference: PathBuf,
+    pub tolerance: Tolerance,
+    /// Номер строки в манифесте, с 1
+    pub line: usize,
+}
+
+impl ReftestEntry {
+    /// Эталон — сохранённое изображение, а не страница
+    pub fn has_image_reference(&self) -> bool {
+        self.reference
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+    }
+
+    /// Совпадает ли итог сравнения с ожидаемым отношением
+    pub fn accepts(&self, comparison: &Comparison) -> bool {
+        match self.relation {
+            Relation::Match => comparison.matches(self.tolerance),
+            Relation::Mismatch => !comparison.matches(self.tolerance),
+        }
+    }
+
+    /// Имя теста для отчётов и файлов различий: имя файла без расширения
+    pub fn name(&self) -> String {
+        self.test
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| format!("line-{}", self.line))
+    }
+}
+
+/// Разобранный манифест
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    pub entries: Vec<ReftestEntry>,
+}
+
+impl Manifest {
+    /// Читает манифест; пути тестов становятся относительными к его каталогу
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReftestError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|source| ReftestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Self::parse(&source, base).map_err(|error| match error {
+            ReftestError::Manifest { line, message, .. } => ReftestError::Manifest {
+                path: path.to_path_buf(),
+                line,
+                message,
+            },
+            other => other,
+        })
+    }
+
+    /// Разбирает текст манифеста; `base` — каталог, от которого считаются пути
+    pub fn parse(source: &str, base: &Path) -> Result<Self, ReftestError> {
+        let mut entries = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let line_number = index + 1;
+            let content = line.split('#').next().unwrap_or_default().trim();
+            if content.is_empty() {
+                continue;
+            }
+            let error = |message: String| ReftestError::Manifest {
+                path: PathBuf::new(),
+                line: line_number,
+                message,
+            };
+
+            let mut tokens = content.split_whitespace().peekable();
+            let mut tolerance = Tolerance::EXACT;
+            if let Some(fuzzy) = tokens.next_if(|token| token.starts_with("fuzzy(")) {
+                tolerance = parse_fuzzy(fuzzy).map_err(error)?;
+            }
+            let relation = match tokens.next() {
+                Some("==") => Relation::Match,
+                Some("!=") => Relation::Mismatch,
+                Some(other) => return Err(error(format!("expected == or !=, got '{other}'"))),
+                None => return Err(error("missing == or !=".to_string())),
+            };
+            let (Some(test), Some(reference)) = (tokens.next(), tokens.next()) else {
+                return Err(error("expected a test and a reference".to_string()));
+            };
+            if let Some(extra) = tokens.next() {
+                return Err(error(format!("unexpected '{extra}'")));
+            }
+
+            entries.push(ReftestEntry {
+                relation,
+                test: base.join(test),
+                reference: base.join(reference),
+                tolerance,
+                line: line_number,
+            });
+        }
+        Ok(Self { entries })
+    }
+}
+
+/// `fuzzy(2,40)` или `fuzzy(0-2,0-40)`
+fn parse_fuzzy(token: &str) -> Result<Tolerance, String> {
+    let invalid = || format!("invalid '{token}': expected fuzzy(difference,pixels)");
+    let arguments = token
+        .strip_prefix("fuzzy(")
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    let (difference, pixels) = arguments.split_once(',').ok_or_else(invalid)?;
+    let upper = |range: &str| {
+        let upper = range.rsplit_once('-').map_or(range, |(_, upper)| upper);
+        upper.trim().parse::<usize>().ok()
+    };
+    let difference = upper(difference)
+        .and_then(|difference| u8::try_from(difference).ok())
+        .ok_or_else(invalid)?;
+    let pixels = upper(pixels).ok_or_else(invalid)?;
+    Ok(Tolerance::new(difference, pixels))
+}
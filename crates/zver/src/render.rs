@@ -1,12 +1,19 @@
+pub mod display_list;
+pub mod hit_test;
 pub mod initialization;
 pub mod operations;
+pub mod software;
 pub mod types;
 pub mod utils;
 
+pub use display_list::{BorderWidths, DisplayItem, DisplayList, FontDescriptor};
+pub use hit_test::HitTestResult;
+pub use software::{Framebuffer, SoftwareRasterizer};
 pub use types::*;
 pub use utils::*;
 
 use std::collections::HashMap;
+use std::sync::OnceLock;
 use wgpu::{Device, Queue, Surface, Texture, TextureView};
 use wgpu_text::TextBrush;
 
@@ -30,7 +37,10 @@ pub struct RenderEngine {
     // Bind group layouts
     image_bind_group_layout: Option<wgpu::BindGroupLayout>,
 
-    // Кэш загруженных изображений
+    // Декодированные изображения по абсолютному URL — общий источник для всех бэкендов
+    decoded_images: HashMap<String, std::sync::Arc<Framebuffer>>,
+
+    // Кэш загруженных в GPU изображений
     image_cache: HashMap<String, std::sync::Arc<ImageTexture>>,
 
     // Буферы для батчинга
@@ -38,10 +48,24 @@ pub struct RenderEngine {
     index_buffer: Option<wgpu::Buffer>,
     vertices: Vec<Vertex>,
     indices: Vec<u16>,
+    // Диапазоны индексов в порядке отрисовки: прямоугольники и изображения вперемешку
+    quad_batches: Vec<QuadBatch>,
+    // Текст кадра с прямоугольником обрезки (scissor) каждого прогона
+    text_runs: Vec<(wgpu_text::glyph_brush::OwnedSection, Option<Rect>)>,
 
     // Инкрементальный рендеринг
     #[allow(dead_code)]
     dirty_regions: Vec<types::Rect>,
+
+    // Display list последнего кадра — общий вход для всех бэкендов
+    display_list: DisplayList,
+
+    // Вход программного растеризатора (headless режим без GPU): кадр рисуется
+    // только по запросу, а не на каждый paint
+    software_frame: Option<SoftwareFrame>,
+
+    // Физических пикселей на CSS-пиксель в кадре программного растеризатора
+    device_pixel_ratio: f32,
 }
 
 impl Default for RenderEngine {
@@ -63,28 +87,129 @@ impl RenderEngine {
             msaa_texture: None,
             msaa_view: None,
             image_bind_group_layout: None,
+            decoded_images: HashMap::new(),
             image_cache: HashMap::new(),
             vertex_buffer: None,
             index_buffer: None,
             vertices: Vec::new(),
             indices: Vec::new(),
+            quad_batches: Vec::new(),
+            text_runs: Vec::new(),
             dirty_regions: Vec::new(),
+            display_list: DisplayList::new(),
+            software_frame: None,
+            device_pixel_ratio: 1.0,
         }
     }
 }
 
 impl RenderEngine {
-    /// Простой метод рендеринга для совместимости
+    /// Рендерит текущий layout документа.
+    ///
+    /// Сначала строится [`DisplayList`], затем он исполняется бэкендом: на GPU,
+    /// если устройство инициализировано. Без GPU кадр растеризуется на CPU
+    /// лениво, при первом обращении к [`RenderEngine::frame`].
     pub async fn paint(
         &mut self,
         layout: &crate::layout::LayoutEngine,
         document: &crate::dom::Document,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Получаем информацию для рендеринга с использованием нового API
-        let _render_info = layout.collect_render_info(document);
-        // Заглушка - основная логика рендеринга будет в других методах
+        fonts: &[crate::css::fonts::LoadedFont],
+    ) -> crate::error::ZverResult<()> {
+        self.display_list = DisplayList::build(layout, document);
+
+        if self.device.is_none() {
+            self.software_frame = Some(SoftwareFrame {
+                fonts: fonts.to_vec(),
+                viewport: layout.viewport_size(),
+                frame: OnceLock::new(),
+            });
+        } else {
+            let display_list = std::mem::take(&mut self.display_list);
+            self.submit_display_list(&display_list);
+            self.display_list = display_list;
+        }
+
         Ok(())
     }
+
+    /// Задаёт плотность пикселей кадра: при `2.0` CSS-пиксель занимает 2×2 пикселя
+    /// [`RenderEngine::frame`]. Display list остаётся в CSS-пикселях
+    pub fn set_device_pixel_ratio(&mut self, device_pixel_ratio: f32) {
+        self.device_pixel_ratio = device_pixel_ratio;
+        self.invalidate_frame();
+    }
+
+    pub fn device_pixel_ratio(&self) -> f32 {
+        self.device_pixel_ratio
+    }
+
+    /// Декодирует изображение (PNG, JPEG) для `<img>` с абсолютным URL `src`.
+    ///
+    /// Возвращает `false`, если формат не распознан. На GPU текстура
+    /// создаётся при следующей отправке display list.
+    pub fn load_image(&mut self, src: &str, bytes: &[u8]) -> bool {
+        let Ok(image) = image::load_from_memory(bytes) else {
+            tracing::warn!("Cannot decode image {}", src);
+            return false;
+        };
+        let image = image.into_rgba8();
+        let (width, height) = image.dimensions();
+        let Some(framebuffer) = Framebuffer::from_pixels(width, height, image.into_raw()) else {
+            return false;
+        };
+
+        self.image_cache.remove(src);
+        self.decoded_images
+            .insert(src.to_string(), std::sync::Arc::new(framebuffer));
+        self.invalidate_frame();
+        true
+    }
+
+    /// Есть ли декодированное изображение для `src`
+    pub fn has_image(&self, src: &str) -> bool {
+        self.decoded_images.contains_key(src)
+    }
+
+    /// Декодированное изображение для `src` — для бэкендов вне движка (egui)
+    pub fn image(&self, src: &str) -> Option<std::sync::Arc<Framebuffer>> {
+        self.decoded_images.get(src).cloned()
+    }
+
+    /// Display list последнего кадра
+    pub fn display_list(&self) -> &DisplayList {
+        &self.display_list
+    }
+
+    /// Кадр последнего paint без GPU; растеризуется при первом обращении
+    pub fn frame(&self) -> Option<&Framebuffer> {
+        let software = self.software_frame.as_ref()?;
+        Some(software.frame.get_or_init(|| {
+            let (width, height) = software.viewport;
+            let dpr = self.device_pixel_ratio;
+            let scaled = (dpr != 1.0).then(|| self.display_list.scaled(dpr));
+            SoftwareRasterizer::new(&software.fonts)
+                .with_images(&self.decoded_images)
+                .rasterize(
+                    scaled.as_ref().unwrap_or(&self.display_list),
+                    (width * dpr).max(1.0).round() as u32,
+                    (height * dpr).max(1.0).round() as u32,
+                )
+        }))
+    }
+
+    /// Сбрасывает растеризованный кадр: входные данные кадра изменились
+    fn invalidate_frame(&mut self) {
+        if let Some(software) = &mut self.software_frame {
+            software.frame = OnceLock::new();
+        }
+    }
+}
+
+/// Всё, что нужно программному растеризатору для кадра последнего paint
+struct SoftwareFrame {
+    fonts: Vec<crate::css::fonts::LoadedFont>,
+    viewport: (f32, f32),
+    frame: OnceLock<Framebuffer>,
 }
 
 impl std::fmt::Debug for RenderEngine {
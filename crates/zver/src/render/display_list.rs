@@ -0,0 +1,585 @@
+//! Display list — независимый от бэкенда список команд отрисовки.
+//!
+//! Строится один раз из [`LayoutEngine`] и вычисленных стилей. Порядок отрисовки
+//! (контексты наложения и z-index), прозрачность и обрезка по `overflow` решаются
+//! здесь, а бэкенды (CPU-растеризатор, egui, wgpu) лишь исполняют команды по порядку.
+
+use super::types::Rect;
+use crate::css::color::{Color, parse_css_color};
+use crate::css::fonts::{self, LoadedFont};
+use crate::css::selectors::PseudoElement;
+use crate::dom::Document;
+use crate::layout::types::{ComputedStyle, Display, FontStyle, FontWeight, Position};
+use crate::layout::{LayoutEngine, LayoutResult};
+use std::sync::Arc;
+
+/// Ширины сторон рамки в пикселях
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BorderWidths {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl BorderWidths {
+    pub fn is_empty(&self) -> bool {
+        self.top <= 0.0 && self.right <= 0.0 && self.bottom <= 0.0 && self.left <= 0.0
+    }
+}
+
+/// Параметры шрифта для отрисовки глифов
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontDescriptor {
+    pub family: Option<String>,
+    pub size: f32,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+}
+
+impl FontDescriptor {
+    /// Подбирает fontdue-шрифт среди загруженных @font-face
+    pub fn resolve(&self, fonts: &[LoadedFont]) -> Option<Arc<fontdue::Font>> {
+        fonts::select_font(
+            fonts,
+            self.family.as_deref(),
+            self.face_weight(),
+            self.face_style(),
+        )
+    }
+
+    /// Загруженное @font-face начертание, которым рисуется прогон — то же, что
+    /// выбирает [`FontDescriptor::resolve`]. `None` — встроенный [`fonts::default_font`]
+    pub fn face<'a>(&self, fonts: &'a [LoadedFont]) -> Option<&'a LoadedFont> {
+        self.family
+            .as_deref()
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .map(|name| name.trim().trim_matches(|c| c == '"' || c == '\''))
+            .filter(|name| !name.is_empty())
+            .find_map(|name| fonts::select_face(fonts, name, self.face_weight(), self.face_style()))
+    }
+
+    fn face_weight(&self) -> fonts::FontWeight {
+        match self.weight {
+            FontWeight::Normal => fonts::FontWeight::Normal,
+            FontWeight::Bold => fonts::FontWeight::Bold,
+        }
+    }
+
+    fn face_style(&self) -> fonts::FontStyle {
+        match self.style {
+            FontStyle::Normal => fonts::FontStyle::Normal,
+            FontStyle::Italic => fonts::FontStyle::Italic,
+        }
+    }
+}
+
+/// Команда отрисовки
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayItem {
+    /// Заливка прямоугольника (фон элемента)
+    FillRect {
+        node_id: usize,
+        rect: Rect,
+        color: Color,
+    },
+    /// Рамка внутри `rect` с шириной каждой стороны из `widths`
+    StrokeBorder {
+        node_id: usize,
+        rect: Rect,
+        widths: BorderWidths,
+        color: Color,
+    },
+    /// Отрезок строки одним шрифтом; `rect` — его часть line box, `baseline` — абсолютная
+    /// координата базовой линии
+    GlyphRun {
+        node_id: usize,
+        rect: Rect,
+        baseline: f32,
+        text: String,
+        font: FontDescriptor,
+        color: Color,
+    },
+    /// Изображение (`<img src>`), растягиваемое в `rect`; `src` разрешён от документа
+    Image {
+        node_id: usize,
+        rect: Rect,
+        src: String,
+    },
+    /// Ограничивает последующие команды прямоугольником (пересекается с текущим)
+    PushClip {
+        rect: Rect,
+    },
+    PopClip,
+    /// Рисует последующие команды в отдельный слой и смешивает его с прозрачностью
+    PushOpacity {
+        opacity: f32,
+    },
+    PopOpacity,
+}
+
+impl DisplayItem {
+    /// DOM узел, породивший команду (у служебных команд его нет)
+    pub fn node_id(&self) -> Option<usize> {
+        match self {
+            Self::FillRect { node_id, .. }
+            | Self::StrokeBorder { node_id, .. }
+            | Self::GlyphRun { node_id, .. }
+            | Self::Image { node_id, .. } => Some(*node_id),
+            _ => None,
+        }
+    }
+
+    /// Геометрия команды рисования
+    pub fn rect(&self) -> Option<Rect> {
+        match self {
+            Self::FillRect { rect, .. }
+            | Self::StrokeBorder { rect, .. }
+            | Self::GlyphRun { rect, .. }
+            | Self::Image { rect, .. } => Some(*rect),
+            _ => None,
+        }
+    }
+}
+
+/// Упорядоченный список команд отрисовки кадра
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayList {
+    items: Vec<DisplayItem>,
+}
+
+impl DisplayList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Строит display list из результатов layout и вычисленных стилей.
+    ///
+    /// Текст разбивается на строки не здесь: используются line box'ы, сохранённые layout.
+    pub fn build(layout: &LayoutEngine, document: &Document) -> Self {
+        Self::build_with(layout, document, false)
+    }
+
+    /// Порядок отрисовки для hit testing: каждый элемент дополнительно даёт
+    /// прозрачный `FillRect` своего border box, даже если сам ничего не рисует,
+    /// а элементы с `opacity: 0` не пропускаются.
+    pub(crate) fn hit_regions(layout: &LayoutEngine, document: &Document) -> Self {
+        Self::build_with(layout, document, true)
+    }
+
+    fn build_with(layout: &LayoutEngine, document: &Document, hit_testing: bool) -> Self {
+        let mut builder = DisplayListBuilder {
+            layout,
+            document,
+            items: Vec::new(),
+            hit_testing,
+        };
+
+        if let Some(root) = document.root
+            && layout.get_layout_result(root).is_some()
+        {
+            builder.paint_stacking_context(root);
+        }
+
+        Self {
+            items: builder.items,
+        }
+    }
+
+    pub fn push(&mut self, item: DisplayItem) {
+        self.items.push(item);
+    }
+
+    /// Копия списка в физических пикселях: координаты, рамки и шрифты умножены на `factor`
+    pub fn scaled(&self, factor: f32) -> Self {
+        let scale = |rect: &Rect| {
+            Rect::new(
+                rect.x * factor,
+                rect.y * factor,
+                rect.width * factor,
+                rect.height * factor,
+            )
+        };
+        let items = self
+            .items
+            .iter()
+            .map(|item| match item {
+                DisplayItem::FillRect {
+                    node_id,
+                    rect,
+                    color,
+                } => DisplayItem::FillRect {
+                    node_id: *node_id,
+                    rect: scale(rect),
+                    color: *color,
+                },
+                DisplayItem::StrokeBorder {
+                    node_id,
+                    rect,
+                    widths,
+                    color,
+                } => DisplayItem::StrokeBorder {
+                    node_id: *node_id,
+                    rect: scale(rect),
+                    widths: BorderWidths {
+                        top: widths.top * factor,
+                        right: widths.right * factor,
+                        bottom: widths.bottom * factor,
+                        left: widths.left * factor,
+                    },
+                    color: *color,
+                },
+                DisplayItem::GlyphRun {
+                    node_id,
+                    rect,
+                    baseline,
+                    text,
+                    font,
+                    color,
+                } => DisplayItem::GlyphRun {
+                    node_id: *node_id,
+                    rect: scale(rect),
+                    baseline: baseline * factor,
+                    text: text.clone(),
+                    font: FontDescriptor {
+                        size: font.size * factor,
+                        ..font.clone()
+                    },
+                    color: *color,
+                },
+                DisplayItem::Image { node_id, rect, src } => DisplayItem::Image {
+                    node_id: *node_id,
+                    rect: scale(rect),
+                    src: src.clone(),
+                },
+                DisplayItem::PushClip { rect } => DisplayItem::PushClip { rect: scale(rect) },
+                other => other.clone(),
+            })
+            .collect();
+        Self { items }
+    }
+
+    pub fn items(&self) -> &[DisplayItem] {
+        &self.items
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, DisplayItem> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Объединённый прямоугольник всех команд рисования
+    pub fn bounds(&self) -> Option<Rect> {
+        self.items
+            .iter()
+            .filter_map(DisplayItem::rect)
+            .reduce(|acc, rect| acc.union(&rect))
+    }
+}
+
+impl<'a> IntoIterator for &'a DisplayList {
+    type Item = &'a DisplayItem;
+    type IntoIter = std::slice::Iter<'a, DisplayItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+/// Дочерний слой контекста наложения вместе с обрезками предков на пути к нему
+struct Layer {
+    node_id: usize,
+    z_index: i32,
+    clips: Vec<Rect>,
+}
+
+struct DisplayListBuilder<'a> {
+    layout: &'a LayoutEngine,
+    document: &'a Document,
+    items: Vec<DisplayItem>,
+    hit_testing: bool,
+}
+
+impl<'a> DisplayListBuilder<'a> {
+    /// Рисует узел как корень контекста наложения (CSS 2.1, Appendix E, упрощённо):
+    /// фон и рамка корня, слои с отрицательным z-index, поток документа,
+    /// позиционированные элементы с z-index auto/0, затем положительные z-index.
+    fn paint_stacking_context(&mut self, node_id: usize) {
+        let style = self.style(node_id);
+        let opacity = style.map(|s| s.opacity).unwrap_or(1.0);
+        if opacity <= 0.0 && !self.hit_testing {
+            return;
+        }
+        if opacity < 1.0 {
+            self.items.push(DisplayItem::PushOpacity { opacity });
+        }
+
+        self.paint_box(node_id);
+
+        let clip = self.overflow_clip(node_id);
+        if let Some(rect) = clip {
+            self.items.push(DisplayItem::PushClip { rect });
+        }
+
+        let mut layers = Vec::new();
+        self.collect_layers(node_id, &mut Vec::new(), &mut layers);
+        // Сортировка стабильная: при равном z-index сохраняется порядок документа
+        layers.sort_by_key(|layer| layer.z_index);
+
+        for layer in layers.iter().filter(|layer| layer.z_index < 0) {
+            self.paint_layer(layer);
+        }
+        self.paint_in_flow_children(node_id);
+        for layer in layers.iter().filter(|layer| layer.z_index >= 0) {
+            self.paint_layer(layer);
+        }
+
+        if clip.is_some() {
+            self.items.push(DisplayItem::PopClip);
+        }
+        if opacity < 1.0 {
+            self.items.push(DisplayItem::PopOpacity);
+        }
+    }
+
+    fn paint_layer(&mut self, layer: &Layer) {
+        for &rect in &layer.clips {
+            self.items.push(DisplayItem::PushClip { rect });
+        }
+        self.paint_stacking_context(layer.node_id);
+        for _ in &layer.clips {
+            self.items.push(DisplayItem::PopClip);
+        }
+    }
+
+    /// Собирает позиционированных потомков и вложенные контексты наложения,
+    /// не заходя внутрь них — их содержимое рисуется в их собственном контексте
+    fn collect_layers(&self, node_id: usize, clips: &mut Vec<Rect>, layers: &mut Vec<Layer>) {
+        for child in self.paint_children(node_id) {
+            if self.is_layer(child) {
+                layers.push(Layer {
+                    node_id: child,
+                    z_index: self.style(child).and_then(|s| s.z_index).unwrap_or(0),
+                    clips: clips.clone(),
+                });
+                continue;
+            }
+
+            let clip = self.overflow_clip(child);
+            if let Some(rect) = clip {
+                clips.push(rect);
+            }
+            self.collect_layers(child, clips, layers);
+            if clip.is_some() {
+                clips.pop();
+            }
+        }
+    }
+
+    /// Рисует непозиционированных потомков в порядке документа
+    fn paint_in_flow_children(&mut self, node_id: usize) {
+        for child in self.paint_children(node_id) {
+            if self.is_layer(child) {
+                continue;
+            }
+
+            self.paint_box(child);
+
+            let clip = self.overflow_clip(child);
+            if let Some(rect) = clip {
+                self.items.push(DisplayItem::PushClip { rect });
+            }
+            self.paint_in_flow_children(child);
+            if clip.is_some() {
+                self.items.push(DisplayItem::PopClip);
+            }
+        }
+    }
+
+    /// Дети узла в порядке отрисовки: ::before, DOM-дети, ::after.
+    /// Узлы без layout (display: none, служебные теги) пропускаются вместе с поддеревом.
+    fn paint_children(&self, node_id: usize) -> Vec<usize> {
+        let mut children = Vec::new();
+        let before = self
+            .document
+            .pseudo_child_id(node_id, PseudoElement::Before);
+        let after = self.document.pseudo_child_id(node_id, PseudoElement::After);
+
+        children.extend(before);
+        if let Some(node) = self.document.nodes.get(&node_id) {
+            children.extend(node.children.iter().copied());
+        }
+        children.extend(after);
+
+        children.retain(|id| self.layout.get_layout_result(*id).is_some());
+        children
+    }
+
+    /// Позиционированный элемент или элемент с opacity < 1 рисуется отдельным слоем
+    fn is_layer(&self, node_id: usize) -> bool {
+        self.style(node_id)
+            .is_some_and(|style| !matches!(style.position, Position::Static) || style.opacity < 1.0)
+    }
+
+    fn style(&self, node_id: usize) -> Option<&'a ComputedStyle> {
+        self.layout.resolved_styles().get(&node_id)
+    }
+
+    /// Прямоугольник обрезки потомков (padding box) для `overflow` != visible
+    fn overflow_clip(&self, node_id: usize) -> Option<Rect> {
+        let style = self.style(node_id)?;
+        if !style.is_scroll_container() {
+            return None;
+        }
+        let layout = self.layout.get_layout_result(node_id)?;
+        let borders = border_widths(style, &layout);
+        Some(Rect {
+            x: layout.x + borders.left,
+            y: layout.y + borders.top,
+            width: (layout.width - borders.left - borders.right).max(0.0),
+            height: (layout.height - borders.top - borders.bottom).max(0.0),
+        })
+    }
+
+    /// Фон, рамка и собственное содержимое (текст, изображение) одного узла
+    fn paint_box(&mut self, node_id: usize) {
+        let (Some(layout), Some(style), Some(node)) = (
+            self.layout.get_layout_result(node_id),
+            self.style(node_id),
+            self.document.nodes.get(&node_id),
+        ) else {
+            return;
+        };
+        if matches!(style.display, Display::None) || layout.width <= 0.0 && layout.height <= 0.0 {
+            return;
+        }
+
+        let rect = Rect::from_layout(&layout);
+
+        if node.tag_name.is_none() {
+            self.paint_text(node_id, &layout, style);
+            return;
+        }
+
+        if self.hit_testing {
+            self.items.push(DisplayItem::FillRect {
+                node_id,
+                rect,
+                color: Color::TRANSPARENT,
+            });
+        }
+
+        if let Some(color) = style.background_color.as_deref().and_then(parse_css_color)
+            && color.a > 0
+        {
+            self.items.push(DisplayItem::FillRect {
+                node_id,
+                rect,
+                color,
+            });
+        }
+
+        let widths = border_widths(style, &layout);
+        if !widths.is_empty() {
+            let color = style
+                .border_color
+                .as_deref()
+                .or(style.color.as_deref())
+                .and_then(parse_css_color)
+                .unwrap_or(Color::BLACK);
+            self.items.push(DisplayItem::StrokeBorder {
+                node_id,
+                rect,
+                widths,
+                color,
+            });
+        }
+
+        if node.tag_name.as_deref() == Some("img")
+            && let Some(src) = node.attributes.get("src")
+        {
+            self.items.push(DisplayItem::Image {
+                node_id,
+                rect: Rect {
+                    x: layout.content_x,
+                    y: layout.content_y,
+                    width: layout.content_width,
+                    height: layout.content_height,
+                },
+                // Абсолютный URL — ключ декодированного изображения в RenderEngine
+                src: self
+                    .document
+                    .resolve_url(src)
+                    .map(|url| url.to_string())
+                    .unwrap_or_else(|| src.clone()),
+            });
+        }
+    }
+
+    /// Строки текста берутся из layout как есть — перенос уже выполнен при измерении
+    fn paint_text(&mut self, node_id: usize, layout: &LayoutResult, style: &ComputedStyle) {
+        let Some(text_layout) = self.layout.text_layout(node_id) else {
+            return;
+        };
+
+        let descriptor = FontDescriptor {
+            family: style.font_family.clone(),
+            size: style.font_size,
+            weight: style.font_weight,
+            style: style.font_style,
+        };
+        let color = style
+            .color
+            .as_deref()
+            .and_then(parse_css_color)
+            .unwrap_or(Color::BLACK);
+
+        // Каждый отрезок рисуется тем шрифтом цепочки fallback, которым его измерил layout
+        for line in &text_layout.lines {
+            for run in &line.runs {
+                self.items.push(DisplayItem::GlyphRun {
+                    node_id,
+                    rect: Rect::new(
+                        layout.content_x + line.x + run.x,
+                        layout.content_y + line.y,
+                        run.width,
+                        line.height,
+                    ),
+                    baseline: layout.content_y + line.baseline,
+                    text: run.text.clone(),
+                    font: FontDescriptor {
+                        family: run.family.clone(),
+                        ..descriptor.clone()
+                    },
+                    color,
+                });
+            }
+        }
+    }
+}
+
+fn border_widths(style: &ComputedStyle, layout: &LayoutResult) -> BorderWidths {
+    BorderWidths {
+        top: resolve_length(style.border.top, layout.width),
+        right: resolve_length(style.border.right, layout.width),
+        bottom: resolve_length(style.border.bottom, layout.width),
+        left: resolve_length(style.border.left, layout.width),
+    }
+}
+
+fn resolve_length(value: taffy::style::LengthPercentage, basis: f32) -> f32 {
+    let raw = value.into_raw();
+    match raw.tag() {
+        taffy::style::CompactLength::LENGTH_TAG => raw.value(),
+        taffy::style::CompactLength::PERCENT_TAG => raw.value() * basis,
+        _ => 0.0,
+    }
+}
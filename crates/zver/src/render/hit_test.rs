@@ -0,0 +1,89 @@
+//! Поиск DOM узла под точкой.
+//!
+//! Обходит те же команды, что и отрисовка ([`DisplayList::hit_regions`]), поэтому
+//! верхним оказывается узел, нарисованный последним: порядок контекстов
+//! наложения и z-index совпадает с картинкой. Обрезка `overflow` ограничивает
+//! попадания так же, как пиксели, а текст попадает только строками (line box),
+//! а не всем прямоугольником текстового узла.
+
+use super::display_list::{DisplayItem, DisplayList};
+use super::types::Rect;
+use crate::dom::Document;
+use crate::layout::{LayoutEngine, PointerEvents};
+
+/// Результат [`hit_test`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitTestResult {
+    /// Верхний узел под точкой (текстовый узел, если точка на строке текста)
+    pub node_id: usize,
+    /// Предки узла от родителя до корня документа
+    pub ancestors: Vec<usize>,
+}
+
+impl HitTestResult {
+    /// Узел и его предки, от самого глубокого к корню
+    pub fn path(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.node_id).chain(self.ancestors.iter().copied())
+    }
+
+    /// Ближайший к точке элемент (для текста — его родитель)
+    pub fn element(&self, document: &Document) -> Option<usize> {
+        self.path()
+            .find(|id| document.nodes.get(id).is_some_and(|node| node.is_element()))
+    }
+}
+
+/// Находит верхний узел в точке `(x, y)` в координатах документа (CSS-пиксели).
+///
+/// Узлы с `pointer-events: none` пропускаются — под ними находится следующий
+/// по порядку отрисовки узел.
+pub fn hit_test(
+    layout: &LayoutEngine,
+    document: &Document,
+    x: f32,
+    y: f32,
+) -> Option<HitTestResult> {
+    let regions = DisplayList::hit_regions(layout, document);
+    let mut clips: Vec<Rect> = Vec::new();
+    let mut hit = None;
+
+    for item in &regions {
+        match item {
+            DisplayItem::PushClip { rect } => {
+                let clip = clips.last().map_or(*rect, |outer| outer.intersect(rect));
+                clips.push(clip);
+            }
+            DisplayItem::PopClip => {
+                clips.pop();
+            }
+            _ => {
+                let (Some(node_id), Some(rect)) = (item.node_id(), item.rect()) else {
+                    continue;
+                };
+                if rect.contains(x, y)
+                    && clips.last().is_none_or(|clip| clip.contains(x, y))
+                    && accepts_pointer(layout, node_id)
+                {
+                    hit = Some(node_id);
+                }
+            }
+        }
+    }
+
+    let node_id = hit?;
+    let mut ancestors = Vec::new();
+    let mut current = document.nodes.get(&node_id).and_then(|node| node.parent);
+    while let Some(id) = current {
+        ancestors.push(id);
+        current = document.nodes.get(&id).and_then(|node| node.parent);
+    }
+    Some(HitTestResult { node_id, ancestors })
+}
+
+fn accepts_pointer(layout: &LayoutEngine, node_id: usize) -> bool {
+    layout
+        .resolved_styles()
+        .get(&node_id)
+        .and_then(|style| style.pointer_events)
+        != Some(PointerEvents::None)
+}
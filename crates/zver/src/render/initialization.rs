@@ -1,14 +1,12 @@
 use super::RenderEngine;
 use super::types::*;
+use crate::error::{ZverError, ZverResult};
 use wgpu::{Device, SurfaceConfiguration};
 use wgpu_text::{BrushBuilder, glyph_brush::ab_glyph::FontArc};
 use winit::window::Window;
 
 impl RenderEngine {
-    pub async fn initialize(
-        &mut self,
-        window: &'static Window,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn initialize(&mut self, window: &'static Window) -> ZverResult<()> {
         let instance = wgpu::Instance::default();
 
         // SAFETY: Creating a surface from a window is inherently unsafe because:
@@ -17,7 +15,8 @@ impl RenderEngine {
         // 3. The surface is stored alongside the window in RenderEngine
         // 4. Both are dropped together, preventing use-after-free
         #[allow(unused_unsafe)]
-        let surface = unsafe { instance.create_surface(window)? };
+        let surface = unsafe { instance.create_surface(window) }
+            .map_err(|e| ZverError::Render(format!("failed to create surface: {}", e)))?;
 
         let adapter = instance
             .request_adapter(&wgpu::RequestAdapterOptions {
@@ -29,7 +28,11 @@ impl RenderEngine {
 
         let adapter = match adapter {
             Ok(adapter) => adapter,
-            Err(_) => return Err("Failed to find suitable adapter".into()),
+            Err(_) => {
+                return Err(ZverError::Render(
+                    "failed to find suitable adapter".to_string(),
+                ));
+            }
         };
 
         let (device, queue) = adapter
@@ -41,7 +44,8 @@ impl RenderEngine {
                 trace: Default::default(),
                 experimental_features: Default::default(),
             })
-            .await?;
+            .await
+            .map_err(|e| ZverError::Render(format!("failed to request device: {}", e)))?;
 
         let surface_caps = surface.get_capabilities(&adapter);
         let surface_format = surface_caps
@@ -66,7 +70,7 @@ impl RenderEngine {
         surface.configure(&device, &config);
 
         // Инициализация текстового браша
-        let font_data = include_bytes!("../../../../assets/fonts/Roboto-Regular.ttf");
+        let font_data = include_bytes!("../../../../assets/fonts/DejaVuSans.ttf");
         let font = FontArc::try_from_slice(font_data).expect("Failed to load font");
         let text_brush = BrushBuilder::using_font(font).build(
             &device,
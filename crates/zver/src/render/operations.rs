@@ -1,5 +1,7 @@
 use super::RenderEngine;
+use super::display_list::{DisplayItem, DisplayList};
 use super::types::*;
+use super::utils::color_to_rgba;
 use wgpu_text::glyph_brush::{Section, Text};
 
 impl RenderEngine {
@@ -37,6 +39,23 @@ impl RenderEngine {
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
+        ensure_buffer_capacity(
+            device,
+            &mut self.vertex_buffer,
+            "Vertex Buffer",
+            wgpu::BufferUsages::VERTEX,
+            std::mem::size_of_val(self.vertices.as_slice()),
+        );
+        // write_buffer требует размер, кратный 4: u16-индексы выравниваются парой
+        let index_bytes = std::mem::size_of_val(self.indices.as_slice()).next_multiple_of(4);
+        ensure_buffer_capacity(
+            device,
+            &mut self.index_buffer,
+            "Index Buffer",
+            wgpu::BufferUsages::INDEX,
+            index_bytes,
+        );
+
         let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
@@ -64,104 +83,431 @@ impl RenderEngine {
                 timestamp_writes: None,
             });
 
-            // Рендерим прямоугольники
+            // Прямоугольники и изображения в порядке display list
             if !self.vertices.is_empty() {
-                self.render_rectangles(&mut render_pass, queue);
+                self.render_quads(&mut render_pass, queue);
             }
-
-            // Рендерим изображения
-            self.render_images(&mut render_pass);
         }
 
         queue.submit(std::iter::once(encoder.finish()));
+
+        // Текст рисуется поверх отдельными проходами: у каждой группы с общим
+        // clip свой scissor rect. TextBrush держит один буфер вершин, поэтому
+        // группа отправляется в очередь до подготовки следующей.
+        if let Some(text_brush) = &mut self.text_brush {
+            let (width, height) = self
+                .state
+                .as_ref()
+                .map(|state| (state.config.width, state.config.height))
+                .unwrap_or((1, 1));
+
+            for group in self.text_runs.chunk_by(|a, b| a.1 == b.1) {
+                let clip = group[0].1;
+                let sections: Vec<_> = group
+                    .iter()
+                    .map(|(section, _)| section.to_borrowed())
+                    .collect();
+                if text_brush.queue(device, queue, sections).is_err() {
+                    continue;
+                }
+
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Text Encoder"),
+                });
+                {
+                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        label: Some("Text Pass"),
+                        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                            view: &view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Load,
+                                store: wgpu::StoreOp::Store,
+                            },
+                            depth_slice: None,
+                        })],
+                        depth_stencil_attachment: None,
+                        occlusion_query_set: None,
+                        timestamp_writes: None,
+                    });
+
+                    if let Some(clip) = clip {
+                        let Some((x, y, w, h)) = scissor_rect(clip, width, height) else {
+                            continue;
+                        };
+                        render_pass.set_scissor_rect(x, y, w, h);
+                    }
+                    text_brush.draw(&mut render_pass);
+                }
+                queue.submit(std::iter::once(encoder.finish()));
+            }
+        }
+
         output.present();
 
         Ok(())
     }
 
-    fn render_rectangles(&self, render_pass: &mut wgpu::RenderPass, queue: &wgpu::Queue) {
-        if let (Some(vertex_buffer), Some(index_buffer), Some(rect_pipeline)) =
-            (&self.vertex_buffer, &self.index_buffer, &self.rect_pipeline)
-        {
+    fn render_quads(&self, render_pass: &mut wgpu::RenderPass, queue: &wgpu::Queue) {
+        if let (
+            Some(vertex_buffer),
+            Some(index_buffer),
+            Some(rect_pipeline),
+            Some(image_pipeline),
+        ) = (
+            &self.vertex_buffer,
+            &self.index_buffer,
+            &self.rect_pipeline,
+            &self.image_pipeline,
+        ) {
             // Обновляем буферы
+            let mut indices = self.indices.clone();
+            if indices.len() % 2 == 1 {
+                indices.push(0);
+            }
             queue.write_buffer(vertex_buffer, 0, bytemuck::cast_slice(&self.vertices));
-            queue.write_buffer(index_buffer, 0, bytemuck::cast_slice(&self.indices));
+            queue.write_buffer(index_buffer, 0, bytemuck::cast_slice(&indices));
 
-            render_pass.set_pipeline(rect_pipeline);
             render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
             render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.indices.len() as u32, 0, 0..1);
+            for batch in &self.quad_batches {
+                match &batch.image {
+                    Some(image) => {
+                        render_pass.set_pipeline(image_pipeline);
+                        render_pass.set_bind_group(0, Some(&*image.bind_group), &[]);
+                    }
+                    None => render_pass.set_pipeline(rect_pipeline),
+                }
+                render_pass.draw_indexed(batch.indices.clone(), 0, 0..1);
+            }
         }
     }
 
-    fn render_images(&self, render_pass: &mut wgpu::RenderPass) {
-        if let Some(image_pipeline) = &self.image_pipeline {
-            render_pass.set_pipeline(image_pipeline);
+    pub fn add_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: [f32; 4]) {
+        self.push_quad(
+            Rect::new(x, y, width, height),
+            Rect::new(0.0, 0.0, 1.0, 1.0),
+            color,
+            None,
+        );
+    }
 
-            for image in self.image_cache.values() {
-                render_pass.set_bind_group(0, Some(&*image.bind_group), &[]);
-                // Здесь должна быть логика рендеринга конкретного изображения
-                // render_pass.draw_indexed(...);
-            }
-        }
+    /// Добавляет текстурированный прямоугольник; `uv` — видимая часть текстуры (0..1)
+    fn add_image(
+        &mut self,
+        rect: Rect,
+        uv: Rect,
+        image: std::sync::Arc<ImageTexture>,
+        opacity: f32,
+    ) {
+        self.push_quad(rect, uv, [1.0, 1.0, 1.0, opacity], Some(image));
     }
 
-    pub fn add_rectangle(&mut self, x: f32, y: f32, width: f32, height: f32, color: [f32; 4]) {
+    fn push_quad(
+        &mut self,
+        rect: Rect,
+        uv: Rect,
+        color: [f32; 4],
+        image: Option<std::sync::Arc<ImageTexture>>,
+    ) {
         let start_index = self.vertices.len() as u16;
+        let corners = [
+            (rect.x, rect.y, uv.x, uv.y),
+            (rect.right(), rect.y, uv.right(), uv.y),
+            (rect.right(), rect.bottom(), uv.right(), uv.bottom()),
+            (rect.x, rect.bottom(), uv.x, uv.bottom()),
+        ];
 
-        // Добавляем вершины прямоугольника
-        self.vertices.extend_from_slice(&[
-            Vertex {
-                position: [x, y],
-                tex_coords: [0.0, 0.0],
-                color,
-            },
-            Vertex {
-                position: [x + width, y],
-                tex_coords: [1.0, 0.0],
-                color,
-            },
-            Vertex {
-                position: [x + width, y + height],
-                tex_coords: [1.0, 1.0],
-                color,
-            },
-            Vertex {
-                position: [x, y + height],
-                tex_coords: [0.0, 1.0],
+        // Добавляем вершины прямоугольника (пиксели переводятся в clip space)
+        for (x, y, u, v) in corners {
+            self.vertices.push(Vertex {
+                position: self.to_clip_space(x, y),
+                tex_coords: [u, v],
                 color,
-            },
-        ]);
+            });
+        }
 
-        // Добавляем индексы для двух треугольников
+        // Ось Y в clip space направлена вверх, поэтому обход против часовой — 0, 2, 1
+        let first = self.indices.len() as u32;
         self.indices.extend_from_slice(&[
             start_index,
-            start_index + 1,
             start_index + 2,
+            start_index + 1,
             start_index,
-            start_index + 2,
             start_index + 3,
+            start_index + 2,
         ]);
+        let end = self.indices.len() as u32;
+
+        // Соседние прямоугольники без текстуры рисуются одним вызовом
+        match self.quad_batches.last_mut() {
+            Some(batch) if image.is_none() && batch.image.is_none() => batch.indices.end = end,
+            _ => self.quad_batches.push(QuadBatch {
+                image,
+                indices: first..end,
+            }),
+        }
+    }
+
+    fn to_clip_space(&self, x: f32, y: f32) -> [f32; 2] {
+        let (width, height) = self
+            .state
+            .as_ref()
+            .map(|state| (state.config.width as f32, state.config.height as f32))
+            .unwrap_or((1.0, 1.0));
+        [x / width * 2.0 - 1.0, 1.0 - y / height * 2.0]
     }
 
     pub fn add_text(&mut self, text: &str, x: f32, y: f32, size: f32, color: [f32; 4]) {
-        if let (Some(text_brush), Some(device), Some(queue)) =
-            (&mut self.text_brush, &self.device, &self.queue)
-        {
-            let section = Section::default()
-                .add_text(Text::new(text).with_color(color).with_scale(size))
-                .with_screen_position((x, y));
+        self.queue_text(text, x, y, size, color, None);
+    }
+
+    fn queue_text(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: [f32; 4],
+        clip: Option<Rect>,
+    ) {
+        let section = Section::default()
+            .add_text(Text::new(text).with_color(color).with_scale(size))
+            .with_screen_position((x, y));
+        self.text_runs.push((section.to_owned(), clip));
+    }
+
+    /// Создаёт GPU-текстуру для декодированного изображения (один раз на URL)
+    fn image_texture(&mut self, src: &str) -> Option<std::sync::Arc<ImageTexture>> {
+        if let Some(texture) = self.image_cache.get(src) {
+            return Some(texture.clone());
+        }
+
+        let image = self.decoded_images.get(src)?;
+        let (device, queue, layout) =
+            match (&self.device, &self.queue, &self.image_bind_group_layout) {
+                (Some(device), Some(queue), Some(layout)) => (device, queue, layout),
+                _ => return None,
+            };
+
+        let size = wgpu::Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Image Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            image.pixels(),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * image.width()),
+                rows_per_image: Some(image.height()),
+            },
+            size,
+        );
 
-            let _ = text_brush.queue(device, queue, vec![&section]);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Image Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Image Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let texture = std::sync::Arc::new(ImageTexture {
+            width: image.width(),
+            height: image.height(),
+            texture: std::sync::Arc::new(texture),
+            view: std::sync::Arc::new(view),
+            bind_group: std::sync::Arc::new(bind_group),
+        });
+        self.image_cache.insert(src.to_string(), texture.clone());
+        Some(texture)
+    }
+
+    /// Переводит display list в батчи прямоугольников и текста wgpu.
+    ///
+    /// Прозрачность групп умножается на альфу каждой команды, прямоугольники и
+    /// изображения обрезаются на CPU, текст — scissor rect своего прохода.
+    pub fn submit_display_list(&mut self, display_list: &DisplayList) {
+        self.clear_frame();
+
+        let mut clips: Vec<Rect> = Vec::new();
+        let mut opacities: Vec<f32> = Vec::new();
+
+        for item in display_list {
+            let clip = clips.last().copied();
+            let opacity: f32 = opacities.iter().product();
+            let clipped = |rect: Rect| match clip {
+                Some(clip) => rect.intersect(&clip),
+                None => rect,
+            };
+
+            match item {
+                DisplayItem::FillRect { rect, color, .. } => {
+                    let rect = clipped(*rect);
+                    if !rect.is_empty() {
+                        self.add_rectangle(
+                            rect.x,
+                            rect.y,
+                            rect.width,
+                            rect.height,
+                            color_to_rgba(*color, opacity),
+                        );
+                    }
+                }
+                DisplayItem::StrokeBorder {
+                    rect,
+                    widths,
+                    color,
+                    ..
+                } => {
+                    let inner_height = rect.height - widths.top - widths.bottom;
+                    let sides = [
+                        Rect::new(rect.x, rect.y, rect.width, widths.top),
+                        Rect::new(
+                            rect.x,
+                            rect.bottom() - widths.bottom,
+                            rect.width,
+                            widths.bottom,
+                        ),
+                        Rect::new(rect.x, rect.y + widths.top, widths.left, inner_height),
+                        Rect::new(
+                            rect.right() - widths.right,
+                            rect.y + widths.top,
+                            widths.right,
+                            inner_height,
+                        ),
+                    ];
+                    for side in sides.map(clipped) {
+                        if !side.is_empty() {
+                            self.add_rectangle(
+                                side.x,
+                                side.y,
+                                side.width,
+                                side.height,
+                                color_to_rgba(*color, opacity),
+                            );
+                        }
+                    }
+                }
+                DisplayItem::GlyphRun {
+                    rect,
+                    text,
+                    font,
+                    color,
+                    ..
+                } => {
+                    if clip.is_none_or(|clip| !rect.intersect(&clip).is_empty()) {
+                        self.queue_text(
+                            text,
+                            rect.x,
+                            rect.y,
+                            font.size,
+                            color_to_rgba(*color, opacity),
+                            clip,
+                        );
+                    }
+                }
+                DisplayItem::Image { rect, src, .. } => {
+                    let visible = clipped(*rect);
+                    if visible.is_empty() {
+                        continue;
+                    }
+                    if let Some(image) = self.image_texture(src) {
+                        // Обрезка сдвигает и текстурные координаты
+                        let uv = Rect::new(
+                            (visible.x - rect.x) / rect.width,
+                            (visible.y - rect.y) / rect.height,
+                            visible.width / rect.width,
+                            visible.height / rect.height,
+                        );
+                        self.add_image(visible, uv, image, opacity);
+                    }
+                }
+                DisplayItem::PushClip { rect } => {
+                    clips.push(clipped(*rect));
+                }
+                DisplayItem::PopClip => {
+                    clips.pop();
+                }
+                DisplayItem::PushOpacity { opacity } => {
+                    opacities.push(*opacity);
+                }
+                DisplayItem::PopOpacity => {
+                    opacities.pop();
+                }
+            }
         }
     }
 
     pub fn clear_frame(&mut self) {
         self.vertices.clear();
         self.indices.clear();
+        self.quad_batches.clear();
+        self.text_runs.clear();
+    }
+}
 
-        // Очищаем очередь текста (это делается автоматически после рендеринга)
+/// Пересоздаёт буфер с запасом, если данные кадра в него не помещаются
+fn ensure_buffer_capacity(
+    device: &wgpu::Device,
+    buffer: &mut Option<wgpu::Buffer>,
+    label: &str,
+    usage: wgpu::BufferUsages,
+    required: usize,
+) {
+    let required = required as u64;
+    if buffer
+        .as_ref()
+        .is_some_and(|buffer| buffer.size() >= required)
+    {
+        return;
     }
+    *buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: required.next_power_of_two(),
+        usage: usage | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    }));
+}
+
+/// Scissor rect в пикселях цели; `None`, если clip целиком за её пределами
+fn scissor_rect(clip: Rect, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let x0 = (clip.x.floor().max(0.0) as u32).min(width);
+    let y0 = (clip.y.floor().max(0.0) as u32).min(height);
+    let x1 = (clip.right().ceil().max(0.0) as u32).min(width);
+    let y1 = (clip.bottom().ceil().max(0.0) as u32).min(height);
+    (x1 > x0 && y1 > y0).then_some((x0, y0, x1 - x0, y1 - y0))
 }
 
 fn create_msaa_texture(
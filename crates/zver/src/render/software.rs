@@ -0,0 +1,402 @@
+//! Программный (CPU) растеризатор.
+//!
+//! Исполняет [`DisplayList`] в RGBA-фреймбуфер без GPU и окна, поэтому скриншоты
+//! можно снимать в CI-контейнерах. Глифы растеризуются через fontdue, шрифты
+//! подбираются среди загруженных @font-face (см. [`crate::css::fonts`]),
+//! изображения берутся из уже декодированных [`RenderEngine::load_image`].
+//!
+//! [`RenderEngine::load_image`]: super::RenderEngine::load_image
+
+use super::display_list::{DisplayItem, DisplayList};
+use super::types::Rect;
+use crate::css::color::Color;
+use crate::css::fonts::{self, LoadedFont};
+use crate::layout::types::FontWeight;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// RGBA-фреймбуфер (8 бит на канал, без premultiplied alpha).
+#[derive(Clone, PartialEq)]
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl std::fmt::Debug for Framebuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Framebuffer")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+impl Framebuffer {
+    /// Создаёт прозрачный фреймбуфер заданного размера
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// Создаёт фреймбуфер из готовых RGBA-данных
+    pub fn from_pixels(width: u32, height: u32, pixels: Vec<u8>) -> Option<Self> {
+        (pixels.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Сырые RGBA-данные построчно, сверху вниз
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    /// Возвращает цвет пикселя или `None` за пределами буфера
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = self.index(x, y);
+        let p = &self.pixels[index..index + 4];
+        Some(Color::new(p[0], p[1], p[2], p[3]))
+    }
+
+    /// Заливает весь буфер цветом без смешивания
+    pub fn clear(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+
+    /// Заливает прямоугольник (координаты округляются до пикселей) с альфа-смешиванием
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Color) {
+        if color.a == 0 || width <= 0.0 || height <= 0.0 {
+            return;
+        }
+
+        let x0 = x.round().max(0.0) as u32;
+        let y0 = y.round().max(0.0) as u32;
+        let x1 = ((x + width).round().max(0.0) as u32).min(self.width);
+        let y1 = ((y + height).round().max(0.0) as u32).min(self.height);
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                self.blend(px, py, color, 1.0);
+            }
+        }
+    }
+
+    /// Смешивает цвет с пикселем с учётом покрытия (0.0..=1.0), source-over
+    pub fn blend_pixel(&mut self, x: i32, y: i32, color: Color, coverage: f32) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return;
+        }
+        self.blend(x as u32, y as u32, color, coverage);
+    }
+
+    /// Накладывает слой поверх буфера с общей прозрачностью (source-over)
+    pub fn composite(&mut self, layer: &Framebuffer, opacity: f32) {
+        let width = self.width.min(layer.width);
+        let height = self.height.min(layer.height);
+        for y in 0..height {
+            for x in 0..width {
+                let index = layer.index(x, y);
+                let p = &layer.pixels[index..index + 4];
+                if p[3] > 0 {
+                    self.blend(x, y, Color::new(p[0], p[1], p[2], p[3]), opacity);
+                }
+            }
+        }
+    }
+
+    /// Рисует изображение, растянутое в `rect` (ближайший пиксель), с альфа-смешиванием
+    pub fn draw_image(&mut self, image: &Framebuffer, rect: Rect, clip: Option<Rect>) {
+        if image.width == 0 || image.height == 0 || rect.is_empty() {
+            return;
+        }
+        let visible = clip.map(|clip| rect.intersect(&clip)).unwrap_or(rect);
+
+        let x0 = visible.x.round().max(0.0) as u32;
+        let y0 = visible.y.round().max(0.0) as u32;
+        let x1 = (visible.right().round().max(0.0) as u32).min(self.width);
+        let y1 = (visible.bottom().round().max(0.0) as u32).min(self.height);
+
+        for py in y0..y1 {
+            let v = (py as f32 + 0.5 - rect.y) / rect.height;
+            let sy = ((v * image.height as f32) as u32).min(image.height - 1);
+            for px in x0..x1 {
+                let u = (px as f32 + 0.5 - rect.x) / rect.width;
+                let sx = ((u * image.width as f32) as u32).min(image.width - 1);
+                let index = image.index(sx, sy);
+                let p = &image.pixels[index..index + 4];
+                self.blend(px, py, Color::new(p[0], p[1], p[2], p[3]), 1.0);
+            }
+        }
+    }
+
+    /// Кодирует содержимое в PNG
+    pub fn encode_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut output = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut output, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+            writer.finish()?;
+        }
+        Ok(output)
+    }
+
+    /// Сохраняет содержимое в PNG-файл
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), png::EncodingError> {
+        let data = self.encode_png()?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: Color, coverage: f32) {
+        let src_a = (color.a as f32 / 255.0) * coverage.clamp(0.0, 1.0);
+        if src_a <= 0.0 {
+            return;
+        }
+
+        let index = self.index(x, y);
+        let dst = &mut self.pixels[index..index + 4];
+        let dst_a = dst[3] as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            return;
+        }
+
+        let mix = |src: u8, dst: u8| -> u8 {
+            let value = (src as f32 * src_a + dst as f32 * dst_a * (1.0 - src_a)) / out_a;
+            value.round().clamp(0.0, 255.0) as u8
+        };
+
+        dst[0] = mix(color.r, dst[0]);
+        dst[1] = mix(color.g, dst[1]);
+        dst[2] = mix(color.b, dst[2]);
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+}
+
+type GlyphKey = (usize, char, u32);
+
+/// Исполнитель [`DisplayList`] на CPU.
+///
+/// Держит кеш растеризованных глифов, поэтому один экземпляр выгодно
+/// переиспользовать для нескольких кадров с одинаковыми шрифтами.
+pub struct SoftwareRasterizer<'a> {
+    fonts: &'a [LoadedFont],
+    images: Option<&'a HashMap<String, Arc<Framebuffer>>>,
+    glyph_cache: HashMap<GlyphKey, (fontdue::Metrics, Vec<u8>)>,
+}
+
+impl<'a> SoftwareRasterizer<'a> {
+    pub fn new(fonts: &'a [LoadedFont]) -> Self {
+        Self {
+            fonts,
+            images: None,
+            glyph_cache: HashMap::new(),
+        }
+    }
+
+    /// Декодированные изображения по URL для команд [`DisplayItem::Image`].
+    ///
+    /// Команды с отсутствующим изображением пропускаются.
+    pub fn with_images(mut self, images: &'a HashMap<String, Arc<Framebuffer>>) -> Self {
+        self.images = Some(images);
+        self
+    }
+
+    /// Исполняет команды display list по порядку на белом фоне
+    pub fn rasterize(
+        &mut self,
+        display_list: &DisplayList,
+        width: u32,
+        height: u32,
+    ) -> Framebuffer {
+        let mut base = Framebuffer::new(width, height);
+        base.clear(Color::WHITE);
+
+        // Слои для групп с opacity: рисуем в прозрачный буфер и смешиваем при PopOpacity
+        let mut layers: Vec<(Framebuffer, f32)> = Vec::new();
+        let mut clips: Vec<Rect> = Vec::new();
+
+        for item in display_list {
+            let clip = clips.last().copied();
+            let target = layers
+                .last_mut()
+                .map(|(layer, _)| layer)
+                .unwrap_or(&mut base);
+
+            match item {
+                DisplayItem::FillRect { rect, color, .. } => {
+                    fill_clipped(target, *rect, *color, clip);
+                }
+                DisplayItem::StrokeBorder {
+                    rect,
+                    widths,
+                    color,
+                    ..
+                } => {
+                    let inner_height = rect.height - widths.top - widths.bottom;
+                    let sides = [
+                        Rect::new(rect.x, rect.y, rect.width, widths.top),
+                        Rect::new(
+                            rect.x,
+                            rect.y + rect.height - widths.bottom,
+                            rect.width,
+                            widths.bottom,
+                        ),
+                        Rect::new(rect.x, rect.y + widths.top, widths.left, inner_height),
+                        Rect::new(
+                            rect.x + rect.width - widths.right,
+                            rect.y + widths.top,
+                            widths.right,
+                            inner_height,
+                        ),
+                    ];
+                    for side in sides {
+                        fill_clipped(target, side, *color, clip);
+                    }
+                }
+                DisplayItem::GlyphRun {
+                    rect,
+                    baseline,
+                    text,
+                    font,
+                    color,
+                    ..
+                } => {
+                    let Some(resolved) = font.resolve(self.fonts) else {
+                        continue;
+                    };
+                    self.draw_run(
+                        target, &resolved, text, rect.x, *baseline, font.size, *color, clip,
+                    );
+                    // Встроенный DejaVu Sans есть только в Regular — полужирный синтезируем двойной отрисовкой
+                    if matches!(font.weight, FontWeight::Bold)
+                        && fonts::default_font()
+                            .is_some_and(|default| Arc::ptr_eq(&resolved, &default))
+                    {
+                        self.draw_run(
+                            target,
+                            &resolved,
+                            text,
+                            rect.x + 0.6,
+                            *baseline,
+                            font.size,
+                            *color,
+                            clip,
+                        );
+                    }
+                }
+                DisplayItem::Image { rect, src, .. } => {
+                    if let Some(image) = self.images.and_then(|images| images.get(src)) {
+                        target.draw_image(image, *rect, clip);
+                    }
+                }
+                DisplayItem::PushClip { rect } => {
+                    let next = clip.map(|current| current.intersect(rect)).unwrap_or(*rect);
+                    clips.push(next);
+                }
+                DisplayItem::PopClip => {
+                    clips.pop();
+                }
+                DisplayItem::PushOpacity { opacity } => {
+                    layers.push((Framebuffer::new(width, height), *opacity));
+                }
+                DisplayItem::PopOpacity => {
+                    if let Some((layer, opacity)) = layers.pop() {
+                        let target = layers
+                            .last_mut()
+                            .map(|(layer, _)| layer)
+                            .unwrap_or(&mut base);
+                        target.composite(&layer, opacity);
+                    }
+                }
+            }
+        }
+
+        base
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_run(
+        &mut self,
+        framebuffer: &mut Framebuffer,
+        font: &Arc<fontdue::Font>,
+        text: &str,
+        x: f32,
+        baseline: f32,
+        size: f32,
+        color: Color,
+        clip: Option<Rect>,
+    ) {
+        let font_key = Arc::as_ptr(font) as usize;
+        let mut pen_x = x;
+        let mut previous = None;
+
+        for ch in text.chars() {
+            // Кернинг учитывается так же, как при измерении в layout
+            if let Some(prev) = previous {
+                pen_x += font.horizontal_kern(prev, ch, size).unwrap_or(0.0);
+            }
+            previous = Some(ch);
+
+            let (metrics, bitmap) = self
+                .glyph_cache
+                .entry((font_key, ch, size.to_bits()))
+                .or_insert_with(|| font.rasterize(ch, size));
+
+            let left = (pen_x + metrics.xmin as f32).round() as i32;
+            let top = (baseline - metrics.height as f32 - metrics.ymin as f32).round() as i32;
+
+            for row in 0..metrics.height {
+                for col in 0..metrics.width {
+                    let coverage = bitmap[row * metrics.width + col];
+                    let (px, py) = (left + col as i32, top + row as i32);
+                    if coverage > 0
+                        && clip.is_none_or(|clip| clip.contains(px as f32 + 0.5, py as f32 + 0.5))
+                    {
+                        framebuffer.blend_pixel(px, py, color, coverage as f32 / 255.0);
+                    }
+                }
+            }
+
+            pen_x += metrics.advance_width;
+        }
+    }
+}
+
+fn fill_clipped(framebuffer: &mut Framebuffer, rect: Rect, color: Color, clip: Option<Rect>) {
+    let rect = match clip {
+        Some(clip) => rect.intersect(&clip),
+        None => rect,
+    };
+    framebuffer.fill_rect(rect.x, rect.y, rect.width, rect.height, color);
+}
@@ -15,6 +15,14 @@ pub struct ImageTexture {
     pub height: u32,
 }
 
+/// Непрерывный диапазон индексов, рисуемый одним вызовом `draw_indexed`
+#[derive(Clone)]
+pub(crate) struct QuadBatch {
+    /// Текстура изображения; `None` — сплошные прямоугольники
+    pub image: Option<Arc<ImageTexture>>,
+    pub indices: std::ops::Range<u32>,
+}
+
 /// Вершина для рендеринга изображений и прямоугольников
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -40,10 +48,66 @@ impl Vertex {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Rect {
     pub x: f32,
     pub y: f32,
     pub width: f32,
     pub height: f32,
 }
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Border box узла из результата layout
+    pub fn from_layout(layout: &crate::layout::LayoutResult) -> Self {
+        Self::new(layout.x, layout.y, layout.width, layout.height)
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0.0 || self.height <= 0.0
+    }
+
+    /// Пересечение прямоугольников (нулевого размера, если они не пересекаются)
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Rect::new(
+            x,
+            y,
+            (self.right().min(other.right()) - x).max(0.0),
+            (self.bottom().min(other.bottom()) - y).max(0.0),
+        )
+    }
+
+    /// Наименьший прямоугольник, содержащий оба
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+}
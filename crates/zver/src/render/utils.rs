@@ -21,3 +21,13 @@ pub fn parse_color(color_str: &str) -> [f32; 4] {
         _ => [0.5, 0.5, 0.5, 1.0],
     }
 }
+
+/// Переводит цвет движка в RGBA для GPU с дополнительным множителем прозрачности
+pub fn color_to_rgba(color: crate::css::color::Color, opacity: f32) -> [f32; 4] {
+    [
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0,
+        color.a as f32 / 255.0 * opacity,
+    ]
+}
@@ -1,36 +1,43 @@
+use crate::network::{NetworkConfig, Response, Url, fetch_local, read_http_response};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 #[derive(Debug, Clone)]
 pub enum Resource {
-    Css(String, String),    // (url, content)
-    Image(String, Vec<u8>), // (url, bytes)
-    Script(String, String), // (url, content)
+    Css(Url, String),    // (url, content)
+    Image(Url, Vec<u8>), // (url, bytes)
+    Script(Url, String), // (url, content)
 }
 
 #[derive(Debug, Clone)]
 pub enum ResourceRequest {
-    Css(String),
-    Image(String),
-    Script(String),
+    Css(Url),
+    Image(Url),
+    Script(Url),
 }
 
 pub struct ResourceLoader {
     request_tx: Option<mpsc::UnboundedSender<ResourceRequest>>,
     response_rx: Option<Arc<tokio::sync::Mutex<mpsc::UnboundedReceiver<Resource>>>>,
     worker_handle: Option<JoinHandle<()>>,
-    #[allow(dead_code)]
     client: Option<reqwest::Client>,
+    config: NetworkConfig,
 }
 
 impl ResourceLoader {
     pub fn new() -> Self {
+        Self::with_config(NetworkConfig::default())
+    }
+
+    /// Загрузчик, чей HTTP-клиент строится из `config` при [`ResourceLoader::init`]
+    pub fn with_config(config: NetworkConfig) -> Self {
         Self {
             request_tx: None,
             response_rx: None,
             worker_handle: None,
             client: None,
+            config,
         }
     }
 
@@ -43,15 +50,11 @@ impl ResourceLoader {
         let (request_tx, request_rx) = mpsc::unbounded_channel();
         let (response_tx, response_rx) = mpsc::unbounded_channel();
 
-        let client = reqwest::Client::builder()
-            .user_agent("Zver/0.1")
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .unwrap_or_else(|e| {
-                eprintln!("Warning: Failed to create custom HTTP client: {}", e);
-                eprintln!("Falling back to default reqwest client");
-                reqwest::Client::new()
-            });
+        let client = self.config.client_builder().build().unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to create custom HTTP client: {}", e);
+            eprintln!("Falling back to default reqwest client");
+            reqwest::Client::new()
+        });
 
         let loader_client = client.clone();
 
@@ -133,46 +136,64 @@ impl ResourceLoader {
     // Загрузка одного ресурса
     async fn fetch_resource(request: ResourceRequest, client: reqwest::Client) -> Option<Resource> {
         match request {
-            ResourceRequest::Css(url) => match client.get(&url).send().await {
-                Ok(response) if response.status().is_success() => match response.text().await {
-                    Ok(content) => Some(Resource::Css(url, content)),
-                    Err(_) => None,
-                },
-                _ => None,
-            },
-            ResourceRequest::Image(url) => match client.get(&url).send().await {
-                Ok(response) if response.status().is_success() => match response.bytes().await {
-                    Ok(bytes) => Some(Resource::Image(url, bytes.to_vec())),
-                    Err(_) => None,
-                },
-                _ => None,
-            },
-            ResourceRequest::Script(url) => match client.get(&url).send().await {
-                Ok(response) if response.status().is_success() => match response.text().await {
-                    Ok(content) => Some(Resource::Script(url, content)),
-                    Err(_) => None,
-                },
-                _ => None,
-            },
+            ResourceRequest::Css(url) => {
+                let response = Self::fetch_response(&url, &client).await?;
+                Some(Resource::Css(url, response.text()))
+            }
+            ResourceRequest::Image(url) => {
+                let response = Self::fetch_response(&url, &client).await?;
+                Some(Resource::Image(url, response.body))
+            }
+            ResourceRequest::Script(url) => {
+                let response = Self::fetch_response(&url, &client).await?;
+                Some(Resource::Script(url, response.text()))
+            }
         }
     }
 
+    // Загрузка по http(s), из файловой системы или data: URL
+    async fn fetch_response(url: &Url, client: &reqwest::Client) -> Option<Response> {
+        if url.is_http() {
+            let response = client.get(url.as_str()).send().await.ok()?;
+            let response = read_http_response(response).await.ok()?;
+            response.is_success().then_some(response)
+        } else {
+            fetch_local(url).await.ok()
+        }
+    }
+
+    /// Загружает ресурсы параллельно и возвращает результаты в порядке запросов.
+    ///
+    /// В отличие от `request_*`/`poll_resources`, вызывающий дожидается всех
+    /// ответов — так нужно при сборке каскада, где важен порядок документа.
+    pub async fn fetch_all(&self, requests: Vec<ResourceRequest>) -> Vec<Option<Resource>> {
+        use futures_util::future::join_all;
+
+        let client = self.client.clone().unwrap_or_default();
+        join_all(
+            requests
+                .into_iter()
+                .map(|request| Self::fetch_resource(request, client.clone())),
+        )
+        .await
+    }
+
     // Запрос на загрузку CSS
-    pub fn request_css(&self, url: String) {
+    pub fn request_css(&self, url: Url) {
         if let Some(tx) = &self.request_tx {
             let _ = tx.send(ResourceRequest::Css(url));
         }
     }
 
     // Запрос на загрузку изображения
-    pub fn request_image(&self, url: String) {
+    pub fn request_image(&self, url: Url) {
         if let Some(tx) = &self.request_tx {
             let _ = tx.send(ResourceRequest::Image(url));
         }
     }
 
     // Запрос на загрузку скрипта
-    pub fn request_script(&self, url: String) {
+    pub fn request_script(&self, url: Url) {
         if let Some(tx) = &self.request_tx {
             let _ = tx.send(ResourceRequest::Script(url));
         }
@@ -194,7 +215,7 @@ impl ResourceLoader {
     }
 
     // Prefetch список URLs
-    pub fn prefetch(&self, urls: &[String], resource_type: &str) {
+    pub fn prefetch(&self, urls: &[Url], resource_type: &str) {
         for url in urls {
             match resource_type {
                 "css" => self.request_css(url.clone()),
@@ -0,0 +1,130 @@
+//! Тесты ZverBuilder: настройки одинаково доходят до всех подсистем
+
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use zver::Zver;
+use zver::clock::Clock;
+use zver::css::media_queries::MediaType;
+use zver::js::JSValue;
+use zver::network::{NetworkConfig, NetworkEngine, Url};
+
+mod common;
+use common::{eval, layout_of, load_into};
+
+#[tokio::test]
+async fn test_viewport_reaches_layout_media_and_scripts() {
+    let engine = Zver::builder()
+        .viewport(375.0, 667.0)
+        .device_pixel_ratio(2.0)
+        .build();
+    load_into(
+        &engine,
+        "zver_builder_viewport.html",
+        r#"<html><head><style>
+            #box { display: block; height: 10px; width: 100%; }
+            @media (max-width: 400px) { #box { width: 50px; } }
+        </style></head><body><div id="box"></div>
+        <script>var size = innerWidth + 'x' + innerHeight + '@' + devicePixelRatio;</script>
+        </body></html>"#,
+    )
+    .await;
+
+    assert_eq!(engine.layout.read().await.viewport_size(), (375.0, 667.0));
+    assert_eq!(
+        layout_of(&engine, "#box").await.width,
+        50.0,
+        "@media видит тот же viewport"
+    );
+    match eval(&engine, "size").await {
+        JSValue::String(size) => assert_eq!(size, "375x667@2"),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+
+    let frame = engine
+        .screenshot()
+        .await
+        .expect("кадр должен быть нарисован");
+    assert_eq!((frame.width(), frame.height()), (750, 1334));
+}
+
+#[tokio::test]
+async fn test_media_type_and_disabled_javascript() {
+    let engine = Zver::builder()
+        .media_type(MediaType::Print)
+        .javascript_enabled(false)
+        .build();
+    assert!(!engine.javascript_enabled());
+    load_into(
+        &engine,
+        "zver_builder_print.html",
+        r#"<html><head><style>
+            #box { display: block; height: 10px; width: 10px; }
+            @media print { #box { width: 20px; } }
+        </style></head><body><div id="box"></div>
+        <script>document.getElementById('box').setAttribute('style', 'width: 300px');</script>
+        </body></html>"#,
+    )
+    .await;
+
+    assert_eq!(
+        layout_of(&engine, "#box").await.width,
+        20.0,
+        "скрипт не исполнялся"
+    );
+    assert!(matches!(
+        eval(&engine, "typeof navigator").await,
+        JSValue::String(kind) if kind == "object"
+    ));
+}
+
+#[tokio::test]
+async fn test_user_agent_and_language() {
+    let engine = Zver::builder()
+        .user_agent("ZverTest/1.0")
+        .accept_language("ru-RU,ru;q=0.9")
+        .timeout(Duration::from_secs(5))
+        .clock(Clock::new_virtual())
+        .build();
+    assert!(engine.clock.is_virtual());
+    match eval(&engine, "navigator.userAgent + '|' + navigator.language").await {
+        JSValue::String(value) => assert_eq!(value, "ZverTest/1.0|ru-RU"),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+
+    // Тот же конфиг задаёт заголовки HTTP-клиента
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+    let server = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(read) => request.extend_from_slice(&buffer[..read]),
+            }
+        }
+        let _ = stream
+            .write_all(b"HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n")
+            .await;
+        String::from_utf8_lossy(&request).to_lowercase()
+    });
+
+    let config = NetworkConfig {
+        user_agent: "ZverTest/1.0".to_string(),
+        accept_language: "ru-RU,ru;q=0.9".to_string(),
+        ..NetworkConfig::default()
+    };
+    let mut network =
+        NetworkEngine::with_client(config.client_builder().no_proxy().build().unwrap())
+            .with_accept_language(&config.accept_language);
+    network.fetch_response(&url).await.unwrap();
+
+    let request = server.await.unwrap();
+    assert!(request.contains("user-agent: zvertest/1.0"), "{request}");
+    assert!(
+        request.contains("accept-language: ru-ru,ru;q=0.9"),
+        "{request}"
+    );
+}
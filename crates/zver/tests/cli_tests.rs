@@ -0,0 +1,137 @@
+//! Тесты консольной утилиты `zver`: дампы, ожидание таймеров, снимок и ошибки
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const PAGE: &str = r#"<html><head><style>
+    body { margin: 0; }
+    #box { width: 100px; height: 50px; background-color: #ff0000; }
+    .hidden { display: none; margin-top: 2em; }
+</style></head><body>
+    <div id="box" class="a b" title="first">Hello</div>
+    <p class="hidden">secret</p>
+    <p>Second</p>
+    <script>
+        console.log('loaded', 1 + 1);
+        setTimeout(function() {
+            console.warn('late');
+            document.getElementById('box').setTextContent('Changed');
+        }, 500);
+    </script>
+</body></html>"#;
+
+fn page(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(name);
+    fs::write(&path, PAGE).unwrap();
+    path
+}
+
+fn zver(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_zver"))
+        .args(args)
+        .output()
+        .expect("zver должен запуститься")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_cli_dumps_text_console_and_waits_for_timers() {
+    let path = page("zver_cli_text.html");
+    let path = path.to_str().unwrap();
+
+    let output = stdout(&zver(&["--dump", "text", "--dump", "console", path]));
+    assert_eq!(output, "Hello\nSecond\n[log] loaded 2\n");
+
+    // Таймер срабатывает только при ожидании, консоль не попадает в stdout сама
+    let output = stdout(&zver(&[
+        "--dump=text",
+        "--dump=console",
+        "--wait=600",
+        path,
+    ]));
+    assert_eq!(output, "Changed\nSecond\n[log] loaded 2\n[warn] late\n");
+
+    // Без флагов печатается DOM; атрибуты упорядочены
+    let output = stdout(&zver(&["--no-js", path]));
+    assert!(output.contains(r#"<div class="a b" id="box" title="first">Hello</div>"#));
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_cli_dumps_layout_and_styles_for_viewport() {
+    let path = page("zver_cli_layout.html");
+    let path = path.to_str().unwrap();
+
+    let output = stdout(&zver(&[
+        "--dump", "layout", "--width", "300", "--no-js", path,
+    ]));
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "html block 0,0 300x600 content 0,0 300x600");
+    assert!(
+        lines.contains(&"    div#box.a.b block 0,0 100x50 content 0,0 100x50"),
+        "{output}"
+    );
+    assert!(!output.contains("secret"));
+
+    let output = stdout(&zver(&["--dump", "styles", "--no-js", path]));
+    assert!(output.contains(
+        "div#box.a.b {\n  background-color: rgb(255, 0, 0);\n  height: 50px;\n  width: 100px;\n}\n"
+    ));
+    // Вычисленные значения: корень получает размер viewport, `em` переведены в пиксели
+    assert!(output.contains("html {\n  height: 600px;\n  width: 800px;\n}\n"));
+    assert!(output.contains("p.hidden {\n  display: none;\n  margin-top: 32px;\n}\n"));
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn test_cli_writes_png_screenshot() {
+    let path = page("zver_cli_screenshot.html");
+    let png = std::env::temp_dir().join("zver_cli_screenshot.png");
+    let output = zver(&[
+        "--screenshot",
+        png.to_str().unwrap(),
+        "--width",
+        "320",
+        "--height",
+        "200",
+        "--dpr",
+        "2",
+        path.to_str().unwrap(),
+    ]);
+    assert_eq!(stdout(&output), "", "снимок без --dump ничего не печатает");
+
+    let bytes = fs::read(&png).unwrap();
+    assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+    // IHDR: ширина и высота кадра в пикселях устройства
+    let width = u32::from_be_bytes(bytes[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(bytes[20..24].try_into().unwrap());
+    assert_eq!((width, height), (640, 400));
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(png);
+}
+
+#[test]
+fn test_cli_reports_usage_and_load_errors() {
+    let output = zver(&["--dump", "pixels", "page.html"]);
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown dump 'pixels'"), "{stderr}");
+    assert!(stderr.contains("Usage: zver"));
+
+    assert_eq!(zver(&[]).status.code(), Some(2));
+    assert!(stdout(&zver(&["--help"])).starts_with("Usage: zver"));
+
+    let missing = std::env::temp_dir().join("zver_cli_missing.html");
+    let output = zver(&[missing.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("failed to load"));
+}
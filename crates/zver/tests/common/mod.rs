@@ -0,0 +1,60 @@
+//! Общие помощники интеграционных тестов: страница из временного файла,
+//! поиск узлов, их layout и вычисление JavaScript
+
+// Каждый тестовый крейт использует только часть помощников
+#![allow(dead_code)]
+
+use std::fs;
+use zver::Zver;
+use zver::js::JSValue;
+use zver::layout::LayoutResult;
+use zver::network::Url;
+
+/// Загружает `html` в `engine` через временный файл `name`; возвращает его URL
+pub async fn load_into(engine: &Zver, name: &str, html: &str) -> Url {
+    let path = std::env::temp_dir().join(name);
+    fs::write(&path, html).unwrap();
+    let url = Url::from_file_path(&path).unwrap();
+    engine
+        .load_url(url.as_str())
+        .await
+        .expect("страница должна загрузиться");
+    let _ = fs::remove_file(path);
+    url
+}
+
+/// Новый движок с загруженной страницей `html`
+pub async fn load(name: &str, html: &str) -> Zver {
+    let engine = Zver::new();
+    load_into(&engine, name, html).await;
+    engine
+}
+
+/// Страница без отступов `html` и `body` со стилями `style`
+pub fn page(style: &str, body: &str) -> String {
+    format!(
+        "<html><head><style>html, body {{ margin: 0; padding: 0; }} {style}</style></head>\
+         <body>{body}</body></html>"
+    )
+}
+
+pub async fn id_of(engine: &Zver, selector: &str) -> usize {
+    engine.dom.read().await.select_ids(selector)[0]
+}
+
+pub async fn layout_of_id(engine: &Zver, node_id: usize) -> LayoutResult {
+    engine
+        .layout
+        .read()
+        .await
+        .get_layout_result(node_id)
+        .expect("у элемента должен быть layout")
+}
+
+pub async fn layout_of(engine: &Zver, selector: &str) -> LayoutResult {
+    layout_of_id(engine, id_of(engine, selector).await).await
+}
+
+pub async fn eval(engine: &Zver, code: &str) -> JSValue {
+    engine.js.write().await.execute(code).unwrap()
+}
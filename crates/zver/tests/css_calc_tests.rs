@@ -0,0 +1,253 @@
+//! Тесты `calc()`, `min()`, `max()` и `clamp()`: разбор, вычисление,
+//! нормализация значений и отложенное разрешение процентов при layout
+
+use zver::css::StyleEngine;
+use zver::css::calc::{CalcContext, CalcExpr, CalcType};
+use zver::dom::Document;
+use zver::layout::LayoutEngine;
+
+fn eval(value: &str, percent_basis: Option<f32>) -> Option<f32> {
+    let context = CalcContext {
+        percent_basis,
+        viewport_width: 1000.0,
+        viewport_height: 500.0,
+        ..CalcContext::default()
+    };
+    CalcExpr::parse(value)?.eval(&context)
+}
+
+#[test]
+fn test_math_expressions_parse_and_evaluate() {
+    assert_eq!(eval("calc(100% - 2rem)", Some(400.0)), Some(368.0));
+    assert_eq!(eval("calc(2px + 3px * 2)", None), Some(8.0));
+    assert_eq!(eval("calc((2px + 3px) * 2)", None), Some(10.0));
+    assert_eq!(eval("CALC(1in / 4 - 1pt * 3)", None), Some(20.0));
+    assert_eq!(eval("min(50%, 120px, 10vw)", Some(400.0)), Some(100.0));
+    assert_eq!(eval("max(1em, calc(10% + 5px))", Some(400.0)), Some(45.0));
+    assert_eq!(eval("clamp(10px, 50%, 100px)", Some(120.0)), Some(60.0));
+    assert_eq!(eval("clamp(10px, 50%, 100px)", Some(400.0)), Some(100.0));
+    // При min > max побеждает min
+    assert_eq!(eval("clamp(50px, 1px, 20px)", None), Some(50.0));
+    // Процент без базы не вычисляется
+    assert_eq!(eval("calc(50% + 1px)", None), None);
+
+    let number = CalcExpr::parse("calc(1 + 2 / 4)").unwrap();
+    assert_eq!(number.calc_type(), Some(CalcType::Number));
+    assert_eq!(number.eval(&CalcContext::default()), Some(1.5));
+
+    for invalid in [
+        "calc(1px * 2px)",
+        "calc(1px / 2px)",
+        "calc(1px + 2)",
+        "calc(1px+2px)",
+        "calc(1px -2px)",
+        "calc(10deg)",
+        "clamp(1px, 2px)",
+        "calc(1px) 2px",
+        "calc()",
+    ] {
+        assert!(CalcExpr::parse(invalid).is_none(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_math_expressions_serialize_and_fold() {
+    let serialize = |value: &str| CalcExpr::parse(value).unwrap().simplify().to_string();
+
+    assert_eq!(serialize("calc(10px + 2 * 5px)"), "20px");
+    assert_eq!(serialize("calc(1in - 6px)"), "90px");
+    assert_eq!(serialize("calc( 100%  -  2REM )"), "calc(100% - 2rem)");
+    assert_eq!(serialize("calc(50%)"), "50%");
+    assert_eq!(
+        serialize("calc((100% - 10px) / 3)"),
+        "calc((100% - 10px) / 3)"
+    );
+    assert_eq!(
+        serialize("calc(100% - (1em + 2px))"),
+        "calc(100% - (1em + 2px))"
+    );
+    assert_eq!(
+        serialize("clamp(1rem, calc(2vw + 4px), 3rem)"),
+        "clamp(1rem, 2vw + 4px, 3rem)"
+    );
+    assert_eq!(
+        serialize("min(10%, max(4px, 1em))"),
+        "min(10%, max(4px, 1em))"
+    );
+}
+
+#[tokio::test]
+async fn test_math_functions_in_computed_styles() {
+    let mut doc = Document::new();
+    doc.parse_html(r#"<html><body><div id="box"></div><p id="bad"></p></body></html>"#)
+        .await
+        .unwrap();
+    let mut engine = StyleEngine::new();
+    engine
+        .parse_css(
+            r#"
+            #box {
+                --inset: 1rem;
+                width: calc(10px + 2 * 5px);
+                height: calc(100% - var(--inset));
+                padding: min(10px, 5%) 4px;
+                margin-left: clamp(1px, 2vw, 3em);
+            }
+            "#,
+        )
+        .unwrap();
+    engine.apply_styles(&doc).unwrap();
+
+    let box_id = doc.get_element_by_id("box").unwrap();
+    let styles = &engine.computed_styles[&box_id];
+    assert_eq!(styles["width"], "20px");
+    assert_eq!(styles["height"], "calc(100% - 1rem)");
+    assert_eq!(styles["padding-top"], "min(10px, 5%)");
+    assert_eq!(styles["padding-right"], "4px");
+    assert_eq!(styles["margin-left"], "clamp(1px, 2vw, 3em)");
+
+    // Неверный тип выражения делает декларацию недействительной
+    let mut invalid = StyleEngine::new();
+    assert!(invalid.parse_css("#bad { width: calc(2 * 3); }").is_err());
+    assert!(
+        invalid
+            .parse_css("#bad { width: calc(1px * 2px); }")
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_percentages_in_math_resolve_at_layout_time() {
+    let mut doc = Document::new();
+    doc.parse_html(
+        r#"<html><body>
+            <div id="outer">
+                <div id="inner"><div id="leaf"></div></div>
+                <div id="clamped"></div>
+                <div id="tall"></div>
+            </div>
+        </body></html>"#,
+    )
+    .await
+    .unwrap();
+    let mut engine = StyleEngine::new();
+    engine
+        .parse_css(
+            r#"
+            body { margin: 0; }
+            #outer { width: 400px; }
+            #inner {
+                width: calc(100% - 40px);
+                height: 20px;
+                margin-left: calc(10% + 5px);
+                padding-left: calc(5% - 10px);
+            }
+            #leaf { width: calc(50% + 1px); height: 10px; }
+            #clamped { width: clamp(50px, 50%, 150px); height: min(30px, 2em); }
+            #tall { width: 10px; height: calc(50% + 10px); }
+            "#,
+        )
+        .unwrap();
+    engine.apply_styles(&doc).unwrap();
+
+    let mut layout = LayoutEngine::new(800.0, 600.0);
+    let results =
+        layout.compute_layout(&doc, &engine.computed_styles, &engine.pseudo_element_styles);
+    let node = |id: &str| results[&doc.get_element_by_id(id).unwrap()];
+
+    let inner = node("inner");
+    // Содержимое #outer шириной 400px
+    assert_eq!(inner.width, 360.0);
+    assert_eq!(inner.x - node("outer").content_x, 45.0);
+    assert_eq!(inner.content_x - inner.x, 10.0);
+    // 50% от content box #inner (360 - 10 padding) + 1px: проценты вложенных
+    // выражений разрешаются после родительских
+    assert_eq!(node("leaf").width, 176.0);
+
+    let clamped = node("clamped");
+    assert_eq!(clamped.width, 150.0);
+    assert_eq!(clamped.height, 30.0);
+
+    // Процент от высоты `auto` не определён: высота ведёт себя как `auto`
+    assert_eq!(node("tall").height, 0.0);
+}
+
+#[tokio::test]
+async fn test_nested_percentages_in_math_use_parent_width() {
+    let mut doc = Document::new();
+    doc.parse_html(
+        r#"<html><body><div id="d1"><div id="d2"><div id="d3"><div id="d4"><div id="d5"><div id="d6"><div id="d7">
+        </div></div></div></div></div></div></div>
+        <p><span id="inline">x</span></p></body></html>"#,
+    )
+    .await
+    .unwrap();
+    let mut engine = StyleEngine::new();
+    engine
+        .parse_css(
+            r#"
+            body { margin: 0; }
+            div { width: calc(50% - 10px); }
+            p { width: 300px; margin: 0; }
+            #inline { padding-left: calc(10% + 1px); }
+            "#,
+        )
+        .unwrap();
+    engine.apply_styles(&doc).unwrap();
+
+    let mut layout = LayoutEngine::new(800.0, 600.0);
+    let results =
+        layout.compute_layout(&doc, &engine.computed_styles, &engine.pseudo_element_styles);
+    let node = |id: &str| results[&doc.get_element_by_id(id).unwrap()];
+
+    // Каждый уровень — от ширины родителя; отрицательная ширина обрезается до нуля
+    let expected = [390.0, 185.0, 82.5, 31.25, 5.625, 0.0, 0.0];
+    for (level, expected) in expected.into_iter().enumerate() {
+        let width = node(&format!("d{}", level + 1)).width;
+        assert!(
+            (width - expected).abs() <= 0.5,
+            "d{}: {width} вместо {expected}",
+            level + 1
+        );
+    }
+
+    // Проценты строчного элемента — от блока-родителя
+    let inline = node("inline");
+    assert_eq!(inline.content_x - inline.x, 31.0);
+}
+
+#[tokio::test]
+async fn test_percentages_in_math_for_gap_and_border_width() {
+    let mut doc = Document::new();
+    doc.parse_html(
+        r#"<html><body>
+            <div id="row"><div id="first"></div><div id="second"></div></div>
+            <div id="framed"></div>
+        </body></html>"#,
+    )
+    .await
+    .unwrap();
+    let mut engine = StyleEngine::new();
+    engine
+        .parse_css(
+            r#"
+            body { margin: 0; width: 400px; }
+            #row { display: flex; gap: calc(10% - 10px); }
+            #row div { width: 50px; height: 10px; }
+            #framed { border-left-width: calc(1% + 1px); }
+            "#,
+        )
+        .unwrap();
+    engine.apply_styles(&doc).unwrap();
+
+    let mut layout = LayoutEngine::new(800.0, 600.0);
+    let results =
+        layout.compute_layout(&doc, &engine.computed_styles, &engine.pseudo_element_styles);
+    let node = |id: &str| results[&doc.get_element_by_id(id).unwrap()];
+
+    // 10% от 400px - 10px
+    assert_eq!(node("second").x - node("first").x, 80.0);
+    // border-width не принимает проценты — объявление отбрасывается
+    let framed = node("framed");
+    assert_eq!(framed.content_x - framed.x, 0.0);
+}
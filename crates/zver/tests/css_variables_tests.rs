@@ -0,0 +1,183 @@
+//! Тесты пользовательских свойств: наследование, fallback, циклы,
+//! недействительные значения и shorthand-ы с `var()`
+
+use zver::css::StyleEngine;
+use zver::css::selectors::PseudoElement;
+use zver::dom::Document;
+
+async fn styled(html: &str, css: &str) -> (Document, StyleEngine) {
+    let mut doc = Document::new();
+    doc.parse_html(html).await.unwrap();
+    let mut engine = StyleEngine::new();
+    engine.parse_css(css).unwrap();
+    engine.apply_styles(&doc).unwrap();
+    (doc, engine)
+}
+
+fn style_value(
+    doc: &Document,
+    engine: &StyleEngine,
+    element_id: &str,
+    property: &str,
+) -> Option<String> {
+    let node_id = doc.get_element_by_id(element_id)?;
+    engine
+        .computed_styles
+        .get(&node_id)
+        .and_then(|map| map.get(property))
+        .cloned()
+}
+
+const TREE: &str = r#"
+    <html><body>
+        <div id="outer">
+            <div id="middle"><p id="inner">text</p></div>
+        </div>
+    </body></html>
+"#;
+
+#[tokio::test]
+async fn test_custom_properties_inherit_and_substitute() {
+    let (doc, engine) = styled(
+        TREE,
+        r#"
+            #outer { --Brand: #ff0000; --gap: 4px; color: var(--Brand); }
+            #middle { --gap: 10px; --double: var(--gap) var(--gap); }
+            #inner {
+                background-color: VAR(--brand, var(--Brand));
+                width: var(--missing, 25px);
+                padding: var(--double);
+                margin: var(--gap) auto;
+            }
+        "#,
+    )
+    .await;
+    let value = |id: &str, property: &str| style_value(&doc, &engine, id, property);
+
+    assert_eq!(
+        value("outer", "color").as_deref(),
+        Some("rgba(255, 0, 0, 1)")
+    );
+    assert_eq!(value("middle", "--double").as_deref(), Some("10px 10px"));
+    // Имена чувствительны к регистру: `--brand` не объявлен, берётся fallback
+    assert_eq!(
+        value("inner", "background-color").as_deref(),
+        Some("rgba(255, 0, 0, 1)")
+    );
+    assert_eq!(value("inner", "width").as_deref(), Some("25px"));
+    assert_eq!(value("inner", "padding-top").as_deref(), Some("10px"));
+    assert_eq!(value("inner", "padding-left").as_deref(), Some("10px"));
+    assert_eq!(value("inner", "margin-top").as_deref(), Some("10px"));
+    assert_eq!(value("inner", "margin-left").as_deref(), Some("auto"));
+
+    let inner = doc.get_element_by_id("inner").unwrap();
+    let custom = &engine.custom_properties[&inner];
+    assert_eq!(custom["--gap"], "10px");
+    assert_eq!(custom["--Brand"], "#ff0000");
+}
+
+#[tokio::test]
+async fn test_cycles_and_invalid_values_at_computed_time() {
+    let (doc, engine) = styled(
+        TREE,
+        r#"
+            #outer { --a: var(--b, 1px); --b: var(--a, 2px); --ok: 3px; --self: var(--self); }
+            #middle { --ok: initial; width: var(--a, 7px); }
+            #inner {
+                height: var(--ok);
+                width: var(--b);
+                margin-top: var(--color);
+                --color: red;
+                color: var(--color);
+                padding-left: 1px;
+                padding-left: var(--color);
+            }
+        "#,
+    )
+    .await;
+    let value = |id: &str, property: &str| style_value(&doc, &engine, id, property);
+
+    // Участники цикла недействительны даже с fallback
+    assert_eq!(value("outer", "--a"), None);
+    assert_eq!(value("outer", "--b"), None);
+    assert_eq!(value("outer", "--self"), None);
+    assert_eq!(value("outer", "--ok").as_deref(), Some("3px"));
+    assert_eq!(value("middle", "width").as_deref(), Some("7px"));
+
+    // `initial` сбрасывает свойство и для потомков
+    assert_eq!(value("inner", "height"), None);
+    assert_eq!(value("inner", "width"), None);
+    // `red` — недопустимая длина: свойство отбрасывается, а не откатывается
+    // к предыдущей декларации
+    assert_eq!(value("inner", "margin-top"), None);
+    assert_eq!(value("inner", "padding-left"), None);
+    assert_eq!(
+        value("inner", "color").as_deref(),
+        Some("rgba(255, 0, 0, 1)")
+    );
+}
+
+#[tokio::test]
+async fn test_shorthand_with_var_keeps_cascade_order() {
+    let (doc, engine) = styled(
+        TREE,
+        r#"
+            #outer { --m: 1px 2px 3px; margin: var(--m); margin-top: 5px; }
+            #middle { margin-left: 9px; padding: var(--m) !important; }
+            #middle { padding-bottom: 0; }
+            #inner { --p: 1px 2px 3px 4px 5px; padding: 8px; padding: var(--p); }
+        "#,
+    )
+    .await;
+    let value = |id: &str, property: &str| style_value(&doc, &engine, id, property);
+
+    assert_eq!(value("outer", "margin-top").as_deref(), Some("5px"));
+    assert_eq!(value("outer", "margin-right").as_deref(), Some("2px"));
+    assert_eq!(value("outer", "margin-bottom").as_deref(), Some("3px"));
+    assert_eq!(value("outer", "margin-left").as_deref(), Some("2px"));
+
+    assert_eq!(value("middle", "margin-left").as_deref(), Some("9px"));
+    assert_eq!(value("middle", "padding-bottom").as_deref(), Some("3px"));
+
+    // Слишком много компонентов после подстановки: все longhand-и недействительны
+    for side in ["top", "right", "bottom", "left"] {
+        assert_eq!(value("inner", &format!("padding-{side}")), None);
+    }
+}
+
+#[tokio::test]
+async fn test_var_in_inline_styles_pseudo_elements_and_restyle() {
+    let (mut doc, mut engine) = styled(
+        r#"<html><body>
+            <div id="outer" style="--label: 'from inline'; --size: 12px">
+                <span id="inner" style="width: var(--size)"></span>
+            </div>
+        </body></html>"#,
+        r#"#inner::before { content: var(--label); color: var(--tone, blue); }"#,
+    )
+    .await;
+
+    let inner = doc.get_element_by_id("inner").unwrap();
+    assert_eq!(
+        style_value(&doc, &engine, "inner", "width").as_deref(),
+        Some("12px")
+    );
+    let before = &engine.pseudo_element_styles[&inner][&PseudoElement::Before];
+    assert_eq!(before.content, "from inline");
+    assert_eq!(before.properties["color"], "rgba(0, 0, 255, 1)");
+
+    // Изменение переменной на предке перевычисляет потомков поддерева
+    let outer = doc.get_element_by_id("outer").unwrap();
+    doc.nodes
+        .get_mut(&outer)
+        .unwrap()
+        .attributes
+        .insert("style".to_string(), "--size: 30px".to_string());
+    let changed = engine.restyle(&doc, &[outer]);
+    assert!(changed.contains(&inner), "{changed:?}");
+    assert_eq!(
+        style_value(&doc, &engine, "inner", "width").as_deref(),
+        Some("30px")
+    );
+    assert!(!engine.pseudo_element_styles.contains_key(&inner));
+}
@@ -0,0 +1,208 @@
+//! Тесты построения display list и его исполнения программным растеризатором
+
+use zver::Zver;
+use zver::css::color::Color;
+use zver::css::fonts::{self as css_fonts, FontFace, LoadedFont};
+use zver::layout::types::{FontStyle, FontWeight};
+use zver::render::{DisplayItem, DisplayList, FontDescriptor};
+
+mod common;
+use common::{id_of, layout_of, load, page};
+
+async fn display_list_of(engine: &Zver) -> DisplayList {
+    engine.render.read().await.display_list().clone()
+}
+
+/// Позиция первой заливки узла в display list
+fn fill_index(list: &DisplayList, id: usize) -> usize {
+    list.iter()
+        .position(|item| matches!(item, DisplayItem::FillRect { node_id, .. } if *node_id == id))
+        .expect("у узла должна быть заливка")
+}
+
+#[tokio::test]
+async fn test_background_precedes_text_of_same_box() {
+    let engine = load(
+        "zver_dl_order.html",
+        r#"<html><body><p id="p" style="background-color: #ffff00">Hello</p></body></html>"#,
+    )
+    .await;
+
+    let list = display_list_of(&engine).await;
+    let p = id_of(&engine, "#p").await;
+
+    let background = fill_index(&list, p);
+    let text = list
+        .iter()
+        .position(|item| matches!(item, DisplayItem::GlyphRun { text, .. } if text == "Hello"))
+        .expect("текст должен попасть в display list");
+    assert!(background < text);
+}
+
+/// Рамка узла из display list
+fn border_of(list: &DisplayList, id: usize) -> Option<zver::render::BorderWidths> {
+    list.iter().find_map(|item| match item {
+        DisplayItem::StrokeBorder {
+            node_id, widths, ..
+        } if *node_id == id => Some(*widths),
+        _ => None,
+    })
+}
+
+#[tokio::test]
+async fn test_calc_border_width_lays_out_and_paints() {
+    let engine = load(
+        "zver_dl_calc_border.html",
+        &page(
+            "div { width: 100px; height: 20px; border: solid #000000; font-size: 16px; } \
+             #calc { border-width: calc(1em - 12px); } \
+             #percent { border-width: 3px; border-width: calc(10% + 2px); }",
+            r#"<div id="calc"></div><div id="percent"></div>"#,
+        ),
+    )
+    .await;
+
+    let list = display_list_of(&engine).await;
+
+    let calc = layout_of(&engine, "#calc").await;
+    assert_eq!(calc.content_x - calc.x, 4.0);
+    assert_eq!(calc.content_width, calc.width - 8.0);
+    let widths = border_of(&list, id_of(&engine, "#calc").await).expect("рамка должна рисоваться");
+    assert_eq!(
+        (widths.top, widths.right, widths.bottom, widths.left),
+        (4.0, 4.0, 4.0, 4.0)
+    );
+
+    // Проценты в border-width недопустимы: объявление отбрасывается, действует предыдущее
+    let percent = layout_of(&engine, "#percent").await;
+    assert_eq!(percent.content_x - percent.x, 3.0);
+    let widths =
+        border_of(&list, id_of(&engine, "#percent").await).expect("рамка должна рисоваться");
+    assert_eq!(widths.left, 3.0);
+}
+
+#[tokio::test]
+async fn test_z_index_orders_positioned_siblings() {
+    let engine = load(
+        "zver_dl_z_index.html",
+        r#"<html><body style="margin: 0">
+            <div id="top" style="position: relative; z-index: 2; height: 100px; background-color: #ff0000"></div>
+            <div id="bottom" style="position: relative; z-index: 1; margin-top: -50px; height: 100px; background-color: #0000ff"></div>
+            <div id="below" style="position: relative; z-index: -1; height: 20px; background-color: #00ff00"></div>
+            <div id="flow" style="height: 20px; background-color: #000000"></div>
+        </body></html>"#,
+    )
+    .await;
+
+    let list = display_list_of(&engine).await;
+    let top = fill_index(&list, id_of(&engine, "#top").await);
+    let bottom = fill_index(&list, id_of(&engine, "#bottom").await);
+    let below = fill_index(&list, id_of(&engine, "#below").await);
+    let flow = fill_index(&list, id_of(&engine, "#flow").await);
+
+    assert!(below < flow, "отрицательный z-index рисуется до потока");
+    assert!(
+        flow < bottom && bottom < top,
+        "положительные z-index по возрастанию"
+    );
+
+    // В зоне перекрытия виден элемент с большим z-index
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!(frame.pixel(10, 75), Some(Color::new(255, 0, 0, 255)));
+}
+
+#[tokio::test]
+async fn test_overflow_hidden_clips_descendants() {
+    let engine = load(
+        "zver_dl_clip.html",
+        r#"<html><body style="margin: 0">
+            <div id="clip" style="overflow: hidden; width: 100px; height: 50px">
+                <div style="width: 300px; height: 200px; background-color: #ff0000"></div>
+            </div>
+        </body></html>"#,
+    )
+    .await;
+
+    let list = display_list_of(&engine).await;
+    let pushes = list
+        .iter()
+        .filter(|item| matches!(item, DisplayItem::PushClip { .. }))
+        .count();
+    let pops = list
+        .iter()
+        .filter(|item| matches!(item, DisplayItem::PopClip))
+        .count();
+    assert_eq!(pushes, 1);
+    assert_eq!(pushes, pops);
+
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!(frame.pixel(50, 25), Some(Color::new(255, 0, 0, 255)));
+    assert_eq!(frame.pixel(150, 25), Some(Color::WHITE));
+    assert_eq!(frame.pixel(50, 100), Some(Color::WHITE));
+}
+
+#[tokio::test]
+async fn test_opacity_group_is_composited() {
+    let engine = load(
+        "zver_dl_opacity.html",
+        r#"<html><body style="margin: 0">
+            <div style="opacity: 0.5; width: 100px; height: 100px; background-color: #ff0000"></div>
+        </body></html>"#,
+    )
+    .await;
+
+    let list = display_list_of(&engine).await;
+    let push = list
+        .iter()
+        .position(|item| matches!(item, DisplayItem::PushOpacity { opacity } if *opacity == 0.5))
+        .expect("группа с opacity должна открываться");
+    let pop = list
+        .iter()
+        .position(|item| matches!(item, DisplayItem::PopOpacity))
+        .expect("группа с opacity должна закрываться");
+    assert!(push < pop);
+
+    let pixel = engine.screenshot().await.unwrap().pixel(50, 50).unwrap();
+    assert_eq!((pixel.r, pixel.a), (255, 255));
+    assert!(
+        (126..=129).contains(&pixel.g),
+        "ожидался розовый, получено {pixel:?}"
+    );
+}
+
+#[test]
+fn test_glyph_run_face_keeps_font_bytes_for_backends() {
+    let bytes = css_fonts::DEFAULT_FONT_DATA;
+    let face = |weight| {
+        let mut face = FontFace::new("Page Sans".to_string());
+        face.weight = weight;
+        let mut font = LoadedFont::new(face);
+        font.load_from_bytes(bytes).unwrap();
+        font
+    };
+    let fonts = vec![
+        face(css_fonts::FontWeight::Normal),
+        face(css_fonts::FontWeight::Bold),
+    ];
+    let descriptor = FontDescriptor {
+        family: Some("'Missing', \"Page Sans\", sans-serif".to_string()),
+        size: 16.0,
+        weight: FontWeight::Bold,
+        style: FontStyle::Normal,
+    };
+
+    let bold = descriptor
+        .face(&fonts)
+        .expect("начертание семейства страницы");
+    assert_eq!(bold.face.weight, css_fonts::FontWeight::Bold);
+    assert_eq!(bold.data.as_deref().map(Vec::as_slice), Some(bytes));
+
+    let fallback = FontDescriptor {
+        family: Some("sans-serif".to_string()),
+        ..descriptor
+    };
+    assert!(
+        fallback.face(&fonts).is_none(),
+        "без @font-face рисует встроенный шрифт"
+    );
+}
@@ -0,0 +1,87 @@
+//! Тесты типизированных ошибок конвейера
+
+use zver::css::StyleEngine;
+use zver::error::{NetworkError, ParseTarget, SourceLocation};
+use zver::js::JSEngine;
+use zver::{Zver, ZverError};
+
+#[tokio::test]
+async fn test_network_failures_are_classified() {
+    let engine = Zver::new();
+
+    let missing = engine
+        .load_url("file:///this/path/does/not/exist/zver_error.html")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        missing,
+        ZverError::Network(NetworkError::Io { .. })
+    ));
+    assert!(!missing.is_recoverable());
+
+    let scheme = engine.load_url("gopher://example.com/").await.unwrap_err();
+    assert!(matches!(
+        scheme,
+        ZverError::Network(NetworkError::UnsupportedScheme(ref scheme)) if scheme == "gopher"
+    ));
+
+    let invalid = engine.load_url("http://[::1").await.unwrap_err();
+    assert!(matches!(
+        invalid,
+        ZverError::Parse {
+            target: ParseTarget::Url,
+            ..
+        }
+    ));
+
+    let unavailable = ZverError::from(NetworkError::Http {
+        url: "https://example.com/".to_string(),
+        status: 503,
+    });
+    assert!(unavailable.is_recoverable());
+    assert!(
+        !ZverError::from(NetworkError::Http {
+            url: "https://example.com/".to_string(),
+            status: 404,
+        })
+        .is_recoverable()
+    );
+}
+
+#[test]
+fn test_script_errors_carry_location() {
+    let mut js = JSEngine::new();
+
+    let syntax = js.execute("var ok = 1;\nvar broken = ;").unwrap_err();
+    match &syntax {
+        ZverError::Script { location, .. } => {
+            assert_eq!(location.as_ref().map(|location| location.line), Some(2));
+        }
+        other => panic!("ожидалась ошибка скрипта, получено {other:?}"),
+    }
+    assert!(syntax.to_string().starts_with("script error at 2:"));
+
+    let thrown = js.execute("throw new TypeError('boom')").unwrap_err();
+    assert!(matches!(thrown, ZverError::Script { .. }));
+    assert!(thrown.to_string().contains("boom"));
+
+    let module = js.execute_module("import { x } from;").unwrap_err();
+    assert!(module.location().is_some());
+}
+
+#[test]
+fn test_css_errors_carry_location() {
+    let mut css = StyleEngine::new();
+    let error = ZverError::from(css.parse_css("p { color: red; }\n\n  ! {}").unwrap_err());
+    match error {
+        ZverError::Parse {
+            target: ParseTarget::Css,
+            location,
+            ..
+        } => assert_eq!(location.map(|location| location.line), Some(3)),
+        other => panic!("ожидалась ошибка CSS, получено {other:?}"),
+    }
+
+    let location = SourceLocation::new(3, 7).with_source("style.css");
+    assert_eq!(location.to_string(), "style.css:3:7");
+}
@@ -0,0 +1,154 @@
+//! Тесты цикла событий: события жизненного цикла, микрозадачи и таймеры
+
+use std::time::Duration;
+use zver::js::JSValue;
+
+mod common;
+use common::{eval, layout_of, load};
+
+const STYLE: &str = r#"<style>
+    #box { display: block; width: 10px; height: 10px; }
+    #box.wide { width: 300px; }
+</style>"#;
+
+#[tokio::test]
+async fn test_lifecycle_events_fire_and_rerender() {
+    let engine = load(
+        "zver_event_loop_lifecycle.html",
+        &format!(
+            r#"<html><head>{STYLE}</head><body>
+            <div id="box"></div>
+            <script>
+                var events = [];
+                document.addEventListener('DOMContentLoaded', function() {{ events.push('dom'); }});
+                window.addEventListener('DOMContentLoaded', function() {{ events.push('window-dom'); }});
+                window.addEventListener('load', function() {{
+                    events.push('load');
+                    document.getElementById('box').setAttribute('class', 'wide');
+                }});
+            </script>
+        </body></html>"#
+        ),
+    )
+    .await;
+
+    match eval(&engine, "events.join(',')").await {
+        JSValue::String(events) => assert_eq!(events, "dom,window-dom,load"),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+    assert_eq!(
+        layout_of(&engine, "#box").await.width,
+        300.0,
+        "изменения из обработчика load попадают в layout"
+    );
+}
+
+#[tokio::test]
+async fn test_microtasks_run_before_timers() {
+    let engine = load(
+        "zver_event_loop_order.html",
+        r#"<html><body><script>
+            var log = [];
+            setTimeout("log.push('timeout')", 0);
+            Promise.resolve().then(function() { log.push('micro'); });
+            log.push('sync');
+        </script></body></html>"#,
+    )
+    .await;
+
+    match eval(&engine, "log.join(',')").await {
+        JSValue::String(log) => assert_eq!(log, "sync,micro,timeout"),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_advance_fires_timers_without_waiting() {
+    let engine = load(
+        "zver_event_loop_advance.html",
+        &format!(
+            r#"<html><head>{STYLE}</head><body>
+            <div id="box"></div>
+            <script>
+                setTimeout("document.getElementById('box').setAttribute('class', 'wide')", 5000);
+            </script>
+        </body></html>"#
+        ),
+    )
+    .await;
+    assert_eq!(engine.run_until_idle().await.unwrap(), 0);
+    assert_eq!(layout_of(&engine, "#box").await.width, 10.0);
+
+    let started = std::time::Instant::now();
+    assert_eq!(engine.advance(Duration::from_secs(4)).await.unwrap(), 0);
+    assert_eq!(layout_of(&engine, "#box").await.width, 10.0);
+
+    assert_eq!(engine.advance(Duration::from_secs(1)).await.unwrap(), 1);
+    assert_eq!(layout_of(&engine, "#box").await.width, 300.0);
+    assert!(
+        started.elapsed() < Duration::from_secs(1),
+        "время страницы не ждёт реального"
+    );
+}
+
+#[tokio::test]
+async fn test_intervals_and_nested_timers() {
+    let engine = load(
+        "zver_event_loop_intervals.html",
+        r#"<html><body><script>
+            var ticks = 0;
+            var id = setInterval("ticks++; if (ticks == 3) clearInterval(id)", 100);
+            var done = false;
+            setTimeout("setTimeout('done = true', 100)", 100);
+        </script></body></html>"#,
+    )
+    .await;
+
+    engine.advance(Duration::from_millis(150)).await.unwrap();
+    assert!(matches!(
+        eval(&engine, "done").await,
+        JSValue::Boolean(false)
+    ));
+
+    engine.advance(Duration::from_millis(50)).await.unwrap();
+    assert!(
+        matches!(eval(&engine, "done").await, JSValue::Boolean(true)),
+        "вложенный таймер отсчитывается от срабатывания внешнего"
+    );
+
+    engine.advance(Duration::from_secs(1)).await.unwrap();
+    assert!(matches!(eval(&engine, "ticks").await, JSValue::Number(n) if n == 3.0));
+}
+
+#[tokio::test]
+async fn test_callbacks_keep_their_closures() {
+    let engine = load(
+        "zver_event_loop_closures.html",
+        r#"<html><body><script>
+            var log = [];
+            function setup() {
+                let count = 0;
+                setTimeout(() => { count++; log.push('timer:' + count); }, 1000);
+                setTimeout(function(a, b) { log.push('args:' + a + b); }, 2000, 'x', 'y');
+                const counter = { n: 5, bump: function() { this.n++; log.push('bound:' + this.n); } };
+                setTimeout(counter.bump.bind(counter), 3000);
+
+                const removed = function() { log.push('removed'); };
+                window.addEventListener('load', removed);
+                window.removeEventListener('load', removed);
+                window.addEventListener('load', function(e) { count++; log.push(e.type + ':' + count); });
+                window.addEventListener('load', { handleEvent: function(e) { log.push('handler:' + e.type); } });
+            }
+            setup();
+        </script></body></html>"#,
+    )
+    .await;
+
+    engine.advance(Duration::from_secs(5)).await.unwrap();
+    match eval(&engine, "log.join(',')").await {
+        JSValue::String(log) => {
+            assert_eq!(log, "load:1,handler:load,timer:2,args:xy,bound:6")
+        }
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
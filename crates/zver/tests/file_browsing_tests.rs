@@ -0,0 +1,144 @@
+//! Тесты индексов каталогов `file://` и определения MIME-типов
+
+use std::fs;
+use zver::Zver;
+use zver::network::{CacheStatus, NetworkEngine, Url, mime};
+
+#[test]
+fn test_mime_detection() {
+    let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    assert_eq!(mime::sniff(png), Some("image/png"));
+    assert_eq!(mime::sniff(b"wOF2\0\x01\0\0"), Some("font/woff2"));
+    assert_eq!(mime::sniff(b"RIFF\x24\0\0\0WEBPVP8 "), Some("image/webp"));
+    assert_eq!(
+        mime::sniff(b"\xef\xbb\xbf  \n<!DOCTYPE HTML><html>"),
+        Some("text/html")
+    );
+    assert_eq!(
+        mime::sniff(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"),
+        Some("image/svg+xml")
+    );
+    assert_eq!(mime::sniff(b"<abbr>not html</abbr>"), None);
+
+    // Расширение важнее содержимого
+    assert_eq!(mime::detect(Some("logo.SVG"), png), "image/svg+xml");
+    assert_eq!(mime::detect(Some("image"), png), "image/png");
+    assert_eq!(
+        mime::detect(Some("theme"), b"body { color: red; }"),
+        "text/plain"
+    );
+    assert_eq!(mime::detect(None, b"\0\x02\x03binary"), mime::OCTET_STREAM);
+}
+
+#[tokio::test]
+async fn test_files_are_served_with_detected_types() {
+    let dir = std::env::temp_dir().join("zver_mime_files");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("photo"), b"\xff\xd8\xff\xe0\0\x10JFIF").unwrap();
+    fs::write(dir.join("site.css"), "p { color: red; }").unwrap();
+
+    let mut network = NetworkEngine::new();
+    let photo = network
+        .fetch_response(&Url::from_file_path(dir.join("photo")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(photo.mime_type().as_deref(), Some("image/jpeg"));
+    assert_eq!(photo.body.len(), 10);
+
+    let css = network
+        .fetch_response(&Url::from_file_path(dir.join("site.css")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(css.mime_type().as_deref(), Some("text/css"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_directory_listing() {
+    let dir = std::env::temp_dir().join("zver_directory_listing");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("b page.html"), "<p>b</p>").unwrap();
+    fs::write(dir.join("a<&>.txt"), "a").unwrap();
+    fs::write(dir.join("c:d.html"), "<p>c</p>").unwrap();
+
+    let mut network = NetworkEngine::new();
+    let response = network
+        .fetch_response(&Url::from_file_path(&dir).unwrap())
+        .await
+        .unwrap();
+    let html = response.text();
+
+    assert!(response.url.as_str().ends_with("/zver_directory_listing/"));
+    assert_eq!(response.mime_type().as_deref(), Some("text/html"));
+    assert!(html.contains("<title>Index of "));
+    assert!(html.contains("<a href=\"../\">../</a>"));
+    assert!(html.contains("<a href=\"b%20page.html\">b page.html</a>"));
+    assert!(html.contains("a&lt;&amp;&gt;.txt"));
+    // Иначе `c:` разбирается как схема URL
+    assert!(html.contains("<a href=\"c%3Ad.html\">c:d.html</a>"));
+    assert_eq!(
+        response
+            .url
+            .join("c%3Ad.html")
+            .unwrap()
+            .to_file_path()
+            .unwrap(),
+        dir.join("c:d.html")
+    );
+
+    // Каталоги перечисляются первыми, затем файлы по алфавиту
+    let nested = html.find("nested/").unwrap();
+    let a = html.find("a&lt;").unwrap();
+    let b = html.find("b page.html").unwrap();
+    assert!(nested < a && a < b);
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_directory_listing_is_not_cached() {
+    let dir = std::env::temp_dir().join("zver_directory_reload");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("first.html"), "<p>1</p>").unwrap();
+
+    let mut network = NetworkEngine::new();
+    let url = Url::from_file_path(&dir).unwrap();
+    let before = network.fetch_response(&url).await.unwrap();
+    assert_eq!(before.cache, CacheStatus::Bypass);
+    assert!(!before.text().contains("second.html"));
+
+    fs::write(dir.join("second.html"), "<p>2</p>").unwrap();
+    let after = network.fetch_response(&url).await.unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(after.cache, CacheStatus::Bypass);
+    assert!(after.text().contains("first.html"));
+    assert!(after.text().contains("second.html"));
+}
+
+#[tokio::test]
+async fn test_browse_folder_links_resolve_inside_it() {
+    let dir = std::env::temp_dir().join("zver_directory_browse");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("index.html"), "<p>fixture</p>").unwrap();
+
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", dir.display()))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    let dom = engine.dom.read().await;
+    let link = dom.select_ids("a[href='index.html']");
+    assert_eq!(link.len(), 1);
+    assert_eq!(
+        dom.resolve_url("index.html").unwrap(),
+        Url::from_file_path(dir.join("index.html")).unwrap()
+    );
+}
@@ -7,7 +7,7 @@ mod fontface_test {
         let css = r#"
             @font-face {
                 font-family: 'TestFont';
-                src: url('../assets/fonts/Roboto-Regular.ttf') format('truetype');
+                src: url('../assets/fonts/DejaVuSans.ttf') format('truetype');
                 font-weight: normal;
                 font-style: normal;
             }
@@ -37,7 +37,7 @@ mod fontface_test {
         let css = r#"
             @font-face {
                 font-family: 'TestFont';
-                src: url('../assets/fonts/Roboto-Regular.ttf');
+                src: url('../assets/fonts/DejaVuSans.ttf');
                 font-weight: normal;
             }
         "#;
@@ -58,7 +58,7 @@ mod fontface_test {
         let css = r#"
             @font-face {
                 font-family: 'TestFontUnicode';
-                src: url('../assets/fonts/Roboto-Regular.ttf') format('truetype');
+                src: url('../assets/fonts/DejaVuSans.ttf') format('truetype');
                 font-weight: normal;
                 font-style: normal;
                 unicode-range: U+0020-007F; /* Latin */
@@ -0,0 +1,92 @@
+//! Тесты hit testing: от точки к DOM узлу и его предкам
+
+use zver::Zver;
+
+mod common;
+use common::{id_of, load};
+
+const PAGE: &str = r#"<html><head><style>
+    #base { display: block; width: 200px; height: 100px; }
+    #overlay { position: relative; margin-top: -100px; margin-left: 50px;
+               width: 50px; height: 50px; z-index: 2; }
+    #ghost { position: relative; margin-top: -50px; margin-left: 120px;
+             width: 50px; height: 50px; z-index: 3; pointer-events: none; }
+    #clip { display: block; width: 100px; height: 20px; overflow: hidden; }
+    #tall { display: block; width: 100px; height: 60px; }
+    #label { display: block; width: 300px; height: 40px; font-size: 16px; }
+</style></head><body>
+    <div id="base"></div>
+    <div id="overlay"></div>
+    <div id="ghost"><span id="inner">x</span></div>
+    <div id="clip"><div id="tall"></div></div>
+    <p id="label">Hello</p>
+</body></html>"#;
+
+async fn element_at(engine: &Zver, x: f32, y: f32) -> Option<usize> {
+    let hit = engine.hit_test(x, y).await?;
+    let dom = engine.dom.read().await;
+    hit.element(&dom)
+}
+
+#[tokio::test]
+async fn test_hit_test_respects_stacking_and_pointer_events() {
+    let engine = load("zver_hit_test.html", PAGE).await;
+    let base = id_of(&engine, "#base").await;
+    let overlay = id_of(&engine, "#overlay").await;
+
+    assert_eq!(element_at(&engine, 10.0, 10.0).await, Some(base));
+    assert_eq!(
+        element_at(&engine, 60.0, 10.0).await,
+        Some(overlay),
+        "позиционированный слой рисуется поверх"
+    );
+    assert_eq!(
+        element_at(&engine, 130.0, 10.0).await,
+        Some(base),
+        "pointer-events: none пропускает точку к нижнему узлу, включая потомков"
+    );
+
+    let hit = engine.hit_test(60.0, 10.0).await.unwrap();
+    let body = id_of(&engine, "body").await;
+    let html = id_of(&engine, "html").await;
+    assert_eq!(hit.ancestors.first(), Some(&body));
+    assert!(hit.ancestors.contains(&html));
+}
+
+#[tokio::test]
+async fn test_hit_test_clips_overflow_and_finds_text() {
+    let engine = load("zver_hit_test.html", PAGE).await;
+    let clip = id_of(&engine, "#clip").await;
+    let tall = id_of(&engine, "#tall").await;
+    let label = id_of(&engine, "#label").await;
+
+    let clip_box = engine.layout.read().await.get_layout_result(clip).unwrap();
+    assert_eq!(
+        element_at(&engine, 10.0, clip_box.y + 10.0).await,
+        Some(tall)
+    );
+    assert_ne!(
+        element_at(&engine, 10.0, clip_box.y + 30.0).await,
+        Some(tall),
+        "часть потомка за overflow: hidden не попадает"
+    );
+
+    let label_box = engine.layout.read().await.get_layout_result(label).unwrap();
+    let on_text = engine
+        .hit_test(label_box.x + 2.0, label_box.y + 8.0)
+        .await
+        .unwrap();
+    let dom = engine.dom.read().await;
+    assert!(
+        dom.nodes[&on_text.node_id].tag_name.is_none(),
+        "точка на строке текста попадает в текстовый узел"
+    );
+    assert_eq!(on_text.ancestors.first(), Some(&label));
+    drop(dom);
+
+    let beside_text = engine
+        .hit_test(label_box.x + 250.0, label_box.y + 8.0)
+        .await
+        .unwrap();
+    assert_eq!(beside_text.node_id, label, "правее строки — сам абзац");
+}
@@ -0,0 +1,317 @@
+//! Тесты HTTP-кэша: Cache-Control, условные запросы, Vary и диск
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use zver::network::cache::{CacheControl, CacheLookup, CachedResponse, HttpCache, parse_http_date};
+use zver::network::{CacheStatus, NetworkEngine, Url};
+
+/// Запускает HTTP/1.1-сервер, отвечающий `respond(запрос, номер)`;
+/// возвращает базовый URL и журнал полученных запросов
+async fn serve<F>(respond: F) -> (String, Arc<Mutex<Vec<String>>>)
+where
+    F: Fn(&str, usize) -> String + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let log = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => request.extend_from_slice(&buffer[..read]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request).to_lowercase();
+            let index = {
+                let mut log = log.lock().unwrap();
+                log.push(request.clone());
+                log.len() - 1
+            };
+            let response = respond(&request, index);
+            let _ = stream.write_all(response.as_bytes()).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+
+    (base, requests)
+}
+
+fn response(status: &str, headers: &[&str], body: &str) -> String {
+    let mut text = format!("HTTP/1.1 {status}\r\nConnection: close\r\n");
+    for header in headers {
+        text.push_str(header);
+        text.push_str("\r\n");
+    }
+    text.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+    text
+}
+
+fn engine() -> NetworkEngine {
+    NetworkEngine::with_client(reqwest::Client::builder().no_proxy().build().unwrap())
+}
+
+fn entry(headers: &[(&str, &str)], now: SystemTime) -> CachedResponse {
+    CachedResponse::new(
+        "https://example.com/a.css",
+        200,
+        headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        b"body".to_vec(),
+        &[("accept-language", "en")],
+        now,
+    )
+}
+
+#[test]
+fn test_cache_control_and_dates() {
+    let control = CacheControl::parse("public, max-age=\"60\", No-Cache, must-revalidate");
+    assert_eq!(control.max_age, Some(60));
+    assert!(control.no_cache && control.must_revalidate && !control.no_store);
+    assert!(CacheControl::parse("no-store").no_store);
+
+    assert_eq!(
+        parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+        Some(UNIX_EPOCH + Duration::from_secs(784_111_777))
+    );
+    assert_eq!(parse_http_date("yesterday"), None);
+}
+
+#[test]
+fn test_freshness_lifetime() {
+    let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+    let now = parse_http_date(date).unwrap();
+
+    // max-age важнее Expires
+    let explicit = entry(
+        &[
+            ("Cache-Control", "max-age=30"),
+            ("Date", date),
+            ("Expires", "Sun, 06 Nov 1994 09:49:37 GMT"),
+        ],
+        now,
+    );
+    assert_eq!(explicit.freshness_lifetime(), Duration::from_secs(30));
+    assert!(explicit.is_fresh(now + Duration::from_secs(29)));
+    assert!(!explicit.is_fresh(now + Duration::from_secs(30)));
+
+    let expires = entry(
+        &[("Date", date), ("Expires", "Sun, 06 Nov 1994 09:49:37 GMT")],
+        now,
+    );
+    assert_eq!(expires.freshness_lifetime(), Duration::from_secs(3600));
+
+    // Эвристика: десятая часть возраста документа
+    let heuristic = entry(
+        &[
+            ("Date", date),
+            ("Last-Modified", "Sun, 06 Nov 1994 07:49:37 GMT"),
+        ],
+        now,
+    );
+    assert_eq!(heuristic.freshness_lifetime(), Duration::from_secs(360));
+
+    let no_cache = entry(&[("Cache-Control", "max-age=60, no-cache")], now);
+    assert!(!no_cache.is_fresh(now));
+}
+
+#[test]
+fn test_vary_selects_matching_requests() {
+    let now = SystemTime::now();
+    let mut cache = HttpCache::new();
+    assert!(cache.store(entry(
+        &[("Cache-Control", "max-age=60"), ("Vary", "Accept-Language")],
+        now
+    )));
+
+    let url = "https://example.com/a.css";
+    assert!(matches!(
+        cache.lookup(url, &[("Accept-Language", "en")], now),
+        CacheLookup::Fresh(_)
+    ));
+    assert!(matches!(
+        cache.lookup(url, &[("Accept-Language", "ru")], now),
+        CacheLookup::Miss
+    ));
+
+    // Vary: * и no-store не сохраняются
+    assert!(!cache.store(entry(
+        &[("Cache-Control", "max-age=60"), ("Vary", "*")],
+        now
+    )));
+    assert!(!cache.store(entry(&[("Cache-Control", "no-store")], now)));
+    assert!(cache.is_empty(), "no-store removes the previous entry");
+}
+
+#[test]
+fn test_disk_store_persists_and_evicts() {
+    let dir = std::env::temp_dir().join("zver_http_cache_disk");
+    let _ = std::fs::remove_dir_all(&dir);
+    let now = SystemTime::now();
+    let make = |url: &str| {
+        CachedResponse::new(
+            url,
+            200,
+            vec![("cache-control".into(), "max-age=600".into())],
+            vec![b'x'; 200],
+            &[],
+            now,
+        )
+    };
+
+    let mut cache = HttpCache::with_disk_store(&dir, 400);
+    assert!(cache.store(make("https://example.com/first")));
+
+    // Новый экземпляр читает запись с диска
+    let mut reopened = HttpCache::with_disk_store(&dir, 400);
+    match reopened.lookup("https://example.com/first", &[], now) {
+        CacheLookup::Fresh(entry) => assert_eq!(entry.body.len(), 200),
+        other => panic!("ожидалась свежая запись, получено {other:?}"),
+    }
+
+    // Вторая запись не помещается вместе с первой — старейшая вытесняется
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(reopened.store(make("https://example.com/second")));
+    let mut fresh = HttpCache::with_disk_store(&dir, 400);
+    assert!(matches!(
+        fresh.lookup("https://example.com/first", &[], now),
+        CacheLookup::Miss
+    ));
+    assert!(matches!(
+        fresh.lookup("https://example.com/second", &[], now),
+        CacheLookup::Fresh(_)
+    ));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_max_age_is_served_from_cache() {
+    let (base, requests) = serve(|_, index| {
+        response(
+            "200 OK",
+            &["Cache-Control: max-age=300"],
+            &format!("body {index}"),
+        )
+    })
+    .await;
+    let mut network = engine();
+    let url = format!("{base}/style.css");
+
+    assert_eq!(network.fetch(&url).await.unwrap(), "body 0");
+    assert_eq!(network.fetch(&url).await.unwrap(), "body 0");
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    let logs = network.logs();
+    assert_eq!(logs[0].cache, CacheStatus::Miss);
+    assert_eq!(logs[1].cache, CacheStatus::Hit);
+    assert_eq!(logs[1].source, "CSS");
+}
+
+#[tokio::test]
+async fn test_no_store_bypasses_cache() {
+    let (base, requests) = serve(|_, index| {
+        response(
+            "200 OK",
+            &["Cache-Control: no-store, max-age=300"],
+            &format!("body {index}"),
+        )
+    })
+    .await;
+    let mut network = engine();
+    let url = format!("{base}/live");
+
+    assert_eq!(network.fetch(&url).await.unwrap(), "body 0");
+    assert_eq!(network.fetch(&url).await.unwrap(), "body 1");
+    assert_eq!(requests.lock().unwrap().len(), 2);
+    assert!(
+        network
+            .logs()
+            .iter()
+            .all(|entry| entry.cache == CacheStatus::Bypass)
+    );
+}
+
+#[tokio::test]
+async fn test_stale_entry_is_revalidated() {
+    let (base, requests) = serve(|request, _| {
+        if request.contains("if-none-match: \"v1\"")
+            && request.contains("if-modified-since: sun, 06 nov 1994 08:49:37 gmt")
+        {
+            response("304 Not Modified", &["Cache-Control: no-cache"], "")
+        } else {
+            response(
+                "200 OK",
+                &[
+                    "Cache-Control: no-cache",
+                    "ETag: \"v1\"",
+                    "Last-Modified: Sun, 06 Nov 1994 08:49:37 GMT",
+                ],
+                "<p>cached</p>",
+            )
+        }
+    })
+    .await;
+    let mut network = engine();
+    let url = format!("{base}/page.html");
+
+    let first = network.fetch(&url).await.unwrap();
+    let second = network.fetch(&url).await.unwrap();
+    assert_eq!(first, "<p>cached</p>");
+    assert_eq!(second, first);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+
+    let statuses: Vec<CacheStatus> = network.logs().iter().map(|entry| entry.cache).collect();
+    assert_eq!(statuses, [CacheStatus::Miss, CacheStatus::Revalidated]);
+    assert_eq!(network.logs()[1].status_code, Some(200));
+}
+
+/// Сервер отдаёт устаревающую запись с `cache_control`, а на перепроверку отвечает 503
+async fn serve_failing_revalidation(cache_control: &'static str) -> String {
+    let (base, _) = serve(move |_, index| {
+        if index == 0 {
+            response("200 OK", &[cache_control, "ETag: \"v1\""], "<p>cached</p>")
+        } else {
+            response("503 Service Unavailable", &[], "down")
+        }
+    })
+    .await;
+    format!("{base}/page.html")
+}
+
+#[tokio::test]
+async fn test_stale_entry_is_served_when_revalidation_fails() {
+    let url = serve_failing_revalidation("Cache-Control: max-age=0").await;
+    let mut network = engine();
+
+    assert_eq!(network.fetch(&url).await.unwrap(), "<p>cached</p>");
+    assert_eq!(network.fetch(&url).await.unwrap(), "<p>cached</p>");
+
+    let statuses: Vec<CacheStatus> = network.logs().iter().map(|entry| entry.cache).collect();
+    assert_eq!(statuses, [CacheStatus::Miss, CacheStatus::Stale]);
+}
+
+#[tokio::test]
+async fn test_must_revalidate_reports_failed_revalidation() {
+    let url = serve_failing_revalidation("Cache-Control: max-age=0, must-revalidate").await;
+    let mut network = engine();
+
+    assert_eq!(network.fetch(&url).await.unwrap(), "<p>cached</p>");
+    // Ответ 503 обычного запроса вернулся бы как есть, а неудачная перепроверка — ошибка
+    let error = network
+        .fetch_response(&Url::parse(&url).unwrap())
+        .await
+        .expect_err("устаревшую запись с must-revalidate отдавать нельзя");
+    assert!(error.to_string().contains("503"), "{error}");
+}
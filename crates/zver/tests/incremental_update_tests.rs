@@ -0,0 +1,246 @@
+//! Тесты пометки грязных узлов и инкрементального `Zver::update`
+
+use zver::css::color::Color;
+use zver::dom::{Dirty, ElementState};
+
+mod common;
+use common::{id_of, layout_of_id, load};
+
+#[tokio::test]
+async fn test_mutations_mark_nodes_dirty() {
+    let mut dom = zver::dom::Document::new();
+    dom.parse_html("<html><body><div id='a'><span>x</span></div><p id='b'>text</p></body></html>")
+        .await
+        .unwrap();
+    assert!(
+        !dom.is_dirty(),
+        "разбор документа не оставляет грязных узлов"
+    );
+
+    let a = dom.get_element_by_id("a").unwrap();
+    let b = dom.get_element_by_id("b").unwrap();
+
+    // Повторная установка того же значения ничего не меняет
+    dom.set_attribute(a, "class", "wide").unwrap();
+    dom.set_attribute(a, "class", "wide").unwrap();
+    assert_eq!(dom.take_dirty().get(&a), Some(&Dirty::STYLE));
+
+    dom.set_element_state(b, ElementState::HOVER, true).unwrap();
+    dom.set_element_state(b, ElementState::HOVER, true).unwrap();
+    assert_eq!(dom.take_dirty().get(&b), Some(&Dirty::STYLE));
+
+    // Замена текста текстом — только геометрия
+    dom.set_text_content(b, "other").unwrap();
+    assert_eq!(dom.take_dirty().get(&b), Some(&Dirty::LAYOUT));
+
+    // Удаление элементов-детей может изменить `:empty` и соседние селекторы
+    let span = dom.nodes[&a].children[0];
+    dom.set_text_content(a, "plain").unwrap();
+    assert_eq!(dom.take_dirty().get(&a), Some(&Dirty::STYLE));
+    assert!(!dom.nodes.contains_key(&span), "старое поддерево удаляется");
+    assert_eq!(dom.nodes[&a].children.len(), 1);
+
+    // Пустой текст меняет `:empty`
+    dom.set_text_content(b, "  ").unwrap();
+    assert_eq!(dom.take_dirty().get(&b), Some(&Dirty::STYLE));
+}
+
+#[tokio::test]
+async fn test_append_child_moves_node_between_parents() {
+    let mut dom = zver::dom::Document::new();
+    dom.parse_html(
+        "<html><body><div id='from'><i id='item'></i></div><div id='to'></div></body></html>",
+    )
+    .await
+    .unwrap();
+    let from = dom.get_element_by_id("from").unwrap();
+    let to = dom.get_element_by_id("to").unwrap();
+    let item = dom.get_element_by_id("item").unwrap();
+
+    dom.append_child(to, item).unwrap();
+
+    assert!(!dom.nodes[&from].children.contains(&item));
+    assert_eq!(dom.nodes[&to].children, vec![item]);
+    let dirty = dom.take_dirty();
+    assert!(dirty.contains_key(&from) && dirty.contains_key(&to));
+}
+
+#[tokio::test]
+async fn test_update_restyles_and_reuses_untouched_taffy_nodes() {
+    let engine = load(
+        "zver_update_attribute.html",
+        r#"<html><head><style>
+            div { display: block; width: 100px; height: 20px; }
+            .wide { width: 300px; }
+        </style></head><body>
+            <div id="target"></div>
+            <div id="other"></div>
+        </body></html>"#,
+    )
+    .await;
+    assert!(
+        !engine.update().await.unwrap(),
+        "без изменений кадр не перерисовывается"
+    );
+
+    let target = id_of(&engine, "#target").await;
+    let other = id_of(&engine, "#other").await;
+    let other_node = engine.layout.read().await.taffy_node(other);
+    assert_eq!(layout_of_id(&engine, target).await.width, 100.0);
+
+    engine
+        .dom
+        .write()
+        .await
+        .set_attribute(target, "class", "wide")
+        .unwrap();
+    assert!(engine.update().await.unwrap());
+
+    assert_eq!(layout_of_id(&engine, target).await.width, 300.0);
+    assert_eq!(layout_of_id(&engine, other).await.width, 100.0);
+    assert_eq!(
+        engine.layout.read().await.taffy_node(other),
+        other_node,
+        "соседний узел Taffy не перестраивается"
+    );
+}
+
+#[tokio::test]
+async fn test_hover_state_repaints() {
+    let engine = load(
+        "zver_update_hover.html",
+        r#"<html><head><style>
+            #box { display: block; width: 50px; height: 50px; background-color: #ff0000; }
+            #box:hover { background-color: #0000ff; }
+        </style></head><body><div id="box"></div></body></html>"#,
+    )
+    .await;
+    let target = id_of(&engine, "#box").await;
+    let rect = engine
+        .layout
+        .read()
+        .await
+        .get_layout_result(target)
+        .unwrap();
+    let (x, y) = ((rect.x + 25.0) as u32, (rect.y + 25.0) as u32);
+    assert_eq!(
+        engine.screenshot().await.unwrap().pixel(x, y),
+        Some(Color::rgb(255, 0, 0))
+    );
+
+    engine
+        .dom
+        .write()
+        .await
+        .set_element_state(target, ElementState::HOVER, true)
+        .unwrap();
+    engine.update().await.unwrap();
+
+    assert_eq!(
+        engine.screenshot().await.unwrap().pixel(x, y),
+        Some(Color::rgb(0, 0, 255))
+    );
+}
+
+#[tokio::test]
+async fn test_sibling_selectors_and_display_changes() {
+    let engine = load(
+        "zver_update_siblings.html",
+        r#"<html><head><style>
+            div, p { display: block; width: 100px; height: 10px; margin: 0; }
+            .on + p { width: 40px; }
+            .gone { display: none; }
+        </style></head><body>
+            <div id="first"></div>
+            <p id="next"></p>
+            <p id="last"></p>
+        </body></html>"#,
+    )
+    .await;
+    let first = id_of(&engine, "#first").await;
+    let next = id_of(&engine, "#next").await;
+    let last = id_of(&engine, "#last").await;
+
+    engine
+        .dom
+        .write()
+        .await
+        .set_attribute(first, "class", "on")
+        .unwrap();
+    engine.update().await.unwrap();
+    assert_eq!(layout_of_id(&engine, next).await.width, 40.0);
+
+    // display: none убирает узел из layout, соседи сдвигаются
+    let last_y = engine
+        .layout
+        .read()
+        .await
+        .get_layout_result(last)
+        .unwrap()
+        .y;
+    engine
+        .dom
+        .write()
+        .await
+        .set_attribute(next, "class", "gone")
+        .unwrap();
+    engine.update().await.unwrap();
+    let layout = engine.layout.read().await;
+    assert!(layout.get_layout_result(next).is_none());
+    assert_eq!(layout.get_layout_result(last).unwrap().y, last_y - 10.0);
+}
+
+#[tokio::test]
+async fn test_text_and_children_changes_relayout() {
+    let engine = load(
+        "zver_update_text.html",
+        r#"<html><head><style>
+            #list { display: block; width: 200px; }
+            #list li { display: block; height: 10px; }
+            #label { display: block; }
+            #label:empty { height: 30px; }
+        </style></head><body>
+            <ul id="list"><li>one</li></ul>
+            <div id="label">text</div>
+        </body></html>"#,
+    )
+    .await;
+    let list = id_of(&engine, "#list").await;
+    let label = id_of(&engine, "#label").await;
+
+    {
+        let mut dom = engine.dom.write().await;
+        let li = dom.create_element("li").unwrap();
+        dom.append_child(list, li).unwrap();
+    }
+    engine.update().await.unwrap();
+    assert_eq!(layout_of_id(&engine, list).await.height, 20.0);
+
+    engine
+        .dom
+        .write()
+        .await
+        .set_text_content(label, "")
+        .unwrap();
+    engine.update().await.unwrap();
+    assert_eq!(layout_of_id(&engine, label).await.height, 30.0);
+}
+
+#[tokio::test]
+async fn test_script_mutations_are_styled_on_load() {
+    let engine = load(
+        "zver_update_script.html",
+        r#"<html><head><style>
+            #box { display: block; width: 10px; height: 10px; }
+            #box.big { width: 120px; }
+        </style></head><body>
+            <div id="box"></div>
+            <script>document.getElementById('box').setAttribute('class', 'big');</script>
+        </body></html>"#,
+    )
+    .await;
+
+    let target = id_of(&engine, "#box").await;
+    assert_eq!(layout_of_id(&engine, target).await.width, 120.0);
+    assert!(!engine.dom.read().await.is_dirty());
+}
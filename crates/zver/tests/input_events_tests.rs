@@ -0,0 +1,309 @@
+//! Тесты ввода: состояния :hover/:active/:focus и DOM-события от указателя и клавиатуры
+
+use std::fs;
+use zver::Zver;
+use zver::dom::ElementState;
+use zver::input::{InputEvent, MouseButton};
+use zver::js::JSValue;
+use zver::js::events::Modifiers;
+
+const PAGE: &str = r#"<html><head><style>
+    #button { display: block; width: 100px; height: 40px; }
+    #button:hover { height: 50px; }
+    #field { display: block; width: 100px; height: 20px; }
+    #field:focus { width: 150px; }
+    #cancel { display: block; width: 100px; height: 20px; }
+</style></head><body>
+    <div id="button" tabindex="0"><span id="label">Press</span></div>
+    <input id="field">
+    <div id="cancel"></div>
+    <script>
+        var log = [];
+        var button = document.getElementById('button');
+        button.addEventListener('mousedown', function(e) { log.push('down:' + e.button); });
+        button.addEventListener('click', function(e) {
+            log.push('click:' + e.clientX + ',' + e.clientY + ':' + e.shiftKey + ':' + e.target.getAttribute('id'));
+        });
+        document.addEventListener('click', function(e) { log.push('document-click'); });
+        document.getElementById('field').addEventListener('focus', function() { log.push('focus'); });
+        document.getElementById('field').addEventListener('keydown', function(e) {
+            log.push('key:' + e.key + ':' + e.code + ':' + e.ctrlKey);
+        });
+        document.getElementById('cancel').addEventListener('click', function(e) {
+            e.preventDefault();
+            e.stopPropagation();
+            log.push('cancel');
+        });
+    </script>
+</body></html>"#;
+
+async fn load() -> Zver {
+    let path = std::env::temp_dir().join("zver_input_events.html");
+    fs::write(&path, PAGE).unwrap();
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", path.display()))
+        .await
+        .unwrap();
+    let _ = fs::remove_file(path);
+    engine
+}
+
+async fn center_of(engine: &Zver, selector: &str) -> (usize, f32, f32) {
+    let node_id = engine.dom.read().await.select_ids(selector)[0];
+    let layout = engine
+        .layout
+        .read()
+        .await
+        .get_layout_result(node_id)
+        .unwrap();
+    (
+        node_id,
+        layout.x + layout.width / 2.0,
+        layout.y + layout.height / 2.0,
+    )
+}
+
+async fn log(engine: &Zver) -> String {
+    match engine.js.write().await.execute("log.join('|')").unwrap() {
+        JSValue::String(log) => log,
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
+
+async fn click(engine: &Zver, x: f32, y: f32, modifiers: Modifiers) -> zver::input::InputOutcome {
+    engine
+        .dispatch_input(InputEvent::PointerDown {
+            x,
+            y,
+            button: MouseButton::Primary,
+            modifiers,
+        })
+        .await
+        .unwrap();
+    engine
+        .dispatch_input(InputEvent::PointerUp {
+            x,
+            y,
+            button: MouseButton::Primary,
+            modifiers,
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_pointer_updates_hover_active_and_dispatches_click() {
+    let engine = load().await;
+    let (button, x, y) = center_of(&engine, "#button").await;
+
+    engine
+        .dispatch_input(InputEvent::PointerMove {
+            x,
+            y,
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    let body = engine.dom.read().await.select_ids("body")[0];
+    {
+        let dom = engine.dom.read().await;
+        assert!(
+            dom.element_state(button)
+                .unwrap()
+                .contains(ElementState::HOVER)
+        );
+        assert!(
+            dom.element_state(body)
+                .unwrap()
+                .contains(ElementState::HOVER),
+            ":hover распространяется на предков"
+        );
+    }
+    assert_eq!(
+        engine
+            .layout
+            .read()
+            .await
+            .get_layout_result(button)
+            .unwrap()
+            .height,
+        50.0,
+        "правило :hover применилось"
+    );
+
+    // Точка на тексте: цель — span, click всплывает к кнопке и document
+    let label = engine.dom.read().await.select_ids("#label")[0];
+    let text_y = y - 15.0;
+    engine
+        .dispatch_input(InputEvent::PointerDown {
+            x: 5.0,
+            y: text_y,
+            button: MouseButton::Primary,
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    assert!(
+        engine
+            .dom
+            .read()
+            .await
+            .element_state(button)
+            .unwrap()
+            .contains(ElementState::ACTIVE)
+    );
+    let shift = Modifiers {
+        shift: true,
+        ..Modifiers::default()
+    };
+    let outcome = engine
+        .dispatch_input(InputEvent::PointerUp {
+            x: 5.0,
+            y: text_y,
+            button: MouseButton::Primary,
+            modifiers: shift,
+        })
+        .await
+        .unwrap();
+    assert!(outcome.clicked && !outcome.default_prevented);
+    assert_eq!(outcome.target, Some(label));
+    assert!(
+        !engine
+            .dom
+            .read()
+            .await
+            .element_state(button)
+            .unwrap()
+            .contains(ElementState::ACTIVE)
+    );
+    assert_eq!(
+        log(&engine).await,
+        format!("down:0|click:5,{text_y}:true:label|document-click")
+    );
+    assert_eq!(
+        engine.focused_element(),
+        Some(button),
+        "tabindex фокусируем"
+    );
+
+    engine
+        .dispatch_input(InputEvent::PointerLeave)
+        .await
+        .unwrap();
+    assert!(
+        !engine
+            .dom
+            .read()
+            .await
+            .element_state(button)
+            .unwrap()
+            .contains(ElementState::HOVER)
+    );
+}
+
+#[tokio::test]
+async fn test_release_outside_page_clears_active_and_buttons() {
+    let engine = load().await;
+    let (button, x, y) = center_of(&engine, "#button").await;
+    engine
+        .js
+        .write()
+        .await
+        .execute("button.addEventListener('mousemove', function(e) { log.push('buttons:' + e.buttons); });")
+        .unwrap();
+
+    engine
+        .dispatch_input(InputEvent::PointerDown {
+            x,
+            y,
+            button: MouseButton::Primary,
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    // Кнопку отпустили над панелью браузера: точка вне страницы
+    let outcome = engine
+        .dispatch_input(InputEvent::PointerUp {
+            x: -40.0,
+            y: -25.0,
+            button: MouseButton::Primary,
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    assert!(!outcome.clicked);
+    assert_eq!(outcome.target, None);
+    assert!(
+        !engine
+            .dom
+            .read()
+            .await
+            .element_state(button)
+            .unwrap()
+            .contains(ElementState::ACTIVE),
+        ":active снят"
+    );
+
+    engine
+        .dispatch_input(InputEvent::PointerMove {
+            x,
+            y,
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(log(&engine).await, "down:0|buttons:0");
+}
+
+#[tokio::test]
+async fn test_focus_keyboard_and_prevent_default() {
+    let engine = load().await;
+    let (field, x, y) = center_of(&engine, "#field").await;
+
+    click(&engine, x, y, Modifiers::default()).await;
+    assert_eq!(engine.focused_element(), Some(field));
+    assert!(
+        engine
+            .dom
+            .read()
+            .await
+            .element_state(field)
+            .unwrap()
+            .contains(ElementState::FOCUS)
+    );
+    assert_eq!(
+        engine
+            .layout
+            .read()
+            .await
+            .get_layout_result(field)
+            .unwrap()
+            .width,
+        150.0,
+        "правило :focus применилось"
+    );
+
+    engine
+        .dispatch_input(InputEvent::KeyDown {
+            key: "a".to_string(),
+            code: "KeyA".to_string(),
+            modifiers: Modifiers {
+                ctrl: true,
+                ..Modifiers::default()
+            },
+        })
+        .await
+        .unwrap();
+
+    let (_, x, y) = center_of(&engine, "#cancel").await;
+    let outcome = click(&engine, x, y, Modifiers::default()).await;
+    assert!(outcome.clicked);
+    assert!(outcome.default_prevented, "preventDefault() у click");
+    assert_eq!(engine.focused_element(), None, "фокус снят щелчком мимо");
+    assert_eq!(
+        log(&engine).await,
+        "focus|document-click|key:a:KeyA:true|cancel",
+        "stopPropagation() не пустил click к document"
+    );
+}
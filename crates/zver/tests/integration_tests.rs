@@ -101,6 +101,7 @@ async fn test_malformed_html() {
 
 /// Тест полного pipeline: HTML + CSS → Layout
 #[tokio::test]
+#[allow(clippy::len_zero, clippy::explicit_auto_deref)]
 async fn test_html_css_layout_pipeline() {
     let engine = Zver::new();
 
@@ -1,6 +1,7 @@
 //! Unit tests for JavaScript integration
 //! Tests DOM API, events, and timer functionality
 
+#[allow(clippy::single_component_path_imports)]
 use tokio;
 use zver::js::JSEngine;
 
@@ -32,6 +33,33 @@ async fn test_js_console_log() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_js_console_messages_are_recorded() {
+    use zver::js::{ConsoleLevel, ConsoleMessage};
+
+    let mut engine = JSEngine::new();
+    engine.console().set_echo(false);
+    engine
+        .execute(
+            "console.log('sum', 1 + 1, true); console.warn('careful'); console.error('failed')",
+        )
+        .unwrap();
+
+    let message = |level, text: &str| ConsoleMessage {
+        level,
+        text: text.to_string(),
+    };
+    assert_eq!(
+        engine.console().take(),
+        vec![
+            message(ConsoleLevel::Log, "sum 2 true"),
+            message(ConsoleLevel::Warn, "careful"),
+            message(ConsoleLevel::Error, "failed"),
+        ]
+    );
+    assert!(engine.console().entries().is_empty());
+}
+
 #[tokio::test]
 async fn test_js_set_timeout() {
     let mut engine = JSEngine::new();
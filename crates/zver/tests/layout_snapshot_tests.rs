@@ -0,0 +1,162 @@
+//! Тесты снимка layout: порядок документа вместо id узлов, текстовый формат
+//! с обратным разбором и сравнение с допуском
+
+use zver::Zver;
+use zver::layout::LayoutSnapshot;
+use zver::layout::snapshot::{SnapshotKind, SnapshotParseError};
+
+mod common;
+use common::{load_into, page};
+
+async fn snapshot(name: &str, style: &str, body: &str) -> LayoutSnapshot {
+    let engine = Zver::builder().viewport(400.0, 300.0).build();
+    load_into(&engine, name, &page(style, body)).await;
+    engine.layout_snapshot().await
+}
+
+const LIST_STYLE: &str =
+    "#list { display: flex; padding: 5px; } .item { width: 40px; height: 20px; }";
+
+#[tokio::test]
+async fn test_snapshot_follows_document_order_not_node_ids() {
+    let markup = snapshot(
+        "zver_snapshot_markup.html",
+        LIST_STYLE,
+        r#"<div id="list"><div id="a" class="item"></div><div id="b" class="item"></div></div>"#,
+    )
+    .await;
+    // Элементы создаются в обратном порядке, поэтому их id идут иначе
+    let scripted = snapshot(
+        "zver_snapshot_scripted.html",
+        LIST_STYLE,
+        r#"<div id="list"></div>
+           <script>
+               var b = document.createElement('div');
+               var a = document.createElement('div');
+               a.setAttribute('id', 'a');
+               b.setAttribute('id', 'b');
+               a.setAttribute('class', 'item');
+               b.setAttribute('class', 'item');
+               var list = document.getElementById('list');
+               list.appendChild(a);
+               list.appendChild(b);
+           </script>"#,
+    )
+    .await;
+
+    let text = markup.to_string();
+    assert!(
+        text.contains(
+            "    div#list flex 0,0 400x30 content 5,5 390x20\n\
+             \x20     div#a.item block 5,5 40x20 content 5,5 40x20\n\
+             \x20     div#b.item block 45,5 40x20 content 45,5 40x20\n"
+        ),
+        "{text}"
+    );
+    assert_eq!(scripted.to_string(), text);
+    assert!(scripted.matches(&markup, 0.0));
+}
+
+#[tokio::test]
+async fn test_snapshot_round_trips_through_text() {
+    let captured = snapshot(
+        "zver_snapshot_round_trip.html",
+        "#card { width: 150px; padding: 4px 6px; border: 1px solid black; }
+         #gone { display: none; }",
+        r#"<div id="card" class="card wide">He said "hi" and left, then came back</div>
+           <div id="gone">hidden text</div>"#,
+    )
+    .await;
+    let text = captured.to_string();
+    assert!(!text.contains("hidden text"), "{text}");
+    assert!(!text.contains("#gone"), "{text}");
+    assert!(text.contains(r#""He said \"hi\""#), "{text}");
+
+    let card = &captured.roots[0].children[0].children[0];
+    assert_eq!(card.label(), "div#card.card.wide");
+    let SnapshotKind::Element { content, .. } = &card.kind else {
+        panic!("ожидался элемент");
+    };
+    assert_eq!((card.rect.width, content.width), (150.0, 136.0));
+    assert_eq!(content.x - card.rect.x, 7.0);
+    let SnapshotKind::Text { runs } = &card.children[0].kind else {
+        panic!("ожидался текст");
+    };
+    assert!(runs.len() > 1, "текст переносится по ширине карточки");
+
+    let parsed = LayoutSnapshot::parse(&text).unwrap();
+    assert_eq!(parsed.to_string(), text);
+    assert!(parsed.matches(&captured, 0.005));
+}
+
+const EXPECTED: &str = "\
+html block 0,0 400x300 content 0,0 400x300
+  body block 0,0 400x50 content 0,0 400x50
+    div#box block 0,0 100.5x50 content 0,0 100.5x50
+      #text 0,0 42x19
+        \"Hello\\tworld \\u{1}\" 0,0 42.25x19.2
+    p block 0,50 400x0 content 0,50 400x0
+";
+
+#[test]
+fn test_snapshot_diff_uses_tolerance_and_reports_paths() {
+    let expected = LayoutSnapshot::parse(EXPECTED).unwrap();
+    assert_eq!(expected.to_string(), EXPECTED);
+    let SnapshotKind::Text { runs } = &expected.roots[0].children[0].children[0].children[0].kind
+    else {
+        panic!("ожидался текст");
+    };
+    assert_eq!(runs[0].text, "Hello\tworld \u{1}");
+
+    let shifted =
+        LayoutSnapshot::parse(&EXPECTED.replace("100.5x50 content", "100.52x50 content")).unwrap();
+    assert!(shifted.matches(&expected, 0.05));
+    let differences = shifted.diff(&expected, 0.001);
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0].path, "html > body > div#box");
+    assert_eq!(
+        differences[0].to_string(),
+        "html > body > div#box: expected `div#box block 0,0 100.5x50 content 0,0 100.5x50`, \
+         got `div#box block 0,0 100.52x50 content 0,0 100.5x50`"
+    );
+
+    // Текст сравнивается точно, недостающие и лишние боксы перечисляются
+    let changed = LayoutSnapshot::parse(
+        &EXPECTED
+            .replace("Hello", "Hallo")
+            .replace("    p block 0,50 400x0 content 0,50 400x0\n", ""),
+    )
+    .unwrap();
+    let differences = changed.diff(&expected, 1.0);
+    let paths: Vec<_> = differences.iter().map(|d| d.path.as_str()).collect();
+    assert_eq!(paths, ["html > body > div#box > #text", "html > body > p"]);
+    assert_eq!(differences[1].actual, "nothing");
+    assert_eq!(expected.diff(&changed, 1.0)[1].expected, "nothing");
+}
+
+#[test]
+fn test_snapshot_parse_errors_name_the_line() {
+    let error = |source: &str| LayoutSnapshot::parse(source).unwrap_err();
+
+    assert_eq!(
+        error("html block 0,0 1x1 content 0,0 1x1\n   body block 0,0 1x1 content 0,0 1x1"),
+        SnapshotParseError {
+            line: 2,
+            message: "indentation must be a multiple of two spaces".to_string()
+        }
+    );
+    assert_eq!(
+        error("html block 0,0 1x1 content 0,0 1x1\n    p block 0,0 1x1 content 0,0 1x1").line,
+        2
+    );
+    assert_eq!(
+        error("html block 0,0 1x1 content 0,0 1x1\n  \"text\" 0,0 1x1").message,
+        "text run outside of #text"
+    );
+    assert_eq!(
+        error("#text 0,0 1x1\n  \"open 0,0 1x1").message,
+        "unterminated string"
+    );
+    assert_eq!(error("html sideways 0,0 1x1 content 0,0 1x1").line, 1);
+    assert!(LayoutSnapshot::parse("").unwrap().roots.is_empty());
+}
@@ -27,6 +27,7 @@ async fn test_basic_layout_computation() {
 }
 
 #[tokio::test]
+#[allow(clippy::collapsible_if)]
 async fn test_viewport_dimensions() {
     let mut doc = Document::new();
     let html = r#"<!DOCTYPE html><html><body></body></html>"#;
@@ -78,6 +79,7 @@ async fn test_layout_invalidation() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_get_then_check)]
 async fn test_display_none_elements() {
     let mut doc = Document::new();
     // Note: inline styles from HTML are not automatically parsed in this test setup
@@ -119,6 +121,7 @@ async fn test_display_none_elements() {
 }
 
 #[tokio::test]
+#[allow(clippy::collapsible_if)]
 async fn test_text_measurement() {
     let mut doc = Document::new();
     let html = r#"<!DOCTYPE html><html><body><p id="text">Hello World</p></body></html>"#;
@@ -141,6 +144,7 @@ async fn test_text_measurement() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_get_then_check)]
 async fn test_nested_layout() {
     let mut doc = Document::new();
     let html = r#"<!DOCTYPE html><html><body><div id="outer"><div id="inner">Content</div></div></body></html>"#;
@@ -294,6 +298,7 @@ async fn test_layout_with_inline_elements() {
 }
 
 #[tokio::test]
+#[allow(clippy::unnecessary_get_then_check)]
 async fn test_script_and_style_tags_excluded() {
     let mut doc = Document::new();
     let html = r#"
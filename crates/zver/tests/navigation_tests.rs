@@ -0,0 +1,338 @@
+//! Тесты переходов по ссылкам и отправки форм: GET, POST и отмена через preventDefault()
+
+use std::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use zver::Zver;
+use zver::input::{InputEvent, InputOutcome, MouseButton};
+use zver::js::JSValue;
+use zver::js::events::Modifiers;
+use zver::navigation::{NavigationTarget, encode_multipart, form_data, form_owner};
+use zver::network::{NetworkConfig, NetworkEngine, Url};
+
+mod common;
+use common::{eval, load_into};
+
+const STYLE: &str =
+    "<style>a, button, input { display: block; width: 100px; height: 20px; }</style>";
+
+async fn load(engine: &Zver, name: &str, body: &str) -> Url {
+    let html = format!("<html><head>{STYLE}</head><body>{body}</body></html>");
+    load_into(engine, name, &html).await
+}
+
+async fn click(engine: &Zver, selector: &str) -> InputOutcome {
+    let node_id = engine.dom.read().await.select_ids(selector)[0];
+    let layout = engine
+        .layout
+        .read()
+        .await
+        .get_layout_result(node_id)
+        .unwrap();
+    let (x, y) = (
+        layout.x + layout.width / 2.0,
+        layout.y + layout.height / 2.0,
+    );
+    for event in [
+        InputEvent::PointerDown {
+            x,
+            y,
+            button: MouseButton::Primary,
+            modifiers: Modifiers::default(),
+        },
+        InputEvent::PointerUp {
+            x,
+            y,
+            button: MouseButton::Primary,
+            modifiers: Modifiers::default(),
+        },
+    ] {
+        let outcome = engine.dispatch_input(event).await.unwrap();
+        if matches!(outcome, InputOutcome { clicked: true, .. }) {
+            return outcome;
+        }
+    }
+    panic!("щелчок по {selector} не дал click");
+}
+
+#[tokio::test]
+async fn test_link_click_navigates_resolved_url_and_honors_target() {
+    let engine = Zver::new();
+    let next = std::env::temp_dir().join("zver_navigation_next.html");
+    fs::write(&next, "<html><body><p id='arrived'>next</p></body></html>").unwrap();
+    let page = load(
+        &engine,
+        "zver_navigation_links.html",
+        r#"<a id="relative" href="zver_navigation_next.html"><span>Next</span></a>
+           <a id="blank" href="https://example.com/docs?q=1" target="_blank">Docs</a>
+           <a id="cancel" href="https://example.com/cancelled">Cancel</a>
+           <script>
+               document.getElementById('cancel').addEventListener('click', function(e) {
+                   e.preventDefault();
+               });
+           </script>"#,
+    )
+    .await;
+
+    // Точка внутри <span>: ссылка находится среди предков цели
+    let outcome = click(&engine, "#relative").await;
+    let request = outcome.navigation.expect("ссылка даёт переход");
+    assert_eq!(request.url, page.join("zver_navigation_next.html").unwrap());
+    assert_eq!(request.target, NavigationTarget::Current);
+    assert!(request.body.is_none());
+
+    let blank = click(&engine, "#blank").await.navigation.unwrap();
+    assert_eq!(blank.url.as_str(), "https://example.com/docs?q=1");
+    assert_eq!(blank.target, NavigationTarget::Blank);
+
+    let cancelled = click(&engine, "#cancel").await;
+    assert!(cancelled.default_prevented);
+    assert_eq!(
+        cancelled.navigation, None,
+        "preventDefault() отменяет переход"
+    );
+
+    engine.navigate(&request).await.unwrap();
+    let _ = fs::remove_file(next);
+    let dom = engine.dom.read().await;
+    assert_eq!(dom.url(), &request.url);
+    assert_eq!(dom.select_ids("#arrived").len(), 1);
+}
+
+#[tokio::test]
+async fn test_get_form_serializes_controls_into_query() {
+    let engine = Zver::new();
+    let page = load(
+        &engine,
+        "zver_navigation_get.html",
+        r#"<form id="search" action="results.html#top">
+               <input id="q" name="q" value="rust lang">
+               <input type="checkbox" name="safe" checked>
+               <input type="checkbox" name="images" value="yes">
+               <input type="radio" name="lang" value="en">
+               <input type="radio" name="lang" value="ru" checked>
+               <input name="disabled" value="x" disabled>
+               <fieldset disabled><input name="fieldset" value="x"></fieldset>
+               <input type="hidden" name="page" value="2">
+               <select name="sort"><option>relevance</option><option value="date" selected>Date</option></select>
+               <textarea name="note">a&amp;b</textarea>
+               <input type="submit" name="other" value="Other">
+               <button id="go" name="go" value="1">Go</button>
+           </form>
+           <input form="search" name="outside" value="yes">"#,
+    )
+    .await;
+
+    let request = click(&engine, "#go").await.navigation.unwrap();
+    assert_eq!(request.target, NavigationTarget::Current);
+    assert!(request.body.is_none());
+    assert_eq!(
+        request.url,
+        page.join(
+            "results.html?q=rust+lang&safe=on&lang=ru&page=2&sort=date&note=a%26b&go=1&outside=yes#top"
+        )
+        .unwrap()
+    );
+
+    // Enter в поле отправляет форму от имени первой кнопки submit
+    let (field, form) = {
+        let dom = engine.dom.read().await;
+        (dom.select_ids("#q")[0], dom.select_ids("#search")[0])
+    };
+    click(&engine, "#q").await;
+    assert_eq!(engine.focused_element(), Some(field));
+    let outcome = engine
+        .dispatch_input(InputEvent::KeyDown {
+            key: "Enter".to_string(),
+            code: "Enter".to_string(),
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    let implicit = outcome.navigation.expect("Enter отправляет форму");
+    assert_eq!(
+        implicit.url.query(),
+        Some("q=rust+lang&safe=on&lang=ru&page=2&sort=date&note=a%26b&other=Other&outside=yes")
+    );
+
+    let entries = form_data(&*engine.dom.read().await, form, None);
+    assert!(
+        !entries
+            .iter()
+            .any(|(name, _)| name == "go" || name == "other")
+    );
+}
+
+#[tokio::test]
+async fn test_form_attribute_resolves_first_connected_form_in_tree_order() {
+    let engine = Zver::new();
+    load(
+        &engine,
+        "zver_navigation_form_owner.html",
+        r#"<div id="slot"></div>
+           <form id="target" action="second.html"></form>
+           <input id="field" form="target" name="q" value="1">"#,
+    )
+    .await;
+
+    let mut dom = engine.dom.write().await;
+    let field = dom.select_ids("#field")[0];
+    let slot = dom.select_ids("#slot")[0];
+    let second = dom.select_ids("form")[0];
+
+    // Отсоединённая форма с тем же id владельцем не становится
+    let detached = dom.create_element("form").unwrap();
+    dom.set_attribute(detached, "id", "target").unwrap();
+    // Вставленная позже, но раньше по дереву — выигрывает
+    let first = dom.create_element("form").unwrap();
+    dom.set_attribute(first, "id", "target").unwrap();
+    dom.append_child(slot, first).unwrap();
+
+    assert_eq!(form_owner(&dom, field), Some(first));
+
+    dom.remove_child(slot, first).unwrap();
+    assert_eq!(form_owner(&dom, field), Some(second));
+}
+
+#[tokio::test]
+async fn test_submit_event_can_cancel_submission() {
+    let engine = Zver::new();
+    load(
+        &engine,
+        "zver_navigation_cancel.html",
+        r#"<form id="form" action="https://example.com/submit" method="post" target="_blank">
+               <input name="q" value="1">
+               <button id="send">Send</button>
+               <button id="plain" type="button">Plain</button>
+           </form>
+           <script>
+               var submits = 0;
+               var block = true;
+               document.getElementById('form').addEventListener('submit', function(e) {
+                   submits++;
+                   if (block) e.preventDefault();
+               });
+           </script>"#,
+    )
+    .await;
+
+    let outcome = click(&engine, "#send").await;
+    assert!(!outcome.default_prevented, "click не отменён");
+    assert_eq!(outcome.navigation, None, "preventDefault() у submit");
+    assert!(matches!(eval(&engine, "submits").await, JSValue::Number(n) if n == 1.0));
+
+    assert_eq!(click(&engine, "#plain").await.navigation, None);
+    assert!(matches!(eval(&engine, "submits").await, JSValue::Number(n) if n == 1.0));
+
+    eval(&engine, "block = false").await;
+    let request = click(&engine, "#send").await.navigation.unwrap();
+    assert_eq!(request.target, NavigationTarget::Blank);
+    assert_eq!(request.url.as_str(), "https://example.com/submit");
+    let body = request.body.unwrap();
+    assert_eq!(body.content_type, "application/x-www-form-urlencoded");
+    assert_eq!(body.data, b"q=1");
+}
+
+/// Принимает один запрос и отвечает страницей; возвращает заголовки и тело запроса
+async fn serve_once(listener: TcpListener, page: &'static str) -> (String, Vec<u8>) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let header_end = loop {
+        if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => panic!("запрос оборвался"),
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+    let length: usize = head
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .map_or(0, |value| value.trim().parse().unwrap());
+    while request.len() < header_end + length {
+        match stream.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => request.extend_from_slice(&buffer[..read]),
+        }
+    }
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: text/html\r\nContent-Length: {}\r\n\r\n{page}",
+        page.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    (head, request[header_end..].to_vec())
+}
+
+#[tokio::test]
+async fn test_post_form_goes_through_network_engine() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let action = format!("http://{}/submit", listener.local_addr().unwrap());
+    let server = tokio::spawn(serve_once(
+        listener,
+        "<html><body><p id='done'>ok</p></body></html>",
+    ));
+
+    let engine = Zver::new();
+    *engine.network.write().await = NetworkEngine::with_client(
+        NetworkConfig::default()
+            .client_builder()
+            .no_proxy()
+            .build()
+            .unwrap(),
+    );
+    load(
+        &engine,
+        "zver_navigation_post.html",
+        &format!(
+            r#"<form action="{action}" method="post" enctype="multipart/form-data">
+                   <input name="title" value="Привет">
+                   <textarea name="text">line 1
+line 2</textarea>
+                   <input type="submit" id="send">
+               </form>"#
+        ),
+    )
+    .await;
+
+    let request = click(&engine, "#send").await.navigation.unwrap();
+    let body = request.body.clone().unwrap();
+    let boundary = body
+        .content_type
+        .strip_prefix("multipart/form-data; boundary=")
+        .expect("multipart с границей")
+        .to_string();
+    engine.navigate(&request).await.unwrap();
+
+    let (head, received) = server.await.unwrap();
+    assert!(head.starts_with("post /submit "), "{head}");
+    assert!(
+        head.contains(&format!(
+            "content-type: multipart/form-data; boundary={}",
+            boundary.to_lowercase()
+        )),
+        "{head}"
+    );
+    let entries = vec![
+        ("title".to_string(), "Привет".to_string()),
+        ("text".to_string(), "line 1\nline 2".to_string()),
+    ];
+    assert_eq!(received, encode_multipart(&entries, &boundary));
+    assert_eq!(
+        String::from_utf8(received).unwrap(),
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nПривет\r\n\
+             --{boundary}\r\nContent-Disposition: form-data; name=\"text\"\r\n\r\nline 1\r\nline 2\r\n\
+             --{boundary}--\r\n"
+        )
+    );
+
+    let dom = engine.dom.read().await;
+    assert_eq!(dom.url().as_str(), action);
+    assert_eq!(dom.select_ids("#done").len(), 1);
+    let log = engine.network.read().await.logs().last().cloned().unwrap();
+    assert_eq!(log.url, action);
+    assert_eq!(log.status_code, Some(200));
+}
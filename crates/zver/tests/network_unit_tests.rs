@@ -1,9 +1,10 @@
 use std::fs;
 /// Unit tests for Network module
 /// Tests cover: caching, timeouts, fallback, file:// protocol, error handling
-use zver::network::NetworkEngine;
+use zver::network::{NetworkEngine, Url};
 
 #[tokio::test]
+#[allow(clippy::assertions_on_constants)]
 async fn test_network_engine_creation() {
     let _engine = NetworkEngine::new();
     // Should create successfully without panic
@@ -257,6 +258,46 @@ async fn test_large_file() {
     let _ = fs::remove_file(test_file);
 }
 
+#[tokio::test]
+async fn test_fetch_many_keeps_order_and_logs() {
+    let mut engine = NetworkEngine::new();
+
+    let temp_dir = std::env::temp_dir();
+    let first = temp_dir.join("zver_test_many_1.js");
+    let second = temp_dir.join("zver_test_many_2.js");
+    fs::write(&first, "first").unwrap();
+    fs::write(&second, "second").unwrap();
+
+    let urls = vec![
+        Url::from_file_path(&second).unwrap(),
+        Url::from_file_path(temp_dir.join("zver_test_many_missing.js")).unwrap(),
+        Url::from_file_path(&first).unwrap(),
+    ];
+    let results: Vec<Result<String, String>> = engine
+        .fetch_many(&urls)
+        .await
+        .into_iter()
+        .map(|result| {
+            result
+                .map(|response| response.text())
+                .map_err(|err| err.to_string())
+        })
+        .collect();
+
+    assert_eq!(results[0].as_deref(), Ok("second"));
+    assert!(results[1].is_err(), "Missing file should fail on its own");
+    assert_eq!(results[2].as_deref(), Ok("first"));
+    assert_eq!(engine.logs().len(), 3, "Every request should be logged");
+
+    // Успешные ответы попадают в общий кэш
+    fs::write(&first, "changed").unwrap();
+    assert_eq!(engine.fetch_url(&urls[2]).await.unwrap(), "first");
+
+    // Cleanup
+    let _ = fs::remove_file(first);
+    let _ = fs::remove_file(second);
+}
+
 // Note: HTTP/HTTPS tests are commented out to avoid external dependencies
 // In a real test environment, you would use a mock HTTP server or httpbin.org
 
@@ -0,0 +1,181 @@
+//! Тесты reftest-харнесса: манифест, сравнение с допуском, файлы различий
+//! и прогон reftest-ов layout из `tests/reftests`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use zver::css::color::Color;
+use zver::reftest::{
+    Manifest, ReftestError, ReftestRunner, Relation, Tolerance, compare, diff_image, load_png,
+};
+use zver::render::Framebuffer;
+
+fn solid(width: u32, height: u32, color: Color) -> Framebuffer {
+    let mut frame = Framebuffer::new(width, height);
+    frame.clear(color);
+    frame
+}
+
+#[test]
+fn test_manifest_parses_relations_fuzzy_and_comments() {
+    let manifest = Manifest::parse(
+        "# comment\n\
+         == a.html a-ref.html\n\
+         \n\
+         fuzzy(0-3,0-40) != b.html b.png # trailing\n\
+         fuzzy(2,5) == c.html c-ref.html\n",
+        Path::new("cases"),
+    )
+    .unwrap();
+
+    let entries = &manifest.entries;
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].relation, Relation::Match);
+    assert_eq!(entries[0].test, Path::new("cases/a.html"));
+    assert_eq!(entries[0].reference, Path::new("cases/a-ref.html"));
+    assert_eq!(entries[0].tolerance, Tolerance::EXACT);
+    assert!(!entries[0].has_image_reference());
+
+    assert_eq!(entries[1].relation, Relation::Mismatch);
+    assert_eq!(entries[1].tolerance, Tolerance::new(3, 40));
+    assert!(entries[1].has_image_reference());
+    assert_eq!(entries[1].line, 4);
+    assert_eq!(entries[2].tolerance, Tolerance::new(2, 5));
+    assert_eq!(entries[2].name(), "c");
+
+    for (source, message) in [
+        ("== a.html", "expected a test and a reference"),
+        ("~= a.html b.html", "expected == or !=, got '~='"),
+        ("fuzzy(300,1) == a.html b.html", "invalid 'fuzzy(300,1)'"),
+        ("== a.html b.html c.html", "unexpected 'c.html'"),
+    ] {
+        let error = Manifest::parse(&format!("# header\n{source}"), Path::new("")).unwrap_err();
+        assert!(
+            matches!(&error, ReftestError::Manifest { line: 2, message: m, .. } if m.contains(message)),
+            "{source}: {error}"
+        );
+    }
+}
+
+#[test]
+fn test_compare_counts_pixels_beyond_tolerance() {
+    let expected = solid(4, 4, Color::rgb(100, 100, 100));
+    let mut actual = expected.clone();
+    actual.fill_rect(0.0, 0.0, 1.0, 1.0, Color::rgb(102, 100, 100));
+    actual.fill_rect(3.0, 3.0, 1.0, 1.0, Color::rgb(0, 100, 100));
+
+    let exact = compare(&actual, &expected, Tolerance::EXACT);
+    assert_eq!((exact.differing_pixels, exact.max_difference), (2, 100));
+    assert!(exact.same_size);
+    assert!(!exact.matches(Tolerance::EXACT));
+    assert!(exact.matches(Tolerance::new(0, 2)));
+
+    // Небольшая разница каналов прощается, большая — нет
+    let fuzzy = compare(&actual, &expected, Tolerance::new(2, 0));
+    assert_eq!(fuzzy.differing_pixels, 1);
+    assert!(compare(&expected, &expected, Tolerance::EXACT).matches(Tolerance::EXACT));
+
+    let diff = diff_image(&actual, &expected, Tolerance::new(2, 0));
+    assert_eq!(diff.pixel(3, 3), Some(Color::rgb(255, 0, 0)));
+    assert_ne!(diff.pixel(0, 0), Some(Color::rgb(255, 0, 0)));
+
+    // Кадры разного размера не совпадают при любом допуске
+    let larger = solid(5, 4, Color::rgb(100, 100, 100));
+    let comparison = compare(&larger, &expected, Tolerance::new(255, 100));
+    assert!(!comparison.same_size);
+    assert_eq!(comparison.differing_pixels, 4);
+    assert!(!comparison.matches(Tolerance::new(255, 100)));
+    assert_eq!(diff_image(&larger, &expected, Tolerance::EXACT).width(), 5);
+}
+
+fn write_case(dir: &Path, files: &[(&str, &str)]) {
+    fs::create_dir_all(dir).unwrap();
+    for (name, contents) in files {
+        fs::write(dir.join(name), contents).unwrap();
+    }
+}
+
+fn page(color: &str) -> String {
+    format!(
+        "<html><head><style>body {{ margin: 0; }} \
+         #box {{ width: 40px; height: 20px; background-color: {color}; }}</style></head>\
+         <body><div id=\"box\"></div></body></html>"
+    )
+}
+
+#[tokio::test]
+async fn test_failed_reftest_writes_diff_images() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reftest-failure");
+    let _ = fs::remove_dir_all(&dir);
+    let red = page("#ff0000");
+    write_case(
+        &dir,
+        &[
+            ("red.html", &red),
+            ("blue.html", &page("#0000ff")),
+            (
+                "reftest.list",
+                "== red.html blue.html\n!= red.html blue.html\n== red.html missing.png\n",
+            ),
+        ],
+    );
+    let output = dir.join("output");
+    let runner = ReftestRunner::new()
+        .builder(zver::Zver::builder().viewport(100.0, 50.0))
+        .output_dir(&output);
+    let report = runner
+        .run(&Manifest::load(dir.join("reftest.list")).unwrap())
+        .await;
+
+    let [failed, passed, missing] = &report.results[..] else {
+        panic!("{report}");
+    };
+    assert!(!failed.passed());
+    assert_eq!(failed.outcome.as_ref().unwrap().differing_pixels, 40 * 20);
+    let names: Vec<_> = failed
+        .artifacts
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(
+        names,
+        ["red.actual.png", "red.expected.png", "red.diff.png"]
+    );
+    let diff = load_png(&output.join("red.diff.png")).unwrap();
+    assert_eq!((diff.width(), diff.height()), (100, 50));
+    assert_eq!(diff.pixel(10, 10), Some(Color::rgb(255, 0, 0)));
+    assert_ne!(diff.pixel(60, 30), Some(Color::rgb(255, 0, 0)));
+
+    assert!(passed.passed());
+    assert!(passed.artifacts.is_empty());
+
+    // Отсутствующий PNG-эталон — ошибка, пока эталоны не обновляют явно
+    assert!(matches!(missing.outcome, Err(ReftestError::Io { .. })));
+    assert!(!report.passed());
+    assert_eq!(report.failures().count(), 2);
+    assert!(report.to_string().ends_with("1 passed, 2 failed"));
+
+    let runner = runner.update_images(true);
+    let manifest = Manifest::parse("== red.html missing.png", &dir).unwrap();
+    assert!(runner.run(&manifest).await.passed());
+    let golden: PathBuf = dir.join("missing.png");
+    assert_eq!(
+        load_png(&golden).unwrap().pixel(5, 5),
+        Some(Color::rgb(255, 0, 0))
+    );
+}
+
+#[tokio::test]
+async fn test_layout_reftests() {
+    let manifest = Manifest::load(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/reftests/reftest.list"
+    ))
+    .unwrap();
+    let report = ReftestRunner::new()
+        .builder(zver::Zver::builder().viewport(320.0, 240.0))
+        .output_dir(Path::new(env!("CARGO_TARGET_TMPDIR")).join("reftests"))
+        .update_images(std::env::var_os("ZVER_UPDATE_REFTESTS").is_some())
+        .run(&manifest)
+        .await;
+    assert!(report.passed(), "{report}");
+}
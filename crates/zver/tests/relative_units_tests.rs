@@ -0,0 +1,223 @@
+//! Тесты относительных единиц: em, rem, %, единицы viewport, ch и ex
+//! вычисляются при наследовании от шрифтов родителя, корня и размеров viewport
+
+use std::collections::HashMap;
+
+use zver::css::StyleEngine;
+use zver::css::fonts::{FontFace, LoadedFont};
+use zver::dom::Document;
+use zver::layout::{FontStyle, FontWeight, LayoutEngine, LayoutResult, TextMeasureContext};
+
+struct Laid {
+    doc: Document,
+    layout: LayoutEngine,
+    results: HashMap<usize, LayoutResult>,
+}
+
+impl Laid {
+    fn node(&self, id: &str) -> LayoutResult {
+        self.results[&self.doc.get_element_by_id(id).unwrap()]
+    }
+
+    fn font_size(&self, id: &str) -> f32 {
+        let node_id = self.doc.get_element_by_id(id).unwrap();
+        self.layout.resolved_styles()[&node_id].font_size
+    }
+}
+
+/// Шрифт из assets под именем `family`
+fn asset_font(file: &str, family: &str) -> LoadedFont {
+    let path = format!("{}/../../assets/fonts/{file}", env!("CARGO_MANIFEST_DIR"));
+    let bytes = std::fs::read(path).unwrap();
+    let mut font = LoadedFont::new(FontFace::new(family.to_string()));
+    font.load_from_bytes(&bytes).unwrap();
+    font
+}
+
+async fn lay_out(html: &str, css: &str, viewport: (f32, f32)) -> Laid {
+    lay_out_with_fonts(html, css, viewport, Vec::new()).await
+}
+
+async fn lay_out_with_fonts(
+    html: &str,
+    css: &str,
+    viewport: (f32, f32),
+    fonts: Vec<LoadedFont>,
+) -> Laid {
+    let mut doc = Document::new();
+    doc.parse_html(html).await.unwrap();
+    let mut engine = StyleEngine::new();
+    engine.parse_css(css).unwrap();
+    engine.apply_styles(&doc).unwrap();
+    let mut layout = LayoutEngine::new(viewport.0, viewport.1);
+    layout.set_fonts(fonts);
+    let results =
+        layout.compute_layout(&doc, &engine.computed_styles, &engine.pseudo_element_styles);
+    Laid {
+        doc,
+        layout,
+        results,
+    }
+}
+
+#[tokio::test]
+async fn test_font_size_compounds_through_inheritance() {
+    let laid = lay_out(
+        r#"<html><body>
+            <div id="a"><div id="b"><div id="c"><span id="d">text</span></div></div></div>
+            <div id="rem"><p id="percent">text</p></div>
+            <h1 id="heading"><span id="inside">text</span></h1>
+            <h1 id="styled">text</h1>
+        </body></html>"#,
+        r#"
+            html { font-size: 10px; }
+            #a { font-size: 20px; }
+            #b { font-size: 1.5em; }
+            #c { font-size: 1.5em; }
+            #rem { font-size: 2.4rem; }
+            #percent { font-size: 50%; }
+            #inside { font-size: smaller; }
+            #styled { font-size: 1.2rem; }
+        "#,
+        (800.0, 600.0),
+    )
+    .await;
+
+    assert_eq!(laid.font_size("b"), 30.0);
+    assert_eq!(laid.font_size("c"), 45.0);
+    // Без собственного font-size наследуется вычисленное значение
+    assert_eq!(laid.font_size("d"), 45.0);
+    assert_eq!(laid.font_size("rem"), 24.0);
+    assert_eq!(laid.font_size("percent"), 12.0);
+    assert_eq!(laid.font_size("heading"), 32.0);
+    assert!((laid.font_size("inside") - 32.0 / 1.2).abs() < 0.01);
+    // Заданный автором размер сильнее умолчания тега
+    assert_eq!(laid.font_size("styled"), 12.0);
+}
+
+#[tokio::test]
+async fn test_lengths_use_element_root_and_viewport_sizes() {
+    let laid = lay_out(
+        r#"<html><body>
+            <div id="em"></div>
+            <div id="viewport"></div>
+            <div id="mixed"></div>
+            <div id="box"></div>
+        </body></html>"#,
+        r#"
+            html { font-size: 8px; }
+            body { margin: 0; font-size: 20px; }
+            #em { font-size: 10px; width: 10em; height: 2rem; }
+            #viewport { width: 50vw; height: 10vh; }
+            #mixed { width: calc(100% - 5em); height: max(10vmin, 1vmax); }
+            #box { width: 1in; height: 12pt; margin-left: 2em; padding-top: 1rem; }
+        "#,
+        (800.0, 400.0),
+    )
+    .await;
+
+    let em = laid.node("em");
+    assert_eq!(em.width, 100.0);
+    assert_eq!(em.height, 16.0);
+
+    let viewport = laid.node("viewport");
+    assert_eq!(viewport.width, 400.0);
+    assert_eq!(viewport.height, 40.0);
+
+    let mixed = laid.node("mixed");
+    // 5em от шрифта body (20px), процент — от ширины body при layout
+    assert_eq!(mixed.width, 700.0);
+    assert_eq!(mixed.height, 40.0);
+
+    let boxed = laid.node("box");
+    assert_eq!(boxed.width, 96.0);
+    assert_eq!(boxed.height, 16.0);
+    assert_eq!(boxed.x, 40.0);
+    assert_eq!(boxed.content_y - boxed.y, 8.0);
+}
+
+#[tokio::test]
+async fn test_gap_and_border_widths_use_relative_units() {
+    let laid = lay_out(
+        r#"<html><body>
+            <div id="row"><div id="first"></div><div id="second"></div></div>
+            <div id="shorthand"></div>
+            <div id="side"></div>
+            <div id="mixed"></div>
+        </body></html>"#,
+        r#"
+            html { font-size: 10px; }
+            body { margin: 0; font-size: 20px; }
+            #row { display: flex; gap: 1rem; }
+            #row div { width: 30px; height: 10px; }
+            #shorthand { border: 0.5em solid; }
+            #side { border-left: 1rem solid red; }
+            #mixed { border-width: calc(1px + 1em); }
+        "#,
+        (800.0, 600.0),
+    )
+    .await;
+
+    assert_eq!(laid.node("second").x - laid.node("first").x, 40.0);
+
+    let shorthand = laid.node("shorthand");
+    assert_eq!(shorthand.content_x - shorthand.x, 10.0);
+    assert_eq!(shorthand.height, 20.0);
+
+    let side = laid.node("side");
+    assert_eq!(side.content_x - side.x, 10.0);
+    assert_eq!(side.height, 0.0);
+
+    let mixed = laid.node("mixed");
+    assert_eq!(mixed.content_x - mixed.x, 21.0);
+    assert_eq!(mixed.height, 42.0);
+}
+
+#[tokio::test]
+async fn test_ch_and_ex_use_font_metrics() {
+    let html = r#"<html><body><div id="ch"></div><div id="ex"></div></body></html>"#;
+    let css = r#"
+        #ch { font-size: 20px; width: 10ch; height: 1px; }
+        #ex { font-size: 20px; width: 4ex; height: 1px; }
+    "#;
+    let font = |family: &str| TextMeasureContext {
+        content: String::new(),
+        font_family: Some(family.to_string()),
+        font_size: 20.0,
+        font_weight: FontWeight::Normal,
+        font_style: FontStyle::Normal,
+    };
+
+    // DejaVu Sans: 2048 единиц на em, advance `0` — 1303, высота `x` — 1120
+    let css_latin = format!("body {{ font-family: Latin; }} {css}");
+    let laid = lay_out_with_fonts(
+        html,
+        &css_latin,
+        (800.0, 600.0),
+        vec![asset_font("DejaVuSans.ttf", "Latin")],
+    )
+    .await;
+    assert_eq!(
+        laid.layout.text_measurer().font_units(&font("Latin")),
+        (12.724609, 10.9375)
+    );
+    // 127.25px и 43.75px, округлённые layout до целых пикселей
+    assert_eq!(laid.node("ch").width, 127.0);
+    assert_eq!(laid.node("ex").width, 44.0);
+
+    // В шрифте иконок нет цифр, а `x` — пустой глиф лигатуры: обе единицы 0.5em
+    let css_icons = format!("body {{ font-family: Icons; }} {css}");
+    let laid = lay_out_with_fonts(
+        html,
+        &css_icons,
+        (800.0, 600.0),
+        vec![asset_font("Phosphor-Regular.ttf", "Icons")],
+    )
+    .await;
+    assert_eq!(
+        laid.layout.text_measurer().font_units(&font("Icons")),
+        (10.0, 10.0)
+    );
+    assert_eq!(laid.node("ch").width, 100.0);
+    assert_eq!(laid.node("ex").width, 40.0);
+}
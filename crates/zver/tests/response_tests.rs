@@ -0,0 +1,179 @@
+//! Тесты `Response`: бинарные тела, метаданные, перенаправления и кодировки
+
+use std::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use zver::network::{CacheStatus, NetworkEngine, Response, Url};
+
+/// HTTP/1.1-сервер, отвечающий `respond(путь)` на каждый запрос
+async fn serve<F>(respond: F) -> String
+where
+    F: Fn(&str) -> Vec<u8> + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => request.extend_from_slice(&buffer[..read]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request).into_owned();
+            let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+            let _ = stream.write_all(&respond(&path)).await;
+            let _ = stream.shutdown().await;
+        }
+    });
+    base
+}
+
+fn http_response(status: &str, headers: &[&str], body: &[u8]) -> Vec<u8> {
+    let mut head = format!("HTTP/1.1 {status}\r\nConnection: close\r\n");
+    for header in headers {
+        head.push_str(header);
+        head.push_str("\r\n");
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+fn engine() -> NetworkEngine {
+    NetworkEngine::with_client(reqwest::Client::builder().no_proxy().build().unwrap())
+}
+
+fn local(content_type: Option<&str>, body: &[u8]) -> Response {
+    Response::local(Url::about_blank(), content_type, body.to_vec())
+}
+
+#[test]
+fn test_charset_detection_order() {
+    // «Привет» в windows-1251
+    let cp1251 = [0xcf, 0xf0, 0xe8, 0xe2, 0xe5, 0xf2];
+
+    let header = local(Some("text/plain; charset=\"windows-1251\""), &cp1251);
+    assert_eq!(header.text(), "Привет");
+
+    let mut meta = b"<html><head><meta charset=windows-1251></head><body>".to_vec();
+    meta.extend_from_slice(&cp1251);
+    assert!(
+        local(Some("text/html"), &meta)
+            .text()
+            .ends_with("<body>Привет")
+    );
+
+    let mut http_equiv =
+        b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=windows-1251\">".to_vec();
+    http_equiv.extend_from_slice(&cp1251);
+    assert!(local(None, &http_equiv).text().ends_with("Привет"));
+
+    let mut css = b"@charset \"windows-1251\";p::before{content:\"".to_vec();
+    css.extend_from_slice(&cp1251);
+    assert!(local(Some("text/css"), &css).text().ends_with("Привет"));
+
+    // BOM важнее заголовка
+    let utf16: Vec<u8> = [0xff, 0xfe]
+        .into_iter()
+        .chain("Привет".encode_utf16().flat_map(u16::to_le_bytes))
+        .collect();
+    let bom = local(Some("text/plain; charset=windows-1251"), &utf16);
+    assert_eq!(bom.encoding(), encoding_rs::UTF_16LE);
+    assert_eq!(bom.text(), "Привет");
+
+    assert_eq!(local(None, "ёж".as_bytes()).text(), "ёж");
+}
+
+#[tokio::test]
+async fn test_binary_file_keeps_bytes_and_type() {
+    let path = std::env::temp_dir().join("zver_response_image.png");
+    let bytes = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0xff, 0x00];
+    fs::write(&path, bytes).unwrap();
+
+    let mut network = NetworkEngine::new();
+    let url = Url::from_file_path(&path).unwrap();
+    let response = network.fetch_response(&url).await.unwrap();
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(response.body, bytes);
+    assert_eq!(response.status, 200);
+    assert_eq!(response.mime_type().as_deref(), Some("image/png"));
+    assert_eq!(response.url, url);
+
+    let log = &network.logs()[0];
+    assert_eq!(log.status_code, Some(200));
+    assert_eq!(log.content_type.as_deref(), Some("image/png"));
+    assert_eq!(log.size, bytes.len());
+}
+
+#[tokio::test]
+async fn test_http_response_metadata_and_redirects() {
+    let base = serve(|path| match path {
+        "/old" => http_response("302 Found", &["Location: /new"], b""),
+        "/new" => http_response(
+            "200 OK",
+            &["Content-Type: text/html; charset=koi8-r", "X-Test: yes"],
+            // «Да» в KOI8-R
+            &[0xe4, 0xc1],
+        ),
+        _ => http_response("404 Not Found", &["Content-Type: text/plain"], b"Not here"),
+    })
+    .await;
+
+    let mut network = engine();
+    let response = network
+        .fetch_response(&Url::parse(&format!("{base}/old")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.url.as_str(), format!("{base}/new"));
+    assert_eq!(response.status, 200);
+    assert_eq!(response.header("x-test"), Some("yes"));
+    assert_eq!(response.mime_type().as_deref(), Some("text/html"));
+    assert_eq!(response.text(), "Да");
+    assert_eq!(response.cache, CacheStatus::Bypass);
+
+    // Страница ошибки доходит до вызывающего вместе со статусом и телом
+    let missing = Url::parse(&format!("{base}/missing")).unwrap();
+    let not_found = network.fetch_response(&missing).await.unwrap();
+    assert_eq!(not_found.status, 404);
+    assert_eq!(not_found.text(), "Not here");
+    let log = network.logs().last().unwrap();
+    assert_eq!(log.status_code, Some(404));
+    assert_eq!(log.status, "HTTP 404");
+
+    // Текстовый fetch по-прежнему считает не-2xx ошибкой
+    assert!(network.fetch(missing.as_str()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_compressed_body_is_decoded() {
+    // gzip от «Сжатый ответ» в UTF-8
+    const GZIPPED: &[u8] = &[
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x01, 0x17, 0x00, 0xe8, 0xff,
+        0xd0, 0xa1, 0xd0, 0xb6, 0xd0, 0xb0, 0xd1, 0x82, 0xd1, 0x8b, 0xd0, 0xb9, 0x20, 0xd0, 0xbe,
+        0xd1, 0x82, 0xd0, 0xb2, 0xd0, 0xb5, 0xd1, 0x82, 0x4d, 0xcb, 0x4c, 0xff, 0x17, 0x00, 0x00,
+        0x00,
+    ];
+    let base = serve(|_| {
+        http_response(
+            "200 OK",
+            &[
+                "Content-Type: text/plain; charset=utf-8",
+                "Content-Encoding: gzip",
+            ],
+            GZIPPED,
+        )
+    })
+    .await;
+
+    let mut network = engine();
+    let response = network
+        .fetch_response(&Url::parse(&format!("{base}/text")).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.text(), "Сжатый ответ");
+}
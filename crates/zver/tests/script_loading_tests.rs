@@ -0,0 +1,216 @@
+//! Тесты загрузки внешних `<script src>` и порядка defer/async/module
+
+use std::fs;
+use std::path::PathBuf;
+use zver::Zver;
+use zver::js::JSValue;
+use zver::js::scripts::{ScriptKind, ScriptTiming, collect_scripts, execution_order};
+
+fn site(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zver_scripts_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+async fn load(name: &str, files: &[(&str, &str)]) -> Zver {
+    let dir = site(name, files);
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", dir.join("index.html").display()))
+        .await
+        .expect("страница должна загрузиться");
+    let _ = fs::remove_dir_all(dir);
+    engine
+}
+
+async fn eval_string(engine: &Zver, code: &str) -> String {
+    match engine.js.write().await.execute(code) {
+        Ok(JSValue::String(value)) => value,
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_collect_scripts_classifies_timing() {
+    let mut dom = zver::dom::Document::new();
+    dom.parse_html(
+        r#"<html><head>
+            <script src="a.js" defer></script>
+            <script defer>inline()</script>
+            <script type="module">mod()</script>
+            <script src="b.js" async></script>
+            <script type="application/json">{}</script>
+            <script nomodule src="legacy.js"></script>
+        </head></html>"#,
+    )
+    .await
+    .unwrap();
+
+    let timings: Vec<(ScriptKind, ScriptTiming)> = execution_order(collect_scripts(&dom))
+        .into_iter()
+        .map(|script| (script.kind, script.timing))
+        .collect();
+    assert_eq!(
+        timings,
+        vec![
+            // defer у встроенного скрипта не действует
+            (ScriptKind::Classic, ScriptTiming::Blocking),
+            (ScriptKind::Classic, ScriptTiming::Defer),
+            (ScriptKind::Module, ScriptTiming::Defer),
+            (ScriptKind::Classic, ScriptTiming::Async),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_external_scripts_run_in_html_order() {
+    let engine = load(
+        "order",
+        &[
+            (
+                "index.html",
+                r#"<html><head>
+                    <script>var order = ['inline'];</script>
+                    <script src="js/deferred.js" defer></script>
+                    <script src="js/async.js" async></script>
+                    <script type="module">order.push('module');</script>
+                    <script src="js/blocking.js"></script>
+                    <script>order.push('after-blocking');</script>
+                </head><body></body></html>"#,
+            ),
+            ("js/blocking.js", "order.push('blocking');"),
+            ("js/deferred.js", "order.push('defer');"),
+            ("js/async.js", "order.push('async');"),
+        ],
+    )
+    .await;
+
+    assert_eq!(
+        eval_string(&engine, "order.join(',')").await,
+        "inline,blocking,after-blocking,defer,module,async"
+    );
+}
+
+#[tokio::test]
+async fn test_script_error_does_not_stop_others() {
+    let engine = load(
+        "errors",
+        &[
+            (
+                "index.html",
+                r#"<html><head>
+                    <script>var ran = [];</script>
+                    <script src="broken.js"></script>
+                    <script src="missing.js"></script>
+                    <script>ran.push('second'); throw new Error('boom');</script>
+                    <script src="ok.js"></script>
+                </head><body></body></html>"#,
+            ),
+            ("broken.js", "ran.push('broken'; this is not javascript"),
+            ("ok.js", "ran.push('ok');"),
+        ],
+    )
+    .await;
+
+    assert_eq!(eval_string(&engine, "ran.join(',')").await, "second,ok");
+}
+
+#[tokio::test]
+async fn test_module_scope_is_isolated() {
+    let engine = load(
+        "module_scope",
+        &[(
+            "index.html",
+            r#"<html><head>
+                <script type="module">const hidden = 1; globalThis.exported = 'visible';</script>
+                <script type="module">undeclared = 1;</script>
+            </head><body></body></html>"#,
+        )],
+    )
+    .await;
+
+    assert_eq!(
+        eval_string(
+            &engine,
+            "typeof hidden + ',' + exported + ',' + typeof undeclared"
+        )
+        .await,
+        // Модули исполняются в strict mode: присваивание необъявленной переменной — ошибка
+        "undefined,visible,undefined"
+    );
+}
+
+#[tokio::test]
+async fn test_module_imports_sibling_files() {
+    let engine = load(
+        "module_imports",
+        &[
+            (
+                "index.html",
+                r#"<html><head>
+                    <script type="module">
+                        import { greet } from './lib/greet.js';
+                        globalThis.inline = greet('inline');
+                    </script>
+                    <script type="module" src="lib/main.js"></script>
+                </head><body></body></html>"#,
+            ),
+            (
+                "lib/main.js",
+                "import { greet } from './greet.js';\n\
+                 import answer from \"../data/answer.js\";\n\
+                 globalThis.external = greet('external') + answer + ',' + import.meta.url.endsWith('/lib/main.js');",
+            ),
+            (
+                "lib/greet.js",
+                "import { suffix } from './suffix.js';\nexport const greet = (name) => 'hi ' + name + suffix;",
+            ),
+            ("lib/suffix.js", "export const suffix = '!';"),
+            ("data/answer.js", "export default 42;"),
+        ],
+    )
+    .await;
+
+    assert_eq!(
+        eval_string(&engine, "inline + ',' + external").await,
+        "hi inline!,hi external!42,true"
+    );
+}
+
+#[test]
+fn test_module_specifiers_and_resolution() {
+    use zver::js::modules::{module_specifiers, resolve_specifier};
+    use zver::network::Url;
+
+    let code = r#"
+        import a from "./a.js";
+        import './side.js';
+        export { b } from '../b.js';
+        const lazy = import( "./lazy.js" );
+        const imported = 1;
+    "#;
+    let mut specifiers = module_specifiers(code);
+    specifiers.sort();
+    assert_eq!(specifiers, ["../b.js", "./a.js", "./lazy.js", "./side.js"]);
+
+    let base = Url::parse("https://example.com/app/main.js").unwrap();
+    assert_eq!(
+        resolve_specifier("../lib/x.js", Some(&base))
+            .unwrap()
+            .as_str(),
+        "https://example.com/lib/x.js"
+    );
+    assert_eq!(
+        resolve_specifier("https://cdn.example/y.js", None)
+            .unwrap()
+            .as_str(),
+        "https://cdn.example/y.js"
+    );
+    // «Голые» имена пакетов не разрешаются
+    assert!(resolve_specifier("lodash", Some(&base)).is_none());
+}
@@ -0,0 +1,265 @@
+//! Тесты прокрутки: `overflow`, смещения scroll-контейнеров и viewport,
+//! колесо и клавиши, `scrollTop`/`window.scrollY` и события `scroll`
+
+use std::collections::HashMap;
+use zver::Zver;
+use zver::css::color::Color;
+use zver::input::InputEvent;
+use zver::js::JSValue;
+use zver::js::events::Modifiers;
+use zver::layout::{ComputedStyle, Overflow};
+
+mod common;
+use common::{id_of, layout_of_id, page};
+
+async fn load(name: &str, style: &str, body: &str) -> Zver {
+    common::load(name, &page(style, body)).await
+}
+
+/// Вычисляет код и применяет вызванные им изменения
+async fn eval(engine: &Zver, code: &str) -> JSValue {
+    let value = common::eval(engine, code).await;
+    engine.run_until_idle().await.unwrap();
+    value
+}
+
+fn number(value: JSValue) -> f64 {
+    match value {
+        JSValue::Number(n) => n,
+        other => panic!("ожидалось число, получено {other:?}"),
+    }
+}
+
+fn wheel(delta_y: f32) -> InputEvent {
+    InputEvent::Wheel {
+        x: 10.0,
+        y: 10.0,
+        delta_x: 0.0,
+        delta_y,
+        modifiers: Modifiers::default(),
+    }
+}
+
+#[test]
+fn test_overflow_is_parsed_per_axis() {
+    let style = |pairs: &[(&str, &str)]| {
+        let properties: HashMap<String, String> = pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ComputedStyle::from_css_properties(&properties).overflow
+    };
+
+    let overflow = style(&[("overflow", "hidden scroll")]);
+    assert_eq!(
+        (overflow.x, overflow.y),
+        (Overflow::Hidden, Overflow::Scroll)
+    );
+
+    let overflow = style(&[("overflow", "clip")]);
+    assert_eq!(
+        (overflow.x, overflow.y),
+        (Overflow::Hidden, Overflow::Hidden)
+    );
+
+    // visible рядом с прокручиваемой осью становится auto
+    let overflow = style(&[("overflow-y", "scroll")]);
+    assert_eq!((overflow.x, overflow.y), (Overflow::Auto, Overflow::Scroll));
+
+    let overflow = style(&[("overflow", "auto"), ("overflow-x", "hidden")]);
+    assert_eq!((overflow.x, overflow.y), (Overflow::Hidden, Overflow::Auto));
+
+    assert_eq!(style(&[("overflow", "visible")]).x, Overflow::Visible);
+    assert!(!ComputedStyle::default().is_scroll_container());
+}
+
+#[tokio::test]
+async fn test_container_scroll_shifts_clips_and_fires_scroll() {
+    let engine = load(
+        "zver_scroll_container.html",
+        "#box { width: 200px; height: 100px; overflow: auto; border: 5px solid black; }
+         .row { height: 50px; }
+         #r5 { background-color: #00ff00; }
+         #after { height: 20px; }",
+        r#"<div id="box">
+               <div id="r1" class="row"></div><div id="r2" class="row"></div>
+               <div id="r3" class="row"></div><div id="r4" class="row"></div>
+               <div id="r5" class="row"></div>
+           </div>
+           <div id="after"></div>
+           <script>
+               var boxScrolls = 0;
+               var documentScrolls = 0;
+               document.getElementById('box').addEventListener('scroll', function() { boxScrolls++; });
+               document.addEventListener('scroll', function() { documentScrolls++; });
+           </script>"#,
+    )
+    .await;
+    let (scroll_box, r1, r5, after) = (
+        id_of(&engine, "#box").await,
+        id_of(&engine, "#r1").await,
+        id_of(&engine, "#r5").await,
+        id_of(&engine, "#after").await,
+    );
+
+    let state = engine.scroll.get(scroll_box).expect("#box прокручивается");
+    // Рамка входит в 200x100 (border-box), видимая область — внутри неё
+    assert_eq!((state.client_width, state.client_height), (190.0, 90.0));
+    assert_eq!(state.scroll_height, 250.0);
+    assert_eq!(state.max_scroll_y(), 160.0);
+    assert_eq!(
+        engine.scroll.get(r1),
+        None,
+        "обычный блок не прокручивается"
+    );
+
+    let box_y = layout_of_id(&engine, scroll_box).await.y;
+    let after_y = layout_of_id(&engine, after).await.y;
+    assert_eq!(layout_of_id(&engine, r1).await.y, box_y + 5.0);
+
+    // Пятая строка за нижним краем обрезана
+    let green = Color::rgb(0, 255, 0);
+    let r5_y = layout_of_id(&engine, r5).await.y;
+    assert_eq!(r5_y, box_y + 205.0);
+    let frame = engine.screenshot().await.unwrap();
+    assert_ne!(frame.pixel(50, (r5_y + 10.0) as u32), Some(green));
+
+    assert!(engine.scroll.scroll_to(scroll_box, 0.0, 80.0));
+    assert!(engine.update().await.unwrap());
+    assert_eq!(layout_of_id(&engine, r1).await.y, box_y + 5.0 - 80.0);
+    assert_eq!(
+        layout_of_id(&engine, after).await.y,
+        after_y,
+        "соседи не сдвигаются"
+    );
+    assert_eq!(number(eval(&engine, "boxScrolls").await), 1.0);
+    assert_eq!(
+        number(eval(&engine, "documentScrolls").await),
+        0.0,
+        "scroll элемента не всплывает"
+    );
+
+    // Смещение ограничено содержимым; нижняя строка видна внутри рамки
+    assert!(engine.scroll.scroll_to(scroll_box, 0.0, 1000.0));
+    engine.update().await.unwrap();
+    assert_eq!(engine.scroll.offset(scroll_box), (0.0, 160.0));
+    assert_eq!(layout_of_id(&engine, r5).await.y, box_y + 45.0);
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!(frame.pixel(50, (box_y + 70.0) as u32), Some(green));
+
+    assert_eq!(
+        number(eval(&engine, "document.getElementById('box').scrollTop").await),
+        160.0
+    );
+    assert_eq!(
+        number(eval(&engine, "document.getElementById('box').scrollHeight").await),
+        250.0
+    );
+    eval(&engine, "document.getElementById('box').scrollTop = 10").await;
+    assert_eq!(engine.scroll.offset(scroll_box), (0.0, 10.0));
+    assert_eq!(layout_of_id(&engine, r1).await.y, box_y + 5.0 - 10.0);
+    assert_eq!(number(eval(&engine, "boxScrolls").await), 3.0);
+}
+
+const TALL_PAGE: &str = r#"<div id="top"></div>
+    <div id="tall"><div id="hidden-box"><div id="inner"></div></div></div>
+    <div id="bottom"></div>
+    <script>
+        var log = [];
+        document.addEventListener('scroll', function() { log.push('document:' + window.scrollY); });
+        window.addEventListener('scroll', function() { log.push('window'); });
+    </script>"#;
+
+const TALL_STYLE: &str = "#top { height: 100px; }
+    #tall { height: 2000px; }
+    #bottom { height: 50px; }
+    #hidden-box { height: 50px; overflow: hidden; }
+    #inner { height: 500px; }";
+
+#[tokio::test]
+async fn test_wheel_and_keys_scroll_viewport() {
+    let engine = load("zver_scroll_viewport.html", TALL_STYLE, TALL_PAGE).await;
+    let (tall, hidden_box) = (
+        id_of(&engine, "#tall").await,
+        id_of(&engine, "#hidden-box").await,
+    );
+    let viewport = engine.scroll.viewport().expect("корень прокручивается");
+    let state = engine.scroll.get(viewport).unwrap();
+    assert_eq!((state.client_width, state.client_height), (800.0, 600.0));
+    assert_eq!(state.scroll_height, 2150.0);
+
+    // Колесо над overflow: hidden прокручивает не его, а страницу
+    engine.dispatch_input(wheel(300.0)).await.unwrap();
+    assert_eq!(engine.scroll.offset(viewport), (0.0, 300.0));
+    assert_eq!(engine.scroll.offset(hidden_box), (0.0, 0.0));
+    assert_eq!(layout_of_id(&engine, tall).await.y, 100.0 - 300.0);
+    assert_eq!(number(eval(&engine, "window.scrollY").await), 300.0);
+    assert_eq!(number(eval(&engine, "pageYOffset").await), 300.0);
+    let hit = engine.hit_test(10.0, 10.0).await.unwrap();
+    assert!(hit.path().any(|id| id == tall));
+    assert!(matches!(
+        eval(&engine, "log.join(',')").await,
+        JSValue::String(log) if log == "document:300,window"
+    ));
+
+    engine
+        .dispatch_input(InputEvent::KeyDown {
+            key: "End".to_string(),
+            code: "End".to_string(),
+            modifiers: Modifiers::default(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(engine.scroll.offset(viewport), (0.0, 1550.0));
+
+    // Отменённый wheel не прокручивает
+    eval(
+        &engine,
+        "document.addEventListener('wheel', function(e) { e.preventDefault(); })",
+    )
+    .await;
+    engine.dispatch_input(wheel(-500.0)).await.unwrap();
+    assert_eq!(engine.scroll.offset(viewport), (0.0, 1550.0));
+
+    // Новая страница открывается сверху
+    engine.load_url("about:blank").await.unwrap();
+    assert_eq!(engine.scroll.offset(viewport), (0.0, 0.0));
+}
+
+#[tokio::test]
+async fn test_window_scroll_to_repaints_and_applies_handler_changes() {
+    let engine = load(
+        "zver_scroll_window.html",
+        "#spacer { height: 1000px; }
+         #target { height: 100px; background-color: #ff0000; }
+         #target.seen { background-color: #0000ff; }
+         #tail { height: 1000px; }",
+        r#"<div id="spacer"></div><div id="target"></div><div id="tail"></div>
+           <script>
+               window.addEventListener('scroll', function() {
+                   document.getElementById('target').setAttribute('class', 'seen');
+               });
+           </script>"#,
+    )
+    .await;
+    let target = id_of(&engine, "#target").await;
+    let viewport = engine.scroll.viewport().unwrap();
+
+    eval(&engine, "window.scrollTo(0, 1000)").await;
+    assert_eq!(engine.scroll.offset(viewport), (0.0, 1000.0));
+    assert_eq!(layout_of_id(&engine, target).await.y, 0.0);
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!(
+        frame.pixel(10, 10),
+        Some(Color::rgb(0, 0, 255)),
+        "изменения из слушателя scroll попали в тот же кадр"
+    );
+
+    eval(&engine, "window.scrollBy({ top: -400 })").await;
+    assert_eq!(engine.scroll.offset(viewport), (0.0, 600.0));
+    assert_eq!(layout_of_id(&engine, target).await.y, 400.0);
+
+    // Выход за границы ограничивается
+    eval(&engine, "scrollTo(0, -50)").await;
+    assert_eq!(number(eval(&engine, "scrollY").await), 0.0);
+}
@@ -0,0 +1,218 @@
+//! Тесты программного (CPU) растеризатора и PNG-скриншотов
+
+use std::fs;
+use zver::css::color::Color;
+use zver::render::{DisplayItem, Framebuffer};
+
+mod common;
+use common::{layout_of, load};
+
+#[tokio::test]
+async fn test_screenshot_matches_viewport() {
+    let engine = load("zver_sw_viewport.html", "<html><body></body></html>").await;
+
+    let frame = engine
+        .screenshot()
+        .await
+        .expect("кадр должен быть нарисован");
+    assert_eq!((frame.width(), frame.height()), (800, 600));
+    assert_eq!(frame.pixel(10, 10), Some(Color::WHITE));
+    assert_eq!(frame.pixel(800, 0), None);
+}
+
+#[tokio::test]
+async fn test_background_and_border_are_rasterized() {
+    let engine = load(
+        "zver_sw_box.html",
+        r#"<html><body>
+            <div id="box" style="width: 100px; height: 60px; background-color: #ff0000; border: 4px solid #0000ff"></div>
+        </body></html>"#,
+    )
+    .await;
+
+    let rect = layout_of(&engine, "#box").await;
+    let frame = engine.screenshot().await.unwrap();
+
+    let center = frame
+        .pixel(
+            (rect.x + rect.width / 2.0) as u32,
+            (rect.y + rect.height / 2.0) as u32,
+        )
+        .unwrap();
+    assert_eq!(center, Color::rgb(255, 0, 0), "фон должен быть красным");
+
+    let border = frame
+        .pixel((rect.x + 1.0) as u32, (rect.y + rect.height / 2.0) as u32)
+        .unwrap();
+    assert_eq!(border, Color::rgb(0, 0, 255), "рамка должна быть синей");
+}
+
+#[tokio::test]
+async fn test_text_is_rasterized_with_fontdue() {
+    let font = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../assets/fonts/DejaVuSans.ttf"
+    );
+    let engine = load(
+        "zver_sw_text.html",
+        &format!(
+            r#"<html><head><style>
+                @font-face {{ font-family: "Test Sans"; src: url("file://{font}") format("truetype"); }}
+                #text {{ font-family: "Test Sans"; color: #000000; font-size: 24px; }}
+            </style></head><body><p id="text">Hello Zver</p></body></html>"#
+        ),
+    )
+    .await;
+
+    assert!(
+        engine.css.read().await.fonts[0].is_loaded(),
+        "шрифт @font-face должен загрузиться"
+    );
+
+    let rect = layout_of(&engine, "#text").await;
+    let frame = engine.screenshot().await.unwrap();
+
+    let mut inked = 0;
+    for y in rect.y as u32..(rect.y + rect.height) as u32 {
+        for x in rect.x as u32..(rect.x + rect.width) as u32 {
+            if let Some(pixel) = frame.pixel(x, y)
+                && pixel.r < 128
+            {
+                inked += 1;
+            }
+        }
+    }
+
+    assert!(
+        inked > 50,
+        "глифы должны оставить тёмные пиксели, найдено {inked}"
+    );
+}
+
+#[tokio::test]
+async fn test_png_roundtrip() {
+    let mut frame = Framebuffer::new(4, 3);
+    frame.clear(Color::WHITE);
+    frame.fill_rect(1.0, 1.0, 2.0, 1.0, Color::rgb(0, 128, 0));
+
+    let png_bytes = frame.encode_png().unwrap();
+    assert_eq!(&png_bytes[..8], b"\x89PNG\r\n\x1a\n");
+
+    let decoder = png::Decoder::new(std::io::Cursor::new(png_bytes));
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+
+    assert_eq!((info.width, info.height), (4, 3));
+    assert_eq!(&buffer[..info.buffer_size()], frame.pixels());
+}
+
+#[test]
+fn test_alpha_blending() {
+    let mut frame = Framebuffer::new(1, 1);
+    frame.clear(Color::WHITE);
+    frame.fill_rect(0.0, 0.0, 1.0, 1.0, Color::new(0, 0, 0, 128));
+
+    let pixel = frame.pixel(0, 0).unwrap();
+    assert_eq!(pixel.a, 255);
+    assert!(
+        (126..=128).contains(&pixel.r),
+        "ожидался серый, получено {pixel:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_img_is_decoded_and_stretched() {
+    // Изображение 2×1: левая половина красная, правая синяя
+    let mut image = Framebuffer::new(2, 1);
+    image.fill_rect(0.0, 0.0, 1.0, 1.0, Color::rgb(255, 0, 0));
+    image.fill_rect(1.0, 0.0, 1.0, 1.0, Color::rgb(0, 0, 255));
+    let png_path = std::env::temp_dir().join("zver_sw_img_pixels.png");
+    fs::write(&png_path, image.encode_png().unwrap()).unwrap();
+
+    let engine = load(
+        "zver_sw_img.html",
+        r#"<html><body>
+            <img id="pic" src="zver_sw_img_pixels.png" style="display: block; width: 80px; height: 40px">
+        </body></html>"#,
+    )
+    .await;
+    let _ = fs::remove_file(png_path);
+
+    let rect = layout_of(&engine, "#pic").await;
+    let frame = engine.screenshot().await.unwrap();
+    let y = (rect.content_y + 20.0) as u32;
+    assert_eq!(
+        frame.pixel((rect.content_x + 10.0) as u32, y),
+        Some(Color::rgb(255, 0, 0))
+    );
+    assert_eq!(
+        frame.pixel((rect.content_x + 70.0) as u32, y),
+        Some(Color::rgb(0, 0, 255))
+    );
+
+    // Декодированные пиксели доступны другим бэкендам (egui) по src из display list
+    let render = engine.render.read().await;
+    let src = render
+        .display_list()
+        .iter()
+        .find_map(|item| match item {
+            DisplayItem::Image { src, .. } => Some(src.clone()),
+            _ => None,
+        })
+        .expect("изображение должно попасть в display list");
+    let decoded = render.image(&src).expect("изображение декодировано");
+    assert_eq!((decoded.width(), decoded.height()), (2, 1));
+    assert_eq!(decoded.pixels(), image.pixels());
+}
+
+#[tokio::test]
+async fn test_img_src_set_by_script_is_loaded_on_update() {
+    let mut image = Framebuffer::new(1, 1);
+    image.clear(Color::rgb(0, 128, 0));
+    let png_path = std::env::temp_dir().join("zver_sw_img_script.png");
+    fs::write(&png_path, image.encode_png().unwrap()).unwrap();
+
+    let engine = load(
+        "zver_sw_img_script.html",
+        r#"<html><body>
+            <img id="pic" style="display: block; width: 20px; height: 20px">
+        </body></html>"#,
+    )
+    .await;
+    engine
+        .js
+        .write()
+        .await
+        .execute("document.getElementById('pic').setAttribute('src', 'zver_sw_img_script.png')")
+        .unwrap();
+    assert!(engine.update().await.unwrap());
+    let _ = fs::remove_file(png_path);
+
+    let rect = layout_of(&engine, "#pic").await;
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!(
+        frame.pixel(
+            (rect.content_x + 10.0) as u32,
+            (rect.content_y + 10.0) as u32
+        ),
+        Some(Color::rgb(0, 128, 0))
+    );
+}
+
+#[test]
+fn test_draw_image_respects_clip() {
+    let mut image = Framebuffer::new(1, 1);
+    image.clear(Color::rgb(0, 128, 0));
+
+    let mut frame = Framebuffer::new(4, 4);
+    frame.clear(Color::WHITE);
+    frame.draw_image(
+        &image,
+        zver::render::Rect::new(0.0, 0.0, 4.0, 4.0),
+        Some(zver::render::Rect::new(0.0, 0.0, 2.0, 4.0)),
+    );
+
+    assert_eq!(frame.pixel(1, 2), Some(Color::rgb(0, 128, 0)));
+    assert_eq!(frame.pixel(2, 2), Some(Color::WHITE));
+}
@@ -0,0 +1,286 @@
+//! Тесты загрузки внешних таблиц стилей: `<link rel="stylesheet">` и `@import`
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use zver::Zver;
+use zver::css::fonts::FontSource;
+use zver::css::stylesheets::{ImportRule, absolutize_urls, split_imports};
+use zver::network::Url;
+
+/// Каталог с файлами страницы; у каждого теста свой, чтобы не мешать параллельным
+fn site(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("zver_stylesheets_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+async fn load(dir: &Path) -> Zver {
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", dir.join("index.html").display()))
+        .await
+        .expect("страница должна загрузиться");
+    let _ = fs::remove_dir_all(dir);
+    engine
+}
+
+async fn computed(engine: &Zver, selector: &str, property: &str) -> Option<String> {
+    let id = engine.dom.read().await.select_ids(selector)[0];
+    engine
+        .css
+        .read()
+        .await
+        .computed_styles
+        .get(&id)?
+        .get(property)
+        .cloned()
+}
+
+#[test]
+fn test_split_imports_stops_at_first_rule() {
+    let css = r#"@charset "utf-8";
+        @import url("base.css");
+        @import 'print.css' print;
+        p { color: red }
+        @import "late.css";"#;
+
+    let (imports, rest) = split_imports(css);
+    assert_eq!(
+        imports,
+        vec![
+            ImportRule {
+                url: "base.css".to_string(),
+                media: None,
+            },
+            ImportRule {
+                url: "print.css".to_string(),
+                media: Some("print".to_string()),
+            },
+        ]
+    );
+    assert!(rest.trim_start().starts_with("p { color: red }"));
+}
+
+#[tokio::test]
+async fn test_link_stylesheet_resolved_relative_to_document() {
+    let dir = site(
+        "link",
+        &[
+            (
+                "index.html",
+                r#"<html><head><link rel="stylesheet" href="css/main.css"></head>
+                <body><p id="p">Hello</p></body></html>"#,
+            ),
+            ("css/main.css", "#p { color: #ff0000; }"),
+        ],
+    );
+
+    let engine = load(&dir).await;
+    assert_eq!(
+        computed(&engine, "#p", "color").await.as_deref(),
+        Some("rgba(255, 0, 0, 1)")
+    );
+}
+
+#[tokio::test]
+async fn test_import_resolved_relative_to_importing_sheet() {
+    let dir = site(
+        "import",
+        &[
+            (
+                "index.html",
+                r#"<html><head><link rel="stylesheet" href="css/main.css"></head>
+                <body><p id="p">Hello</p><div id="d"></div></body></html>"#,
+            ),
+            (
+                "css/main.css",
+                "@import url(\"parts/base.css\");\n#d { width: 10px; }",
+            ),
+            ("css/parts/base.css", "#p { color: #00ff00; }"),
+        ],
+    );
+
+    let engine = load(&dir).await;
+    assert_eq!(
+        computed(&engine, "#p", "color").await.as_deref(),
+        Some("rgba(0, 255, 0, 1)")
+    );
+    assert_eq!(
+        computed(&engine, "#d", "width").await.as_deref(),
+        Some("10px")
+    );
+}
+
+#[test]
+fn test_absolutize_urls_rewrites_relative_references() {
+    let base = Url::parse("https://example.com/css/theme/main.css").unwrap();
+    let css = r#"a { background: url(img/a.png) no-repeat; }
+        @media screen { b { background-image: URL( '../b.png' ) } }
+        c { mask: url(#clip); cursor: url("data:image/png;base64,AA=="); }
+        /* url(comment.png) */ d { content: "url(string.png)"; }"#;
+
+    let rewritten = absolutize_urls(css, &base);
+    assert!(rewritten.contains(r#"url("https://example.com/css/theme/img/a.png") no-repeat"#));
+    assert!(rewritten.contains(r#"background-image: url("https://example.com/css/b.png")"#));
+    assert!(rewritten.contains("url(#clip)"));
+    assert!(rewritten.contains(r#"url("data:image/png;base64,AA==")"#));
+    assert!(rewritten.contains("/* url(comment.png) */"));
+    assert!(rewritten.contains(r#""url(string.png)""#));
+}
+
+#[tokio::test]
+async fn test_urls_in_imported_sheet_resolve_against_that_sheet() {
+    let dir = site(
+        "import_urls",
+        &[
+            (
+                "index.html",
+                r#"<html><head><link rel="stylesheet" href="css/main.css"></head>
+                <body><p id="p">Hello</p></body></html>"#,
+            ),
+            ("css/main.css", "@import \"theme/fonts.css\";"),
+            (
+                "css/theme/fonts.css",
+                "@font-face { font-family: Brand; src: url(files/brand.woff2); }\n\
+                 #p { font-family: Brand; }",
+            ),
+        ],
+    );
+    let theme = Url::from_file_path(dir.join("css/theme"))
+        .unwrap()
+        .as_directory();
+
+    let engine = load(&dir).await;
+    let css = engine.css.read().await;
+    match css.fonts[0].face.sources.first() {
+        Some(FontSource::Url { url, .. }) => {
+            assert_eq!(url, theme.join("files/brand.woff2").unwrap().as_str());
+        }
+        other => panic!("ожидался url() источник шрифта, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_stylesheets_cascade_in_document_order() {
+    let dir = site(
+        "order",
+        &[
+            (
+                "index.html",
+                r#"<html><head>
+                    <link rel="stylesheet" href="first.css">
+                    <style>#p { color: #00ff00; }</style>
+                    <link rel="stylesheet" href="last.css">
+                </head><body><p id="p">Hello</p><p id="q">World</p></body></html>"#,
+            ),
+            ("first.css", "#p, #q { color: #ff0000; }"),
+            ("last.css", "#q { color: #0000ff; }"),
+        ],
+    );
+
+    let engine = load(&dir).await;
+    assert_eq!(
+        computed(&engine, "#p", "color").await.as_deref(),
+        Some("rgba(0, 255, 0, 1)")
+    );
+    assert_eq!(
+        computed(&engine, "#q", "color").await.as_deref(),
+        Some("rgba(0, 0, 255, 1)")
+    );
+}
+
+#[tokio::test]
+async fn test_non_matching_media_is_skipped() {
+    let dir = site(
+        "media",
+        &[
+            (
+                "index.html",
+                r#"<html><head>
+                    <link rel="stylesheet" href="screen.css" media="screen">
+                    <link rel="stylesheet" href="print.css" media="print">
+                    <link rel="alternate stylesheet" href="print.css">
+                </head><body><p id="p">Hello</p></body></html>"#,
+            ),
+            (
+                "screen.css",
+                "@import \"print.css\" print;\n#p { color: #00ff00; }",
+            ),
+            ("print.css", "#p { color: #ff0000; }"),
+        ],
+    );
+
+    let engine = load(&dir).await;
+    assert_eq!(
+        computed(&engine, "#p", "color").await.as_deref(),
+        Some("rgba(0, 255, 0, 1)")
+    );
+}
+
+#[tokio::test]
+async fn test_missing_stylesheet_does_not_break_page() {
+    let dir = site(
+        "missing",
+        &[(
+            "index.html",
+            r#"<html><head><link rel="stylesheet" href="nope.css">
+            <style>#p { color: #00ff00; }</style></head>
+            <body><p id="p">Hello</p></body></html>"#,
+        )],
+    );
+
+    let engine = load(&dir).await;
+    assert_eq!(
+        computed(&engine, "#p", "color").await.as_deref(),
+        Some("rgba(0, 255, 0, 1)")
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_declarations_in_linked_sheet_are_dropped() {
+    let dir = site(
+        "invalid",
+        &[
+            (
+                "index.html",
+                r#"<html><head>
+                    <link rel="stylesheet" href="site.css">
+                    <style>#q { width: 10px; }</style>
+                </head><body><p id="p">Hello</p><p id="q">World</p></body></html>"#,
+            ),
+            (
+                "site.css",
+                r#"#p { width: fit-content; color: #00ff00; }
+                #q { color: hsl(0 0% 50%); height: 5px; }
+                @supports (display: grid) { #p { color: #ff0000; } }
+                #p { margin-left: 3px; }"#,
+            ),
+        ],
+    );
+
+    let engine = load(&dir).await;
+    assert_eq!(
+        computed(&engine, "#p", "color").await.as_deref(),
+        Some("rgba(0, 255, 0, 1)")
+    );
+    assert_eq!(computed(&engine, "#p", "width").await, None);
+    assert_eq!(
+        computed(&engine, "#p", "margin-left").await.as_deref(),
+        Some("3px")
+    );
+    assert_eq!(computed(&engine, "#q", "color").await, None);
+    assert_eq!(
+        computed(&engine, "#q", "height").await.as_deref(),
+        Some("5px")
+    );
+    // Следующая таблица применяется, несмотря на ошибки в предыдущей
+    assert_eq!(
+        computed(&engine, "#q", "width").await.as_deref(),
+        Some("10px")
+    );
+}
@@ -0,0 +1,213 @@
+//! Тесты измерения текста по шрифтам и переноса строк по UAX #14
+
+use std::collections::HashMap;
+use zver::Zver;
+use zver::css::fonts::{FontFace, LoadedFont};
+use zver::dom::Document;
+use zver::layout::types::{FontStyle, FontWeight};
+use zver::layout::{LayoutEngine, TextMeasureContext, TextMeasurer};
+use zver::render::DisplayItem;
+
+fn context(content: &str, font_family: Option<&str>) -> TextMeasureContext {
+    TextMeasureContext {
+        content: content.to_string(),
+        font_family: font_family.map(str::to_string),
+        font_size: 16.0,
+        font_weight: FontWeight::Normal,
+        font_style: FontStyle::Normal,
+    }
+}
+
+/// Иконочный TTF из assets: строчная латиница, пробел и значки в U+E000…,
+/// заглавной латиницы и CJK в нём нет
+fn icon_font() -> LoadedFont {
+    let bytes = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../assets/fonts/Phosphor-Regular.ttf"
+    ))
+    .unwrap();
+    let mut font = LoadedFont::new(FontFace::new("Icons".to_string()));
+    font.load_from_bytes(&bytes).unwrap();
+    font
+}
+
+fn line_texts(measurer: &TextMeasurer, ctx: &TextMeasureContext, width: f32) -> Vec<String> {
+    measurer
+        .layout_text(ctx, Some(width))
+        .lines
+        .into_iter()
+        .map(|line| line.text)
+        .collect()
+}
+
+#[test]
+fn test_wraps_at_break_opportunities() {
+    let measurer = TextMeasurer::default();
+    let ctx = context("aaa   bbb\n ccc", None);
+    let chain = measurer.font_chain(&ctx);
+    let two_words = measurer.text_width(&chain, "aaa bbb", 16.0);
+
+    assert_eq!(
+        line_texts(&measurer, &ctx, two_words + 1.0),
+        vec!["aaa bbb", "ccc"]
+    );
+
+    let layout = measurer.layout_text(&ctx, Some(two_words + 1.0));
+    assert_eq!(layout.lines.len(), 2);
+    assert!((layout.width - two_words).abs() < 0.01);
+    assert_eq!(layout.height, layout.lines[1].y + layout.lines[1].height);
+}
+
+#[test]
+fn test_uax14_breaks_after_hyphen_and_between_ideographs() {
+    let measurer = TextMeasurer::default();
+
+    let ctx = context("well-known", None);
+    let chain = measurer.font_chain(&ctx);
+    let prefix = measurer.text_width(&chain, "well-", 16.0);
+    assert_eq!(line_texts(&measurer, &ctx, prefix), vec!["well-", "known"]);
+
+    let ctx = context("漢字漢字", None);
+    let chain = measurer.font_chain(&ctx);
+    let half = measurer.text_width(&chain, "漢字", 16.0);
+    assert_eq!(line_texts(&measurer, &ctx, half), vec!["漢字", "漢字"]);
+}
+
+#[test]
+fn test_long_word_overflows_instead_of_breaking() {
+    let measurer = TextMeasurer::default();
+    let ctx = context("unbreakable", None);
+
+    let layout = measurer.layout_text(&ctx, Some(1.0));
+    assert_eq!(layout.lines.len(), 1);
+    assert!(layout.width > 1.0);
+}
+
+#[test]
+fn test_measurement_uses_dejavu_advances_and_kerning() {
+    let measurer = TextMeasurer::default();
+
+    // DejaVu Sans, 2048 единиц на em: H = 1540, A = V = 1401, T = 1251, o = 1253;
+    // кернинг AV = -131, To = -348
+    let width = |text: &str| measurer.layout_text(&context(text, None), None).width;
+    assert!((width("H") - 12.03125).abs() < 0.01);
+    assert!((width("AV") - 20.867_188).abs() < 0.01);
+    assert!((width("To") - 16.84375).abs() < 0.01);
+}
+
+#[test]
+fn test_glyphs_missing_from_family_fall_back_per_code_point() {
+    let measurer = TextMeasurer::new(vec![icon_font()]);
+    let ctx = context("A\u{E000}", Some("'Icons', sans-serif"));
+
+    let layout = measurer.layout_text(&ctx, None);
+    let runs = &layout.lines[0].runs;
+
+    // «A» нет в Icons — берётся встроенный DejaVu; значок остаётся в Icons (1em)
+    assert_eq!(runs.len(), 2);
+    assert_eq!(
+        (runs[0].text.as_str(), runs[0].family.as_deref()),
+        ("A", None)
+    );
+    assert_eq!(
+        (runs[1].text.as_str(), runs[1].family.as_deref()),
+        ("\u{E000}", Some("Icons"))
+    );
+    assert!((runs[0].width - 10.945_313).abs() < 0.01);
+    assert!((runs[1].x - runs[0].width).abs() < 0.01);
+    assert!((runs[1].width - 16.0).abs() < 0.01);
+    assert!((layout.width - 26.945_313).abs() < 0.01);
+}
+
+#[test]
+fn test_cjk_and_emoji_without_glyphs_are_not_measured_as_notdef() {
+    let measurer = TextMeasurer::default();
+
+    // Ни в одном шрифте нет глифов: вместо .notdef (0.6em) — две ячейки по 0.6em
+    let layout = measurer.layout_text(&context("漢🦀", None), None);
+    assert!((layout.width - 38.4).abs() < 0.01);
+}
+
+#[tokio::test]
+async fn test_layout_stores_line_boxes_for_text_nodes() {
+    let mut doc = Document::new();
+    doc.parse_html(
+        r#"<html><body><div id="box" style="width: 120px; font-family: Icons">ONE TWO THREE FOUR FIVE SIX</div></body></html>"#,
+    )
+    .await
+    .unwrap();
+
+    let mut styles = HashMap::new();
+    let box_id = doc.select_ids("#box")[0];
+    styles.insert(
+        box_id,
+        HashMap::from([
+            ("width".to_string(), "120px".to_string()),
+            ("font-family".to_string(), "Icons".to_string()),
+        ]),
+    );
+
+    let mut layout = LayoutEngine::new(800.0, 600.0);
+    layout.set_fonts(vec![icon_font()]);
+    layout.compute_layout(&doc, &styles, &HashMap::new());
+
+    let text_id = doc.nodes[&box_id].children[0];
+    let text_layout = layout
+        .text_layout(text_id)
+        .expect("у текстового узла должны быть строки");
+    let result = layout.get_layout_result(text_id).unwrap();
+
+    assert!(text_layout.lines.len() > 1, "текст должен перенестись");
+    assert!(text_layout.lines.iter().all(|line| line.width <= 120.0));
+    // Taffy округляет итоговые размеры до пикселей
+    assert!((result.height - text_layout.height).abs() <= 0.5);
+}
+
+#[tokio::test]
+async fn test_paint_draws_lines_measured_by_layout() {
+    let path = std::env::temp_dir().join("zver_text_lines.html");
+    std::fs::write(
+        &path,
+        r#"<html><body><p id="p" style="width: 90px">Lorem ipsum dolor sit amet consectetur</p></body></html>"#,
+    )
+    .unwrap();
+
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", path.display()))
+        .await
+        .unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let text_id = {
+        let dom = engine.dom.read().await;
+        let p = dom.select_ids("#p")[0];
+        dom.nodes[&p].children[0]
+    };
+    let measured: Vec<String> = engine
+        .layout
+        .read()
+        .await
+        .text_layout(text_id)
+        .unwrap()
+        .lines
+        .iter()
+        .map(|line| line.text.clone())
+        .collect();
+    let painted: Vec<String> = engine
+        .render
+        .read()
+        .await
+        .display_list()
+        .iter()
+        .filter_map(|item| match item {
+            DisplayItem::GlyphRun { node_id, text, .. } if *node_id == text_id => {
+                Some(text.clone())
+            }
+            _ => None,
+        })
+        .collect();
+
+    assert!(measured.len() > 1);
+    assert_eq!(measured, painted);
+}
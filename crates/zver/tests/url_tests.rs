@@ -0,0 +1,195 @@
+//! Тесты разбора URL, `<base href>`, `data:`/`about:blank` и `location`
+
+use std::fs;
+use zver::Zver;
+use zver::dom::Document;
+use zver::js::JSValue;
+use zver::network::{NetworkEngine, Url};
+
+#[test]
+fn test_parse_absolute_and_file_paths() {
+    let url = Url::parse("https://example.com:8080/a/b.html?q=1#top").unwrap();
+    assert_eq!(url.scheme(), "https");
+    assert_eq!(url.host_str(), Some("example.com"));
+    assert_eq!(url.port(), Some(8080));
+    assert_eq!(url.path(), "/a/b.html");
+    assert_eq!(url.query(), Some("q=1"));
+    assert_eq!(url.fragment(), Some("top"));
+    assert_eq!(url.origin(), "https://example.com:8080");
+
+    // Путь без схемы — файл
+    let file = Url::parse("/tmp/zver page.html").unwrap();
+    assert!(file.is_file());
+    assert_eq!(file.as_str(), "file:///tmp/zver%20page.html");
+    assert_eq!(
+        file.to_file_path().unwrap(),
+        std::path::PathBuf::from("/tmp/zver page.html")
+    );
+    assert_eq!(file.file_name().as_deref(), Some("zver page.html"));
+
+    // file://./ — относительно текущего каталога
+    let relative = Url::parse("file://./index.html").unwrap();
+    assert_eq!(
+        relative.to_file_path().unwrap(),
+        std::env::current_dir().unwrap().join("index.html")
+    );
+}
+
+#[test]
+fn test_join_resolves_relative_references() {
+    let base = Url::parse("https://example.com/docs/guide/index.html").unwrap();
+    assert_eq!(
+        base.join("../img/logo.png").unwrap().as_str(),
+        "https://example.com/docs/img/logo.png"
+    );
+    assert_eq!(
+        base.join("/root.css").unwrap().as_str(),
+        "https://example.com/root.css"
+    );
+    assert_eq!(
+        base.join("//cdn.example.org/x.js").unwrap().as_str(),
+        "https://cdn.example.org/x.js"
+    );
+    assert_eq!(
+        base.join("#section").unwrap().as_str(),
+        "https://example.com/docs/guide/index.html#section"
+    );
+}
+
+#[test]
+fn test_data_urls_are_decoded() {
+    let plain = Url::parse("data:,Hello%2C%20World!").unwrap();
+    let data = plain.data().unwrap();
+    assert_eq!(data.mime_type, "text/plain;charset=US-ASCII");
+    assert_eq!(data.body, b"Hello, World!");
+
+    let encoded = Url::parse("data:text/html;base64,PGgxPkhpPC9oMT4=").unwrap();
+    let data = encoded.data().unwrap();
+    assert_eq!(data.mime_type, "text/html");
+    assert_eq!(data.body, b"<h1>Hi</h1>");
+
+    assert!(Url::parse("https://example.com").unwrap().data().is_none());
+}
+
+#[tokio::test]
+async fn test_network_fetches_data_and_about_blank() {
+    let mut network = NetworkEngine::new();
+    assert_eq!(
+        network.fetch("data:text/html,<p>inline</p>").await.unwrap(),
+        "<p>inline</p>"
+    );
+    assert_eq!(network.fetch("about:blank").await.unwrap(), "");
+    assert!(network.fetch("gopher://example.com/").await.is_err());
+}
+
+#[tokio::test]
+async fn test_base_href_overrides_document_url() {
+    let dir = std::env::temp_dir().join("zver_url_base");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("assets/css")).unwrap();
+    fs::write(
+        dir.join("index.html"),
+        r#"<html><head><base href="assets/"><link rel="stylesheet" href="css/site.css"></head>
+        <body><p id="p">Hello</p><a id="link" href="page.html">next</a></body></html>"#,
+    )
+    .unwrap();
+    fs::write(dir.join("assets/css/site.css"), "#p { color: #ff0000; }").unwrap();
+
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", dir.join("index.html").display()))
+        .await
+        .unwrap();
+    let _ = fs::remove_dir_all(&dir);
+
+    let dom = engine.dom.read().await;
+    let document_url = Url::from_file_path(dir.join("index.html")).unwrap();
+    assert_eq!(dom.url(), &document_url);
+    assert_eq!(
+        dom.resolve_url("page.html").unwrap(),
+        Url::from_file_path(dir.join("assets/page.html")).unwrap()
+    );
+
+    let p = dom.select_ids("#p")[0];
+    drop(dom);
+    let color = engine.css.read().await.computed_styles[&p]
+        .get("color")
+        .cloned();
+    assert_eq!(color.as_deref(), Some("rgba(255, 0, 0, 1)"));
+}
+
+#[tokio::test]
+async fn test_base_href_follows_tree_order_and_mutations() {
+    let mut doc = Document::new();
+    doc.parse_html("<html><head></head><body></body></html>")
+        .await
+        .unwrap();
+    doc.set_url(Url::parse("https://example.com/dir/index.html").unwrap());
+    let head = doc.select_ids("head")[0];
+    let body = doc.select_ids("body")[0];
+    let resolved = |doc: &Document| doc.resolve_url("a.html").unwrap().to_string();
+    assert_eq!(resolved(&doc), "https://example.com/dir/a.html");
+
+    // Отсоединённый <base> не действует, пока не вставлен в документ
+    let late = doc.create_element("base").unwrap();
+    doc.set_attribute(late, "href", "https://late.example.org/")
+        .unwrap();
+    assert_eq!(resolved(&doc), "https://example.com/dir/a.html");
+    doc.append_child(body, late).unwrap();
+    assert_eq!(resolved(&doc), "https://late.example.org/a.html");
+
+    // Созданный позже, но раньше по дереву — первый
+    let early = doc.create_element("base").unwrap();
+    doc.set_attribute(early, "href", "https://early.example.org/")
+        .unwrap();
+    doc.append_child(head, early).unwrap();
+    assert_eq!(resolved(&doc), "https://early.example.org/a.html");
+
+    doc.set_attribute(early, "href", "/root/").unwrap();
+    assert_eq!(resolved(&doc), "https://example.com/root/a.html");
+
+    doc.remove_child(head, early).unwrap();
+    assert_eq!(resolved(&doc), "https://late.example.org/a.html");
+}
+
+#[tokio::test]
+async fn test_data_document_and_location() {
+    let source =
+        "data:text/html,<script>var seen = location.protocol + ' ' + location.href;</script>";
+    let engine = Zver::new();
+    engine.load_url(source).await.unwrap();
+
+    let href = Url::parse(source).unwrap();
+    match engine.js.write().await.execute("seen").unwrap() {
+        JSValue::String(seen) => assert_eq!(seen, format!("data: {href}")),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_location_reflects_document_url() {
+    let path = std::env::temp_dir().join("zver_url_location.html");
+    fs::write(
+        &path,
+        "<html><body><script>var parts = [location.protocol, location.pathname, String(location)];</script></body></html>",
+    )
+    .unwrap();
+
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", path.display()))
+        .await
+        .unwrap();
+    let _ = fs::remove_file(&path);
+
+    let url = Url::from_file_path(&path).unwrap();
+    let result = engine.js.write().await.execute("parts.join('|')").unwrap();
+    match result {
+        JSValue::String(parts) => {
+            assert_eq!(parts, format!("file:|{}|{}", url.path(), url.as_str()))
+        }
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+
+    assert!(engine.dom.read().await.url().is_file());
+}
@@ -0,0 +1,78 @@
+//! Тесты изменения viewport: @media, relayout и событие resize
+
+use std::fs;
+use zver::Zver;
+use zver::js::JSValue;
+
+async fn layout_of(engine: &Zver, selector: &str) -> (f32, f32) {
+    let node_id = engine.dom.read().await.select_ids(selector)[0];
+    let result = engine
+        .layout
+        .read()
+        .await
+        .get_layout_result(node_id)
+        .expect("у элемента должен быть layout");
+    (result.width, result.height)
+}
+
+#[tokio::test]
+async fn test_set_viewport_reevaluates_media_and_fires_resize() {
+    let path = std::env::temp_dir().join("zver_viewport_resize.html");
+    fs::write(
+        &path,
+        r#"<html><head><style>
+            #box { display: block; width: 200px; height: 10px; }
+            #box.resized { height: 30px; }
+            #fluid { display: block; width: 50%; height: 10px; }
+            @media (max-width: 600px) { #box { width: 50px; } }
+        </style></head><body>
+        <div id="box"></div><div id="fluid"></div>
+        <script>
+            var resizes = [];
+            window.addEventListener('resize', function() {
+                resizes.push(innerWidth + 'x' + innerHeight);
+                document.getElementById('box').setAttribute('class', 'resized');
+            });
+        </script>
+        </body></html>"#,
+    )
+    .unwrap();
+
+    let engine = Zver::new();
+    engine
+        .load_url(&format!("file://{}", path.display()))
+        .await
+        .unwrap();
+    let _ = fs::remove_file(path);
+    assert_eq!(layout_of(&engine, "#box").await, (200.0, 10.0));
+    assert_eq!(layout_of(&engine, "#fluid").await.0, 400.0);
+
+    assert!(
+        engine.set_viewport(500.0, 400.0, 1.0).await.unwrap(),
+        "(max-width: 600px) начал применяться"
+    );
+    assert_eq!(layout_of(&engine, "#box").await, (50.0, 30.0));
+    assert_eq!(layout_of(&engine, "#fluid").await.0, 250.0);
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!((frame.width(), frame.height()), (500, 400));
+
+    assert!(
+        !engine.set_viewport(450.0, 400.0, 2.0).await.unwrap(),
+        "набор @media правил не изменился"
+    );
+    assert_eq!(layout_of(&engine, "#fluid").await.0, 225.0);
+    assert_eq!(engine.css.read().await.viewport_width, 450.0);
+    let frame = engine.screenshot().await.unwrap();
+    assert_eq!((frame.width(), frame.height()), (900, 800));
+
+    match engine
+        .js
+        .write()
+        .await
+        .execute("resizes.join(',') + '@' + devicePixelRatio")
+        .unwrap()
+    {
+        JSValue::String(log) => assert_eq!(log, "500x400,450x400@2"),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
@@ -0,0 +1,184 @@
+//! Тесты часов страницы: виртуальное время для таймеров, Date, rAF и CSS-анимаций
+
+use std::collections::HashMap;
+use std::time::Duration;
+use zver::Zver;
+use zver::clock::Clock;
+use zver::css::animations::{AnimationConfig, interpolate_value};
+use zver::js::JSValue;
+
+mod common;
+use common::{layout_of, load_into};
+
+async fn load_virtual(name: &str, html: &str) -> Zver {
+    let engine = Zver::with_clock(Clock::new_virtual());
+    load_into(&engine, name, html).await;
+    engine
+}
+
+async fn number(engine: &Zver, code: &str) -> f64 {
+    match engine.js.write().await.execute(code).unwrap() {
+        JSValue::Number(n) => n,
+        other => panic!("ожидалось число, получено {other:?}"),
+    }
+}
+
+#[test]
+fn test_virtual_clock_moves_only_when_advanced() {
+    let clock = Clock::new_virtual();
+    assert!(clock.is_virtual());
+    assert_eq!(clock.now(), Duration::ZERO);
+
+    let shared = clock.clone();
+    shared.advance(Duration::from_millis(250));
+    clock.advance_to(Duration::from_millis(100));
+    assert_eq!(
+        clock.now(),
+        Duration::from_millis(250),
+        "часы не идут назад"
+    );
+    assert!(!Clock::real().is_virtual());
+}
+
+#[tokio::test]
+async fn test_date_and_performance_follow_virtual_clock() {
+    let engine = load_virtual(
+        "zver_clock_date.html",
+        r#"<html><body><script>
+            var startDate = Date.now();
+            var startPerf = performance.now();
+        </script></body></html>"#,
+    )
+    .await;
+
+    assert_eq!(number(&engine, "new Date().getUTCFullYear()").await, 2000.0);
+    assert_eq!(number(&engine, "Date.now() - startDate").await, 0.0);
+
+    engine.advance(Duration::from_secs(10)).await.unwrap();
+    assert_eq!(number(&engine, "Date.now() - startDate").await, 10_000.0);
+    assert_eq!(
+        number(&engine, "performance.now() - startPerf").await,
+        10_000.0
+    );
+}
+
+#[tokio::test]
+async fn test_animation_frames_run_once_per_frame() {
+    let engine = load_virtual(
+        "zver_clock_raf.html",
+        r#"<html><body><script>
+            var frames = [];
+            function step(timestamp) {
+                frames.push(timestamp);
+                if (frames.length < 3) requestAnimationFrame(step);
+            }
+            requestAnimationFrame(step);
+            var cancelled = requestAnimationFrame(function() { frames.push(-1); });
+            cancelAnimationFrame(cancelled);
+        </script></body></html>"#,
+    )
+    .await;
+    assert_eq!(
+        number(&engine, "frames.length").await,
+        0.0,
+        "кадры ждут перевода часов"
+    );
+
+    engine.advance(Duration::from_millis(100)).await.unwrap();
+    assert_eq!(number(&engine, "frames.length").await, 3.0);
+    let first = number(&engine, "frames[0]").await;
+    let gap = number(&engine, "frames[1] - frames[0]").await;
+    assert!((first - 1000.0 / 60.0).abs() < 0.01, "первый кадр: {first}");
+    assert!((gap - 1000.0 / 60.0).abs() < 0.01, "шаг кадров: {gap}");
+}
+
+#[tokio::test]
+async fn test_animation_frame_callbacks_keep_their_closures() {
+    let engine = load_virtual(
+        "zver_clock_raf_closure.html",
+        r#"<html><body><script>
+            var result = "";
+            (function() {
+                var x = 0;
+                function tick(timestamp) {
+                    x += 10;
+                    if (x < 30) {
+                        requestAnimationFrame(tick);
+                    } else {
+                        result = "x=" + x + ":" + (timestamp > 0);
+                    }
+                }
+                requestAnimationFrame(tick);
+            })();
+        </script></body></html>"#,
+    )
+    .await;
+
+    engine.advance(Duration::from_millis(100)).await.unwrap();
+    match engine.js.write().await.execute("result").unwrap() {
+        JSValue::String(result) => assert_eq!(result, "x=30:true"),
+        other => panic!("ожидалась строка, получено {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_css_animation_is_sampled_from_clock() {
+    let engine = load_virtual(
+        "zver_clock_animation.html",
+        r#"<html><head><style>
+            @keyframes grow { from { width: 100px; } to { width: 300px; } }
+            #box { display: block; height: 10px; width: 50px; animation: grow 10s linear forwards; }
+        </style></head><body><div id="box"></div></body></html>"#,
+    )
+    .await;
+    assert_eq!(layout_of(&engine, "#box").await.width, 100.0);
+
+    engine.advance(Duration::from_secs(5)).await.unwrap();
+    assert_eq!(layout_of(&engine, "#box").await.width, 200.0);
+
+    engine.advance(Duration::from_secs(10)).await.unwrap();
+    assert_eq!(
+        layout_of(&engine, "#box").await.width,
+        300.0,
+        "forwards удерживает 100%"
+    );
+}
+
+#[test]
+fn test_animation_config_and_interpolation() {
+    let properties = HashMap::from([
+        (
+            "animation".to_string(),
+            "pulse 2s ease-in 500ms infinite alternate".to_string(),
+        ),
+        ("animation-fill-mode".to_string(), "both".to_string()),
+    ]);
+    let config = AnimationConfig::from_properties(&properties).unwrap();
+    assert_eq!(config.name, "pulse");
+    assert_eq!(config.duration, 2.0);
+    assert_eq!(config.delay, 0.5);
+    assert!(config.iteration_count.is_infinite());
+
+    assert_eq!(config.progress_at(0.0), Some(0.0), "backwards до старта");
+    assert_eq!(config.progress_at(1.5), Some(0.5));
+    assert_eq!(
+        config.progress_at(3.0),
+        Some(0.75),
+        "вторая итерация идёт обратно"
+    );
+
+    assert_eq!(interpolate_value("0", "40px", 0.25), "10px");
+    assert_eq!(
+        interpolate_value("rgba(0, 0, 0, 1)", "rgba(255, 0, 100, 1)", 0.5),
+        "rgba(128, 0, 50, 1)"
+    );
+    assert_eq!(interpolate_value("block", "none", 0.4), "block");
+    assert_eq!(
+        AnimationConfig::from_properties(&HashMap::from([(
+            "animation-name".to_string(),
+            "none".to_string()
+        )]))
+        .map(|config| config.name),
+        None
+    );
+}
@@ -1,4 +1,5 @@
 use zver::Zver;
+use zver::layout::LayoutSnapshot;
 
 /// Пример демонстрации работы с новым Layout API и RenderInfo
 #[tokio::main]
@@ -30,45 +31,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let render_info = layout.get_all_render_info(&dom);
             println!("🎨 RenderInfo элементов: {}", render_info.len());
 
-            // Анализ результатов layout
-            println!("\n📊 Анализ layout результатов:");
+            // Дерево боксов в порядке документа: тот же формат, что у `zver --dump layout`
+            let snapshot = LayoutSnapshot::capture(&dom, &layout);
+            println!("\n📊 Дерево layout:\n{}", snapshot);
+
             let mut total_area = 0.0;
             let mut text_nodes = 0;
             let mut element_nodes = 0;
-
             for info in &render_info {
-                let area = info.layout.width * info.layout.height;
-                total_area += area;
-
+                total_area += info.layout.width * info.layout.height;
                 if info.node.tag_name.is_none() {
                     text_nodes += 1;
-                    if let Some(text) = &info.node.text_content {
-                        let preview = if text.chars().count() > 20 {
-                            format!("{}...", text.chars().take(20).collect::<String>())
-                        } else {
-                            text.clone()
-                        };
-                        println!(
-                            "  📝 Текст: \"{}\" - {:.0}x{:.0} at ({:.0},{:.0})",
-                            preview,
-                            info.layout.width,
-                            info.layout.height,
-                            info.layout.x,
-                            info.layout.y
-                        );
-                    }
                 } else {
                     element_nodes += 1;
-                    if let Some(tag) = &info.node.tag_name {
-                        println!(
-                            "  🏷️  <{}> - {:.0}x{:.0} at ({:.0},{:.0})",
-                            tag,
-                            info.layout.width,
-                            info.layout.height,
-                            info.layout.x,
-                            info.layout.y
-                        );
-                    }
                 }
             }
 